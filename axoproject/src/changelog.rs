@@ -1,5 +1,6 @@
 //! Support for interpreting changelogs
 
+use axoprocess::Cmd;
 use camino::Utf8Path;
 
 use crate::errors::Result;
@@ -143,3 +144,203 @@ fn try_extract_changelog_unreleased(
         body: release_notes.notes.to_string(),
     })
 }
+
+/// The Conventional Commit types that get a section in a generated changelog,
+/// in the order the sections appear (breaking changes of any type come first)
+const COMMIT_SECTIONS: &[(&str, &str)] = &[
+    ("feat", "Features"),
+    ("fix", "Bug Fixes"),
+    ("perf", "Performance Improvements"),
+];
+
+/// Generate the changelog for a version from the Conventional Commits made since
+/// the previous release, for projects that don't keep a CHANGELOG.
+///
+/// `tag` is the tag being announced. The previous release is the closest earlier tag
+/// with the same format (so `my-app-v1.2.0` looks for `my-app-v*`). If `tag` doesn't
+/// exist yet (e.g. we're planning a release locally), HEAD is used in its place.
+/// If `path` is given, only commits touching it are considered, so each package in
+/// a monorepo can get its own changelog.
+///
+/// Returns `Ok(None)` if none of the commits are features, fixes, performance
+/// improvements, or breaking changes.
+pub fn changelog_from_commits(
+    git: &str,
+    repo_dir: &Utf8Path,
+    tag: &str,
+    version: &Version,
+    path: Option<&Utf8Path>,
+) -> Result<Option<ChangelogInfo>> {
+    let end = if git_output(
+        git,
+        repo_dir,
+        "check for release tag",
+        &[
+            "rev-parse",
+            "--verify",
+            "--quiet",
+            &format!("{tag}^{{commit}}"),
+        ],
+    )?
+    .is_some()
+    {
+        tag.to_owned()
+    } else {
+        "HEAD".to_owned()
+    };
+
+    let version_string = version.to_string();
+    let tag_pattern = if tag.contains(&version_string) {
+        tag.replacen(&version_string, "*", 1)
+    } else {
+        "*".to_owned()
+    };
+    let previous_tag = git_output(
+        git,
+        repo_dir,
+        "find previous release tag",
+        &[
+            "describe",
+            "--tags",
+            "--abbrev=0",
+            "--match",
+            &tag_pattern,
+            &format!("{end}^"),
+        ],
+    )?;
+    let range = match previous_tag {
+        Some(previous_tag) => format!("{previous_tag}..{end}"),
+        None => end,
+    };
+
+    // Each commit is its abbreviated hash and full message, separated by a unit
+    // separator, and commits are separated by record separators
+    let mut args = vec!["log", "--format=%h%x1f%B%x1e", &range];
+    if let Some(path) = path {
+        args.extend(["--", path.as_str()]);
+    }
+    let log = git_output(git, repo_dir, "read commits since previous release", &args)?
+        .unwrap_or_default();
+
+    Ok(changelog_from_commit_log_inner(&log, version))
+}
+
+/// Build a changelog from the output of `git log --format=%h%x1f%B%x1e`
+/// (inner version for testing)
+pub fn changelog_from_commit_log_inner(log: &str, version: &Version) -> Option<ChangelogInfo> {
+    let mut breaking = vec![];
+    let mut sections = vec![vec![]; COMMIT_SECTIONS.len()];
+    for record in log.split('\x1e') {
+        let Some((hash, message)) = record.trim().split_once('\x1f') else {
+            continue;
+        };
+        let Some(commit) = ConventionalCommit::parse(message) else {
+            continue;
+        };
+        let entry = commit.entry(hash);
+        if commit.breaking {
+            breaking.push(entry);
+        } else if let Some(idx) = COMMIT_SECTIONS
+            .iter()
+            .position(|(kind, _)| kind.eq_ignore_ascii_case(commit.kind))
+        {
+            sections[idx].push(entry);
+        }
+    }
+
+    let rendered = std::iter::once(("Breaking Changes", breaking))
+        .chain(
+            COMMIT_SECTIONS
+                .iter()
+                .map(|(_, heading)| *heading)
+                .zip(sections),
+        )
+        .filter(|(_, entries)| !entries.is_empty())
+        .map(|(heading, entries)| {
+            // git log lists the newest commits first, but changelogs read better in order
+            let entries = entries.into_iter().rev().collect::<Vec<_>>().join("\n");
+            format!("### {heading}\n\n{entries}\n")
+        })
+        .collect::<Vec<_>>();
+    if rendered.is_empty() {
+        return None;
+    }
+
+    Some(ChangelogInfo {
+        title: format!("Version {version}"),
+        body: rendered.join("\n"),
+    })
+}
+
+/// The parts of a Conventional Commit message we care about
+struct ConventionalCommit<'a> {
+    kind: &'a str,
+    scope: Option<&'a str>,
+    description: &'a str,
+    breaking: bool,
+}
+
+impl<'a> ConventionalCommit<'a> {
+    /// Parse a commit message, returning None if it doesn't follow Conventional Commits
+    fn parse(message: &'a str) -> Option<Self> {
+        let (summary, body) = message.split_once('\n').unwrap_or((message, ""));
+        let (prefix, description) = summary.split_once(':')?;
+        let description = description.trim();
+        let (prefix, bang) = match prefix.strip_suffix('!') {
+            Some(prefix) => (prefix, true),
+            None => (prefix, false),
+        };
+        let (kind, scope) = match prefix.split_once('(') {
+            Some((kind, scope)) => (kind, Some(scope.strip_suffix(')')?)),
+            None => (prefix, None),
+        };
+        if kind.is_empty()
+            || !kind.chars().all(|c| c.is_ascii_alphanumeric())
+            || description.is_empty()
+        {
+            return None;
+        }
+        let breaking = bang
+            || body.lines().any(|line| {
+                line.starts_with("BREAKING CHANGE:") || line.starts_with("BREAKING-CHANGE:")
+            });
+
+        Some(Self {
+            kind,
+            scope,
+            description,
+            breaking,
+        })
+    }
+
+    /// Render this commit as a changelog bullet
+    fn entry(&self, hash: &str) -> String {
+        let description = self.description;
+        match self.scope {
+            Some(scope) => format!("- **{scope}:** {description} ({hash})"),
+            None => format!("- {description} ({hash})"),
+        }
+    }
+}
+
+/// Run git, returning its trimmed stdout if it succeeded
+fn git_output(
+    git: &str,
+    repo_dir: &Utf8Path,
+    summary: &str,
+    args: &[&str],
+) -> Result<Option<String>> {
+    let mut cmd = Cmd::new(git, summary);
+    for arg in args {
+        cmd.arg(arg);
+    }
+    cmd.stdout(std::process::Stdio::piped())
+        .stderr(std::process::Stdio::piped())
+        .check(false)
+        .current_dir(repo_dir);
+    let output = cmd.output()?;
+    if !output.status.success() {
+        return Ok(None);
+    }
+    Ok(Some(String::from_utf8(output.stdout)?.trim().to_owned()))
+}
@@ -37,8 +37,8 @@ pub mod rust;
 #[cfg(test)]
 mod tests;
 
-pub use crate::repo::GithubRepo;
 use crate::repo::GithubRepoInput;
+pub use crate::repo::{GithubRepo, GitlabRepo};
 /// A sorted map impl
 pub type SortedMap<K, V> = std::collections::BTreeMap<K, V>;
 
@@ -404,6 +404,11 @@ impl RepositoryUrl {
     pub fn github_repo(&self) -> Result<GithubRepo> {
         GithubRepoInput::new(self.0.clone())?.parse()
     }
+
+    /// Returns a struct which contains the GitLab instance, namespace, and name of the repository.
+    pub fn gitlab_repo(&self) -> Result<GitlabRepo> {
+        GitlabRepo::from_url(&self.0)
+    }
 }
 
 /// Computed info about a package
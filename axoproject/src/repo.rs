@@ -120,6 +120,97 @@ impl GithubRepoInput {
     }
 }
 
+/// Represents a GitLab repository (on gitlab.com or a self-managed instance)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GitlabRepo {
+    /// The URL of the GitLab instance, e.g. `"https://gitlab.com"`
+    pub host: String,
+    /// The namespace of the project, which may include subgroups, e.g. `"mygroup/mysubgroup"`
+    pub namespace: String,
+    /// The name of the project
+    pub name: String,
+}
+
+impl GitlabRepo {
+    /// Returns the URL of the GitLab instance the repository is on.
+    pub fn domain(&self) -> String {
+        self.host.clone()
+    }
+
+    /// The full path of the project, e.g. `"mygroup/mysubgroup/myproject"`
+    pub fn full_path(&self) -> String {
+        format!("{}/{}", self.namespace, self.name)
+    }
+
+    /// Path component. Used with `domain` to construct `web_url`.
+    pub fn web_path(&self) -> String {
+        format!("/{}", self.full_path())
+    }
+
+    /// Returns a URL suitable for web access to the repository.
+    pub fn web_url(&self) -> String {
+        format!("{}{}", self.domain(), self.web_path())
+    }
+
+    /// Returns the API URL of the GitLab instance, e.g. `"https://gitlab.com/api/v4"`
+    pub fn api_url(&self) -> String {
+        format!("{}/api/v4", self.domain())
+    }
+
+    /// Constructs a new GitLab repository from a repository URL. Notably, this does not check
+    /// whether the repo actually exists, or whether the host actually runs GitLab.
+    pub fn from_url(repo_url: &str) -> Result<Self> {
+        let repo_string = repo_url.to_owned();
+        let (host, path) = if let Some(rest) = repo_string.strip_prefix("git@") {
+            // git@gitlab.example.com:mygroup/myproject.git
+            let Some((domain, path)) = rest.split_once(':') else {
+                return Err(AxoprojectError::RepoParseError { repo: repo_string });
+            };
+            (format!("https://{domain}"), path.to_owned())
+        } else if repo_string.starts_with("https") || repo_string.starts_with("git+https") {
+            let parsed = Url::parse(repo_string.trim_start_matches("git+"))?;
+            let Some(domain) = parsed.host_str() else {
+                return Err(AxoprojectError::RepoParseError { repo: repo_string });
+            };
+            let host = if let Some(port) = parsed.port() {
+                format!("https://{domain}:{port}")
+            } else {
+                format!("https://{domain}")
+            };
+            (host, parsed.path().to_owned())
+        } else {
+            return Err(AxoprojectError::UnknownRepoStyle { url: repo_string });
+        };
+
+        // GitLab projects can be nested in arbitrarily many subgroups, so the
+        // last segment is the name and everything before it is the namespace
+        let path = path.trim_matches('/');
+        let path = path.strip_suffix(".git").unwrap_or(path);
+        let segments = path
+            .split('/')
+            .filter(|s| !s.trim().is_empty())
+            .collect::<Vec<_>>();
+        let Some((name, namespace)) = segments.split_last() else {
+            return Err(AxoprojectError::RepoParseError { repo: repo_string });
+        };
+        if namespace.is_empty() || namespace.contains(&"-") {
+            // `/-/` separates the project path from e.g. `/-/tree/main` in web URLs
+            return Err(AxoprojectError::RepoParseError { repo: repo_string });
+        }
+        Ok(GitlabRepo {
+            host,
+            namespace: namespace.join("/"),
+            name: name.to_string(),
+        })
+    }
+}
+
+impl fmt::Display for GitlabRepo {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "({})", self.full_path())
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -153,4 +244,42 @@ mod tests {
         assert_eq!(parsed.owner, actual_owner);
         assert_eq!(parsed.name, actual_name);
     }
+
+    #[test]
+    fn it_parses_a_gitlab_repo_string() {
+        let input = "https://gitlab.com/axodotdev/oranda";
+        let parsed = GitlabRepo::from_url(input).unwrap();
+        assert_eq!(parsed.host, "https://gitlab.com");
+        assert_eq!(parsed.namespace, "axodotdev");
+        assert_eq!(parsed.name, "oranda");
+        assert_eq!(parsed.api_url(), "https://gitlab.com/api/v4");
+    }
+
+    #[test]
+    fn it_parses_a_nested_gitlab_repo_string() {
+        let input = "https://git.example.com:8443/axodotdev/tools/oranda.git";
+        let parsed = GitlabRepo::from_url(input).unwrap();
+        assert_eq!(parsed.host, "https://git.example.com:8443");
+        assert_eq!(parsed.namespace, "axodotdev/tools");
+        assert_eq!(parsed.name, "oranda");
+        assert_eq!(
+            parsed.web_url(),
+            "https://git.example.com:8443/axodotdev/tools/oranda"
+        );
+    }
+
+    #[test]
+    fn it_parses_a_gitlab_ssh_repo_string() {
+        let input = "git@gitlab.example.com:axodotdev/tools/oranda.git";
+        let parsed = GitlabRepo::from_url(input).unwrap();
+        assert_eq!(parsed.host, "https://gitlab.example.com");
+        assert_eq!(parsed.namespace, "axodotdev/tools");
+        assert_eq!(parsed.name, "oranda");
+    }
+
+    #[test]
+    fn it_rejects_a_gitlab_repo_without_namespace() {
+        assert!(GitlabRepo::from_url("https://gitlab.com/oranda").is_err());
+        assert!(GitlabRepo::from_url("https://gitlab.com/axodotdev/oranda/-/tree/main").is_err());
+    }
 }
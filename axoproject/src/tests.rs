@@ -584,6 +584,56 @@ fn test_changelog_errors() {
     ));
 }
 
+#[test]
+fn test_changelog_from_commits() {
+    use crate::changelog::changelog_from_commit_log_inner as test;
+    // Newest first, like git log
+    let log = [
+        "aaaaaaa\x1fchore: release 1.1.0\n",
+        "bbbbbbb\x1ffeat(cli)!: remove the --legacy flag\n",
+        "ccccccc\x1ffix: handle empty input\n\nBREAKING CHANGE: empty input is now an error\n",
+        "ddddddd\x1fperf: cache parsed manifests\n",
+        "eeeeeee\x1ffix(parser): accept trailing commas\n",
+        "fffffff\x1fMerge branch 'main' into feature: things\n",
+        "0000000\x1ffeat: add a --json flag\n\nIt prints json.\n",
+    ]
+    .join("\x1e\n");
+
+    assert_eq!(
+        test(&log, &ver("1.1.0")).unwrap(),
+        ChangelogInfo {
+            title: "Version 1.1.0".to_owned(),
+            body: "### Breaking Changes
+
+- handle empty input (ccccccc)
+- **cli:** remove the --legacy flag (bbbbbbb)
+
+### Features
+
+- add a --json flag (0000000)
+
+### Bug Fixes
+
+- **parser:** accept trailing commas (eeeeeee)
+
+### Performance Improvements
+
+- cache parsed manifests (ddddddd)
+"
+            .to_owned()
+        }
+    );
+
+    // Nothing worth announcing
+    assert_eq!(
+        test(
+            "aaaaaaa\x1fchore: bump deps\n\x1e\nbbbbbbb\x1fwip\n\x1e",
+            &ver("1.1.0")
+        ),
+        None
+    );
+}
+
 #[test]
 fn test_generic_c() {
     let workspaces = WorkspaceGraph::find("tests/projects/generic-c/".into(), None).unwrap();
@@ -689,6 +689,17 @@ pub struct Release {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display: Option<bool>,
+    /// The title of this app's own changelog entry for this version
+    ///
+    /// Only set when several apps are announced together and this one
+    /// has notes separate from the announcement's.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog_title: Option<String>,
+    /// The body of this app's own changelog entry for this version
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog_body: Option<String>,
     /// The artifacts for this release (zips, debuginfo, metadata...)
     #[serde(default)]
     #[serde(skip_serializing_if = "Vec::is_empty")]
@@ -697,6 +708,10 @@ pub struct Release {
     #[serde(default)]
     #[serde(skip_serializing_if = "Hosting::is_empty")]
     pub hosting: Hosting,
+    /// Where `dist publish` publishes this release to
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Publishing::is_empty")]
+    pub publishing: Publishing,
 }
 
 declare_strongly_typed_string! {
@@ -752,6 +767,10 @@ pub struct Artifact {
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
     pub checksum: Option<ArtifactId>,
+    /// id of an Artifact that contains a detached signature of this Artifact
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub signature: Option<ArtifactId>,
     /// checksums for this artifact
     ///
     /// keys are the name of an algorithm like "sha256" or "sha512"
@@ -832,6 +851,12 @@ pub enum ArtifactKind {
     /// The checksums of many artifacts
     #[serde(rename = "unified-checksum")]
     UnifiedChecksum,
+    /// A detached signature of another artifact
+    #[serde(rename = "signature")]
+    Signature,
+    /// An in-toto SLSA provenance statement for a release
+    #[serde(rename = "provenance")]
+    Provenance,
     /// A tarball containing the source code
     #[serde(rename = "source-tarball")]
     SourceTarball,
@@ -1022,7 +1047,10 @@ impl DistManifest {
                 env: Some(environment_variables),
                 artifacts: vec![],
                 hosting: Hosting::default(),
+                publishing: Publishing::default(),
                 display: None,
+                changelog_title: None,
+                changelog_body: None,
                 display_name: None,
             });
             self.releases.last_mut().unwrap()
@@ -1074,6 +1102,10 @@ pub struct Hosting {
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github: Option<GithubHosting>,
+    /// Hosted on GitLab Releases and the GitLab Generic Package Registry
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gitlab: Option<GitlabHosting>,
     /// Files simply hosted on some static file server
     #[serde(default)]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -1096,6 +1128,43 @@ pub struct GithubHosting {
     pub repo: String,
 }
 
+/// GitLab Hosting
+///
+/// Artifacts are uploaded to a generic package in the project's package registry,
+/// and linked from a GitLab Release.
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct GitlabHosting {
+    /// The URL of the GitLab API, usually `"https://gitlab.com/api/v4"`
+    /// (This can vary for self-managed GitLab instances)
+    pub api_url: String,
+    /// The full path of the project, e.g. `"mygroup/mysubgroup/myproject"`
+    pub project_path: String,
+    /// The name of the generic package the artifacts are uploaded to
+    pub package_name: String,
+    /// The version of the generic package the artifacts are uploaded to
+    pub package_version: String,
+}
+
+impl GitlabHosting {
+    /// The project ID to use in API paths (the URL-encoded project path)
+    pub fn project_id(&self) -> String {
+        self.project_path.replace('/', "%2F")
+    }
+
+    /// Get the URL that artifacts should be downloaded from (append the artifact name to the URL)
+    ///
+    /// e.g. `https://gitlab.com/api/v4/projects/myowner%2Fmyrepo/packages/generic/myapp/1.0.0`
+    pub fn artifact_download_url(&self) -> String {
+        format!(
+            "{}/projects/{}/packages/generic/{}/{}",
+            self.api_url,
+            self.project_id(),
+            self.package_name,
+            self.package_version
+        )
+    }
+}
+
 /// Simple Hosting
 #[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct SimpleHosting {
@@ -1111,6 +1180,8 @@ pub struct SimpleHosting {
 pub enum HostingStyle {
     /// Hosting via GitHub
     Github,
+    /// Hosting via GitLab
+    Gitlab,
     /// Hosting via a simple static file server
     Simple,
 }
@@ -1120,6 +1191,7 @@ impl Hosting {
     pub fn artifact_download_urls(&self) -> Option<Vec<String>> {
         let Hosting {
             github,
+            gitlab,
             simple,
             order,
         } = &self;
@@ -1149,6 +1221,16 @@ impl Hosting {
             }
         }
 
+        if let Some(host) = &gitlab {
+            let priority = order
+                .iter()
+                .flatten()
+                .position(|key| *key == HostingStyle::Gitlab);
+            if priority.is_some() || !priority_matters {
+                results.push((priority, host.artifact_download_url()));
+            }
+        }
+
         if results.is_empty() {
             return None;
         }
@@ -1161,13 +1243,57 @@ impl Hosting {
     pub fn is_empty(&self) -> bool {
         let Hosting {
             github,
+            gitlab,
             simple,
             order: _,
         } = &self;
-        github.is_none() && simple.is_none()
+        github.is_none() && gitlab.is_none() && simple.is_none()
     }
 }
 
+/// Package managers a release gets published to
+#[derive(Clone, Debug, Default, Deserialize, Serialize, JsonSchema)]
+pub struct Publishing {
+    /// Published to a Homebrew tap
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub homebrew: Option<HomebrewPublishing>,
+    /// Published to an npm registry
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub npm: Option<NpmPublishing>,
+}
+
+impl Publishing {
+    /// Gets whether there's nowhere to publish to
+    pub fn is_empty(&self) -> bool {
+        let Publishing { homebrew, npm } = self;
+        homebrew.is_none() && npm.is_none()
+    }
+}
+
+/// Publishing a Homebrew formula
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct HomebrewPublishing {
+    /// The GitHub repo of the tap, e.g. `"myowner/homebrew-tap"`
+    pub tap: String,
+    /// Whether to publish prereleases
+    #[serde(default)]
+    pub prereleases: bool,
+}
+
+/// Publishing an npm package
+#[derive(Clone, Debug, Deserialize, Serialize, JsonSchema)]
+pub struct NpmPublishing {
+    /// The registry to publish to, if not npmjs.com
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
+    /// Whether to publish prereleases
+    #[serde(default)]
+    pub prereleases: bool,
+}
+
 /// Information about dynamic libraries used by a binary
 #[derive(Clone, Default, Debug, Deserialize, Serialize, JsonSchema)]
 pub struct Linkage {
@@ -1191,6 +1317,15 @@ pub struct Linkage {
     #[serde(default)]
     #[serde(skip_serializing_if = "SortedSet::is_empty")]
     pub frameworks: SortedSet<Library>,
+    /// glibc symbol versions required by the binary (e.g. "GLIBC_2.34"),
+    /// mapped to the symbols which require them; only used on Linux
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub glibc_symbol_versions: BTreeMap<String, SortedSet<String>>,
+    /// The newest glibc version required by any of the binary's symbols; only used on Linux
+    #[serde(default)]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_glibc_version: Option<GlibcVersion>,
 }
 
 /// Represents the package manager a library was installed by
@@ -1215,6 +1350,9 @@ declare_strongly_typed_string! {
     /// A chocolatey package name, cf. <https://community.chocolatey.org/packages>
     pub struct ChocolateyPackageName => &ChocolateyPackageNameRef;
 
+    /// A dnf package name, cf. <https://packages.fedoraproject.org/>
+    pub struct DnfPackageName => &DnfPackageNameRef;
+
     /// A pip package name
     pub struct PipPackageName => &PipPackageNameRef;
 
@@ -1246,6 +1384,8 @@ impl Linkage {
             public_unmanaged,
             other,
             frameworks,
+            glibc_symbol_versions,
+            max_glibc_version,
         } = val;
         self.system.extend(system.iter().cloned());
         self.homebrew.extend(homebrew.iter().cloned());
@@ -1253,6 +1393,15 @@ impl Linkage {
             .extend(public_unmanaged.iter().cloned());
         self.other.extend(other.iter().cloned());
         self.frameworks.extend(frameworks.iter().cloned());
+        for (version, symbols) in glibc_symbol_versions {
+            self.glibc_symbol_versions
+                .entry(version.clone())
+                .or_default()
+                .extend(symbols.iter().cloned());
+        }
+        if max_glibc_version > &self.max_glibc_version {
+            self.max_glibc_version.clone_from(max_glibc_version);
+        }
     }
 }
 
@@ -86,17 +86,26 @@
 //! versioned in lockstep, where we pretend the packages have the given version.
 //! This mode just selects all the distable packages.
 
+use std::collections::BTreeMap;
 use std::fmt::Display;
 
+use axoprocess::Cmd;
+use axoproject::changelog::{changelog_from_commits, ChangelogInfo};
 use axoproject::PackageIdx;
 use axotag::{parse_tag, Package, PartialAnnouncementTag, ReleaseType};
-use cargo_dist_schema::{DistManifest, GithubHosting, TripleName, TripleNameRef};
+use camino::Utf8Path;
+use cargo_dist_schema::{
+    Artifact, ArtifactId, ChecksumExtension, ChecksumValue, DistManifest, GithubHosting, Release,
+    TripleName, TripleNameRef,
+};
 use itertools::Itertools;
 use semver::Version;
-use tracing::info;
+use serde::Serialize;
+use tracing::{info, warn};
 
 use crate::{
-    config::LibraryStyle,
+    backend::templates::{Templates, TEMPLATE_ANNOUNCE_GITHUB},
+    config::{v1::hosts::WorkspaceHostConfig, LibraryStyle},
     errors::{DistError, DistResult},
     platform::triple_to_display_name,
     DistGraphBuilder, SortedMap,
@@ -179,7 +188,10 @@ pub enum TagMode {
 }
 
 impl<'a> DistGraphBuilder<'a> {
-    pub(crate) fn compute_announcement_info(&mut self, announcing: &AnnouncementTag) {
+    pub(crate) fn compute_announcement_info(
+        &mut self,
+        announcing: &AnnouncementTag,
+    ) -> DistResult<()> {
         // Default to using the tag as a title
         self.manifest.announcement_title = Some(announcing.tag.clone());
         self.manifest.announcement_tag = Some(announcing.tag.clone());
@@ -187,12 +199,42 @@ impl<'a> DistGraphBuilder<'a> {
 
         // Refine the answers
         self.compute_announcement_changelog(announcing);
-        self.compute_announcement_github();
+        self.compute_announcement_github()
     }
 
     /// Try to compute changelogs for the announcement
     pub fn compute_announcement_changelog(&mut self, announcing: &AnnouncementTag) {
-        let info = if let Some(announcing_version) = &announcing.version {
+        // When announcing several apps at once, each one can have its own notes too
+        let multi_app = announcing.version.is_some() && announcing.rust_releases.len() > 1;
+        if multi_app {
+            self.compute_release_changelogs(announcing);
+        }
+
+        let info = if self.inner.config.hosts.changelog_from_commits {
+            // Every app just got the commits that touched it, listing all of them
+            // again at the top would only repeat those
+            if multi_app {
+                return;
+            }
+            // A single-package announcement only wants the commits that touched that package
+            let (version, path) = if let Some(announcing_version) = &announcing.version {
+                (axoproject::Version::Cargo(announcing_version.clone()), None)
+            } else if let Some(announcing_package) = announcing.package {
+                let package = self.workspaces.package(announcing_package);
+                let version = package
+                    .version
+                    .clone()
+                    .expect("cargo package without a version!?");
+                (version, Some(package.package_root.as_path()))
+            } else {
+                unreachable!("you're neither announcing a version or a package!?");
+            };
+            let Some(info) = self.changelog_from_commits(&announcing.tag, &version, path) else {
+                return;
+            };
+
+            info
+        } else if let Some(announcing_version) = &announcing.version {
             // Try to find the version we're announcing in the top level CHANGELOG/RELEASES
             let version = axoproject::Version::Cargo(announcing_version.clone());
             let Ok(Some(info)) = self
@@ -238,9 +280,83 @@ impl<'a> DistGraphBuilder<'a> {
         self.manifest.announcement_changelog = Some(clean_notes.into_owned());
     }
 
+    /// Try to find each announced app's own changelog entry, for announcements with several apps
+    fn compute_release_changelogs(&mut self, announcing: &AnnouncementTag) {
+        let root_changelog = self
+            .workspaces
+            .root_workspace()
+            .root_auto_includes
+            .changelog
+            .clone();
+        for release in &announcing.rust_releases {
+            let package = self.workspaces.package(release.package_idx);
+            let package_name = &package.name;
+            let version = package
+                .version
+                .as_ref()
+                .expect("cargo package without a version!?");
+            let info = if self.inner.config.hosts.changelog_from_commits {
+                self.changelog_from_commits(
+                    &announcing.tag,
+                    version,
+                    Some(package.package_root.as_path()),
+                )
+            } else if package.changelog_file.is_none() || package.changelog_file == root_changelog {
+                // This package just has the workspace's changelog, which the announcement already has
+                None
+            } else if let Ok(Some(info)) = package.changelog_for_version(version) {
+                Some(info)
+            } else {
+                info!("failed to find {version} in {package_name} changelogs, skipping its release notes");
+                None
+            };
+            let Some(info) = info else {
+                continue;
+            };
+
+            let out_release = self
+                .manifest
+                .ensure_release(package_name.clone(), version.to_string());
+            out_release.changelog_title = Some(info.title);
+            let clean_notes = newline_converter::dos2unix(&info.body);
+            out_release.changelog_body = Some(clean_notes.into_owned());
+        }
+    }
+
+    /// Try to generate a changelog from the Conventional Commits since the previous release
+    fn changelog_from_commits(
+        &self,
+        tag: &str,
+        version: &axoproject::Version,
+        path: Option<&Utf8Path>,
+    ) -> Option<ChangelogInfo> {
+        let Some(git) = &self.inner.tools.git else {
+            warn!("changelog-from-commits is enabled, but git isn't installed; skipping changelog generation");
+            return None;
+        };
+
+        match changelog_from_commits(&git.cmd, &self.inner.repo_dir, tag, version, path) {
+            Ok(Some(info)) => Some(info),
+            Ok(None) => {
+                info!(
+                    "no notable commits since the previous release, skipping changelog generation"
+                );
+                None
+            }
+            Err(e) => {
+                warn!("failed to generate a changelog from commits, skipping changelog generation: {e}");
+                None
+            }
+        }
+    }
+
     /// If we're publishing to Github, generate some Github notes
-    fn compute_announcement_github(&mut self) {
-        announcement_github(&mut self.manifest);
+    fn compute_announcement_github(&mut self) -> DistResult<()> {
+        announcement_github(
+            &self.inner.templates,
+            self.inner.config.hosts.announcement_template.as_deref(),
+            &mut self.manifest,
+        )
     }
 }
 
@@ -390,6 +506,17 @@ pub(crate) fn select_tag(
     // Make sure axotag agrees with what we did
     require_axotag_consistency(graph, &announcing, settings)?;
 
+    // Snapshots are announced under their rolling tag instead of their version's tag
+    let snapshot_tag = match &settings.tag {
+        TagMode::ForceMaxAndTimestamp => graph.inner.config.hosts.snapshot_tag.clone(),
+        TagMode::Force(tag) if is_snapshot_tag(graph, tag) => Some(tag.clone()),
+        _ => None,
+    };
+    if let Some(tag) = snapshot_tag {
+        info!("announcing snapshot under rolling tag: {}", tag);
+        announcing.tag = tag;
+    }
+
     // Ok, we're done, return the result
     let mut version = None;
     let mut package = None;
@@ -633,6 +760,12 @@ fn ensure_tag(
                 announcing.release = ReleaseType::Version("1.0.0-FAKEVER".parse().unwrap());
             }
         }
+        TagMode::Force(tag) if is_snapshot_tag(graph, tag) => {
+            // Later jobs of a snapshot release are only handed the rolling tag, so work out
+            // the version again. The snapshot-version template only looks at the commit,
+            // so this agrees with what the job that picked the tag computed.
+            force_snapshot_version(graph, releases, announcing)?;
+        }
         TagMode::Force(tag) => {
             // We've been given a tag (presumably from a previous plan step)
             // to force all distable packages to conform to, mutating their versions to match.
@@ -665,37 +798,175 @@ fn ensure_tag(
             }
         }
         TagMode::ForceMaxAndTimestamp => {
-            // We've just been told to release all distable packages at all cost.
-            //
-            // The biggest issue with this is that they might be different versions,
-            // but we need to make a tag that axotag agrees matches all the packages
-            // we're trying to release (because e.g. axo Releases will check that server-side).
-            //
-            // So we do the following set of transforms to ensure that.
-            let packages = releases.iter().map(|release| release.package_idx);
-            // First, get the maximum version of all distable packages, as a way to get
-            // a "reasonable" version. This will work for a fully unified version workspace,
-            // or for a workspace where packages that didn't change are allowed to not bump ver.
-            let mut forced_version = maximum_version(graph, packages.clone()).unwrap();
-            // Add a timestamp buildid to the version so this release is unique and a prerelease.
-            timestamp_version(&mut forced_version);
-            // Overwrite all distable packages to have this new version
-            overwrite_package_versions(graph, packages.clone(), &forced_version);
-            // Make a tag for that version
-            let tag = format!("v{forced_version}");
-            // Ask axotag to make sense of it all
-            *announcing = parse_tag_for_all_packages(graph, &tag)?;
+            force_snapshot_version(graph, releases, announcing)?;
         }
     }
 
     Ok(())
 }
 
+/// Force all distable packages to a unique prerelease version, for releasing untagged pushes
+fn force_snapshot_version(
+    graph: &mut DistGraphBuilder,
+    releases: &[ReleaseArtifacts],
+    announcing: &mut PartialAnnouncementTag,
+) -> DistResult<()> {
+    // We've just been told to release all distable packages at all cost.
+    //
+    // The biggest issue with this is that they might be different versions,
+    // but we need to make a tag that axotag agrees matches all the packages
+    // we're trying to release (because e.g. axo Releases will check that server-side).
+    //
+    // So we do the following set of transforms to ensure that.
+    let packages = releases.iter().map(|release| release.package_idx);
+    // First, get the maximum version of all distable packages, as a way to get
+    // a "reasonable" version. This will work for a fully unified version workspace,
+    // or for a workspace where packages that didn't change are allowed to not bump ver.
+    let mut forced_version = maximum_version(graph, packages.clone()).unwrap();
+    // Add a buildid to the version so this release is unique and a prerelease.
+    // Rolling snapshots get rebuilt by every job, so they need one that doesn't
+    // depend on when it's computed.
+    if let Some(template) = snapshot_version_template(&graph.inner.config.hosts) {
+        forced_version = render_snapshot_version(&template, |var| {
+            snapshot_version_var(graph, var, &forced_version)
+        })?;
+    } else {
+        timestamp_version(&mut forced_version);
+    }
+    // Overwrite all distable packages to have this new version
+    overwrite_package_versions(graph, packages.clone(), &forced_version);
+    // Make a tag for that version
+    let tag = format!("v{forced_version}");
+    // Ask axotag to make sense of it all
+    *announcing = parse_tag_for_all_packages(graph, &tag)?;
+
+    Ok(())
+}
+
+/// The snapshot-version template used for rolling snapshots, if none is given
+const DEFAULT_SNAPSHOT_VERSION: &str = "{version}-nightly.{date}.{commit_count}";
+
+/// The snapshot-version template untagged releases use, if any
+fn snapshot_version_template(hosts: &WorkspaceHostConfig) -> Option<String> {
+    hosts.snapshot_version.clone().or_else(|| {
+        hosts
+            .snapshot_tag
+            .is_some()
+            .then(|| DEFAULT_SNAPSHOT_VERSION.to_owned())
+    })
+}
+
+/// Whether announcing needs the repo's full git history, rather than a shallow clone
+/// of the commit being released (as CI usually makes)
+pub(crate) fn needs_full_git_history(hosts: &WorkspaceHostConfig) -> bool {
+    hosts.changelog_from_commits
+        || snapshot_version_template(hosts)
+            .is_some_and(|template| template.contains("{commit_count}"))
+}
+
+/// Whether this is the rolling tag snapshots are announced under
+fn is_snapshot_tag(graph: &DistGraphBuilder, tag: &str) -> bool {
+    graph.inner.config.hosts.snapshot_tag.as_deref() == Some(tag)
+}
+
+/// Fill in the variables of a snapshot-version template
+///
+/// `var` gets the value of each variable the template uses, or None if it doesn't exist.
+fn render_snapshot_version(
+    template: &str,
+    mut var: impl FnMut(&str) -> DistResult<Option<String>>,
+) -> DistResult<Version> {
+    let mut rendered = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let Some(len) = rest[start..].find('}') else {
+            break;
+        };
+        rendered.push_str(&rest[..start]);
+        let name = &rest[start + 1..start + len];
+        let Some(value) = var(name)? else {
+            return Err(DistError::SnapshotVersionUnknownVar {
+                template: template.to_owned(),
+                var: name.to_owned(),
+            });
+        };
+        rendered.push_str(&value);
+        rest = &rest[start + len + 1..];
+    }
+    rendered.push_str(rest);
+
+    rendered
+        .parse()
+        .map_err(|details| DistError::SnapshotVersionInvalid {
+            template: template.to_owned(),
+            version: rendered,
+            details,
+        })
+}
+
+/// Get the value of a snapshot-version variable
+fn snapshot_version_var(
+    graph: &DistGraphBuilder,
+    var: &str,
+    version: &Version,
+) -> DistResult<Option<String>> {
+    let git_args: &[&str] = match var {
+        "version" => return Ok(Some(version.to_string())),
+        // Use the commit's date rather than today's, so every job agrees on it
+        "date" => &["log", "-1", "--format=%cd", "--date=format-local:%Y%m%d"],
+        "short_sha" => &["rev-parse", "--short", "HEAD"],
+        "commit_count" => &["rev-list", "--count", "HEAD"],
+        "branch" => &["rev-parse", "--abbrev-ref", "HEAD"],
+        _ => return Ok(None),
+    };
+    let needs_git = || DistError::SnapshotVersionNeedsGit {
+        var: var.to_owned(),
+    };
+
+    let git = graph.inner.tools.git.as_ref().ok_or_else(needs_git)?;
+    let mut cmd = Cmd::new(&git.cmd, "get info for snapshot-version");
+    for arg in git_args {
+        cmd.arg(arg);
+    }
+    let output = cmd
+        .env("TZ", "UTC")
+        .current_dir(&graph.inner.repo_dir)
+        .log(None)
+        .output()
+        .map_err(|_| needs_git())?;
+    let mut value = String::from_utf8_lossy(&output.stdout).trim().to_owned();
+
+    if var == "branch" {
+        // CI usually checks out a detached HEAD, but tells us what branch it's for
+        if value == "HEAD" {
+            value = ["GITHUB_HEAD_REF", "GITHUB_REF_NAME", "CI_COMMIT_REF_NAME"]
+                .into_iter()
+                .filter_map(|name| std::env::var(name).ok())
+                .find(|name| !name.is_empty())
+                .ok_or_else(needs_git)?;
+        }
+        // Branches can contain all sorts of things a version can't
+        value = value
+            .chars()
+            .map(|c| if c.is_ascii_alphanumeric() { c } else { '-' })
+            .collect();
+    }
+    if value.is_empty() {
+        return Err(needs_git());
+    }
+    if var == "short_sha" {
+        // Like git describe, so a sha of all digits with a leading zero can
+        // still go in a prerelease
+        value = format!("g{value}");
+    }
+
+    Ok(Some(value))
+}
+
 /// Modify the version to include a timestamp in the prerelease portion
+///
+/// This is what untagged releases get if they don't have a snapshot-version template.
 fn timestamp_version(version: &mut Version) {
-    // FIXME: it would be nice if this was configurable with a template
-    // the current template here is `{version}-alpha.{timestamp}`.
-    // Although as actually uses this is `{max_version}-alpha.{timestamp}`.
     if version.pre.is_empty() {
         // FIXME?: should we actually unconditionally do this?
         version.pre = semver::Prerelease::new("alpha").unwrap();
@@ -703,7 +974,6 @@ fn timestamp_version(version: &mut Version) {
 
     let now = std::time::SystemTime::now();
     let secs = now.duration_since(std::time::UNIX_EPOCH).unwrap().as_secs();
-    // FIXME?: use chrono for better format
     version.pre = semver::Prerelease::new(&format!("{}.{}", version.pre, secs)).unwrap();
 }
 
@@ -844,44 +1114,128 @@ pub fn announcement_axodotdev(manifest: &DistManifest) -> String {
     format!("# {title}\n\n{body}")
 }
 
+/// Everything a template for the GitHub/GitLab Release body gets to work with
+#[derive(Debug, Serialize)]
+struct AnnouncementContext<'a> {
+    /// The full dist-manifest, for anything not covered by the rest of this
+    manifest: &'a DistManifest,
+    /// The release notes for the whole announcement
+    changelog: Option<&'a str>,
+    /// The releases that should appear in the body
+    releases: Vec<AnnouncedRelease<'a>>,
+}
+
+/// A release as it should appear in the GitHub/GitLab Release body
+#[derive(Debug, Serialize)]
+struct AnnouncedRelease<'a> {
+    /// The release as it appears in the manifest
+    release: &'a Release,
+    /// The name of the app
+    app_name: &'a str,
+    /// The version of the app
+    app_version: &'a str,
+    /// How to refer to the app
+    display_name: &'a str,
+    /// The app's display name and version, for headings
+    heading: String,
+    /// The title of the app's own release notes, if it has any
+    changelog_title: Option<&'a str>,
+    /// The app's own release notes, if it has any
+    changelog: Option<&'a str>,
+    /// Installers that are run with a one-liner
+    installers: Vec<AnnouncedInstaller<'a>>,
+    /// The url files in this release can be downloaded from
+    download_url: Option<String>,
+    /// The archives, installers, and symbols that can be downloaded
+    artifacts: Vec<AnnouncedArtifact<'a>>,
+    /// Where the attestations for these artifacts live, if there are any
+    attestations: Option<AnnouncedAttestations<'a>>,
+}
+
+/// An installer that's run with a one-liner
+#[derive(Debug, Serialize)]
+struct AnnouncedInstaller<'a> {
+    /// What the installer is
+    description: &'a str,
+    /// The command to run it
+    hint: &'a str,
+}
+
+/// A file that can be downloaded from a release
+#[derive(Debug, Serialize)]
+struct AnnouncedArtifact<'a> {
+    /// The artifact as it appears in the manifest
+    artifact: &'a Artifact,
+    /// The name of the file (None if it's purely informative)
+    name: Option<&'a ArtifactId>,
+    /// Where to download the file
+    url: Option<String>,
+    /// The platforms the file is for, in human-readable form
+    platform: String,
+    /// The name of the file containing the file's checksum
+    checksum_name: Option<&'a ArtifactId>,
+    /// Where to download the file containing the file's checksum
+    checksum_url: Option<String>,
+    /// The file's checksums, keyed by algorithm
+    checksums: &'a BTreeMap<ChecksumExtension, ChecksumValue>,
+    /// The name of the file containing the file's detached signature
+    signature_name: Option<&'a ArtifactId>,
+    /// Where to download the file containing the file's detached signature
+    signature_url: Option<String>,
+}
+
+/// Where to find GitHub Artifact Attestations
+#[derive(Debug, Serialize)]
+struct AnnouncedAttestations<'a> {
+    owner: &'a str,
+    repo: &'a str,
+}
+
 /// If we're publishing to Github, generate the announcement body
 ///
+/// This uses the user's template if they have one, otherwise our built-in layout.
+///
 /// Currently mutates the manifest, in the future it should output it
-pub fn announcement_github(manifest: &mut DistManifest) {
-    use std::fmt::Write;
-
-    let mut gh_body = String::new();
+pub fn announcement_github(
+    templates: &Templates,
+    template: Option<&Utf8Path>,
+    manifest: &mut DistManifest,
+) -> DistResult<()> {
+    let Some(context) = announcement_context(manifest) else {
+        return Ok(());
+    };
+    let gh_body = if let Some(template) = template {
+        templates.render_user_file_to_clean_string(template, &context)?
+    } else {
+        templates.render_file_to_clean_string(TEMPLATE_ANNOUNCE_GITHUB, &context)?
+    };
 
-    // add release notes
-    if let Some(changelog) = manifest.announcement_changelog.as_ref() {
-        gh_body.push_str("## Release Notes\n\n");
-        gh_body.push_str(changelog);
-        gh_body.push_str("\n\n");
-    }
+    info!("successfully generated github release body!");
+    manifest.announcement_github_body = Some(gh_body);
+    Ok(())
+}
 
-    // Add the contents of each Release to the body
-    let mut announcing_github = false;
+/// Gather up everything that should go in the GitHub/GitLab Release body
+///
+/// Returns None if there's nothing to announce there.
+fn announcement_context(manifest: &DistManifest) -> Option<AnnouncementContext<'_>> {
+    let mut releases = vec![];
     for release in &manifest.releases {
         // Only bother if there's actually github hosting
-        if release.hosting.github.is_none() {
+        // (GitLab Releases render the same markdown, so reuse it there too)
+        if release.hosting.github.is_none() && release.hosting.gitlab.is_none() {
             continue;
         }
         // Skip "hidden" apps
         if !release.display.unwrap_or(true) {
             continue;
         }
-        announcing_github = true;
 
         let display_name = release.display_name.as_ref().unwrap_or(&release.app_name);
-        let heading_suffix = format!("{} {}", display_name, release.app_version);
-
-        // Delineate releases if there's more than 1
-        if manifest.releases.len() > 1 {
-            writeln!(gh_body, "# {heading_suffix}\n").unwrap();
-        }
+        let heading = format!("{} {}", display_name, release.app_version);
 
         // Sort out all the artifacts in this Release
-        let mut global_installers = vec![];
+        let mut installers = vec![];
         let mut local_installers = vec![];
         let mut bundles = vec![];
         let mut symbols = vec![];
@@ -891,16 +1245,17 @@ pub fn announcement_github(manifest: &mut DistManifest) {
                 cargo_dist_schema::ArtifactKind::ExecutableZip => bundles.push(artifact),
                 cargo_dist_schema::ArtifactKind::Symbols => symbols.push(artifact),
                 cargo_dist_schema::ArtifactKind::Installer => {
-                    if let (Some(desc), Some(hint)) =
+                    if let (Some(description), Some(hint)) =
                         (&artifact.description, &artifact.install_hint)
                     {
-                        global_installers.push((desc, hint));
+                        installers.push(AnnouncedInstaller { description, hint });
                     } else {
                         local_installers.push(artifact);
                     }
                 }
-                cargo_dist_schema::ArtifactKind::Checksum => {
-                    // Do Nothing (will be included with the artifact it checksums)
+                cargo_dist_schema::ArtifactKind::Checksum
+                | cargo_dist_schema::ArtifactKind::Signature => {
+                    // Do Nothing (will be included with the artifact it checksums/signs)
                 }
                 cargo_dist_schema::ArtifactKind::Unknown => {
                     // Do nothing
@@ -911,14 +1266,6 @@ pub fn announcement_github(manifest: &mut DistManifest) {
             }
         }
 
-        if !global_installers.is_empty() {
-            writeln!(gh_body, "## Install {heading_suffix}\n").unwrap();
-            for (desc, hint) in global_installers {
-                writeln!(&mut gh_body, "### {}\n", desc).unwrap();
-                writeln!(&mut gh_body, "```sh\n{}\n```\n", hint).unwrap();
-            }
-        }
-
         let mut other_artifacts: Vec<_> = bundles
             .into_iter()
             .chain(local_installers)
@@ -932,60 +1279,71 @@ pub fn announcement_github(manifest: &mut DistManifest) {
             .unwrap_or_default()
             .into_iter()
             .next();
-        if !other_artifacts.is_empty() {
-            if let Some(download_url) = download_url {
-                writeln!(gh_body, "## Download {heading_suffix}\n",).unwrap();
-                gh_body.push_str("|  File  | Platform | Checksum |\n");
-                gh_body.push_str("|--------|----------|----------|\n");
-                for artifact in &other_artifacts {
-                    // Artifacts with no name do not exist as files, and should have had install-hints
-                    let Some(name) = &artifact.name else {
-                        continue;
-                    };
-
-                    let artifact_download_url = format!("{download_url}/{name}");
-                    let download = format!("[{name}]({artifact_download_url})");
-                    let checksum = if let Some(checksum_name) = &artifact.checksum {
-                        let checksum_download_url = format!("{download_url}/{checksum_name}");
-                        format!("[checksum]({checksum_download_url})")
-                    } else {
-                        String::new()
-                    };
-                    let mut triple = artifact
-                        .target_triples
-                        .iter()
-                        .map(|t| triple_to_display_name(t).unwrap_or_else(|| t.as_str()))
-                        .join(", ");
-                    if triple.is_empty() {
-                        triple = "Unknown".to_string();
-                    }
-                    writeln!(&mut gh_body, "| {download} | {triple} | {checksum} |").unwrap();
+
+        let attestations = if !other_artifacts.is_empty() && manifest.github_attestations {
+            release
+                .hosting
+                .github
+                .as_ref()
+                .map(|GithubHosting { owner, repo, .. }| AnnouncedAttestations { owner, repo })
+        } else {
+            None
+        };
+
+        let artifacts = other_artifacts
+            .into_iter()
+            .map(|artifact| {
+                let url = |name: &ArtifactId| {
+                    download_url
+                        .as_ref()
+                        .map(|download_url| format!("{download_url}/{name}"))
+                };
+                let mut platform = artifact
+                    .target_triples
+                    .iter()
+                    .map(|t| triple_to_display_name(t).unwrap_or_else(|| t.as_str()))
+                    .join(", ");
+                if platform.is_empty() {
+                    platform = "Unknown".to_string();
                 }
-                writeln!(&mut gh_body).unwrap();
-            }
-        }
+                AnnouncedArtifact {
+                    artifact,
+                    name: artifact.name.as_ref(),
+                    url: artifact.name.as_ref().and_then(url),
+                    platform,
+                    checksum_name: artifact.checksum.as_ref(),
+                    checksum_url: artifact.checksum.as_ref().and_then(url),
+                    checksums: &artifact.checksums,
+                    signature_name: artifact.signature.as_ref(),
+                    signature_url: artifact.signature.as_ref().and_then(url),
+                }
+            })
+            .collect();
 
-        if !other_artifacts.is_empty() && manifest.github_attestations {
-            if let Some(GithubHosting { owner, repo, .. }) = &release.hosting.github {
-                writeln!(&mut gh_body, "## Verifying GitHub Artifact Attestations\n",).unwrap();
-                writeln!(&mut gh_body, "The artifacts in this release have attestations generated with GitHub Artifact Attestations. These can be verified by using the [GitHub CLI](https://cli.github.com/manual/gh_attestation_verify):").unwrap();
-                writeln!(
-                    &mut gh_body,
-                    "```sh\ngh attestation verify <file-path of downloaded artifact> --repo {owner}/{repo}\n```\n",
-                ).unwrap();
-                writeln!(&mut gh_body, "You can also download the attestation from [GitHub](https://github.com/{owner}/{repo}/attestations) and verify against that directly:").unwrap();
-                writeln!(
-                    &mut gh_body,
-                    "```sh\ngh attestation verify <file-path of downloaded artifact> --bundle <file-path of downloaded attestation>\n```\n",
-                ).unwrap();
-            }
-        }
+        releases.push(AnnouncedRelease {
+            release,
+            app_name: &release.app_name,
+            app_version: &release.app_version,
+            display_name,
+            heading,
+            changelog_title: release.changelog_title.as_deref(),
+            changelog: release.changelog_body.as_deref(),
+            installers,
+            download_url,
+            artifacts,
+            attestations,
+        });
     }
 
-    if announcing_github {
-        info!("successfully generated github release body!");
-        manifest.announcement_github_body = Some(gh_body);
+    if releases.is_empty() {
+        return None;
     }
+
+    Some(AnnouncementContext {
+        manifest,
+        changelog: manifest.announcement_changelog.as_deref(),
+        releases,
+    })
 }
 
 /// Create a key for Properly sorting a list of target triples
@@ -1030,9 +1388,151 @@ fn sortable_triple(triple: &TripleNameRef) -> Vec<String> {
 
 #[cfg(test)]
 mod tests {
-    use cargo_dist_schema::TripleNameRef;
+    use camino::Utf8PathBuf;
+    use cargo_dist_schema::{DistManifest, TripleNameRef};
+
+    use super::{
+        announcement_github, render_snapshot_version, sortable_triple, DEFAULT_SNAPSHOT_VERSION,
+    };
+    use crate::backend::templates::Templates;
+    use crate::errors::{DistError, DistResult};
+
+    fn manifest() -> DistManifest {
+        serde_json::from_str(
+            r#"{
+                "announcement_tag": "v1.0.0",
+                "announcement_changelog": "Fixed everything",
+                "releases": [{
+                    "app_name": "app",
+                    "app_version": "1.0.0",
+                    "artifacts": ["app-installer.sh", "app-x86_64-unknown-linux-gnu.tar.xz"],
+                    "hosting": {
+                        "github": {
+                            "artifact_base_url": "https://github.com",
+                            "artifact_download_path": "/me/app/releases/download/v1.0.0",
+                            "owner": "me",
+                            "repo": "app"
+                        }
+                    }
+                }],
+                "artifacts": {
+                    "app-installer.sh": {
+                        "name": "app-installer.sh",
+                        "kind": "installer",
+                        "install_hint": "curl -sSf https://example.com | sh",
+                        "description": "Install prebuilt binaries via shell script"
+                    },
+                    "app-x86_64-unknown-linux-gnu.tar.xz": {
+                        "name": "app-x86_64-unknown-linux-gnu.tar.xz",
+                        "kind": "executable-zip",
+                        "target_triples": ["x86_64-unknown-linux-gnu"],
+                        "checksum": "app-x86_64-unknown-linux-gnu.tar.xz.sha256",
+                        "checksums": { "sha256": "abc123" }
+                    }
+                }
+            }"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn renders_builtin_announcement() {
+        let templates = Templates::new().unwrap();
+        let mut manifest = manifest();
+        announcement_github(&templates, None, &mut manifest).unwrap();
+        assert_eq!(
+            manifest.announcement_github_body.unwrap(),
+            r#"## Release Notes
+
+Fixed everything
+
+## Install app 1.0.0
+
+### Install prebuilt binaries via shell script
+
+```sh
+curl -sSf https://example.com | sh
+```
+
+## Download app 1.0.0
+
+|  File  | Platform | Checksum |
+|--------|----------|----------|
+| [app-x86_64-unknown-linux-gnu.tar.xz](https://github.com/me/app/releases/download/v1.0.0/app-x86_64-unknown-linux-gnu.tar.xz) | x64 Linux | [checksum](https://github.com/me/app/releases/download/v1.0.0/app-x86_64-unknown-linux-gnu.tar.xz.sha256) |
+
+"#
+        );
+    }
+
+    #[test]
+    fn renders_per_app_changelogs() {
+        let mut manifest: DistManifest = serde_json::from_str(
+            r#"{
+                "announcement_tag": "v1.2.0",
+                "releases": [{
+                    "app_name": "one",
+                    "app_version": "1.2.0",
+                    "hosting": { "github": {
+                        "artifact_base_url": "https://github.com",
+                        "artifact_download_path": "/me/repo/releases/download/v1.2.0",
+                        "owner": "me",
+                        "repo": "repo"
+                    } },
+                    "changelog_title": "1.2.0 - 2026-10-01",
+                    "changelog_body": "one got faster"
+                }, {
+                    "app_name": "two",
+                    "app_version": "1.2.0",
+                    "hosting": { "github": {
+                        "artifact_base_url": "https://github.com",
+                        "artifact_download_path": "/me/repo/releases/download/v1.2.0",
+                        "owner": "me",
+                        "repo": "repo"
+                    } },
+                    "changelog_title": "Version 1.2.0 (the big one)",
+                    "changelog_body": "two got smaller"
+                }]
+            }"#,
+        )
+        .unwrap();
+        let templates = Templates::new().unwrap();
+        announcement_github(&templates, None, &mut manifest).unwrap();
+        assert_eq!(
+            manifest.announcement_github_body.unwrap(),
+            r#"# one 1.2.0
+
+## 1.2.0 - 2026-10-01
+
+one got faster
+
+# two 1.2.0
+
+## Version 1.2.0 (the big one)
+
+two got smaller
+
+"#
+        );
+    }
 
-    use super::sortable_triple;
+    #[test]
+    fn renders_user_announcement() {
+        let tmp = temp_dir::TempDir::new().unwrap();
+        let template = Utf8PathBuf::from_path_buf(tmp.child("release.md.j2")).unwrap();
+        axoasset::LocalAsset::write_new(
+            "{{ manifest.announcement_tag }}\n{% for release in releases %}{% for artifact in release.artifacts %}{{ artifact.name }} {{ artifact.checksums.sha256 }} {{ artifact.url }}\n{% endfor %}{% endfor %}",
+            &template,
+        )
+        .unwrap();
+
+        let templates = Templates::new().unwrap();
+        let mut manifest = manifest();
+        announcement_github(&templates, Some(&template), &mut manifest).unwrap();
+        assert_eq!(
+            manifest.announcement_github_body.unwrap(),
+            "v1.0.0\napp-x86_64-unknown-linux-gnu.tar.xz abc123 https://github.com/me/app/releases/download/v1.0.0/app-x86_64-unknown-linux-gnu.tar.xz\n"
+        );
+    }
     #[test]
     fn sort_platforms() {
         let mut targets = vec![
@@ -1084,4 +1584,42 @@ mod tests {
             ]
         );
     }
+
+    fn snapshot_var(var: &str) -> DistResult<Option<String>> {
+        let value = match var {
+            "version" => "1.2.0",
+            "date" => "20261017",
+            "short_sha" => "g0123456",
+            "commit_count" => "345",
+            "branch" => "main",
+            _ => return Ok(None),
+        };
+        Ok(Some(value.to_owned()))
+    }
+
+    #[test]
+    fn renders_snapshot_version() {
+        let version = render_snapshot_version(DEFAULT_SNAPSHOT_VERSION, snapshot_var).unwrap();
+        assert_eq!(version.to_string(), "1.2.0-nightly.20261017.345");
+        assert!(!version.pre.is_empty());
+
+        let version =
+            render_snapshot_version("{version}-{branch}.{date}+{short_sha}", snapshot_var).unwrap();
+        assert_eq!(version.to_string(), "1.2.0-main.20261017+g0123456");
+
+        let version = render_snapshot_version("{version}-dev.{short_sha}", snapshot_var).unwrap();
+        assert_eq!(version.to_string(), "1.2.0-dev.g0123456");
+    }
+
+    #[test]
+    fn rejects_bad_snapshot_versions() {
+        let err = render_snapshot_version("{version}-{build}", snapshot_var).unwrap_err();
+        assert!(matches!(err, DistError::SnapshotVersionUnknownVar { var, .. } if var == "build"));
+
+        // Forgetting the version leaves something that isn't a version at all
+        let err = render_snapshot_version("nightly-{date}", snapshot_var).unwrap_err();
+        assert!(
+            matches!(err, DistError::SnapshotVersionInvalid { version, .. } if version == "nightly-20261017")
+        );
+    }
 }
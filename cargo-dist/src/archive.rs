@@ -0,0 +1,395 @@
+//! Reproducible archives
+//!
+//! By default archives are made by axoasset, which copies whatever the filesystem says
+//! about each file (mtimes, owners, permissions, directory order) into the archive. That
+//! means two builds of the same commit never have the same checksum.
+//!
+//! When `reproducible-archives = true` is set, we instead write archives ourselves with:
+//!
+//! * entries sorted by path
+//! * every entry stamped with the same time (`SOURCE_DATE_EPOCH`, or the commit time of the tag)
+//! * uid/gid 0 with no user/group names
+//! * permissions normalized to 755 (directories and executables) or 644 (everything else)
+//! * no timestamps or OS info in the compressor's own headers
+
+use std::{
+    fs::File,
+    io::{self, Write},
+};
+
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::{
+    config::{CompressionImpl, ZipStyle},
+    errors::{DistError, DistResult},
+    Tool,
+};
+
+/// Get the time reproducible archive entries should be stamped with
+///
+/// This is `SOURCE_DATE_EPOCH` if it's set, otherwise the commit time of the tag
+/// being announced (or HEAD, if the tag doesn't exist yet).
+pub fn source_date_epoch(git: Option<&Tool>, repo_dir: &Utf8Path, tag: &str) -> DistResult<u64> {
+    if let Ok(value) = std::env::var("SOURCE_DATE_EPOCH") {
+        return value
+            .trim()
+            .parse()
+            .map_err(|_| DistError::InvalidSourceDateEpoch { value });
+    }
+
+    if let Some(git) = git {
+        for rev in [format!("{tag}^{{commit}}"), "HEAD".to_owned()] {
+            let output = Cmd::new(&git.cmd, "get the commit time")
+                .arg("log")
+                .arg("-1")
+                .arg("--format=%ct")
+                .arg(&rev)
+                .arg("--")
+                .current_dir(repo_dir)
+                .log(None)
+                .output();
+            if let Ok(output) = output {
+                if let Ok(time) = String::from_utf8_lossy(&output.stdout).trim().parse() {
+                    return Ok(time);
+                }
+            }
+        }
+    }
+
+    Err(DistError::NoSourceDateEpoch {
+        tag: tag.to_owned(),
+    })
+}
+
+/// Zip up a directory reproducibly, stamping every entry with `mtime`
+pub fn reproducible_archive(
+    src_path: &Utf8Path,
+    dest_path: &Utf8Path,
+    zip_style: &ZipStyle,
+    with_root: Option<&Utf8Path>,
+    mtime: u64,
+) -> DistResult<()> {
+    write_archive(src_path, dest_path, zip_style, with_root, mtime).map_err(|details| {
+        DistError::ReproducibleArchive {
+            dest_path: dest_path.to_owned(),
+            details,
+        }
+    })
+}
+
+/// A file or directory that will go in an archive
+struct Entry {
+    /// The path in the archive, with `/` separators
+    name: String,
+    /// The file to read the contents from (None for directories)
+    file: Option<Utf8PathBuf>,
+    /// The normalized permissions
+    mode: u32,
+}
+
+const DIR_MODE: u32 = 0o755;
+const EXECUTABLE_MODE: u32 = 0o755;
+const FILE_MODE: u32 = 0o644;
+
+fn collect_entries(src_path: &Utf8Path, with_root: Option<&Utf8Path>) -> io::Result<Vec<Entry>> {
+    let mut entries = vec![];
+
+    // If there's a root prefix, add entries for all of its components
+    let mut prefix = vec![];
+    if let Some(root) = with_root {
+        for component in root.components() {
+            prefix.push(component.as_str().to_owned());
+            entries.push(Entry {
+                name: prefix.join("/"),
+                file: None,
+                mode: DIR_MODE,
+            });
+        }
+    }
+
+    let mut dirs = vec![(src_path.to_owned(), prefix)];
+    while let Some((dir, prefix)) = dirs.pop() {
+        for dir_entry in dir.read_dir_utf8()? {
+            let dir_entry = dir_entry?;
+            let path = dir_entry.path().to_owned();
+            let mut name = prefix.clone();
+            name.push(dir_entry.file_name().to_owned());
+
+            // Follow symlinks, like axoasset does
+            let metadata = std::fs::metadata(&path)?;
+            if metadata.is_dir() {
+                entries.push(Entry {
+                    name: name.join("/"),
+                    file: None,
+                    mode: DIR_MODE,
+                });
+                dirs.push((path, name));
+            } else {
+                entries.push(Entry {
+                    name: name.join("/"),
+                    mode: if is_executable(&metadata) {
+                        EXECUTABLE_MODE
+                    } else {
+                        FILE_MODE
+                    },
+                    file: Some(path),
+                });
+            }
+        }
+    }
+
+    entries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(entries)
+}
+
+#[cfg(unix)]
+fn is_executable(metadata: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    metadata.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_metadata: &std::fs::Metadata) -> bool {
+    false
+}
+
+fn write_archive(
+    src_path: &Utf8Path,
+    dest_path: &Utf8Path,
+    zip_style: &ZipStyle,
+    with_root: Option<&Utf8Path>,
+    mtime: u64,
+) -> io::Result<()> {
+    let entries = collect_entries(src_path, with_root)?;
+    let file = File::create(dest_path)?;
+    match zip_style {
+        ZipStyle::Zip => write_zip(file, &entries, mtime)?,
+        ZipStyle::Tar(CompressionImpl::Gzip) => {
+            let level = compression_level("AXOASSET_GZ_LEVEL", 6);
+            // GzBuilder leaves the header's mtime at 0 and its OS byte as "unknown"
+            let encoder = flate2::GzBuilder::new().write(file, flate2::Compression::new(level));
+            write_tar(encoder, &entries, mtime)?.finish()?;
+        }
+        ZipStyle::Tar(CompressionImpl::Xzip) => {
+            let level = compression_level("AXOASSET_XZ_LEVEL", 9);
+            let encoder = xz2::write::XzEncoder::new(file, level);
+            write_tar(encoder, &entries, mtime)?.finish()?;
+        }
+        ZipStyle::Tar(CompressionImpl::Zstd) => {
+            let level = compression_level("AXOASSET_ZSTD_LEVEL", 3);
+            let encoder = zstd::stream::Encoder::new(file, level)?;
+            write_tar(encoder, &entries, mtime)?.finish()?;
+        }
+        ZipStyle::TempDir => {
+            // no-op
+        }
+    }
+    Ok(())
+}
+
+/// Use the same compression levels (and overrides) as axoasset
+fn compression_level<T: std::str::FromStr>(env_var: &str, default: T) -> T {
+    std::env::var(env_var)
+        .ok()
+        .and_then(|val| val.parse().ok())
+        .unwrap_or(default)
+}
+
+fn write_tar<W: Write>(out: W, entries: &[Entry], mtime: u64) -> io::Result<W> {
+    let mut tar = tar::Builder::new(out);
+    for entry in entries {
+        let mut header = tar::Header::new_gnu();
+        header.set_mode(entry.mode);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mtime(mtime);
+        if let Some(path) = &entry.file {
+            let file = File::open(path)?;
+            header.set_entry_type(tar::EntryType::Regular);
+            header.set_size(file.metadata()?.len());
+            tar.append_data(&mut header, &entry.name, file)?;
+        } else {
+            header.set_entry_type(tar::EntryType::Directory);
+            header.set_size(0);
+            tar.append_data(&mut header, format!("{}/", entry.name), io::empty())?;
+        }
+    }
+    tar.into_inner()
+}
+
+fn write_zip(file: File, entries: &[Entry], mtime: u64) -> io::Result<()> {
+    use zip::{write::SimpleFileOptions, CompressionMethod};
+
+    let options = SimpleFileOptions::default()
+        .compression_method(CompressionMethod::Deflated)
+        .last_modified_time(zip_time(mtime));
+
+    let mut zip = zip::ZipWriter::new(file);
+    for entry in entries {
+        let options = options.unix_permissions(entry.mode);
+        if let Some(path) = &entry.file {
+            zip.start_file(entry.name.as_str(), options)?;
+            io::copy(&mut File::open(path)?, &mut zip)?;
+        } else {
+            zip.add_directory(entry.name.as_str(), options)?;
+        }
+    }
+    zip.finish()?;
+    Ok(())
+}
+
+/// Convert a unix timestamp to a zip (DOS) timestamp
+///
+/// DOS timestamps can't go earlier than 1980, so anything before that is clamped.
+fn zip_time(mtime: u64) -> zip::DateTime {
+    use chrono::{Datelike, Timelike};
+
+    i64::try_from(mtime)
+        .ok()
+        .and_then(|secs| chrono::DateTime::from_timestamp(secs, 0))
+        .and_then(|time| {
+            zip::DateTime::from_date_and_time(
+                time.year().try_into().ok()?,
+                time.month() as u8,
+                time.day() as u8,
+                time.hour() as u8,
+                time.minute() as u8,
+                time.second() as u8,
+            )
+            .ok()
+        })
+        .unwrap_or_default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use sha2::Digest;
+    use std::time::{Duration, SystemTime};
+
+    const EPOCH: u64 = 1_700_000_000;
+
+    /// Make the same tree, but with the files written in a different order,
+    /// with different mtimes and different (non-executable) permissions
+    fn make_tree(root: &Utf8Path, reversed: bool, mtime: SystemTime, readonly: bool) {
+        let mut files = vec![
+            ("README.md", "hello\n", false),
+            ("bin/axolotlsay", "#!/bin/sh\necho hi\n", true),
+            ("share/doc/axolotlsay/LICENSE", "MIT\n", false),
+        ];
+        if reversed {
+            files.reverse();
+        }
+        for (name, contents, executable) in files {
+            let path = root.join(name);
+            std::fs::create_dir_all(path.parent().unwrap()).unwrap();
+            std::fs::write(&path, contents).unwrap();
+            File::options()
+                .write(true)
+                .open(&path)
+                .unwrap()
+                .set_modified(mtime)
+                .unwrap();
+            #[cfg(unix)]
+            {
+                use std::os::unix::fs::PermissionsExt;
+                let mode = match (executable, readonly) {
+                    (true, _) => 0o755,
+                    (false, true) => 0o600,
+                    (false, false) => 0o664,
+                };
+                std::fs::set_permissions(&path, std::fs::Permissions::from_mode(mode)).unwrap();
+            }
+            #[cfg(not(unix))]
+            let _ = (executable, readonly);
+        }
+    }
+
+    fn hash(path: &Utf8Path) -> Vec<u8> {
+        sha2::Sha256::digest(std::fs::read(path).unwrap()).to_vec()
+    }
+
+    #[test]
+    fn archives_are_reproducible() {
+        let (_tmp, tmp_root) = crate::create_tmp().unwrap();
+        let first = tmp_root.join("first");
+        let second = tmp_root.join("second");
+        make_tree(&first, false, SystemTime::UNIX_EPOCH, false);
+        make_tree(
+            &second,
+            true,
+            SystemTime::UNIX_EPOCH + Duration::from_secs(2_000_000_000),
+            true,
+        );
+
+        for (i, zip_style) in [
+            ZipStyle::Zip,
+            ZipStyle::Tar(CompressionImpl::Gzip),
+            ZipStyle::Tar(CompressionImpl::Xzip),
+            ZipStyle::Tar(CompressionImpl::Zstd),
+        ]
+        .iter()
+        .enumerate()
+        {
+            let root = Utf8Path::new("axolotlsay-x86_64-unknown-linux-gnu");
+            let first_archive = tmp_root.join(format!("first-{i}{}", zip_style.ext()));
+            let second_archive = tmp_root.join(format!("second-{i}{}", zip_style.ext()));
+            reproducible_archive(&first, &first_archive, zip_style, Some(root), EPOCH).unwrap();
+            reproducible_archive(&second, &second_archive, zip_style, Some(root), EPOCH).unwrap();
+            assert_eq!(
+                hash(&first_archive),
+                hash(&second_archive),
+                "{zip_style:?} archives differ"
+            );
+        }
+    }
+
+    #[test]
+    fn tar_entries_are_normalized() {
+        let (_tmp, tmp_root) = crate::create_tmp().unwrap();
+        let src = tmp_root.join("src");
+        make_tree(&src, true, SystemTime::now(), false);
+        let archive_path = tmp_root.join("archive.tar.gz");
+        reproducible_archive(
+            &src,
+            &archive_path,
+            &ZipStyle::Tar(CompressionImpl::Gzip),
+            None,
+            EPOCH,
+        )
+        .unwrap();
+
+        let decoder = flate2::read::GzDecoder::new(File::open(&archive_path).unwrap());
+        assert_eq!(decoder.header().unwrap().mtime(), 0);
+        let mut archive = tar::Archive::new(decoder);
+        let mut names = vec![];
+        for entry in archive.entries().unwrap() {
+            let entry = entry.unwrap();
+            let header = entry.header();
+            assert_eq!(header.mtime().unwrap(), EPOCH);
+            assert_eq!(header.uid().unwrap(), 0);
+            assert_eq!(header.gid().unwrap(), 0);
+            let name = entry.path().unwrap().to_string_lossy().into_owned();
+            let mode = header.mode().unwrap();
+            if cfg!(unix) && name == "bin/axolotlsay" {
+                assert_eq!(mode, EXECUTABLE_MODE);
+            } else if name == "README.md" {
+                assert_eq!(mode, FILE_MODE);
+            }
+            names.push(name);
+        }
+        assert_eq!(
+            names,
+            [
+                "README.md",
+                "bin/",
+                "bin/axolotlsay",
+                "share/",
+                "share/doc/",
+                "share/doc/axolotlsay/",
+                "share/doc/axolotlsay/LICENSE",
+            ]
+        );
+    }
+}
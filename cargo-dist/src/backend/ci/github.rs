@@ -8,32 +8,30 @@ use axoasset::{LocalAsset, SourceFile};
 use axoprocess::Cmd;
 use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{
-    target_lexicon::{self, Architecture, OperatingSystem, Triple},
-    AptPackageName, ChocolateyPackageName, ContainerImageRef, GhaRunStep,
-    GithubAttestationsFilters, GithubAttestationsPhase, GithubGlobalJobConfig,
-    GithubLocalJobConfig, GithubMatrix, GithubRunnerConfig, GithubRunnerRef, GithubRunners,
-    HomebrewPackageName, PackageInstallScript, PackageVersion, PipPackageName, TripleNameRef,
+    target_lexicon::{Architecture, OperatingSystem, Triple},
+    ContainerImageRef, GhaRunStep, GithubAttestationsFilters, GithubAttestationsPhase,
+    GithubGlobalJobConfig, GithubLocalJobConfig, GithubMatrix, GithubRunnerConfig, GithubRunnerRef,
+    GithubRunners, TripleNameRef,
 };
-use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 use tracing::warn;
 
 use crate::{
+    announce::needs_full_git_history,
     backend::{diff_files, templates::TEMPLATE_CI_GITHUB},
-    build_wrapper_for_cross,
     config::{
         v1::{ci::github::GithubCiConfig, publishers::PublisherConfig},
-        DependencyKind, GithubPermission, GithubPermissionMap, GithubReleasePhase, HostingStyle,
+        GithubPermission, GithubPermissionMap, GithubReleasePhase, HostingStyle,
         JinjaGithubRepoPair, JobStyle, ProductionMode, PublishStyle, SystemDependencies,
     },
     errors::DistResult,
     platform::{github_runners::target_for_github_runner_or_default, targets},
-    CargoBuildWrapper, DistError, DistGraph, SortedMap, SortedSet,
+    DistError, DistGraph, SortedMap, SortedSet,
 };
 
 use super::{
-    CargoAuditableInstallStrategy, CargoCyclonedxInstallStrategy, DistInstallSettings,
-    DistInstallStrategy, InstallStrategy, OmniborInstallStrategy,
+    system_deps_install_script, CargoAuditableInstallStrategy, CargoCyclonedxInstallStrategy,
+    DistInstallSettings, DistInstallStrategy, InstallStrategy, OmniborInstallStrategy,
 };
 
 #[cfg(not(windows))]
@@ -72,8 +70,12 @@ pub struct GithubCiInfo {
     pub pr_run_mode: cargo_dist_schema::PrRunMode,
     /// global task
     pub global_task: GithubGlobalJobConfig,
-    /// homebrew tap
-    pub tap: Option<String>,
+    /// homebrew taps that packages publish to
+    pub homebrew_taps: Vec<String>,
+    /// scoop bucket
+    pub scoop_bucket: Option<String>,
+    /// repo to push winget manifests to
+    pub winget_repo: Option<String>,
     /// plan jobs
     pub plan_jobs: Vec<GithubCiJob>,
     /// local artifacts jobs
@@ -92,10 +94,16 @@ pub struct GithubCiInfo {
     pub ssldotcom_windows_sign: Option<ProductionMode>,
     /// Whether to enable macOS codesigning
     pub macos_sign: bool,
+    /// Whether to make detached minisign signatures of artifacts
+    pub minisign_sign: bool,
     /// what hosting provider we're using
     pub hosting_providers: Vec<HostingStyle>,
+    /// whether the simple host uploads to S3-compatible storage
+    pub simple_s3: bool,
     /// whether to prefix release.yml and the tag pattern
     pub tag_namespace: Option<String>,
+    /// Whether to check out the full git history (and tags) instead of a shallow clone
+    pub fetch_full_history: bool,
     /// Extra permissions the workflow file should have
     pub root_permissions: Option<GithubPermissionMap>,
     /// Extra build steps
@@ -130,6 +138,8 @@ pub struct GithubReleaseInfo {
     pub github_attestations_phase: GithubAttestationsPhase,
     /// `gh` command to run to create the release
     pub release_command: String,
+    /// rolling tag that snapshot releases replace
+    pub snapshot_tag: Option<String>,
     /// Which phase to create the release at
     pub release_phase: GithubReleasePhase,
 }
@@ -222,7 +232,9 @@ impl GithubCiInfo {
         let release_branch = ci_config.release_branch.clone();
         let ssldotcom_windows_sign = dist.config.builds.ssldotcom_windows_sign.clone();
         let macos_sign = dist.config.builds.macos_sign;
+        let minisign_sign = dist.config.builds.minisign_public_key.is_some();
         let tag_namespace = ci_config.tag_namespace.clone();
+        let fetch_full_history = needs_full_git_history(&dist.config.hosts);
         let pr_run_mode = ci_config.pr_run_mode;
 
         let github_release = GithubReleaseInfo::new(dist)?;
@@ -260,6 +272,13 @@ impl GithubCiInfo {
             .expect("should not be possible to have the Github CI backend without hosting!?")
             .hosts
             .clone();
+        let simple_s3 = hosting_providers.contains(&HostingStyle::Simple)
+            && dist
+                .config
+                .hosts
+                .simple
+                .as_ref()
+                .is_some_and(|simple| simple.s3.is_some());
 
         // Build up the task matrix for building Artifacts
         let mut tasks = vec![];
@@ -284,7 +303,7 @@ impl GithubCiInfo {
             install_omnibor: need_omnibor.then_some(omnibor_install_strategy.dash()),
         };
 
-        let tap = dist.global_homebrew_tap.clone();
+        let homebrew_taps = dist.homebrew_taps.clone();
 
         let mut job_permissions = ci_config.permissions.clone();
         // user publish jobs default to elevated privileges
@@ -301,13 +320,26 @@ impl GithubCiInfo {
         root_permissions.insert("contents".to_owned(), GithubPermission::Write);
 
         let mut publish_jobs = vec![];
-        if let Some(PublisherConfig { homebrew, npm, .. }) = &dist.global_publishers {
+        if let Some(PublisherConfig {
+            homebrew,
+            npm,
+            scoop,
+            winget,
+            ..
+        }) = &dist.global_publishers
+        {
             if homebrew.is_some() {
                 publish_jobs.push(PublishStyle::Homebrew.to_string());
             }
             if npm.is_some() {
                 publish_jobs.push(PublishStyle::Npm.to_string());
             }
+            if scoop.is_some() {
+                publish_jobs.push(PublishStyle::Scoop.to_string());
+            }
+            if winget.is_some() {
+                publish_jobs.push(PublishStyle::Winget.to_string());
+            }
         }
 
         let plan_jobs = build_jobs(&ci_config.plan_jobs, &job_permissions)?;
@@ -337,7 +369,12 @@ impl GithubCiInfo {
             for target in &targets {
                 write!(dist_args, " --target={target}").unwrap();
             }
-            let packages_install = system_deps_install_script(&runner, &targets, &dependencies)?;
+            let packages_install = system_deps_install_script(
+                &real_triple,
+                runner.container.as_ref(),
+                &targets,
+                &dependencies,
+            )?;
             tasks.push(GithubLocalJobConfig {
                 targets: Some(targets.iter().copied().map(|s| s.to_owned()).collect()),
                 cache_provider: cache_provider_for_runner(&runner),
@@ -396,7 +433,9 @@ impl GithubCiInfo {
             build_local_artifacts,
             dispatch_releases,
             release_branch,
-            tap,
+            homebrew_taps,
+            scoop_bucket: dist.global_scoop_bucket.clone(),
+            winget_repo: dist.global_winget_repo.clone(),
             plan_jobs,
             local_artifacts_jobs,
             global_artifacts_jobs,
@@ -409,7 +448,10 @@ impl GithubCiInfo {
             global_task,
             ssldotcom_windows_sign,
             macos_sign,
+            minisign_sign,
+            fetch_full_history,
             hosting_providers,
+            simple_s3,
             root_permissions,
             github_build_setup,
             github_release,
@@ -523,6 +565,7 @@ impl GithubReleaseInfo {
             github_attestations_filters,
             github_attestations_phase,
             release_command,
+            snapshot_tag: dist.config.hosts.snapshot_tag.clone(),
             release_phase,
         }))
     }
@@ -666,6 +709,11 @@ fn github_runner_for_target(
         return Ok(Some(runner.clone()));
     }
 
+    // Universal binaries build both slices on whatever builds arm64 macs
+    if target == targets::TARGET_UNIVERSAL2_MAC {
+        return github_runner_for_target(targets::TARGET_ARM64_MAC, custom_runners);
+    }
+
     let target_triple: Triple = target.parse()?;
 
     // We want to default to older runners to minimize the places
@@ -713,196 +761,6 @@ fn cargo_xwin() -> GithubRunnerConfig {
     }
 }
 
-fn brewfile_from<'a>(packages: impl Iterator<Item = &'a HomebrewPackageName>) -> String {
-    packages
-        .map(|p| {
-            let lower = p.as_str().to_ascii_lowercase();
-            // Although `brew install` can take either a formula or a cask,
-            // Brewfiles require you to use the `cask` verb for casks and `brew`
-            // for formulas.
-            if lower.starts_with("homebrew/cask") || lower.starts_with("homebrew/homebrew-cask") {
-                format!(r#"cask "{p}""#).to_owned()
-            } else {
-                format!(r#"brew "{p}""#).to_owned()
-            }
-        })
-        .join("\n")
-}
-
-fn brew_bundle_command<'a>(packages: impl Iterator<Item = &'a HomebrewPackageName>) -> String {
-    format!(
-        r#"cat << EOF >Brewfile
-{}
-EOF
-
-brew bundle install"#,
-        brewfile_from(packages)
-    )
-}
-
-fn system_deps_install_script(
-    rc: &GithubRunnerConfig,
-    targets: &[&TripleNameRef],
-    packages: &SystemDependencies,
-) -> DistResult<Option<PackageInstallScript>> {
-    let mut brew_packages: SortedSet<HomebrewPackageName> = Default::default();
-    let mut apt_packages: SortedSet<(AptPackageName, Option<PackageVersion>)> = Default::default();
-    let mut chocolatey_packages: SortedSet<(ChocolateyPackageName, Option<PackageVersion>)> =
-        Default::default();
-
-    let host = rc.real_triple();
-    match host.operating_system {
-        OperatingSystem::Darwin(_) => {
-            for (name, pkg) in &packages.homebrew {
-                if !pkg.0.stage_wanted(&DependencyKind::Build) {
-                    continue;
-                }
-                if !targets.iter().any(|target| pkg.0.wanted_for_target(target)) {
-                    continue;
-                }
-                brew_packages.insert(name.clone());
-            }
-        }
-        OperatingSystem::Linux => {
-            // We currently don't support non-apt package managers on Linux
-            // is_none() means a native build, probably on GitHub's
-            // apt-using runners.
-            if rc.container.is_none()
-                || rc.container.as_ref().and_then(|c| c.package_manager)
-                    == Some(cargo_dist_schema::PackageManager::Apt)
-            {
-                for (name, pkg) in &packages.apt {
-                    if !pkg.0.stage_wanted(&DependencyKind::Build) {
-                        continue;
-                    }
-                    if !targets.iter().any(|target| pkg.0.wanted_for_target(target)) {
-                        continue;
-                    }
-                    apt_packages.insert((name.clone(), pkg.0.version.clone()));
-                }
-
-                let has_musl_target = targets.iter().any(|target| {
-                    target.parse().unwrap().environment == target_lexicon::Environment::Musl
-                });
-                if has_musl_target {
-                    // musl builds may require musl-tools to build;
-                    // necessary for more complex software
-                    apt_packages.insert((AptPackageName::new("musl-tools".to_owned()), None));
-                }
-            }
-        }
-        OperatingSystem::Windows => {
-            for (name, pkg) in &packages.chocolatey {
-                if !pkg.0.stage_wanted(&DependencyKind::Build) {
-                    continue;
-                }
-                if !targets.iter().any(|target| pkg.0.wanted_for_target(target)) {
-                    continue;
-                }
-                chocolatey_packages.insert((name.clone(), pkg.0.version.clone()));
-            }
-        }
-        _ => {
-            panic!(
-                "unsupported host operating system: {:?}",
-                host.operating_system
-            )
-        }
-    }
-
-    let mut lines = vec![];
-    if !brew_packages.is_empty() {
-        lines.push(brew_bundle_command(brew_packages.iter()))
-    }
-
-    // If we're crossing, we'll most likely be running from a container with
-    // no sudo. We should avoid calling sudo in that case.
-    let sudo = if rc.container.is_some() { "" } else { "sudo " };
-    if !apt_packages.is_empty() {
-        lines.push(format!("{sudo}apt-get update"));
-        let args = apt_packages
-            .iter()
-            .map(|(pkg, version)| {
-                if let Some(v) = version {
-                    format!("{pkg}={v}")
-                } else {
-                    pkg.to_string()
-                }
-            })
-            .join(" ");
-        lines.push(format!("{sudo}apt-get install {args}"));
-    }
-
-    for (pkg, version) in &chocolatey_packages {
-        lines.push(if let Some(v) = version {
-            format!("choco install {pkg} --version={v} --yes")
-        } else {
-            format!("choco install {pkg} --yes")
-        });
-    }
-
-    // Regardless of what we're doing, we might need build wrappers!
-    let mut required_wrappers: SortedSet<CargoBuildWrapper> = Default::default();
-    for target in targets {
-        let target = target.parse().unwrap();
-        if let Some(wrapper) = build_wrapper_for_cross(&host, &target)? {
-            required_wrappers.insert(wrapper);
-        }
-    }
-
-    let mut pip_pkgs: SortedSet<PipPackageName> = Default::default();
-    if required_wrappers.contains(&CargoBuildWrapper::ZigBuild) {
-        pip_pkgs.insert(PipPackageName::new("cargo-zigbuild".to_owned()));
-    }
-    if required_wrappers.contains(&CargoBuildWrapper::Xwin) {
-        pip_pkgs.insert(PipPackageName::new("cargo-xwin".to_owned()));
-    }
-
-    if !pip_pkgs.is_empty() {
-        let push_pip_install_lines = |lines: &mut Vec<String>| {
-            if host.operating_system == OperatingSystem::Linux {
-                // make sure pip is installed — on dnf-based distros we might need to install
-                // it (true for e.g. the `quay.io/pypa/manylinux_2_28_x86_64` image)
-                //
-                // this doesn't work for all distros of course — others might need to be added
-                // later. there's no universal way to install tooling in dist right now anyway.
-                lines.push("  if ! command -v pip3 > /dev/null 2>&1; then".to_owned());
-                lines.push("    dnf install --assumeyes python3-pip".to_owned());
-                lines.push("    pip3 install --upgrade pip".to_owned());
-                lines.push("  fi".to_owned());
-            }
-        };
-
-        for pip_pkg in pip_pkgs {
-            match pip_pkg.as_str() {
-                "cargo-xwin" => {
-                    // that one could already be installed
-                    lines.push("if ! command -v cargo-xwin > /dev/null 2>&1; then".to_owned());
-                    push_pip_install_lines(&mut lines);
-                    lines.push("  pip3 install cargo-xwin".to_owned());
-                    lines.push("fi".to_owned());
-                }
-                "cargo-zigbuild" => {
-                    // that one could already be installed
-                    lines.push("if ! command -v cargo-zigbuild > /dev/null 2>&1; then".to_owned());
-                    push_pip_install_lines(&mut lines);
-                    lines.push("  pip3 install cargo-zigbuild".to_owned());
-                    lines.push("fi".to_owned());
-                }
-                _ => {
-                    lines.push(format!("pip3 install {pip_pkg}"));
-                }
-            }
-        }
-    }
-
-    Ok(if lines.is_empty() {
-        None
-    } else {
-        Some(PackageInstallScript::new(lines.join("\n")))
-    })
-}
-
 /// Builder for looking up and reporting errors in the steps provided by the
 /// `github-build-setup` configuration
 pub struct GithubJobStepsBuilder {
@@ -1215,4 +1073,24 @@ mod tests {
             .unwrap();
         assert_eq!(out.if_expr, Some(false.into()));
     }
+
+    #[test]
+    fn universal2_uses_the_arm64_mac_runner() {
+        let runner = |custom: &GithubRunners| {
+            github_runner_for_target(targets::TARGET_UNIVERSAL2_MAC, custom)
+                .unwrap()
+                .unwrap()
+                .runner
+        };
+        let default = github_runner_for_target(targets::TARGET_ARM64_MAC, &GithubRunners::new())
+            .unwrap()
+            .unwrap();
+        assert_eq!(runner(&GithubRunners::new()), default.runner);
+
+        let custom = GithubRunners::from_iter([(
+            targets::TARGET_ARM64_MAC.to_owned(),
+            runner_to_config(GithubRunnerRef::from_str("my-mac")),
+        )]);
+        assert_eq!(runner(&custom).as_str(), "my-mac");
+    }
 }
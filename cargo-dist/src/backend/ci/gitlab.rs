@@ -0,0 +1,468 @@
+//! CI script generation for GitLab
+//!
+//! Unlike GitHub, GitLab can't fan out a dynamic job matrix computed by `dist plan`,
+//! so we statically emit one build job per runner when generating the pipeline.
+//! `dist generate --check` catches any drift between the two.
+
+use axoasset::LocalAsset;
+use camino::Utf8PathBuf;
+use cargo_dist_schema::{
+    target_lexicon::{Architecture, OperatingSystem, Triple},
+    ContainerConfig, ContainerImageRef, GhaRunStep, PackageInstallScript, PackageManager,
+    TripleName, TripleNameRef,
+};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{
+    announce::needs_full_git_history,
+    backend::{diff_files, templates::TEMPLATE_CI_GITLAB},
+    config::{
+        v1::{ci::gitlab::GitlabCiConfig, publishers::PublisherConfig},
+        HostingStyle, JobStyle, PublishStyle, SystemDependencies,
+    },
+    errors::DistResult,
+    platform::targets,
+    DistGraph, SortedMap, SortedSet,
+};
+
+use super::{
+    system_deps_install_script, CargoAuditableInstallStrategy, CargoCyclonedxInstallStrategy,
+    DistInstallSettings, InstallStrategy, OmniborInstallStrategy,
+};
+
+const GITLAB_CI_FILE: &str = ".gitlab-ci.yml";
+
+/// The image we run linux jobs in if nothing else is specified.
+///
+/// We want to default to older distros to minimize the places
+/// where random system dependencies can creep in and be very
+/// recent. This helps with portability!
+const DEFAULT_LINUX_IMAGE: &str = "rust:1-bullseye";
+const CARGO_XWIN_IMAGE: &str = "messense/cargo-xwin";
+const MACOS_IMAGE: &str = "macos-14-xcode-15";
+const LINUX_ARM64_RUNNER_TAG: &str = "saas-linux-small-arm64";
+const MACOS_RUNNER_TAG: &str = "saas-macos-medium-m1";
+const WINDOWS_RUNNER_TAG: &str = "saas-windows-medium-amd64";
+
+/// Info about running dist in GitLab CI
+///
+/// THESE FIELDS ARE LOAD-BEARING because they're used in the templates.
+#[derive(Debug, Serialize)]
+pub struct GitlabCiInfo {
+    /// Cached path to .gitlab-ci.yml
+    #[serde(skip_serializing)]
+    pub gitlab_ci_file: Utf8PathBuf,
+    /// Version of rust toolchain to install (deprecated)
+    pub rust_version: Option<String>,
+    /// How to install dist when "coordinating" (plan, global build, etc.)
+    pub dist_install_for_coordinator: GhaRunStep,
+    /// Whether to cache builds
+    pub cache_builds: bool,
+    /// Whether to include builtin local artifacts tasks
+    pub build_local_artifacts: bool,
+    /// Whether to make CI get dispatched manually instead of by tag
+    pub dispatch_releases: bool,
+    /// Trigger releases on pushes to this branch instead of ci
+    pub release_branch: Option<String>,
+    /// What kind of job to run on merge request
+    pub pr_run_mode: cargo_dist_schema::PrRunMode,
+    /// prefix for the tag pattern
+    pub tag_namespace: Option<String>,
+    /// Whether to clone the full git history (and tags) instead of a shallow clone
+    pub fetch_full_history: bool,
+    /// The runner all the "global" jobs run on
+    pub global_runner: GitlabRunnerConfig,
+    /// Args to pass to dist for the global build
+    pub global_dist_args: String,
+    /// How to install cargo-cyclonedx on the global runner
+    pub install_cargo_cyclonedx: GhaRunStep,
+    /// How to install omnibor on the global runner
+    pub install_omnibor: GhaRunStep,
+    /// local artifacts jobs
+    pub local_artifacts_jobs: Vec<GitlabLocalJob>,
+    /// homebrew taps that packages publish to
+    pub homebrew_taps: Vec<String>,
+    /// scoop bucket
+    pub scoop_bucket: Option<String>,
+    /// repo to push winget manifests to
+    pub winget_repo: Option<String>,
+    /// builtin publish jobs
+    pub publish_jobs: Vec<String>,
+    /// user-specified jobs, pulled into the pipeline with `include`
+    pub user_jobs: Vec<String>,
+    /// what hosting provider we're using
+    pub hosting_providers: Vec<HostingStyle>,
+    /// Whether to install cargo-auditable
+    pub need_cargo_auditable: bool,
+    /// Whether to run cargo-cyclonedx
+    pub need_cargo_cyclonedx: bool,
+    /// Whether to install and run omnibor-cli
+    pub need_omnibor: bool,
+}
+
+/// Where a GitLab job should run
+#[derive(Debug, Clone, Serialize, PartialEq, Eq, PartialOrd, Ord)]
+pub struct GitlabRunnerConfig {
+    /// The runner tag to select the machine with (None means "any runner")
+    pub tag: Option<String>,
+    /// The image to run the job in, for executors that support that
+    pub image: Option<String>,
+    /// The triple of the machine (or container) the job runs on
+    pub host: TripleName,
+}
+
+/// A job that builds local artifacts for some targets
+#[derive(Debug, Serialize)]
+pub struct GitlabLocalJob {
+    /// The targets this job builds
+    pub targets: Vec<TripleName>,
+    /// Where to run the job
+    pub runner: GitlabRunnerConfig,
+    /// Whether the job's script is powershell (otherwise it's a posix shell)
+    pub powershell: bool,
+    /// cli flags to pass to dist
+    pub dist_args: String,
+    /// How to install dist
+    pub install_dist: GhaRunStep,
+    /// How to install cargo-auditable
+    pub install_cargo_auditable: Option<GhaRunStep>,
+    /// How to install omnibor
+    pub install_omnibor: Option<GhaRunStep>,
+    /// How to install system dependencies
+    pub packages_install: Option<PackageInstallScript>,
+}
+
+impl GitlabCiInfo {
+    /// Compute the GitLab CI stuff
+    pub fn new(dist: &DistGraph, ci_config: &GitlabCiConfig) -> DistResult<GitlabCiInfo> {
+        // Legacy deprecated support
+        let rust_version = dist.config.builds.cargo.rust_toolchain_version.clone();
+
+        // If they don't specify a dist version, use this one
+        let self_dist_version = super::SELF_DIST_VERSION.parse().unwrap();
+        let dist_version = dist
+            .config
+            .dist_version
+            .as_ref()
+            .unwrap_or(&self_dist_version);
+        let release_branch = ci_config.release_branch.clone();
+        let pr_run_mode = ci_config.pr_run_mode;
+        let caching_could_be_profitable =
+            release_branch.is_some() || pr_run_mode == cargo_dist_schema::PrRunMode::Upload;
+        let cache_builds = ci_config
+            .cache_builds
+            .unwrap_or(caching_could_be_profitable);
+
+        let need_cargo_auditable = dist.config.builds.cargo.cargo_auditable;
+        let need_cargo_cyclonedx = dist.config.builds.cargo.cargo_cyclonedx;
+        let need_omnibor = dist.config.builds.omnibor;
+
+        // Figure out what builds we need to do
+        let mut dependencies = SystemDependencies::default();
+        let mut local_targets: SortedSet<&TripleNameRef> = SortedSet::new();
+        for release in &dist.releases {
+            for target in &release.targets {
+                local_targets.insert(target);
+            }
+            dependencies.append(&mut release.config.builds.system_dependencies.clone());
+        }
+
+        let dist_install_strategy = (DistInstallSettings {
+            version: dist_version,
+            url_override: dist.config.dist_url_override.as_deref(),
+        })
+        .install_strategy();
+
+        // Unlike the GitHub backend we tolerate not having hosting, since
+        // there's no GitLab-native hosting to default to
+        let hosting_providers = dist
+            .hosting
+            .as_ref()
+            .map(|hosting| hosting.hosts.clone())
+            .unwrap_or_default();
+
+        let global_runner = default_global_runner_config(ci_config.runners.get("global"));
+
+        let mut publish_jobs = vec![];
+        if let Some(PublisherConfig {
+            homebrew,
+            npm,
+            scoop,
+            winget,
+            ..
+        }) = &dist.global_publishers
+        {
+            if homebrew.is_some() {
+                publish_jobs.push(PublishStyle::Homebrew.to_string());
+            }
+            if npm.is_some() {
+                publish_jobs.push(PublishStyle::Npm.to_string());
+            }
+            if scoop.is_some() {
+                publish_jobs.push(PublishStyle::Scoop.to_string());
+            }
+            if winget.is_some() {
+                publish_jobs.push(PublishStyle::Winget.to_string());
+            }
+        }
+
+        let mut user_jobs = vec![];
+        for jobs in [
+            &ci_config.plan_jobs,
+            &ci_config.build_local_jobs,
+            &ci_config.build_global_jobs,
+            &ci_config.host_jobs,
+            &ci_config.publish_jobs,
+            &ci_config.post_announce_jobs,
+        ] {
+            for JobStyle::User(name) in jobs {
+                user_jobs.push(name.clone());
+            }
+        }
+
+        // Figure out what Local Artifact tasks we need
+        let local_runs = distribute_targets_to_runners(
+            local_targets,
+            &ci_config.runners,
+            ci_config.merge_tasks,
+        )?;
+        let mut local_artifacts_jobs = vec![];
+        for (runner, targets) in local_runs {
+            use std::fmt::Write;
+            let real_triple: Triple = runner.host.parse()?;
+            let install_dist = dist_install_strategy.for_triple(&real_triple);
+            let install_cargo_auditable = CargoAuditableInstallStrategy.for_triple(&real_triple);
+            let install_omnibor = OmniborInstallStrategy.for_triple(&real_triple);
+
+            let mut dist_args = String::from("--artifacts=local");
+            for target in &targets {
+                write!(dist_args, " --target={target}").unwrap();
+            }
+            // Linux jobs run as root in a (Debian-based) container
+            let container = runner
+                .image
+                .as_ref()
+                .filter(|_| real_triple.operating_system == OperatingSystem::Linux)
+                .map(|image| ContainerConfig {
+                    image: ContainerImageRef::from_str(image).to_owned(),
+                    host: runner.host.clone(),
+                    package_manager: Some(PackageManager::Apt),
+                });
+            let packages_install = system_deps_install_script(
+                &real_triple,
+                container.as_ref(),
+                &targets,
+                &dependencies,
+            )?;
+            local_artifacts_jobs.push(GitlabLocalJob {
+                targets: targets.iter().map(|&t| t.to_owned()).collect(),
+                powershell: real_triple.operating_system == OperatingSystem::Windows,
+                runner,
+                dist_args,
+                install_dist,
+                install_cargo_auditable: need_cargo_auditable.then_some(install_cargo_auditable),
+                install_omnibor: need_omnibor.then_some(install_omnibor),
+                packages_install,
+            });
+        }
+
+        Ok(GitlabCiInfo {
+            gitlab_ci_file: dist.repo_dir.join(GITLAB_CI_FILE),
+            rust_version,
+            dist_install_for_coordinator: dist_install_strategy.dash(),
+            cache_builds,
+            build_local_artifacts: ci_config.build_local_artifacts,
+            dispatch_releases: ci_config.dispatch_releases,
+            release_branch,
+            pr_run_mode,
+            tag_namespace: ci_config.tag_namespace.clone(),
+            fetch_full_history: needs_full_git_history(&dist.config.hosts),
+            global_runner,
+            global_dist_args: "--artifacts=global".to_owned(),
+            install_cargo_cyclonedx: CargoCyclonedxInstallStrategy.dash(),
+            install_omnibor: OmniborInstallStrategy.dash(),
+            local_artifacts_jobs,
+            homebrew_taps: dist.homebrew_taps.clone(),
+            scoop_bucket: dist.global_scoop_bucket.clone(),
+            winget_repo: dist.global_winget_repo.clone(),
+            publish_jobs,
+            user_jobs,
+            hosting_providers,
+            need_cargo_auditable,
+            need_cargo_cyclonedx,
+            need_omnibor,
+        })
+    }
+
+    /// Generate the requested configuration and returns it as a string.
+    pub fn generate_gitlab_ci(&self, dist: &DistGraph) -> DistResult<String> {
+        let rendered = dist
+            .templates
+            .render_file_to_clean_string(TEMPLATE_CI_GITLAB, self)?;
+
+        Ok(rendered)
+    }
+
+    /// Write .gitlab-ci.yml to disk
+    pub fn write_to_disk(&self, dist: &DistGraph) -> DistResult<()> {
+        let rendered = self.generate_gitlab_ci(dist)?;
+
+        LocalAsset::write_new_all(&rendered, &self.gitlab_ci_file)?;
+        eprintln!("generated GitLab CI to {}", self.gitlab_ci_file);
+
+        Ok(())
+    }
+
+    /// Check whether the new configuration differs from the config on disk
+    /// without actually writing the result.
+    pub fn check(&self, dist: &DistGraph) -> DistResult<()> {
+        let rendered = self.generate_gitlab_ci(dist)?;
+        diff_files(&self.gitlab_ci_file, &rendered)
+    }
+}
+
+/// Given a set of targets we want to build local artifacts for, map them to GitLab runners.
+///
+/// If `merge` is set, targets that can be built on the same runner share a job,
+/// otherwise each target gets its own job for latency and fault-isolation.
+fn distribute_targets_to_runners<'a>(
+    targets: SortedSet<&'a TripleNameRef>,
+    custom_runners: &SortedMap<TripleName, String>,
+    merge: bool,
+) -> DistResult<Vec<(GitlabRunnerConfig, Vec<&'a TripleNameRef>)>> {
+    let mut groups = Vec::<(GitlabRunnerConfig, Vec<&TripleNameRef>)>::new();
+    for target in targets {
+        let runner = gitlab_runner_for_target(target, custom_runners.get(target))?;
+        let runner = runner.unwrap_or_else(|| {
+            let fallback = default_global_runner_config(custom_runners.get(target));
+            warn!(
+                "not sure which gitlab runner should be used for {target}, assuming {}",
+                fallback.host
+            );
+            fallback
+        });
+        if merge {
+            if let Some((_, group)) = groups.iter_mut().find(|(r, _)| *r == runner) {
+                group.push(target);
+                continue;
+            }
+        }
+        groups.push((runner, vec![target]));
+    }
+    Ok(groups)
+}
+
+fn default_global_runner_config(custom_tag: Option<&String>) -> GitlabRunnerConfig {
+    GitlabRunnerConfig {
+        tag: custom_tag.cloned(),
+        image: Some(DEFAULT_LINUX_IMAGE.to_owned()),
+        host: targets::TARGET_X64_LINUX_GNU.to_owned(),
+    }
+}
+
+/// Get the appropriate GitLab runner for building a target
+///
+/// The defaults are the gitlab.com hosted runners, a custom tag swaps out the
+/// runner but keeps the image/host we'd otherwise pick.
+fn gitlab_runner_for_target(
+    target: &TripleNameRef,
+    custom_tag: Option<&String>,
+) -> DistResult<Option<GitlabRunnerConfig>> {
+    let runner =
+        |tag: Option<&str>, image: Option<&str>, host: &TripleNameRef| GitlabRunnerConfig {
+            tag: custom_tag.cloned().or(tag.map(|t| t.to_owned())),
+            image: image.map(|i| i.to_owned()),
+            host: host.to_owned(),
+        };
+
+    // Universal binaries build both slices on the (arm64) mac runner
+    if target == targets::TARGET_UNIVERSAL2_MAC {
+        return Ok(Some(runner(
+            Some(MACOS_RUNNER_TAG),
+            Some(MACOS_IMAGE),
+            targets::TARGET_ARM64_MAC,
+        )));
+    }
+
+    let target_triple: Triple = target.parse()?;
+
+    let result = Some(match target_triple.operating_system {
+        OperatingSystem::Linux => {
+            if matches!(target_triple.architecture, Architecture::Aarch64(_)) {
+                runner(
+                    Some(LINUX_ARM64_RUNNER_TAG),
+                    Some(DEFAULT_LINUX_IMAGE),
+                    targets::TARGET_ARM64_LINUX_GNU,
+                )
+            } else {
+                runner(
+                    None,
+                    Some(DEFAULT_LINUX_IMAGE),
+                    targets::TARGET_X64_LINUX_GNU,
+                )
+            }
+        }
+        OperatingSystem::Darwin(_) => runner(
+            Some(MACOS_RUNNER_TAG),
+            Some(MACOS_IMAGE),
+            targets::TARGET_ARM64_MAC,
+        ),
+        OperatingSystem::Windows => {
+            // Default to cargo-xwin for Windows cross-compiles
+            if target_triple.architecture != Architecture::X86_64 {
+                runner(None, Some(CARGO_XWIN_IMAGE), targets::TARGET_X64_LINUX_MUSL)
+            } else {
+                runner(Some(WINDOWS_RUNNER_TAG), None, targets::TARGET_X64_WINDOWS)
+            }
+        }
+        _ => return Ok(None),
+    });
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn triples(names: &[&'static str]) -> SortedSet<&'static TripleNameRef> {
+        names.iter().map(|n| TripleNameRef::from_str(n)).collect()
+    }
+
+    #[test]
+    fn split_runners_by_default() {
+        let targets = triples(&["aarch64-apple-darwin", "x86_64-apple-darwin"]);
+        let runs = distribute_targets_to_runners(targets, &SortedMap::new(), false).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].0, runs[1].0);
+        assert_eq!(runs[0].0.tag.as_deref(), Some(MACOS_RUNNER_TAG));
+    }
+
+    #[test]
+    fn merged_runners_share_a_job() {
+        let targets = triples(&[
+            "aarch64-apple-darwin",
+            "x86_64-apple-darwin",
+            "x86_64-unknown-linux-gnu",
+        ]);
+        let runs = distribute_targets_to_runners(targets, &SortedMap::new(), true).unwrap();
+        assert_eq!(runs.len(), 2);
+        assert_eq!(runs[0].1.len(), 2);
+        assert_eq!(runs[1].0.tag, None);
+        assert_eq!(runs[1].0.image.as_deref(), Some(DEFAULT_LINUX_IMAGE));
+    }
+
+    #[test]
+    fn custom_runner_tags() {
+        let targets = triples(&["x86_64-unknown-linux-gnu", "x86_64-pc-windows-msvc"]);
+        let custom = SortedMap::from_iter([(
+            TripleName::new("x86_64-unknown-linux-gnu".to_owned()),
+            "my-runner".to_owned(),
+        )]);
+        let runs = distribute_targets_to_runners(targets, &custom, false).unwrap();
+        assert_eq!(runs[0].0.tag.as_deref(), Some(WINDOWS_RUNNER_TAG));
+        assert_eq!(runs[1].0.tag.as_deref(), Some("my-runner"));
+        assert_eq!(runs[1].0.image.as_deref(), Some(DEFAULT_LINUX_IMAGE));
+    }
+}
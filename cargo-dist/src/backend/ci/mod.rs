@@ -1,17 +1,27 @@
 //! Support for generating CI scripts for running dist
 
 use cargo_dist_schema::{
-    target_lexicon::{OperatingSystem, Triple},
-    DashScript, GhaRunStep, PowershellScript,
+    target_lexicon::{self, OperatingSystem, Triple},
+    AptPackageName, ChocolateyPackageName, ContainerConfig, DashScript, GhaRunStep,
+    HomebrewPackageName, PackageInstallScript, PackageVersion, PipPackageName, PowershellScript,
+    TripleNameRef,
 };
+use itertools::Itertools;
 use semver::Version;
 use serde::Serialize;
 
-use crate::config::v0::CargoDistUrlOverrideRef;
+use crate::{
+    build_wrapper_for_cross,
+    config::{v0::CargoDistUrlOverrideRef, DependencyKind, SystemDependencies},
+    errors::DistResult,
+    platform::targets,
+    CargoBuildWrapper, SortedSet,
+};
 
-use self::github::GithubCiInfo;
+use self::{github::GithubCiInfo, gitlab::GitlabCiInfo};
 
 pub mod github;
+pub mod gitlab;
 
 /// The current version of dist
 const SELF_DIST_VERSION: &str = env!("CARGO_PKG_VERSION");
@@ -41,6 +51,8 @@ const OMNIBOR_VERSION: &str = "0.7.0";
 pub struct CiInfo {
     /// Github CI
     pub github: Option<GithubCiInfo>,
+    /// GitLab CI
+    pub gitlab: Option<GitlabCiInfo>,
 }
 
 /// Gives us the full information re: the version of dist we're supposed
@@ -212,3 +224,200 @@ impl InstallStrategy for OmniborInstallStrategy {
         PowershellScript::new(format!(r#"powershell -c "irm {installer_url} | iex""#)).into()
     }
 }
+
+fn brewfile_from<'a>(packages: impl Iterator<Item = &'a HomebrewPackageName>) -> String {
+    packages
+        .map(|p| {
+            let lower = p.as_str().to_ascii_lowercase();
+            // Although `brew install` can take either a formula or a cask,
+            // Brewfiles require you to use the `cask` verb for casks and `brew`
+            // for formulas.
+            if lower.starts_with("homebrew/cask") || lower.starts_with("homebrew/homebrew-cask") {
+                format!(r#"cask "{p}""#).to_owned()
+            } else {
+                format!(r#"brew "{p}""#).to_owned()
+            }
+        })
+        .join("\n")
+}
+
+fn brew_bundle_command<'a>(packages: impl Iterator<Item = &'a HomebrewPackageName>) -> String {
+    format!(
+        r#"cat << EOF >Brewfile
+{}
+EOF
+
+brew bundle install"#,
+        brewfile_from(packages)
+    )
+}
+
+/// Compute the script that installs the system dependencies needed to build the given
+/// targets on a machine with the given host (optionally running inside a container)
+fn system_deps_install_script(
+    host: &Triple,
+    container: Option<&ContainerConfig>,
+    targets: &[&TripleNameRef],
+    packages: &SystemDependencies,
+) -> DistResult<Option<PackageInstallScript>> {
+    // Magic targets are really builds for some other targets
+    let targets = targets
+        .iter()
+        .flat_map(|target| targets::build_targets(target))
+        .collect::<Vec<_>>();
+    let mut brew_packages: SortedSet<HomebrewPackageName> = Default::default();
+    let mut apt_packages: SortedSet<(AptPackageName, Option<PackageVersion>)> = Default::default();
+    let mut chocolatey_packages: SortedSet<(ChocolateyPackageName, Option<PackageVersion>)> =
+        Default::default();
+
+    match host.operating_system {
+        OperatingSystem::Darwin(_) => {
+            for (name, pkg) in &packages.homebrew {
+                if !pkg.0.stage_wanted(&DependencyKind::Build) {
+                    continue;
+                }
+                if !targets.iter().any(|target| pkg.0.wanted_for_target(target)) {
+                    continue;
+                }
+                brew_packages.insert(name.clone());
+            }
+        }
+        OperatingSystem::Linux => {
+            // We currently don't support non-apt package managers on Linux
+            // is_none() means a native build, probably on GitHub's
+            // apt-using runners.
+            if container.is_none()
+                || container.and_then(|c| c.package_manager)
+                    == Some(cargo_dist_schema::PackageManager::Apt)
+            {
+                for (name, pkg) in &packages.apt {
+                    if !pkg.0.stage_wanted(&DependencyKind::Build) {
+                        continue;
+                    }
+                    if !targets.iter().any(|target| pkg.0.wanted_for_target(target)) {
+                        continue;
+                    }
+                    apt_packages.insert((name.clone(), pkg.0.version.clone()));
+                }
+
+                let has_musl_target = targets.iter().any(|target| {
+                    target.parse().unwrap().environment == target_lexicon::Environment::Musl
+                });
+                if has_musl_target {
+                    // musl builds may require musl-tools to build;
+                    // necessary for more complex software
+                    apt_packages.insert((AptPackageName::new("musl-tools".to_owned()), None));
+                }
+            }
+        }
+        OperatingSystem::Windows => {
+            for (name, pkg) in &packages.chocolatey {
+                if !pkg.0.stage_wanted(&DependencyKind::Build) {
+                    continue;
+                }
+                if !targets.iter().any(|target| pkg.0.wanted_for_target(target)) {
+                    continue;
+                }
+                chocolatey_packages.insert((name.clone(), pkg.0.version.clone()));
+            }
+        }
+        _ => {
+            panic!(
+                "unsupported host operating system: {:?}",
+                host.operating_system
+            )
+        }
+    }
+
+    let mut lines = vec![];
+    if !brew_packages.is_empty() {
+        lines.push(brew_bundle_command(brew_packages.iter()))
+    }
+
+    // If we're crossing, we'll most likely be running from a container with
+    // no sudo. We should avoid calling sudo in that case.
+    let sudo = if container.is_some() { "" } else { "sudo " };
+    if !apt_packages.is_empty() {
+        lines.push(format!("{sudo}apt-get update"));
+        let args = apt_packages
+            .iter()
+            .map(|(pkg, version)| {
+                if let Some(v) = version {
+                    format!("{pkg}={v}")
+                } else {
+                    pkg.to_string()
+                }
+            })
+            .join(" ");
+        lines.push(format!("{sudo}apt-get install {args}"));
+    }
+
+    for (pkg, version) in &chocolatey_packages {
+        lines.push(if let Some(v) = version {
+            format!("choco install {pkg} --version={v} --yes")
+        } else {
+            format!("choco install {pkg} --yes")
+        });
+    }
+
+    // Regardless of what we're doing, we might need build wrappers!
+    let mut required_wrappers: SortedSet<CargoBuildWrapper> = Default::default();
+    for target in &targets {
+        let target = target.parse().unwrap();
+        if let Some(wrapper) = build_wrapper_for_cross(host, &target)? {
+            required_wrappers.insert(wrapper);
+        }
+    }
+
+    let mut pip_pkgs: SortedSet<PipPackageName> = Default::default();
+    if required_wrappers.contains(&CargoBuildWrapper::ZigBuild) {
+        pip_pkgs.insert(PipPackageName::new("cargo-zigbuild".to_owned()));
+    }
+    if required_wrappers.contains(&CargoBuildWrapper::Xwin) {
+        pip_pkgs.insert(PipPackageName::new("cargo-xwin".to_owned()));
+    }
+
+    if !pip_pkgs.is_empty() {
+        let push_pip_install_lines = |lines: &mut Vec<String>| {
+            if host.operating_system == OperatingSystem::Linux {
+                // make sure pip is installed — on dnf-based distros we might need to install
+                // it (true for e.g. the `quay.io/pypa/manylinux_2_28_x86_64` image)
+                //
+                // this doesn't work for all distros of course — others might need to be added
+                // later. there's no universal way to install tooling in dist right now anyway.
+                lines.push("  if ! command -v pip3 > /dev/null 2>&1; then".to_owned());
+                lines.push("    dnf install --assumeyes python3-pip".to_owned());
+                lines.push("    pip3 install --upgrade pip".to_owned());
+                lines.push("  fi".to_owned());
+            }
+        };
+
+        for pip_pkg in pip_pkgs {
+            match pip_pkg.as_str() {
+                "cargo-xwin" => {
+                    // that one could already be installed
+                    lines.push("if ! command -v cargo-xwin > /dev/null 2>&1; then".to_owned());
+                    push_pip_install_lines(&mut lines);
+                    lines.push("  pip3 install cargo-xwin".to_owned());
+                    lines.push("fi".to_owned());
+                }
+                "cargo-zigbuild" => {
+                    // that one could already be installed
+                    lines.push("if ! command -v cargo-zigbuild > /dev/null 2>&1; then".to_owned());
+                    push_pip_install_lines(&mut lines);
+                    lines.push("  pip3 install cargo-zigbuild".to_owned());
+                    lines.push("fi".to_owned());
+                }
+                _ => {
+                    lines.push(format!("pip3 install {pip_pkg}"));
+                }
+            }
+        }
+    }
+
+    Ok(if lines.is_empty() {
+        None
+    } else {
+        Some(PackageInstallScript::new(lines.join("\n")))
+    })
+}
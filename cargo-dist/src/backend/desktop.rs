@@ -0,0 +1,304 @@
+//! Code for bundling FreeDesktop desktop entries (and icons) with linux builds
+//!
+//! These are laid out in archives the same way they would be under `/usr/share`,
+//! so an archive can be unpacked into a prefix as-is, and the shell installer can
+//! copy them into the user's XDG data dir.
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::errors::{DistError, DistResult};
+
+/// The dir (relative to the root of an archive) that desktop entries go in
+pub const DESKTOP_ENTRY_DIR: &str = "share/applications";
+/// The dir (relative to the root of an archive) that icons go in
+pub const ICON_THEME_DIR: &str = "share/icons/hicolor";
+
+/// A desktop entry to bundle with an archive
+#[derive(Debug, Clone)]
+pub struct DesktopEntry {
+    /// Name (without extension) of the installed desktop entry and icon
+    pub id: String,
+    /// A user-provided desktop entry to use instead of generating one
+    pub source: Option<Utf8PathBuf>,
+    /// The name of the app to display
+    pub name: String,
+    /// A brief description of the app
+    pub comment: Option<String>,
+    /// The executable to launch
+    pub exec: String,
+    /// An icon to install for the entry
+    pub icon: Option<Utf8PathBuf>,
+    /// MIME types the app can open
+    pub mime_types: Vec<String>,
+    /// Keywords to help find the app
+    pub keywords: Vec<String>,
+}
+
+impl DesktopEntry {
+    /// Path of the desktop entry, relative to the root of the archive
+    pub fn entry_path(&self) -> Utf8PathBuf {
+        Utf8Path::new(DESKTOP_ENTRY_DIR).join(format!("{}.desktop", self.id))
+    }
+}
+
+/// The kinds of icon that icon themes support
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum IconFormat {
+    /// A bitmap, installed to a dir for its size
+    Png,
+    /// A vector image, installed to the "scalable" dir
+    Svg,
+}
+
+impl IconFormat {
+    /// Get the format of an icon from its path
+    pub fn from_path(path: &Utf8Path) -> DistResult<Self> {
+        match path
+            .extension()
+            .map(|ext| ext.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("png") => Ok(Self::Png),
+            Some("svg") => Ok(Self::Svg),
+            _ => Err(DistError::UnsupportedDesktopIcon {
+                path: path.to_owned(),
+            }),
+        }
+    }
+}
+
+/// Write a desktop entry (and its icon) into the dir an archive is being built from
+pub(crate) fn write_desktop_entry(entry: &DesktopEntry, archive_dir: &Utf8Path) -> DistResult<()> {
+    if let Some(icon) = &entry.icon {
+        let contents = LocalAsset::load_bytes(icon)?;
+        let dir = match IconFormat::from_path(icon)? {
+            IconFormat::Png => {
+                let (width, height) =
+                    png_dimensions(&contents).map_err(|details| DistError::InvalidDesktopIcon {
+                        path: icon.clone(),
+                        details,
+                    })?;
+                format!("{width}x{height}")
+            }
+            IconFormat::Svg => "scalable".to_owned(),
+        };
+        let ext = icon.extension().unwrap_or_default().to_ascii_lowercase();
+        let dest_dir = archive_dir.join(ICON_THEME_DIR).join(dir).join("apps");
+        LocalAsset::create_dir_all(&dest_dir)?;
+        LocalAsset::copy_file_to_file(icon, dest_dir.join(format!("{}.{ext}", entry.id)))?;
+    }
+
+    let contents = if let Some(source) = &entry.source {
+        let mut contents = LocalAsset::load_string(source)?;
+        if entry.icon.is_some() {
+            contents = set_desktop_entry_key(&contents, "Icon", &entry.id, false);
+        }
+        if !entry.mime_types.is_empty() {
+            contents =
+                set_desktop_entry_key(&contents, "MimeType", &list_value(&entry.mime_types), true);
+        }
+        contents
+    } else {
+        generate_desktop_entry(entry)
+    };
+    LocalAsset::write_new_all(&contents, archive_dir.join(entry.entry_path()))?;
+    Ok(())
+}
+
+/// Generate a desktop entry for an app that didn't provide its own
+fn generate_desktop_entry(entry: &DesktopEntry) -> String {
+    let mut lines = vec![
+        "[Desktop Entry]".to_owned(),
+        "Type=Application".to_owned(),
+        "Version=1.5".to_owned(),
+        format!("Name={}", string_value(&entry.name)),
+    ];
+    if let Some(comment) = &entry.comment {
+        lines.push(format!("Comment={}", string_value(comment)));
+    }
+    if entry.mime_types.is_empty() {
+        lines.push(format!("Exec={}", entry.exec));
+    } else {
+        lines.push(format!("Exec={} %F", entry.exec));
+    }
+    if entry.icon.is_some() {
+        lines.push(format!("Icon={}", entry.id));
+    }
+    lines.push("Terminal=false".to_owned());
+    if !entry.mime_types.is_empty() {
+        lines.push(format!("MimeType={}", list_value(&entry.mime_types)));
+    }
+    if !entry.keywords.is_empty() {
+        lines.push(format!("Keywords={}", list_value(&entry.keywords)));
+    }
+
+    let mut contents = lines.join("\n");
+    contents.push('\n');
+    contents
+}
+
+/// Set a key in the main group of a desktop entry, keeping the rest of the file as-is
+///
+/// If `overwrite` is false, an existing value for the key is left alone.
+fn set_desktop_entry_key(contents: &str, key: &str, value: &str, overwrite: bool) -> String {
+    let mut lines = contents.lines().map(ToOwned::to_owned).collect::<Vec<_>>();
+    let new_line = format!("{key}={value}");
+
+    let Some(group_start) = lines
+        .iter()
+        .position(|line| line.trim() == "[Desktop Entry]")
+    else {
+        lines.push(String::new());
+        lines.push("[Desktop Entry]".to_owned());
+        lines.push(new_line);
+        return lines.join("\n") + "\n";
+    };
+    let group_end = lines[group_start + 1..]
+        .iter()
+        .position(|line| line.trim_start().starts_with('['))
+        .map(|idx| group_start + 1 + idx)
+        .unwrap_or(lines.len());
+
+    let existing = (group_start + 1..group_end).find(|&idx| {
+        lines[idx]
+            .split_once('=')
+            .is_some_and(|(line_key, _)| line_key.trim() == key)
+    });
+    match existing {
+        Some(idx) if overwrite => lines[idx] = new_line,
+        Some(_) => {}
+        None => {
+            // Put it after the last non-blank line of the group, so we don't
+            // end up on the wrong side of the whitespace between groups
+            let insert_at = (group_start + 1..group_end)
+                .rev()
+                .find(|&idx| !lines[idx].trim().is_empty())
+                .map(|idx| idx + 1)
+                .unwrap_or(group_start + 1);
+            lines.insert(insert_at, new_line);
+        }
+    }
+    lines.join("\n") + "\n"
+}
+
+/// Escape a value of type "string" or "localestring"
+fn string_value(val: &str) -> String {
+    val.replace('\\', "\\\\")
+        .replace('\n', "\\n")
+        .replace('\t', "\\t")
+        .replace('\r', "\\r")
+}
+
+/// Format a list of values, which are always terminated with a semicolon
+fn list_value(vals: &[String]) -> String {
+    vals.iter()
+        .map(|val| format!("{};", string_value(val).replace(';', "\\;")))
+        .collect()
+}
+
+/// Read the dimensions of a PNG out of its header
+fn png_dimensions(contents: &[u8]) -> Result<(u32, u32), String> {
+    const PNG_SIGNATURE: &[u8] = b"\x89PNG\r\n\x1a\n";
+    if !contents.starts_with(PNG_SIGNATURE) {
+        return Err("the file doesn't appear to be a PNG".to_owned());
+    }
+    // The IHDR chunk always comes first: length, type, then width and height
+    let Some(ihdr) = contents.get(8..24) else {
+        return Err("the PNG is truncated".to_owned());
+    };
+    if &ihdr[4..8] != b"IHDR" {
+        return Err("the PNG is missing its IHDR chunk".to_owned());
+    }
+    let width = u32::from_be_bytes(ihdr[8..12].try_into().unwrap());
+    let height = u32::from_be_bytes(ihdr[12..16].try_into().unwrap());
+    if width != height {
+        return Err(format!(
+            "icon themes only support square icons, but this one is {width}x{height}"
+        ));
+    }
+    Ok((width, height))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entry() -> DesktopEntry {
+        DesktopEntry {
+            id: "axolotlsay".to_owned(),
+            source: None,
+            name: "Axolotl Say".to_owned(),
+            comment: Some("cute axolotl\nsays things".to_owned()),
+            exec: "axolotlsay".to_owned(),
+            icon: Some("assets/axolotl.svg".into()),
+            mime_types: vec!["text/plain".to_owned(), "text/markdown".to_owned()],
+            keywords: vec![],
+        }
+    }
+
+    #[test]
+    fn test_generate_desktop_entry() {
+        assert_eq!(
+            generate_desktop_entry(&entry()),
+            "[Desktop Entry]
+Type=Application
+Version=1.5
+Name=Axolotl Say
+Comment=cute axolotl\\nsays things
+Exec=axolotlsay %F
+Icon=axolotlsay
+Terminal=false
+MimeType=text/plain;text/markdown;
+"
+        );
+    }
+
+    #[test]
+    fn test_set_desktop_entry_key() {
+        let contents = "[Desktop Entry]
+Name=Axolotl Say
+MimeType=text/plain;
+
+[Desktop Action New]
+Name=New
+";
+        assert_eq!(
+            set_desktop_entry_key(contents, "MimeType", "image/png;", true),
+            "[Desktop Entry]
+Name=Axolotl Say
+MimeType=image/png;
+
+[Desktop Action New]
+Name=New
+"
+        );
+        assert_eq!(
+            set_desktop_entry_key(contents, "MimeType", "image/png;", false),
+            contents
+        );
+        assert_eq!(
+            set_desktop_entry_key(contents, "Icon", "axolotlsay", false),
+            "[Desktop Entry]
+Name=Axolotl Say
+MimeType=text/plain;
+Icon=axolotlsay
+
+[Desktop Action New]
+Name=New
+"
+        );
+    }
+
+    #[test]
+    fn test_png_dimensions() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\x0dIHDR".to_vec();
+        png.extend(128u32.to_be_bytes());
+        png.extend(128u32.to_be_bytes());
+        assert_eq!(png_dimensions(&png), Ok((128, 128)));
+
+        png.truncate(20);
+        assert!(png_dimensions(&png).is_err());
+        assert!(png_dimensions(b"<svg></svg>").is_err());
+    }
+}
@@ -0,0 +1,586 @@
+//! Code for generating Debian .deb packages
+//!
+//! A .deb is an `ar` archive containing three members, in this order:
+//!
+//! * `debian-binary`: the format version (`2.0\n`)
+//! * `control.tar.gz`: package metadata (the `control` file)
+//! * `data.tar.gz`: the files to install, laid out relative to `/`
+//!
+//! All of these are simple enough that we build them ourselves instead of
+//! depending on `dpkg-deb` being installed, which means .debs can be built
+//! on any platform.
+
+use std::collections::{BTreeMap, BTreeSet};
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::TripleNameRef;
+use flate2::{write::GzEncoder, Compression};
+use tracing::info;
+
+use crate::{tasks::StaticAssetKind, DistResult};
+
+use super::is_executable;
+
+/// Info about a Debian package installer
+#[derive(Debug, Clone)]
+pub struct DebInstallerInfo {
+    /// Name of the Debian package
+    pub package_name: String,
+    /// Version of the Debian package
+    pub version: String,
+    /// Debian architecture (amd64, arm64, ...)
+    pub architecture: String,
+    /// The Maintainer field
+    pub maintainer: String,
+    /// A brief description of the app
+    pub description: Option<String>,
+    /// The app's authors
+    pub authors: Vec<String>,
+    /// The app's license, as an SPDX expression
+    pub license: Option<String>,
+    /// The URL to the app's homepage
+    pub homepage: Option<String>,
+    /// The URL to the app's source repository
+    pub repository: Option<String>,
+    /// Entries of the Depends field
+    pub depends: Vec<String>,
+    /// Final file path of the deb
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries get copied to
+    pub package_dir: Utf8PathBuf,
+    /// Executables to install to /usr/bin (names in package_dir)
+    pub executables: Vec<String>,
+    /// Libraries to install to /usr/lib (names in package_dir)
+    pub libraries: Vec<String>,
+    /// Executable aliases
+    pub bin_aliases: BTreeMap<String, Vec<String>>,
+    /// Static assets to include (README, LICENSE, ...)
+    pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+    /// If set, stamp every entry with this time instead of now (reproducible-archives)
+    pub source_date_epoch: Option<u64>,
+}
+
+impl DebInstallerInfo {
+    /// Build the deb installer
+    pub fn build(&self) -> DistResult<()> {
+        info!("building a deb: {}", self.file_path);
+
+        let (data, installed_size) = self.data_tar()?;
+        let control = self.control_tar(installed_size)?;
+
+        let mut deb = Vec::new();
+        deb.extend_from_slice(b"!<arch>\n");
+        append_ar_member(&mut deb, "debian-binary", b"2.0\n");
+        append_ar_member(&mut deb, "control.tar.gz", &control);
+        append_ar_member(&mut deb, "data.tar.gz", &data);
+
+        if let Some(parent) = self.file_path.parent() {
+            LocalAsset::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.file_path, deb)?;
+
+        Ok(())
+    }
+
+    /// Contents of the `control` file
+    pub fn control_file(&self, installed_size: u64) -> String {
+        let mut control = String::new();
+        control.push_str(&format!("Package: {}\n", self.package_name));
+        control.push_str(&format!("Version: {}\n", self.version));
+        control.push_str(&format!("Architecture: {}\n", self.architecture));
+        control.push_str(&format!("Maintainer: {}\n", self.maintainer));
+        control.push_str(&format!("Installed-Size: {installed_size}\n"));
+        if !self.depends.is_empty() {
+            control.push_str(&format!("Depends: {}\n", self.depends.join(", ")));
+        }
+        control.push_str("Section: utils\n");
+        control.push_str("Priority: optional\n");
+        if let Some(homepage) = self.homepage.as_ref().or(self.repository.as_ref()) {
+            control.push_str(&format!("Homepage: {homepage}\n"));
+        }
+
+        // The first line of the description is the synopsis, every following
+        // line has to be indented, with blank lines spelled " ."
+        let description = self
+            .description
+            .as_deref()
+            .map(str::trim)
+            .filter(|desc| !desc.is_empty())
+            .unwrap_or(&self.package_name);
+        let mut lines = description.lines();
+        control.push_str(&format!(
+            "Description: {}\n",
+            lines.next().unwrap_or_default().trim()
+        ));
+        for line in lines {
+            let line = line.trim_end();
+            if line.is_empty() {
+                control.push_str(" .\n");
+            } else {
+                control.push_str(&format!(" {line}\n"));
+            }
+        }
+
+        control
+    }
+
+    /// Contents of the machine-readable `copyright` file, if we know the license
+    fn copyright_file(&self) -> Option<String> {
+        let license = self.license.as_ref()?;
+        let mut copyright = String::new();
+        copyright.push_str(
+            "Format: https://www.debian.org/doc/packaging-manuals/copyright-format/1.0/\n",
+        );
+        copyright.push_str(&format!("Upstream-Name: {}\n", self.package_name));
+        if !self.authors.is_empty() {
+            copyright.push_str(&format!(
+                "Upstream-Contact: {}\n",
+                self.authors.join(",\n ")
+            ));
+        }
+        if let Some(repository) = &self.repository {
+            copyright.push_str(&format!("Source: {repository}\n"));
+        }
+        copyright.push_str("\nFiles: *\n");
+        if !self.authors.is_empty() {
+            copyright.push_str(&format!("Copyright: {}\n", self.authors.join(",\n ")));
+        }
+        copyright.push_str(&format!("License: {license}\n"));
+        Some(copyright)
+    }
+
+    /// Build data.tar.gz, returning it and the installed size in KiB
+    fn data_tar(&self) -> DistResult<(Vec<u8>, u64)> {
+        let mut tar = DebTar::new(self.mtime());
+        let doc_dir = format!("./usr/share/doc/{}", self.package_name);
+        let share_dir = format!("./usr/share/{}", self.package_name);
+
+        for exe in &self.executables {
+            let contents = std::fs::read(self.package_dir.join(exe))?;
+            tar.file(&format!("./usr/bin/{exe}"), &contents, 0o755)?;
+        }
+        for (bin, aliases) in &self.bin_aliases {
+            for alias in aliases {
+                tar.symlink(&format!("./usr/bin/{alias}"), bin)?;
+            }
+        }
+        for lib in &self.libraries {
+            let contents = std::fs::read(self.package_dir.join(lib))?;
+            tar.file(&format!("./usr/lib/{lib}"), &contents, 0o644)?;
+        }
+        for (kind, src_path) in &self.static_assets {
+            let Some(file_name) = src_path.file_name() else {
+                continue;
+            };
+            let dest_dir = match kind {
+                StaticAssetKind::Readme | StaticAssetKind::License | StaticAssetKind::Changelog => {
+                    &doc_dir
+                }
+                StaticAssetKind::Other => &share_dir,
+            };
+            tar.path(src_path, &format!("{dest_dir}/{file_name}"))?;
+        }
+        if let Some(copyright) = self.copyright_file() {
+            tar.file(&format!("{doc_dir}/copyright"), copyright.as_bytes(), 0o644)?;
+        }
+
+        let installed_size = tar.size.div_ceil(1024);
+        Ok((tar.finish()?, installed_size))
+    }
+
+    /// Build control.tar.gz
+    fn control_tar(&self, installed_size: u64) -> DistResult<Vec<u8>> {
+        let mut tar = DebTar::new(self.mtime());
+        tar.file(
+            "./control",
+            self.control_file(installed_size).as_bytes(),
+            0o644,
+        )?;
+        tar.finish()
+    }
+
+    /// The time to stamp every tar entry with
+    fn mtime(&self) -> u64 {
+        self.source_date_epoch.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        })
+    }
+}
+
+/// Get the Debian architecture name for a target triple
+pub fn debian_arch(target: &TripleNameRef) -> Option<&'static str> {
+    let triple = target.as_str();
+    let arch = triple.split('-').next().unwrap_or_default();
+    let arch = match arch {
+        "x86_64" => "amd64",
+        "i386" | "i586" | "i686" => "i386",
+        "aarch64" => "arm64",
+        "powerpc64le" => "ppc64el",
+        "powerpc64" => "ppc64",
+        "s390x" => "s390x",
+        "riscv64gc" | "riscv64" => "riscv64",
+        "loongarch64" => "loong64",
+        "mips64el" => "mips64el",
+        arm if arm.starts_with("arm") || arm.starts_with("thumbv7") => {
+            if triple.ends_with("hf") {
+                "armhf"
+            } else {
+                "armel"
+            }
+        }
+        _ => return None,
+    };
+    Some(arch)
+}
+
+/// Convert a version to one that sorts correctly in Debian
+///
+/// semver prereleases sort *before* the release, which Debian spells with `~`.
+pub fn debian_version(version: &str) -> String {
+    let (version, build) = match version.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (version, None),
+    };
+    let mut result = match version.split_once('-') {
+        Some((release, pre)) => format!("{release}~{pre}"),
+        None => version.to_owned(),
+    };
+    if let Some(build) = build {
+        result.push('+');
+        result.push_str(build);
+    }
+    result
+}
+
+/// Convert a package name to a valid Debian package name
+///
+/// Debian package names may only contain lowercase letters, digits, `+`, `-`, and `.`
+pub fn debian_package_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | '0'..='9' | '+' | '-' | '.' => c,
+            'A'..='Z' => c.to_ascii_lowercase(),
+            _ => '-',
+        })
+        .collect()
+}
+
+/// A tarball being built in memory, with every file owned by root
+///
+/// Paths are given dpkg-style (`./usr/bin/...`); the tar crate drops the
+/// leading `./`, which dpkg is happy to accept.
+struct DebTar {
+    builder: tar::Builder<GzEncoder<Vec<u8>>>,
+    dirs: BTreeSet<String>,
+    mtime: u64,
+    size: u64,
+}
+
+impl DebTar {
+    fn new(mtime: u64) -> Self {
+        let mut tar = Self {
+            builder: tar::Builder::new(GzEncoder::new(Vec::new(), Compression::best())),
+            dirs: BTreeSet::new(),
+            mtime,
+            size: 0,
+        };
+        // Can't fail, this is all in memory
+        let _ = tar.dir(".");
+        tar
+    }
+
+    fn header(&self, entry_type: tar::EntryType, mode: u32, size: u64) -> tar::Header {
+        let mut header = tar::Header::new_gnu();
+        header.set_entry_type(entry_type);
+        header.set_mode(mode);
+        header.set_size(size);
+        header.set_uid(0);
+        header.set_gid(0);
+        header.set_mtime(self.mtime);
+        // These only fail if the names don't fit, and they do
+        let _ = header.set_username("root");
+        let _ = header.set_groupname("root");
+        header
+    }
+
+    fn dir(&mut self, path: &str) -> DistResult<()> {
+        if self.dirs.contains(path) {
+            return Ok(());
+        }
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            self.dir(parent)?;
+        }
+        let mut header = self.header(tar::EntryType::Directory, 0o755, 0);
+        self.builder
+            .append_data(&mut header, format!("{path}/"), std::io::empty())?;
+        self.dirs.insert(path.to_owned());
+        Ok(())
+    }
+
+    fn parent_dir(&mut self, path: &str) -> DistResult<()> {
+        if let Some((parent, _)) = path.rsplit_once('/') {
+            self.dir(parent)?;
+        }
+        Ok(())
+    }
+
+    fn file(&mut self, path: &str, contents: &[u8], mode: u32) -> DistResult<()> {
+        self.parent_dir(path)?;
+        let mut header = self.header(tar::EntryType::Regular, mode, contents.len() as u64);
+        self.builder.append_data(&mut header, path, contents)?;
+        self.size += contents.len() as u64;
+        Ok(())
+    }
+
+    fn symlink(&mut self, path: &str, target: &str) -> DistResult<()> {
+        self.parent_dir(path)?;
+        let mut header = self.header(tar::EntryType::Symlink, 0o777, 0);
+        self.builder.append_link(&mut header, path, target)?;
+        Ok(())
+    }
+
+    /// Add a file or (recursively) a directory from disk
+    fn path(&mut self, src_path: &Utf8Path, path: &str) -> DistResult<()> {
+        if src_path.is_dir() {
+            self.dir(path)?;
+            let mut entries = src_path.read_dir_utf8()?.collect::<Result<Vec<_>, _>>()?;
+            entries.sort_by(|a, b| a.file_name().cmp(b.file_name()));
+            for entry in entries {
+                self.path(entry.path(), &format!("{path}/{}", entry.file_name()))?;
+            }
+        } else {
+            let contents = std::fs::read(src_path)?;
+            let mode = if is_executable(src_path) {
+                0o755
+            } else {
+                0o644
+            };
+            self.file(path, &contents, mode)?;
+        }
+        Ok(())
+    }
+
+    fn finish(self) -> DistResult<Vec<u8>> {
+        Ok(self.builder.into_inner()?.finish()?)
+    }
+}
+
+/// Append a member to an `ar` archive, in the common format dpkg expects
+fn append_ar_member(ar: &mut Vec<u8>, name: &str, contents: &[u8]) {
+    let mut header = Vec::with_capacity(60);
+    let _ = writeln!(
+        header,
+        "{:<16}{:<12}{:<6}{:<6}{:<8}{:<10}`",
+        name,
+        0,
+        0,
+        0,
+        100644,
+        contents.len()
+    );
+    ar.extend_from_slice(&header);
+    ar.extend_from_slice(contents);
+    // Members are aligned to 2 bytes
+    if contents.len() % 2 == 1 {
+        ar.push(b'\n');
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    fn info(dir: &Utf8Path) -> DebInstallerInfo {
+        DebInstallerInfo {
+            package_name: "axolotlsay".to_owned(),
+            version: "0.1.0".to_owned(),
+            architecture: "amd64".to_owned(),
+            maintainer: "Axo Developer <hello@axo.dev>".to_owned(),
+            description: Some("💬 a CLI for learning to distribute CLIs in rust".to_owned()),
+            authors: vec!["Axo Developer <hello@axo.dev>".to_owned()],
+            license: Some("MIT OR Apache-2.0".to_owned()),
+            homepage: None,
+            repository: Some("https://github.com/axodotdev/axolotlsay".to_owned()),
+            depends: vec!["libssl3".to_owned(), "zlib1g (>= 1:1.2.11)".to_owned()],
+            file_path: dir.join("axolotlsay-x86_64-unknown-linux-gnu.deb"),
+            package_dir: dir.join("axolotlsay-x86_64-unknown-linux-gnu_deb"),
+            executables: vec!["axolotlsay".to_owned()],
+            libraries: vec![],
+            bin_aliases: BTreeMap::from([("axolotlsay".to_owned(), vec!["say".to_owned()])]),
+            static_assets: vec![],
+            source_date_epoch: None,
+        }
+    }
+
+    /// Split an ar archive into (name, contents) pairs
+    fn read_ar(bytes: &[u8]) -> Vec<(String, Vec<u8>)> {
+        assert_eq!(&bytes[..8], b"!<arch>\n");
+        let mut members = vec![];
+        let mut pos = 8;
+        while pos < bytes.len() {
+            let header = std::str::from_utf8(&bytes[pos..pos + 60]).unwrap();
+            assert!(header.ends_with("`\n"));
+            let name = header[..16].trim().to_owned();
+            let size: usize = header[48..58].trim().parse().unwrap();
+            pos += 60;
+            members.push((name, bytes[pos..pos + size].to_vec()));
+            pos += size + size % 2;
+        }
+        members
+    }
+
+    /// List the (path, mode, contents) of everything in a tar.gz
+    fn read_tar_gz(bytes: &[u8]) -> Vec<(String, u32, String)> {
+        let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(bytes));
+        archive
+            .entries()
+            .unwrap()
+            .map(|entry| {
+                let mut entry = entry.unwrap();
+                let path = entry.path().unwrap().to_string_lossy().into_owned();
+                let mode = entry.header().mode().unwrap();
+                let mut contents = String::new();
+                if entry.header().entry_type().is_symlink() {
+                    contents = entry
+                        .link_name()
+                        .unwrap()
+                        .unwrap()
+                        .to_string_lossy()
+                        .into_owned();
+                } else {
+                    entry.read_to_string(&mut contents).unwrap();
+                }
+                (path, mode, contents)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn maps_debian_arches() {
+        let arch = |t: &str| debian_arch(TripleNameRef::from_str(t));
+        assert_eq!(arch("x86_64-unknown-linux-gnu"), Some("amd64"));
+        assert_eq!(arch("x86_64-unknown-linux-musl"), Some("amd64"));
+        assert_eq!(arch("aarch64-unknown-linux-gnu"), Some("arm64"));
+        assert_eq!(arch("i686-unknown-linux-gnu"), Some("i386"));
+        assert_eq!(arch("armv7-unknown-linux-gnueabihf"), Some("armhf"));
+        assert_eq!(arch("arm-unknown-linux-gnueabi"), Some("armel"));
+        assert_eq!(arch("powerpc64le-unknown-linux-gnu"), Some("ppc64el"));
+        assert_eq!(arch("riscv64gc-unknown-linux-gnu"), Some("riscv64"));
+        assert_eq!(arch("sparc64-unknown-linux-gnu"), None);
+    }
+
+    #[test]
+    fn converts_versions() {
+        assert_eq!(debian_version("1.2.3"), "1.2.3");
+        assert_eq!(debian_version("1.2.3-alpha.1"), "1.2.3~alpha.1");
+        assert_eq!(debian_version("1.2.3-rc.1+build.5"), "1.2.3~rc.1+build.5");
+        assert_eq!(debian_version("1.2.3+build.5"), "1.2.3+build.5");
+        assert_eq!(debian_package_name("My_App"), "my-app");
+    }
+
+    #[test]
+    fn writes_control_file() {
+        let mut info = info(Utf8Path::new("/tmp"));
+        info.description = Some("A tool\n\nthat does things\nwell".to_owned());
+        insta::assert_snapshot!(info.control_file(1234), @r###"
+        Package: axolotlsay
+        Version: 0.1.0
+        Architecture: amd64
+        Maintainer: Axo Developer <hello@axo.dev>
+        Installed-Size: 1234
+        Depends: libssl3, zlib1g (>= 1:1.2.11)
+        Section: utils
+        Priority: optional
+        Homepage: https://github.com/axodotdev/axolotlsay
+        Description: A tool
+         .
+         that does things
+         well
+        "###);
+    }
+
+    #[test]
+    fn builds_deb() {
+        let tmp = temp_dir::TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(tmp.path()).unwrap();
+        let mut info = info(dir);
+        LocalAsset::write_new_all("#!/bin/sh\necho hi\n", info.package_dir.join("axolotlsay"))
+            .unwrap();
+        LocalAsset::write_new_all("# axolotlsay\n", dir.join("README.md")).unwrap();
+        info.static_assets = vec![(StaticAssetKind::Readme, dir.join("README.md"))];
+        info.build().unwrap();
+
+        let deb = std::fs::read(&info.file_path).unwrap();
+        let members = read_ar(&deb);
+        let names: Vec<_> = members.iter().map(|(name, _)| name.as_str()).collect();
+        assert_eq!(names, ["debian-binary", "control.tar.gz", "data.tar.gz"]);
+        assert_eq!(members[0].1, b"2.0\n");
+
+        let control = read_tar_gz(&members[1].1);
+        assert_eq!(control.len(), 2);
+        assert_eq!(control[1].0, "control");
+        assert!(control[1].2.contains("Installed-Size: 1\n"));
+
+        let data = read_tar_gz(&members[2].1);
+        let paths: Vec<_> = data
+            .iter()
+            .map(|(path, mode, _)| format!("{path} {mode:o}"))
+            .collect();
+        assert_eq!(
+            paths,
+            [
+                "./ 755",
+                "usr/ 755",
+                "usr/bin/ 755",
+                "usr/bin/axolotlsay 755",
+                "usr/bin/say 777",
+                "usr/share/ 755",
+                "usr/share/doc/ 755",
+                "usr/share/doc/axolotlsay/ 755",
+                "usr/share/doc/axolotlsay/README.md 644",
+                "usr/share/doc/axolotlsay/copyright 644",
+            ]
+        );
+        assert_eq!(data[3].2, "#!/bin/sh\necho hi\n");
+        assert_eq!(data[4].2, "axolotlsay");
+        assert!(data[9].2.contains("License: MIT OR Apache-2.0\n"));
+    }
+
+    #[test]
+    fn builds_reproducible_deb() {
+        const EPOCH: u64 = 1_700_000_000;
+        let tmp = temp_dir::TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(tmp.path()).unwrap();
+        let mut info = info(dir);
+        info.source_date_epoch = Some(EPOCH);
+        LocalAsset::write_new_all("#!/bin/sh\necho hi\n", info.package_dir.join("axolotlsay"))
+            .unwrap();
+        info.build().unwrap();
+        let first = std::fs::read(&info.file_path).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        info.build().unwrap();
+        let second = std::fs::read(&info.file_path).unwrap();
+        assert_eq!(first, second, "debs differ");
+
+        for (name, member) in read_ar(&first).into_iter().skip(1) {
+            let mut archive = tar::Archive::new(flate2::read::GzDecoder::new(&member[..]));
+            for entry in archive.entries().unwrap() {
+                let entry = entry.unwrap();
+                assert_eq!(
+                    entry.header().mtime().unwrap(),
+                    EPOCH,
+                    "{name}: {} isn't stamped with the epoch",
+                    entry.path().unwrap().display()
+                );
+            }
+        }
+    }
+}
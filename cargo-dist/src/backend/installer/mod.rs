@@ -4,15 +4,20 @@
 
 use std::collections::BTreeMap;
 
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{ArtifactId, EnvironmentVariables, Hosting, TripleName};
+use deb::DebInstallerInfo;
 use homebrew::HomebrewFragments;
 use macpkg::PkgInstallerInfo;
+use rpm::RpmInstallerInfo;
+use scoop::ScoopInstallerInfo;
 use serde::Serialize;
+use winget::{WingetInstallerInfo, WingetManifestType};
 
 use crate::{
     config::{JinjaInstallPathStrategy, LibraryStyle, ZipStyle},
     platform::{PlatformSupport, RuntimeConditions},
+    sign::InstallerSigningKey,
     InstallReceipt, ReleaseIdx,
 };
 
@@ -20,12 +25,16 @@ use self::homebrew::HomebrewInstallerInfo;
 use self::msi::MsiInstallerInfo;
 use self::npm::NpmInstallerInfo;
 
+pub mod deb;
 pub mod homebrew;
 pub mod macpkg;
 pub mod msi;
 pub mod npm;
 pub mod powershell;
+pub mod rpm;
+pub mod scoop;
 pub mod shell;
+pub mod winget;
 
 /// A kind of an installer
 #[derive(Debug, Clone)]
@@ -43,6 +52,14 @@ pub enum InstallerImpl {
     Msi(MsiInstallerInfo),
     /// Mac pkg installer
     Pkg(PkgInstallerInfo),
+    /// Debian deb installer
+    Deb(DebInstallerInfo),
+    /// RPM installer
+    Rpm(RpmInstallerInfo),
+    /// Scoop manifest
+    Scoop(ScoopImpl),
+    /// winget manifest
+    Winget(WingetImpl),
 }
 
 /// Information needed to make a homebrew installer
@@ -55,6 +72,40 @@ pub struct HomebrewImpl {
     pub fragments: HomebrewFragments<ExecutableZipFragment>,
 }
 
+/// Information needed to make a Scoop manifest
+#[derive(Debug, Clone)]
+pub struct ScoopImpl {
+    /// Base information
+    pub info: ScoopInstallerInfo,
+
+    /// Various fragments (x86_64 windows, arm64 windows, etc.)
+    pub fragments: WindowsFragments<ExecutableZipFragment>,
+}
+
+/// Information needed to make one of the winget manifests
+#[derive(Debug, Clone)]
+pub struct WingetImpl {
+    /// Base information
+    pub info: WingetInstallerInfo,
+
+    /// Which of the manifests for the package this is
+    pub manifest_type: WingetManifestType,
+
+    /// Various fragments (x86_64 windows, arm64 windows, etc.)
+    pub fragments: WindowsFragments<ExecutableZipFragment>,
+}
+
+/// Fragments for installers that only handle Windows
+#[derive(Debug, Clone, Serialize)]
+pub struct WindowsFragments<T> {
+    /// Windows AMD64 artifact
+    pub x86_64_windows: Option<T>,
+    /// Windows x86 artifact
+    pub x86_windows: Option<T>,
+    /// Windows ARM64 artifact
+    pub arm64_windows: Option<T>,
+}
+
 /// Generic info about an installer
 #[derive(Debug, Clone, Serialize)]
 pub struct InstallerInfo {
@@ -93,6 +144,8 @@ pub struct InstallerInfo {
     pub platform_support: Option<PlatformSupport>,
     /// Environment variables for installer customization
     pub env_vars: Option<EnvironmentVariables>,
+    /// Public key to verify detached signatures of artifacts with, if any
+    pub signing_key: Option<InstallerSigningKey>,
 }
 
 /// A fake fragment of an ExecutableZip artifact for installers
@@ -124,3 +177,18 @@ pub struct UpdaterFragment {
     /// The binary the artifact contains (name, assumed at root)
     pub binary: ArtifactId,
 }
+
+/// Whether the file at the given path is executable, for packaging it with the right mode
+#[cfg(unix)]
+pub(crate) fn is_executable(path: &Utf8Path) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    path.metadata()
+        .map(|m| m.permissions().mode() & 0o111 != 0)
+        .unwrap_or(false)
+}
+
+/// Whether the file at the given path is executable, for packaging it with the right mode
+#[cfg(not(unix))]
+pub(crate) fn is_executable(_path: &Utf8Path) -> bool {
+    false
+}
@@ -6,7 +6,14 @@ use cargo_dist_schema::TripleName;
 use tracing::info;
 use wix::print::{wxs::WxsRenders, RenderOutput};
 
-use crate::{backend::diff_files, config, errors::*, DistGraph};
+use crate::{
+    backend::diff_files,
+    config::{self, MsiBuilder},
+    errors::*,
+    DistGraph,
+};
+
+mod native;
 
 const METADATA_WIX: &str = "wix";
 const WIX_GUID_KEYS: &[&str] = &["upgrade-guid", "path-guid"];
@@ -27,6 +34,10 @@ pub struct MsiInstallerInfo {
     pub wxs_path: Utf8PathBuf,
     /// Path to the package Cargo.toml associated with this msi
     pub manifest_path: Utf8PathBuf,
+    /// The version of the package, in the Major.Minor.Patch form msi requires
+    pub version: String,
+    /// What to build the msi with
+    pub builder: MsiBuilder,
 }
 
 impl MsiInstallerInfo {
@@ -35,6 +46,18 @@ impl MsiInstallerInfo {
     /// Note that this assumes `write_wsx_to_disk` was run beforehand (via `dist generate`),
     /// which should be enforced by `check_wsx` (via `dist generate --check`).
     pub fn build(&self, dist: &DistGraph) -> DistResult<()> {
+        match self.builder {
+            MsiBuilder::Wix => self.build_with_wix()?,
+            MsiBuilder::Native => native::build_msi(self)?,
+        }
+
+        assert!(self.file_path.exists());
+        dist.signer.sign(&self.file_path)?;
+        Ok(())
+    }
+
+    /// Build the msi with the WiX toolset, via cargo-wix
+    fn build_with_wix(&self) -> DistResult<()> {
         info!("building an msi: {}", self.file_path);
 
         let mut b = wix::create::Builder::new();
@@ -60,9 +83,6 @@ impl MsiInstallerInfo {
             msi: self.file_path.file_name().unwrap().to_owned(),
             details: e,
         })?;
-
-        assert!(self.file_path.exists());
-        dist.signer.sign(&self.file_path)?;
         Ok(())
     }
 
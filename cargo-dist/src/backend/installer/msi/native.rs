@@ -0,0 +1,1897 @@
+//! A pure-Rust msi builder
+//!
+//! This turns the same `wix/main.wxs` that WiX would consume into an msi, without
+//! needing the WiX toolset (and therefore Windows). It only understands the subset
+//! of WiX v3 that cargo-wix's template uses (plus a few common additions like
+//! launch conditions), and refuses anything else rather than quietly producing an
+//! installer that differs from what WiX would have built.
+
+use std::io::{Cursor, Write};
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use msi::{Category, CodePage, Column, Insert, Language, Package, PackageType, Value};
+use roxmltree::{Document, Node};
+use sha2::{Digest, Sha256};
+use tracing::{info, warn};
+
+use super::MsiInstallerInfo;
+use crate::{errors::*, SortedMap};
+
+/// Properties that the `MajorUpgrade` element makes the Upgrade table set
+const UPGRADE_DETECTED: &str = "WIX_UPGRADE_DETECTED";
+const DOWNGRADE_DETECTED: &str = "WIX_DOWNGRADE_DETECTED";
+
+/// The standard actions we sequence, and where Windows Installer expects them
+///
+/// The bool is whether the action also belongs in the InstallUISequence.
+const STANDARD_ACTIONS: &[(&str, i32, bool)] = &[
+    ("FindRelatedProducts", 25, true),
+    ("LaunchConditions", 100, true),
+    ("ValidateProductID", 700, true),
+    ("CostInitialize", 800, true),
+    ("FileCost", 900, true),
+    ("CostFinalize", 1000, true),
+    ("MigrateFeatureStates", 1200, true),
+    ("ExecuteAction", 1300, true),
+    ("InstallValidate", 1400, false),
+    ("InstallInitialize", 1500, false),
+    ("ProcessComponents", 1600, false),
+    ("UnpublishFeatures", 1800, false),
+    ("RemoveEnvironmentStrings", 3300, false),
+    ("RemoveFiles", 3500, false),
+    ("RemoveFolders", 3600, false),
+    ("CreateFolders", 3700, false),
+    ("InstallFiles", 4000, false),
+    ("WriteEnvironmentStrings", 5200, false),
+    ("RegisterUser", 6000, false),
+    ("RegisterProduct", 6100, false),
+    ("PublishFeatures", 6300, false),
+    ("PublishProduct", 6400, false),
+    ("InstallFinalize", 6600, false),
+];
+
+/// Build an msi from the info's wxs, without WiX
+pub(crate) fn build_msi(info: &MsiInstallerInfo) -> DistResult<()> {
+    info!("building an msi natively: {}", info.file_path);
+    let msi_name = info.file_path.file_name().unwrap_or_default().to_owned();
+
+    let wxs = LocalAsset::load_string(&info.wxs_path)?;
+    let base_dir = info
+        .manifest_path
+        .parent()
+        .expect("Cargo.toml had no parent dir!?");
+    let model = read_wxs(&wxs, base_dir, preprocessor_for(info)).map_err(|details| {
+        DistError::NativeMsiWxs {
+            wxs: info.wxs_path.clone(),
+            details,
+        }
+    })?;
+    let payload = Payload::load(&model).map_err(|details| DistError::NativeMsi {
+        msi: msi_name.clone(),
+        details,
+    })?;
+    let msi = write_msi(&model, &payload).map_err(|details| DistError::NativeMsi {
+        msi: msi_name.clone(),
+        details,
+    })?;
+    std::fs::write(&info.file_path, msi)?;
+    Ok(())
+}
+
+/// Set up the variables that cargo-wix would pass to WiX's preprocessor
+fn preprocessor_for(info: &MsiInstallerInfo) -> Preprocessor {
+    let target = info.target.as_str();
+    let mut parts = target.split('-');
+    let arch = parts.next().unwrap_or_default();
+    let vendor = parts.next().unwrap_or_default();
+    let env = parts.next_back().unwrap_or_default();
+    let build_arch = match arch {
+        "aarch64" => "arm64",
+        "i586" | "i686" => "x86",
+        _ => "x64",
+    };
+
+    let mut vars = SortedMap::new();
+    vars.insert("Version".to_owned(), info.version.clone());
+    vars.insert("CargoProfile".to_owned(), "dist".to_owned());
+    vars.insert("CargoTargetBinDir".to_owned(), info.package_dir.to_string());
+    vars.insert("TargetTriple".to_owned(), target.to_owned());
+    vars.insert("TargetVendor".to_owned(), vendor.to_owned());
+    vars.insert("TargetEnv".to_owned(), env.to_owned());
+    Preprocessor { vars, build_arch }
+}
+
+/// Just enough of WiX's preprocessor to handle cargo-wix's template
+struct Preprocessor {
+    /// `$(var.*)` variables, from the build and from `<?define?>`
+    vars: SortedMap<String, String>,
+    /// `$(sys.BUILDARCH)`
+    build_arch: &'static str,
+}
+
+impl Preprocessor {
+    /// Expand all the `$(...)` references in a string
+    fn substitute(&self, input: &str) -> Result<String, String> {
+        if input.contains("!(") {
+            return Err(format!(
+                "localization and binder variables aren't supported: {input:?}"
+            ));
+        }
+        let mut output = String::new();
+        let mut rest = input;
+        while let Some(start) = rest.find("$(") {
+            output.push_str(&rest[..start]);
+            let after = &rest[start + 2..];
+            let end = after
+                .find(')')
+                .ok_or_else(|| format!("unterminated variable reference in {input:?}"))?;
+            output.push_str(&self.lookup(&after[..end])?);
+            rest = &after[end + 1..];
+        }
+        output.push_str(rest);
+        Ok(output)
+    }
+
+    fn lookup(&self, name: &str) -> Result<String, String> {
+        let (kind, var) = name.split_once('.').unwrap_or(("var", name));
+        match kind {
+            "var" => self
+                .vars
+                .get(var)
+                .cloned()
+                .ok_or_else(|| format!("undefined preprocessor variable $({name})")),
+            "sys" if var == "BUILDARCH" => Ok(self.build_arch.to_owned()),
+            "env" => {
+                std::env::var(var).map_err(|_| format!("environment variable {var} isn't set"))
+            }
+            _ => Err(format!("unsupported preprocessor variable $({name})")),
+        }
+    }
+
+    /// Apply a `<?define NAME = "value" ?>`
+    fn define(&mut self, definition: &str) -> Result<(), String> {
+        let (name, value) = definition.split_once('=').unwrap_or((definition, ""));
+        let value = value.trim();
+        let value = value
+            .strip_prefix('"')
+            .and_then(|v| v.strip_suffix('"'))
+            .unwrap_or(value);
+        let value = self.substitute(value)?;
+        self.vars.insert(name.trim().to_owned(), value);
+        Ok(())
+    }
+
+    /// Evaluate the condition of an `<?if?>`
+    fn eval(&self, condition: &str) -> Result<bool, String> {
+        let tokens = tokenize(condition)?;
+        let mut parser = ConditionParser {
+            pp: self,
+            tokens: &tokens,
+            pos: 0,
+        };
+        let result = parser.or()?;
+        if parser.pos != tokens.len() {
+            return Err(format!("couldn't parse condition {condition:?}"));
+        }
+        Ok(result)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Token {
+    LParen,
+    RParen,
+    And,
+    Or,
+    Not,
+    /// `=`, `!=`, or `~=` (case-insensitive equality)
+    Op(&'static str),
+    /// A literal or variable reference to compare
+    Value(String),
+}
+
+fn tokenize(condition: &str) -> Result<Vec<Token>, String> {
+    let chars = condition.chars().collect::<Vec<_>>();
+    let mut tokens = vec![];
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            c if c.is_whitespace() => i += 1,
+            '(' => {
+                tokens.push(Token::LParen);
+                i += 1;
+            }
+            ')' => {
+                tokens.push(Token::RParen);
+                i += 1;
+            }
+            '=' => {
+                tokens.push(Token::Op("="));
+                i += 1;
+            }
+            c @ ('!' | '~') if chars.get(i + 1) == Some(&'=') => {
+                tokens.push(Token::Op(if c == '!' { "!=" } else { "~=" }));
+                i += 2;
+            }
+            '"' => {
+                let len = chars[i + 1..]
+                    .iter()
+                    .position(|&c| c == '"')
+                    .ok_or_else(|| format!("unterminated string in condition {condition:?}"))?;
+                tokens.push(Token::Value(chars[i + 1..i + 1 + len].iter().collect()));
+                i += len + 2;
+            }
+            c @ ('!' | '~' | '<' | '>') => {
+                return Err(format!(
+                    "unsupported operator {c:?} in condition {condition:?}"
+                ))
+            }
+            _ => {
+                let mut word = String::new();
+                let mut in_var = false;
+                while let Some(&c) = chars.get(i) {
+                    if in_var {
+                        in_var = c != ')';
+                    } else if c.is_whitespace() || "()=!~<>\"".contains(c) {
+                        break;
+                    } else if c == '$' && chars.get(i + 1) == Some(&'(') {
+                        in_var = true;
+                        word.push(c);
+                        word.push('(');
+                        i += 2;
+                        continue;
+                    }
+                    word.push(c);
+                    i += 1;
+                }
+                tokens.push(match word.to_ascii_lowercase().as_str() {
+                    "and" => Token::And,
+                    "or" => Token::Or,
+                    "not" => Token::Not,
+                    _ => Token::Value(word),
+                });
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+struct ConditionParser<'a> {
+    pp: &'a Preprocessor,
+    tokens: &'a [Token],
+    pos: usize,
+}
+
+impl ConditionParser<'_> {
+    fn eat(&mut self, token: &Token) -> bool {
+        if self.tokens.get(self.pos) == Some(token) {
+            self.pos += 1;
+            true
+        } else {
+            false
+        }
+    }
+
+    fn or(&mut self) -> Result<bool, String> {
+        let mut result = self.and()?;
+        while self.eat(&Token::Or) {
+            let rhs = self.and()?;
+            result = result || rhs;
+        }
+        Ok(result)
+    }
+
+    fn and(&mut self) -> Result<bool, String> {
+        let mut result = self.unary()?;
+        while self.eat(&Token::And) {
+            let rhs = self.unary()?;
+            result = result && rhs;
+        }
+        Ok(result)
+    }
+
+    fn unary(&mut self) -> Result<bool, String> {
+        if self.eat(&Token::Not) {
+            return Ok(!self.unary()?);
+        }
+        if self.eat(&Token::LParen) {
+            let result = self.or()?;
+            if !self.eat(&Token::RParen) {
+                return Err("unbalanced parentheses in condition".to_owned());
+            }
+            return Ok(result);
+        }
+        let lhs = self.value()?;
+        let Some(Token::Op(op)) = self.tokens.get(self.pos) else {
+            return Err(format!("expected a comparison after {lhs:?}"));
+        };
+        self.pos += 1;
+        let rhs = self.value()?;
+        Ok(match *op {
+            "=" => lhs == rhs,
+            "!=" => lhs != rhs,
+            _ => lhs.eq_ignore_ascii_case(&rhs),
+        })
+    }
+
+    fn value(&mut self) -> Result<String, String> {
+        match self.tokens.get(self.pos) {
+            Some(Token::Value(value)) => {
+                self.pos += 1;
+                self.pp.substitute(value)
+            }
+            _ => Err("expected a value in condition".to_owned()),
+        }
+    }
+}
+
+/// The state of one `<?if?>` block
+struct IfBlock {
+    /// Whether the enclosing block is active
+    parent_active: bool,
+    /// Whether we're in the branch that was taken
+    active: bool,
+    /// Whether any branch has been taken yet
+    taken: bool,
+}
+
+/// Everything we need to know from the wxs to write an msi
+#[derive(Debug, Default)]
+struct MsiModel {
+    product_code: String,
+    product_name: String,
+    upgrade_code: Option<String>,
+    manufacturer: String,
+    language: u16,
+    codepage: Option<i32>,
+    version: String,
+    package: PackageModel,
+    major_upgrade: Option<MajorUpgradeModel>,
+    media: Vec<MediaModel>,
+    properties: SortedMap<String, String>,
+    secure_properties: Vec<String>,
+    launch_conditions: Vec<(String, String)>,
+    directories: Vec<DirectoryModel>,
+    components: Vec<ComponentModel>,
+    files: Vec<FileModel>,
+    environment: Vec<EnvironmentModel>,
+    features: Vec<FeatureModel>,
+    feature_components: Vec<(String, String)>,
+    set_properties: Vec<SetPropertyModel>,
+    icons: Vec<(String, Utf8PathBuf)>,
+}
+
+/// The `<Package>`, which becomes the msi's summary info
+#[derive(Debug, Default)]
+struct PackageModel {
+    keywords: Vec<String>,
+    description: Option<String>,
+    comments: Option<String>,
+    manufacturer: Option<String>,
+    installer_version: i32,
+    per_machine: bool,
+    codepage: Option<i32>,
+    /// The architecture for the summary info's template ("x64", "Arm64", or "Intel")
+    platform: String,
+}
+
+impl PackageModel {
+    fn is_64bit(&self) -> bool {
+        self.platform != "Intel"
+    }
+}
+
+#[derive(Debug)]
+struct MajorUpgradeModel {
+    sequence: i32,
+    downgrade_error: Option<String>,
+    allow_downgrades: bool,
+    allow_same_version: bool,
+}
+
+#[derive(Debug)]
+struct MediaModel {
+    id: i32,
+    cabinet: String,
+    disk_prompt: Option<String>,
+}
+
+#[derive(Debug)]
+struct DirectoryModel {
+    id: String,
+    parent: Option<String>,
+    default_dir: String,
+}
+
+#[derive(Debug)]
+struct ComponentModel {
+    id: String,
+    guid: Option<String>,
+    directory: String,
+    attributes: i32,
+    key_path: Option<String>,
+}
+
+#[derive(Debug)]
+struct FileModel {
+    id: String,
+    component: String,
+    file_name: String,
+    source: Utf8PathBuf,
+    disk_id: i32,
+}
+
+#[derive(Debug)]
+struct EnvironmentModel {
+    id: String,
+    name: String,
+    value: String,
+    component: String,
+}
+
+#[derive(Debug)]
+struct FeatureModel {
+    id: String,
+    parent: Option<String>,
+    title: Option<String>,
+    description: Option<String>,
+    display: i32,
+    level: i32,
+    directory: Option<String>,
+    attributes: i32,
+}
+
+#[derive(Debug)]
+struct SetPropertyModel {
+    action: String,
+    property: String,
+    value: String,
+    condition: Option<String>,
+    /// The standard action to schedule relative to, and whether to go after it
+    anchor: (String, bool),
+    ui: bool,
+    execute: bool,
+}
+
+/// Parse a wxs into the tables we'll write
+fn read_wxs(wxs: &str, base_dir: &Utf8Path, pp: Preprocessor) -> Result<MsiModel, String> {
+    let doc = Document::parse(wxs).map_err(|e| e.to_string())?;
+    let mut reader = WxsReader {
+        doc: &doc,
+        pp,
+        base_dir,
+        warned_ui: false,
+        display_counter: 0,
+        model: MsiModel::default(),
+    };
+
+    let roots = reader.children(doc.root())?;
+    let wix = match &roots[..] {
+        [wix] if wix.tag_name().name() == "Wix" => *wix,
+        _ => return Err("expected a single <Wix> element".to_owned()),
+    };
+    let mut product = None;
+    for child in reader.children(wix)? {
+        if child.tag_name().name() == "Product" && product.is_none() {
+            product = Some(child);
+        } else {
+            return Err(reader.unsupported(child));
+        }
+    }
+    let product = product.ok_or_else(|| "no <Product> element found".to_owned())?;
+    reader.read_product(product)?;
+    reader.validate()?;
+    Ok(reader.model)
+}
+
+struct WxsReader<'a, 'input> {
+    doc: &'a Document<'input>,
+    pp: Preprocessor,
+    base_dir: &'a Utf8Path,
+    warned_ui: bool,
+    display_counter: i32,
+    model: MsiModel,
+}
+
+impl<'a, 'input> WxsReader<'a, 'input> {
+    /// Get the child elements of a node, applying any preprocessor instructions along the way
+    fn children(&mut self, node: Node<'a, 'input>) -> Result<Vec<Node<'a, 'input>>, String> {
+        let mut blocks: Vec<IfBlock> = vec![];
+        let mut elements = vec![];
+        for child in node.children() {
+            let active = blocks.last().map(|b| b.active).unwrap_or(true);
+            if child.is_element() {
+                if active {
+                    elements.push(child);
+                }
+                continue;
+            }
+            let Some(pi) = child.pi() else {
+                continue;
+            };
+            let value = pi.value.unwrap_or_default().trim();
+            let line = self.doc.text_pos_at(child.range().start).row;
+            let at = |msg: String| format!("line {line}: {msg}");
+            match pi.target {
+                "define" if active => self.pp.define(value).map_err(at)?,
+                "undef" if active => {
+                    self.pp.vars.remove(value);
+                }
+                "if" | "ifdef" | "ifndef" => {
+                    let result = active
+                        && match pi.target {
+                            "if" => self.pp.eval(value).map_err(at)?,
+                            "ifdef" => self.pp.vars.contains_key(value),
+                            _ => !self.pp.vars.contains_key(value),
+                        };
+                    blocks.push(IfBlock {
+                        parent_active: active,
+                        active: result,
+                        taken: result,
+                    });
+                }
+                "elseif" | "else" => {
+                    let block = blocks.last_mut().ok_or_else(|| {
+                        self.error(child, format!("<?{}?> without <?if?>", pi.target))
+                    })?;
+                    let result = block.parent_active
+                        && !block.taken
+                        && (pi.target == "else" || self.pp.eval(value).map_err(at)?);
+                    block.active = result;
+                    block.taken |= result;
+                }
+                "endif" => {
+                    blocks
+                        .pop()
+                        .ok_or_else(|| self.error(child, "<?endif?> without <?if?>".to_owned()))?;
+                }
+                "warning" if active => warn!("{}", self.pp.substitute(value).map_err(at)?),
+                "error" if active => {
+                    return Err(self.error(child, self.pp.substitute(value).map_err(at)?))
+                }
+                "define" | "undef" | "warning" | "error" => {}
+                target => {
+                    return Err(self.error(child, format!("<?{target}?> isn't supported")));
+                }
+            }
+        }
+        if !blocks.is_empty() {
+            return Err(self.error(node, "unterminated <?if?>".to_owned()));
+        }
+        Ok(elements)
+    }
+
+    fn error(&self, node: Node, msg: String) -> String {
+        let pos = self.doc.text_pos_at(node.range().start);
+        format!("line {}: {msg}", pos.row)
+    }
+
+    fn unsupported(&self, node: Node) -> String {
+        self.error(
+            node,
+            format!("<{}> isn't supported", node.tag_name().name()),
+        )
+    }
+
+    fn attr(&self, node: Node, name: &str) -> Result<Option<String>, String> {
+        node.attribute(name)
+            .map(|val| self.pp.substitute(val).map_err(|e| self.error(node, e)))
+            .transpose()
+    }
+
+    fn required(&self, node: Node, name: &str) -> Result<String, String> {
+        self.attr(node, name)?.ok_or_else(|| {
+            self.error(
+                node,
+                format!("<{}> is missing {name}", node.tag_name().name()),
+            )
+        })
+    }
+
+    fn yes_no(&self, node: Node, name: &str, default: bool) -> Result<bool, String> {
+        match self.attr(node, name)?.as_deref() {
+            None => Ok(default),
+            Some("yes") => Ok(true),
+            Some("no") => Ok(false),
+            Some(other) => {
+                Err(self.error(node, format!("{name} should be yes or no, not {other:?}")))
+            }
+        }
+    }
+
+    fn int(&self, node: Node, name: &str) -> Result<Option<i32>, String> {
+        self.attr(node, name)?
+            .map(|val| {
+                val.parse().map_err(|_| {
+                    self.error(node, format!("{name} should be a number, not {val:?}"))
+                })
+            })
+            .transpose()
+    }
+
+    fn text(&self, node: Node) -> Result<Option<String>, String> {
+        let text = node.text().unwrap_or_default().trim();
+        if text.is_empty() {
+            Ok(None)
+        } else {
+            self.pp
+                .substitute(text)
+                .map(Some)
+                .map_err(|e| self.error(node, e))
+        }
+    }
+
+    /// WiX (and the UI it brings along) isn't something we can reproduce, so just let people know
+    fn skip_ui(&mut self, node: Node) {
+        if !self.warned_ui {
+            warn!(
+                "the native msi builder doesn't support WiX's UI, so <{}> is ignored and the msi will only show a progress dialog",
+                node.tag_name().name()
+            );
+            self.warned_ui = true;
+        }
+    }
+
+    fn path(&self, path: &str) -> Utf8PathBuf {
+        self.base_dir.join(path.replace('\\', "/"))
+    }
+
+    fn read_product(&mut self, product: Node<'a, 'input>) -> Result<(), String> {
+        let code = self.required(product, "Id")?;
+        self.model.product_code = if code == "*" {
+            new_guid()
+        } else {
+            normalize_guid(&code)
+                .ok_or_else(|| self.error(product, format!("invalid Id {code:?}")))?
+        };
+        self.model.product_name = self.required(product, "Name")?;
+        self.model.upgrade_code = self
+            .attr(product, "UpgradeCode")?
+            .map(|code| {
+                normalize_guid(&code)
+                    .ok_or_else(|| self.error(product, format!("invalid UpgradeCode {code:?}")))
+            })
+            .transpose()?;
+        self.model.manufacturer = self.required(product, "Manufacturer")?;
+        self.model.language = self
+            .int(product, "Language")?
+            .and_then(|lang| u16::try_from(lang).ok())
+            .ok_or_else(|| self.error(product, "Language should be a language code".to_owned()))?;
+        self.model.codepage = self.int(product, "Codepage")?;
+        self.model.version = self.required(product, "Version")?;
+        if !Category::Version.validate(&self.model.version) {
+            return Err(self.error(
+                product,
+                format!("{:?} isn't a valid msi version", self.model.version),
+            ));
+        }
+
+        for child in self.children(product)? {
+            match child.tag_name().name() {
+                "Package" => self.read_package(child)?,
+                "MajorUpgrade" => self.read_major_upgrade(child)?,
+                "Media" => self.read_media(child)?,
+                "Property" => self.read_property(child)?,
+                "Condition" => {
+                    let message = self.required(child, "Message")?;
+                    let condition = self
+                        .text(child)?
+                        .ok_or_else(|| self.error(child, "<Condition> is empty".to_owned()))?;
+                    self.model.launch_conditions.push((condition, message));
+                }
+                "Directory" => self.read_directory(child, None)?,
+                "Feature" => self.read_feature(child, None)?,
+                "SetProperty" => self.read_set_property(child)?,
+                "Icon" => {
+                    let id = self.required(child, "Id")?;
+                    let source = self.required(child, "SourceFile")?;
+                    self.model.icons.push((id, self.path(&source)));
+                }
+                "UI" | "UIRef" | "WixVariable" => self.skip_ui(child),
+                _ => return Err(self.unsupported(child)),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_package(&mut self, node: Node) -> Result<(), String> {
+        let platform = match self.attr(node, "Platform")?.as_deref() {
+            None => match self.pp.build_arch {
+                "x86" => "Intel",
+                "arm64" => "Arm64",
+                _ => "x64",
+            },
+            Some("x86" | "intel") => "Intel",
+            Some("x64") => "x64",
+            Some("arm64") => "Arm64",
+            Some(other) => {
+                return Err(self.error(node, format!("unsupported Platform {other:?}")));
+            }
+        };
+        if !self.yes_no(node, "Compressed", false)? {
+            return Err(self.error(
+                node,
+                "only Compressed='yes' packages are supported".to_owned(),
+            ));
+        }
+        let per_machine = match self.attr(node, "InstallScope")?.as_deref() {
+            Some("perUser") => false,
+            None | Some("perMachine") => true,
+            Some(other) => {
+                return Err(self.error(node, format!("unsupported InstallScope {other:?}")));
+            }
+        };
+        let mut installer_version = self.int(node, "InstallerVersion")?.unwrap_or(500);
+        if platform == "Arm64" {
+            // Windows Installer only learned about Arm64 in 5.0
+            installer_version = installer_version.max(500);
+        }
+        self.model.package = PackageModel {
+            keywords: self
+                .attr(node, "Keywords")?
+                .unwrap_or_else(|| "Installer".to_owned())
+                .split([',', ';'])
+                .map(|word| word.trim().to_owned())
+                .filter(|word| !word.is_empty())
+                .collect(),
+            description: self.attr(node, "Description")?,
+            comments: self.attr(node, "Comments")?,
+            manufacturer: self.attr(node, "Manufacturer")?,
+            installer_version,
+            per_machine,
+            codepage: self.int(node, "SummaryCodepage")?,
+            platform: platform.to_owned(),
+        };
+        Ok(())
+    }
+
+    fn read_major_upgrade(&mut self, node: Node) -> Result<(), String> {
+        let schedule = self
+            .attr(node, "Schedule")?
+            .unwrap_or_else(|| "afterInstallValidate".to_owned());
+        let sequence = match schedule.as_str() {
+            "afterInstallValidate" => 1401,
+            "afterInstallInitialize" => 1501,
+            "afterInstallFinalize" => 6601,
+            other => return Err(self.error(node, format!("unsupported Schedule {other:?}"))),
+        };
+        self.model.major_upgrade = Some(MajorUpgradeModel {
+            sequence,
+            downgrade_error: self.attr(node, "DowngradeErrorMessage")?,
+            allow_downgrades: self.yes_no(node, "AllowDowngrades", false)?,
+            allow_same_version: self.yes_no(node, "AllowSameVersionUpgrades", false)?,
+        });
+        Ok(())
+    }
+
+    fn read_media(&mut self, node: Node) -> Result<(), String> {
+        if !self.yes_no(node, "EmbedCab", false)? {
+            return Err(self.error(node, "only EmbedCab='yes' media is supported".to_owned()));
+        }
+        let cabinet = self.required(node, "Cabinet")?;
+        if !Category::Cabinet.validate(&cabinet) {
+            return Err(self.error(node, format!("invalid Cabinet name {cabinet:?}")));
+        }
+        self.model.media.push(MediaModel {
+            id: self.int(node, "Id")?.unwrap_or(1),
+            cabinet,
+            disk_prompt: self.attr(node, "DiskPrompt")?,
+        });
+        Ok(())
+    }
+
+    fn read_property(&mut self, node: Node) -> Result<(), String> {
+        let id = self.required(node, "Id")?;
+        let value = match self.attr(node, "Value")? {
+            Some(value) => Some(value),
+            None => self.text(node)?,
+        };
+        if self.yes_no(node, "Secure", false)? {
+            self.model.secure_properties.push(id.clone());
+        }
+        if let Some(value) = value {
+            self.model.properties.insert(id, value);
+        }
+        Ok(())
+    }
+
+    fn read_directory(
+        &mut self,
+        node: Node<'a, 'input>,
+        parent: Option<&str>,
+    ) -> Result<(), String> {
+        let id = self.required(node, "Id")?;
+        let default_dir = match self.attr(node, "Name")? {
+            Some(name) if parent.is_none() => name,
+            Some(name) => msi_file_name(&name, self.attr(node, "ShortName")?),
+            None => ".".to_owned(),
+        };
+        self.model.directories.push(DirectoryModel {
+            id: id.clone(),
+            parent: parent.map(ToOwned::to_owned),
+            default_dir,
+        });
+        for child in self.children(node)? {
+            match child.tag_name().name() {
+                "Directory" => self.read_directory(child, Some(&id))?,
+                "Component" => self.read_component(child, &id)?,
+                _ => return Err(self.unsupported(child)),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_component(&mut self, node: Node<'a, 'input>, directory: &str) -> Result<(), String> {
+        let id = self.required(node, "Id")?;
+        let mut key_path = None;
+        let mut first_file = None;
+        for child in self.children(node)? {
+            match child.tag_name().name() {
+                "File" => {
+                    let file_id = self.required(child, "Id")?;
+                    let source = match self.attr(child, "Source")? {
+                        Some(source) => source,
+                        None => self.required(child, "src")?,
+                    };
+                    let source = self.path(&source);
+                    let name = match self.attr(child, "Name")? {
+                        Some(name) => name,
+                        None => source
+                            .file_name()
+                            .ok_or_else(|| self.error(child, format!("{source} isn't a file")))?
+                            .to_owned(),
+                    };
+                    if self.yes_no(child, "KeyPath", false)? {
+                        key_path = Some(file_id.clone());
+                    }
+                    first_file.get_or_insert_with(|| file_id.clone());
+                    self.model.files.push(FileModel {
+                        id: file_id,
+                        component: id.clone(),
+                        file_name: msi_file_name(&name, self.attr(child, "ShortName")?),
+                        source,
+                        disk_id: self.int(child, "DiskId")?.unwrap_or(1),
+                    });
+                }
+                "Environment" => {
+                    let env_id = self.required(child, "Id")?;
+                    let name = self.required(child, "Name")?;
+                    let value = self.attr(child, "Value")?.unwrap_or_default();
+                    let separator = self
+                        .attr(child, "Separator")?
+                        .unwrap_or_else(|| ";".to_owned());
+                    let mut prefix = String::new();
+                    match self.attr(child, "Action")?.as_deref() {
+                        None | Some("set") => prefix.push('='),
+                        Some("create") => prefix.push('+'),
+                        Some("remove") => prefix.push('!'),
+                        Some(other) => {
+                            return Err(self.error(child, format!("unsupported Action {other:?}")))
+                        }
+                    }
+                    if !self.yes_no(child, "Permanent", false)? {
+                        prefix.push('-');
+                    }
+                    if self.yes_no(child, "System", false)? {
+                        prefix.push('*');
+                    }
+                    let value = match self.attr(child, "Part")?.as_deref() {
+                        None | Some("all") => value,
+                        Some("first") => format!("{value}{separator}[~]"),
+                        Some("last") => format!("[~]{separator}{value}"),
+                        Some(other) => {
+                            return Err(self.error(child, format!("unsupported Part {other:?}")))
+                        }
+                    };
+                    self.model.environment.push(EnvironmentModel {
+                        id: env_id,
+                        name: format!("{prefix}{name}"),
+                        value,
+                        component: id.clone(),
+                    });
+                }
+                _ => return Err(self.unsupported(child)),
+            }
+        }
+
+        if !self.yes_no(node, "KeyPath", false)? {
+            key_path = key_path.or(first_file);
+        }
+        let guid = match self.attr(node, "Guid")?.as_deref() {
+            None | Some("*") => Some(self.component_guid(&id, directory, key_path.as_deref())),
+            Some("") => None,
+            Some(guid) => Some(
+                normalize_guid(guid)
+                    .ok_or_else(|| self.error(node, format!("invalid Guid {guid:?}")))?,
+            ),
+        };
+        let win64 = self.yes_no(node, "Win64", self.model.package.is_64bit())?;
+        self.model.components.push(ComponentModel {
+            id,
+            guid,
+            directory: directory.to_owned(),
+            attributes: if win64 { 256 } else { 0 },
+            key_path,
+        });
+        Ok(())
+    }
+
+    /// Generate a stable GUID for a component, like WiX does for `Guid='*'`
+    ///
+    /// This needs to be the same from version to version of the product, so
+    /// it's derived from the component's identity rather than randomly generated.
+    fn component_guid(&self, id: &str, directory: &str, key_path: Option<&str>) -> String {
+        let mut hasher = Sha256::new();
+        for part in [
+            self.model.upgrade_code.as_deref().unwrap_or_default(),
+            directory,
+            id,
+            key_path.unwrap_or_default(),
+        ] {
+            hasher.update(part.as_bytes());
+            hasher.update([0]);
+        }
+        let hash = hasher.finalize();
+        let bytes: [u8; 16] = hash[..16].try_into().unwrap();
+        format_guid(uuid::Builder::from_sha1_bytes(bytes).into_uuid())
+    }
+
+    fn read_feature(&mut self, node: Node<'a, 'input>, parent: Option<&str>) -> Result<(), String> {
+        let id = self.required(node, "Id")?;
+        self.display_counter += 1;
+        let display = match self.attr(node, "Display")?.as_deref() {
+            None | Some("collapse") => self.display_counter * 2 - 1,
+            Some("expand") => self.display_counter * 2,
+            Some("hidden") => 0,
+            Some(other) => return Err(self.error(node, format!("unsupported Display {other:?}"))),
+        };
+        let mut attributes = 0;
+        if !self.yes_no(node, "AllowAdvertise", true)? {
+            // msidbFeatureAttributesDisallowAdvertise
+            attributes |= 8;
+        }
+        if self.attr(node, "Absent")?.as_deref() == Some("disallow") {
+            // msidbFeatureAttributesUIDisallowAbsent
+            attributes |= 16;
+        }
+        self.model.features.push(FeatureModel {
+            id: id.clone(),
+            parent: parent.map(ToOwned::to_owned),
+            title: self.attr(node, "Title")?,
+            description: self.attr(node, "Description")?,
+            display,
+            level: self.int(node, "Level")?.unwrap_or(1),
+            directory: self.attr(node, "ConfigurableDirectory")?,
+            attributes,
+        });
+        for child in self.children(node)? {
+            match child.tag_name().name() {
+                "Feature" => self.read_feature(child, Some(&id))?,
+                "ComponentRef" => {
+                    let component = self.required(child, "Id")?;
+                    self.model.feature_components.push((id.clone(), component));
+                }
+                _ => return Err(self.unsupported(child)),
+            }
+        }
+        Ok(())
+    }
+
+    fn read_set_property(&mut self, node: Node) -> Result<(), String> {
+        let property = self.required(node, "Id")?;
+        let anchor = match (self.attr(node, "After")?, self.attr(node, "Before")?) {
+            (Some(action), None) => (action, true),
+            (None, Some(action)) => (action, false),
+            _ => {
+                return Err(self.error(
+                    node,
+                    "<SetProperty> needs exactly one of After or Before".to_owned(),
+                ))
+            }
+        };
+        if !STANDARD_ACTIONS.iter().any(|(name, ..)| *name == anchor.0) {
+            return Err(self.error(
+                node,
+                format!("can't schedule relative to the {} action", anchor.0),
+            ));
+        }
+        let (ui, execute) = match self.attr(node, "Sequence")?.as_deref() {
+            None | Some("both") => (true, true),
+            Some("ui") => (true, false),
+            Some("execute") => (false, true),
+            Some(other) => return Err(self.error(node, format!("unsupported Sequence {other:?}"))),
+        };
+        self.model.set_properties.push(SetPropertyModel {
+            action: self
+                .attr(node, "Action")?
+                .unwrap_or_else(|| format!("Set{property}")),
+            property,
+            value: self.required(node, "Value")?,
+            condition: self.text(node)?,
+            anchor,
+            ui,
+            execute,
+        });
+        Ok(())
+    }
+
+    /// Check that everything refers to things that exist
+    fn validate(&self) -> Result<(), String> {
+        let model = &self.model;
+        if model.package.installer_version == 0 {
+            return Err("no <Package> element found".to_owned());
+        }
+        let has_dir = |id: &str| model.directories.iter().any(|dir| dir.id == id);
+        for feature in &model.features {
+            if let Some(dir) = &feature.directory {
+                if !has_dir(dir) {
+                    return Err(format!(
+                        "feature {} refers to an unknown directory {dir}",
+                        feature.id
+                    ));
+                }
+            }
+        }
+        for (feature, component) in &model.feature_components {
+            if !model.components.iter().any(|c| &c.id == component) {
+                return Err(format!(
+                    "feature {feature} refers to an unknown component {component}"
+                ));
+            }
+        }
+        for component in &model.components {
+            if !model
+                .feature_components
+                .iter()
+                .any(|(_, c)| c == &component.id)
+            {
+                return Err(format!(
+                    "component {} isn't included in any feature",
+                    component.id
+                ));
+            }
+        }
+        for file in &model.files {
+            if !model.media.iter().any(|media| media.id == file.disk_id) {
+                return Err(format!(
+                    "file {} is on an unknown disk {}",
+                    file.id, file.disk_id
+                ));
+            }
+        }
+        if model.major_upgrade.is_some() && model.upgrade_code.is_none() {
+            return Err("<MajorUpgrade> requires the Product to have an UpgradeCode".to_owned());
+        }
+        Ok(())
+    }
+}
+
+/// Get the DefaultDir/FileName format for a name: `short|long`, or just the name if it's already short
+fn msi_file_name(name: &str, short_name: Option<String>) -> String {
+    if is_short_name(name) {
+        return name.to_owned();
+    }
+    let short_name = short_name.unwrap_or_else(|| {
+        // Like WiX, generate a short name from a hash of the long name
+        let hash = Sha256::digest(name.as_bytes());
+        let base = hash[..8]
+            .iter()
+            .map(|byte| char::from(b"abcdefghijklmnopqrstuvwxyz012345"[usize::from(byte % 32)]))
+            .collect::<String>();
+        let ext = name
+            .rsplit_once('.')
+            .map(|(_, ext)| {
+                ext.chars()
+                    .filter(|c| c.is_ascii_alphanumeric())
+                    .take(3)
+                    .collect::<String>()
+            })
+            .unwrap_or_default();
+        if ext.is_empty() {
+            base
+        } else {
+            format!("{base}.{ext}")
+        }
+    });
+    format!("{short_name}|{name}")
+}
+
+/// Whether a name is a valid 8.3 filename, which can be used as-is
+fn is_short_name(name: &str) -> bool {
+    let (base, ext) = name.split_once('.').unwrap_or((name, ""));
+    let valid = |part: &str| {
+        part.chars()
+            .all(|c| c.is_ascii_alphanumeric() || "_-!#$%&'@^`{}~".contains(c))
+    };
+    !base.is_empty() && base.len() <= 8 && ext.len() <= 3 && valid(base) && valid(ext)
+}
+
+fn new_guid() -> String {
+    format_guid(uuid::Uuid::new_v4())
+}
+
+fn format_guid(guid: uuid::Uuid) -> String {
+    format!("{{{}}}", guid.as_hyphenated().to_string().to_uppercase())
+}
+
+fn normalize_guid(guid: &str) -> Option<String> {
+    let guid = guid.trim_start_matches('{').trim_end_matches('}');
+    uuid::Uuid::parse_str(guid).ok().map(format_guid)
+}
+
+/// The contents of the files that go into the msi
+struct Payload {
+    /// Sizes of each file in the model
+    file_sizes: Vec<i32>,
+    /// The sequence number of each file in the model
+    file_sequences: Vec<i32>,
+    /// The cabinet for each media in the model
+    cabinets: Vec<Vec<u8>>,
+    /// The contents of each icon in the model
+    icons: Vec<Vec<u8>>,
+}
+
+impl Payload {
+    fn load(model: &MsiModel) -> std::io::Result<Self> {
+        let mut file_sizes = vec![0; model.files.len()];
+        let mut file_sequences = vec![0; model.files.len()];
+        let mut cabinets = vec![];
+        let mut sequence = 0;
+        for media in &model.media {
+            let files = model
+                .files
+                .iter()
+                .enumerate()
+                .filter(|(_, file)| file.disk_id == media.id)
+                .collect::<Vec<_>>();
+            let mut builder = cab::CabinetBuilder::new();
+            let folder = builder.add_folder(cab::CompressionType::MsZip);
+            for (_, file) in &files {
+                folder.add_file(file.id.clone());
+            }
+            let mut writer = builder.build(Cursor::new(vec![]))?;
+            for (idx, file) in files {
+                let contents = std::fs::read(&file.source).map_err(|e| {
+                    std::io::Error::new(e.kind(), format!("couldn't read {}: {e}", file.source))
+                })?;
+                file_sizes[idx] = i32::try_from(contents.len()).map_err(|_| {
+                    std::io::Error::other(format!("{} is too big for an msi", file.source))
+                })?;
+                sequence += 1;
+                file_sequences[idx] = sequence;
+                let mut file_writer = writer
+                    .next_file()?
+                    .expect("cabinet should have a slot for every file");
+                file_writer.write_all(&contents)?;
+            }
+            cabinets.push(writer.finish()?.into_inner());
+        }
+        let icons = model
+            .icons
+            .iter()
+            .map(|(_, path)| std::fs::read(path))
+            .collect::<Result<_, _>>()?;
+        Ok(Self {
+            file_sizes,
+            file_sequences,
+            cabinets,
+            icons,
+        })
+    }
+}
+
+/// Write out the msi database
+fn write_msi(model: &MsiModel, payload: &Payload) -> std::io::Result<Vec<u8>> {
+    let mut package = Package::create(PackageType::Installer, Cursor::new(vec![]))?;
+    let codepage = |id: Option<i32>| {
+        id.map(|id| {
+            CodePage::from_id(id).ok_or_else(|| {
+                std::io::Error::new(
+                    std::io::ErrorKind::InvalidInput,
+                    format!("unsupported codepage {id}"),
+                )
+            })
+        })
+        .transpose()
+    };
+    if let Some(codepage) = codepage(model.codepage)? {
+        package.set_database_codepage(codepage);
+    }
+
+    let summary = package.summary_info_mut();
+    if let Some(codepage) = codepage(model.package.codepage)? {
+        summary.set_codepage(codepage);
+    }
+    summary.set_title("Installation Database");
+    summary.set_subject(
+        model
+            .package
+            .description
+            .clone()
+            .unwrap_or_else(|| model.product_name.clone()),
+    );
+    summary.set_author(
+        model
+            .package
+            .manufacturer
+            .clone()
+            .unwrap_or_else(|| model.manufacturer.clone()),
+    );
+    summary.set_keywords(&model.package.keywords);
+    summary.set_comments(model.package.comments.clone().unwrap_or_else(|| {
+        "This installer database contains the logic and data required to install [ProductName]."
+            .to_owned()
+    }));
+    summary.set_arch(model.package.platform.clone());
+    summary.set_languages(&[Language::from_code(model.language)]);
+    summary.set_uuid(uuid::Uuid::new_v4());
+    summary.set_page_count(model.package.installer_version);
+    // Long file names, compressed
+    summary.set_word_count(2);
+    summary.set_creating_application(format!("dist {}", env!("CARGO_PKG_VERSION")));
+
+    // Properties
+    let mut properties = SortedMap::new();
+    properties.insert("ProductCode".to_owned(), model.product_code.clone());
+    properties.insert("ProductName".to_owned(), model.product_name.clone());
+    properties.insert("ProductVersion".to_owned(), model.version.clone());
+    properties.insert("ProductLanguage".to_owned(), model.language.to_string());
+    properties.insert("Manufacturer".to_owned(), model.manufacturer.clone());
+    if let Some(upgrade_code) = &model.upgrade_code {
+        properties.insert("UpgradeCode".to_owned(), upgrade_code.clone());
+    }
+    if model.package.per_machine {
+        properties.insert("ALLUSERS".to_owned(), "1".to_owned());
+    }
+    let mut secure_properties = model.secure_properties.clone();
+    if model.major_upgrade.is_some() {
+        secure_properties.push(DOWNGRADE_DETECTED.to_owned());
+        secure_properties.push(UPGRADE_DETECTED.to_owned());
+    }
+    if !secure_properties.is_empty() {
+        properties.insert(
+            "SecureCustomProperties".to_owned(),
+            secure_properties.join(";"),
+        );
+    }
+    properties.extend(model.properties.clone());
+    package.create_table(
+        "Property",
+        vec![
+            Column::build("Property").primary_key().id_string(72),
+            Column::build("Value").localizable().text_string(0),
+        ],
+    )?;
+    package.insert_rows(
+        Insert::into("Property").rows(
+            properties
+                .into_iter()
+                .map(|(key, value)| vec![Value::from(key), Value::from(value)])
+                .collect(),
+        ),
+    )?;
+
+    // Directories
+    package.create_table(
+        "Directory",
+        vec![
+            Column::build("Directory").primary_key().id_string(72),
+            Column::build("Directory_Parent").nullable().id_string(72),
+            Column::build("DefaultDir")
+                .localizable()
+                .category(Category::DefaultDir)
+                .string(255),
+        ],
+    )?;
+    package.insert_rows(
+        Insert::into("Directory").rows(
+            model
+                .directories
+                .iter()
+                .map(|dir| {
+                    vec![
+                        Value::from(dir.id.as_str()),
+                        opt(&dir.parent),
+                        Value::from(dir.default_dir.as_str()),
+                    ]
+                })
+                .collect(),
+        ),
+    )?;
+
+    // Components and files
+    package.create_table(
+        "Component",
+        vec![
+            Column::build("Component").primary_key().id_string(72),
+            Column::build("ComponentId")
+                .nullable()
+                .category(Category::Guid)
+                .string(38),
+            Column::build("Directory_").id_string(72),
+            Column::build("Attributes").int16(),
+            Column::build("Condition")
+                .nullable()
+                .category(Category::Condition)
+                .string(255),
+            Column::build("KeyPath").nullable().id_string(72),
+        ],
+    )?;
+    package.insert_rows(
+        Insert::into("Component").rows(
+            model
+                .components
+                .iter()
+                .map(|component| {
+                    vec![
+                        Value::from(component.id.as_str()),
+                        opt(&component.guid),
+                        Value::from(component.directory.as_str()),
+                        Value::Int(component.attributes),
+                        Value::Null,
+                        opt(&component.key_path),
+                    ]
+                })
+                .collect(),
+        ),
+    )?;
+    package.create_table(
+        "File",
+        vec![
+            Column::build("File").primary_key().id_string(72),
+            Column::build("Component_").id_string(72),
+            Column::build("FileName")
+                .localizable()
+                .category(Category::Filename)
+                .string(255),
+            Column::build("FileSize").range(0, i32::MAX).int32(),
+            Column::build("Version")
+                .nullable()
+                .category(Category::Version)
+                .string(72),
+            Column::build("Language")
+                .nullable()
+                .category(Category::Language)
+                .string(20),
+            Column::build("Attributes").nullable().int16(),
+            Column::build("Sequence").range(1, 32767).int16(),
+        ],
+    )?;
+    package.insert_rows(
+        Insert::into("File").rows(
+            model
+                .files
+                .iter()
+                .enumerate()
+                .map(|(idx, file)| {
+                    vec![
+                        Value::from(file.id.as_str()),
+                        Value::from(file.component.as_str()),
+                        Value::from(file.file_name.as_str()),
+                        Value::Int(payload.file_sizes[idx]),
+                        Value::Null,
+                        Value::Null,
+                        // msidbFileAttributesVital
+                        Value::Int(512),
+                        Value::Int(payload.file_sequences[idx]),
+                    ]
+                })
+                .collect(),
+        ),
+    )?;
+
+    // Media (with embedded cabinets)
+    package.create_table(
+        "Media",
+        vec![
+            Column::build("DiskId")
+                .primary_key()
+                .range(1, 32767)
+                .int16(),
+            Column::build("LastSequence").range(0, 32767).int16(),
+            Column::build("DiskPrompt").nullable().text_string(64),
+            Column::build("Cabinet")
+                .nullable()
+                .category(Category::Cabinet)
+                .string(255),
+            Column::build("VolumeLabel").nullable().text_string(32),
+            Column::build("Source")
+                .nullable()
+                .category(Category::Property)
+                .string(72),
+        ],
+    )?;
+    let mut last_sequence = 0;
+    for (media, cabinet) in model.media.iter().zip(&payload.cabinets) {
+        last_sequence = model
+            .files
+            .iter()
+            .zip(&payload.file_sequences)
+            .filter(|(file, _)| file.disk_id == media.id)
+            .map(|(_, &sequence)| sequence)
+            .max()
+            .unwrap_or(last_sequence);
+        package.insert_rows(Insert::into("Media").row(vec![
+            Value::Int(media.id),
+            Value::Int(last_sequence),
+            opt(&media.disk_prompt),
+            Value::from(format!("#{}", media.cabinet)),
+            Value::Null,
+            Value::Null,
+        ]))?;
+        package.write_stream(&media.cabinet)?.write_all(cabinet)?;
+    }
+
+    // Features
+    package.create_table(
+        "Feature",
+        vec![
+            Column::build("Feature").primary_key().id_string(38),
+            Column::build("Feature_Parent").nullable().id_string(38),
+            Column::build("Title")
+                .nullable()
+                .localizable()
+                .text_string(64),
+            Column::build("Description")
+                .nullable()
+                .localizable()
+                .text_string(255),
+            Column::build("Display").nullable().range(0, 32767).int16(),
+            Column::build("Level").range(0, 32767).int16(),
+            Column::build("Directory_")
+                .nullable()
+                .category(Category::UpperCase)
+                .string(72),
+            Column::build("Attributes").int16(),
+        ],
+    )?;
+    package.insert_rows(
+        Insert::into("Feature").rows(
+            model
+                .features
+                .iter()
+                .map(|feature| {
+                    vec![
+                        Value::from(feature.id.as_str()),
+                        opt(&feature.parent),
+                        opt(&feature.title),
+                        opt(&feature.description),
+                        Value::Int(feature.display),
+                        Value::Int(feature.level),
+                        opt(&feature.directory),
+                        Value::Int(feature.attributes),
+                    ]
+                })
+                .collect(),
+        ),
+    )?;
+    package.create_table(
+        "FeatureComponents",
+        vec![
+            Column::build("Feature_").primary_key().id_string(38),
+            Column::build("Component_").primary_key().id_string(72),
+        ],
+    )?;
+    package.insert_rows(
+        Insert::into("FeatureComponents").rows(
+            model
+                .feature_components
+                .iter()
+                .map(|(feature, component)| {
+                    vec![
+                        Value::from(feature.as_str()),
+                        Value::from(component.as_str()),
+                    ]
+                })
+                .collect(),
+        ),
+    )?;
+
+    // Environment variables
+    if !model.environment.is_empty() {
+        package.create_table(
+            "Environment",
+            vec![
+                Column::build("Environment").primary_key().id_string(72),
+                Column::build("Name").localizable().text_string(255),
+                Column::build("Value")
+                    .nullable()
+                    .localizable()
+                    .formatted_string(255),
+                Column::build("Component_").id_string(72),
+            ],
+        )?;
+        package.insert_rows(
+            Insert::into("Environment").rows(
+                model
+                    .environment
+                    .iter()
+                    .map(|env| {
+                        vec![
+                            Value::from(env.id.as_str()),
+                            Value::from(env.name.as_str()),
+                            Value::from(env.value.as_str()),
+                            Value::from(env.component.as_str()),
+                        ]
+                    })
+                    .collect(),
+            ),
+        )?;
+    }
+
+    // Upgrades and launch conditions
+    let mut launch_conditions = model.launch_conditions.clone();
+    if let (Some(upgrade), Some(upgrade_code)) = (&model.major_upgrade, &model.upgrade_code) {
+        package.create_table(
+            "Upgrade",
+            vec![
+                Column::build("UpgradeCode")
+                    .primary_key()
+                    .category(Category::Guid)
+                    .string(38),
+                Column::build("VersionMin")
+                    .primary_key()
+                    .nullable()
+                    .text_string(20),
+                Column::build("VersionMax")
+                    .primary_key()
+                    .nullable()
+                    .text_string(20),
+                Column::build("Language")
+                    .primary_key()
+                    .nullable()
+                    .category(Category::Language)
+                    .string(255),
+                Column::build("Attributes").primary_key().int32(),
+                Column::build("Remove").nullable().formatted_string(255),
+                Column::build("ActionProperty")
+                    .category(Category::UpperCase)
+                    .string(72),
+            ],
+        )?;
+        // msidbUpgradeAttributesMigrateFeatures, and msidbUpgradeAttributesVersionMaxInclusive
+        let upgrade_attributes = if upgrade.allow_same_version {
+            1 | 512
+        } else {
+            1
+        };
+        let mut rows = vec![vec![
+            Value::from(upgrade_code.as_str()),
+            Value::Null,
+            if upgrade.allow_downgrades {
+                Value::Null
+            } else {
+                Value::from(model.version.as_str())
+            },
+            Value::Null,
+            Value::Int(upgrade_attributes),
+            Value::Null,
+            Value::from(UPGRADE_DETECTED),
+        ]];
+        if !upgrade.allow_downgrades {
+            rows.push(vec![
+                Value::from(upgrade_code.as_str()),
+                Value::from(model.version.as_str()),
+                Value::Null,
+                Value::Null,
+                // msidbUpgradeAttributesOnlyDetect
+                Value::Int(2),
+                Value::Null,
+                Value::from(DOWNGRADE_DETECTED),
+            ]);
+            if let Some(message) = &upgrade.downgrade_error {
+                launch_conditions.push((format!("NOT {DOWNGRADE_DETECTED}"), message.clone()));
+            }
+        }
+        package.insert_rows(Insert::into("Upgrade").rows(rows))?;
+    }
+    if !launch_conditions.is_empty() {
+        package.create_table(
+            "LaunchCondition",
+            vec![
+                Column::build("Condition")
+                    .primary_key()
+                    .category(Category::Condition)
+                    .string(255),
+                Column::build("Description")
+                    .localizable()
+                    .formatted_string(255),
+            ],
+        )?;
+        package.insert_rows(
+            Insert::into("LaunchCondition").rows(
+                launch_conditions
+                    .into_iter()
+                    .map(|(condition, message)| vec![Value::from(condition), Value::from(message)])
+                    .collect(),
+            ),
+        )?;
+    }
+
+    // Icons
+    if !model.icons.is_empty() {
+        package.create_table(
+            "Icon",
+            vec![
+                Column::build("Name").primary_key().id_string(72),
+                Column::build("Data").binary(),
+            ],
+        )?;
+        for ((name, _), contents) in model.icons.iter().zip(&payload.icons) {
+            package.insert_rows(
+                Insert::into("Icon").row(vec![Value::from(name.as_str()), Value::Binary]),
+            )?;
+            package
+                .write_stream(&format!("Icon.{name}"))?
+                .write_all(contents)?;
+        }
+    }
+
+    // Custom actions (from SetProperty)
+    if !model.set_properties.is_empty() {
+        package.create_table(
+            "CustomAction",
+            vec![
+                Column::build("Action").primary_key().id_string(72),
+                Column::build("Type").range(1, 32767).int16(),
+                Column::build("Source")
+                    .nullable()
+                    .category(Category::CustomSource)
+                    .string(72),
+                Column::build("Target").nullable().formatted_string(255),
+            ],
+        )?;
+        package.insert_rows(
+            Insert::into("CustomAction").rows(
+                model
+                    .set_properties
+                    .iter()
+                    .map(|set| {
+                        vec![
+                            Value::from(set.action.as_str()),
+                            // msidbCustomActionTypeProperty | msidbCustomActionTypeTextData
+                            Value::Int(51),
+                            Value::from(set.property.as_str()),
+                            Value::from(set.value.as_str()),
+                        ]
+                    })
+                    .collect(),
+            ),
+        )?;
+    }
+
+    // Sequences
+    for (table, ui) in [
+        ("InstallExecuteSequence", false),
+        ("InstallUISequence", true),
+    ] {
+        let mut actions = SortedMap::new();
+        for &(action, sequence, in_ui) in STANDARD_ACTIONS {
+            let wanted = match action {
+                "ExecuteAction" => ui,
+                "FindRelatedProducts" | "MigrateFeatureStates" => model.major_upgrade.is_some(),
+                "LaunchConditions" => {
+                    model.major_upgrade.is_some() || !model.launch_conditions.is_empty()
+                }
+                "RemoveEnvironmentStrings" | "WriteEnvironmentStrings" => {
+                    !model.environment.is_empty()
+                }
+                _ => !ui || in_ui,
+            };
+            if wanted && (!ui || in_ui) {
+                actions.insert(sequence, (action.to_owned(), None));
+            }
+        }
+        if let (Some(upgrade), false) = (&model.major_upgrade, ui) {
+            actions.insert(
+                upgrade.sequence,
+                ("RemoveExistingProducts".to_owned(), None),
+            );
+        }
+        for set in &model.set_properties {
+            if !(if ui { set.ui } else { set.execute }) {
+                continue;
+            }
+            let (anchor, after) = &set.anchor;
+            let base = STANDARD_ACTIONS
+                .iter()
+                .find(|(name, ..)| name == anchor)
+                .map(|(_, sequence, _)| *sequence)
+                .unwrap_or_default();
+            let mut sequence = if *after { base + 1 } else { base - 1 };
+            while actions.contains_key(&sequence) {
+                sequence += if *after { 1 } else { -1 };
+            }
+            actions.insert(sequence, (set.action.clone(), set.condition.clone()));
+        }
+
+        package.create_table(
+            table,
+            vec![
+                Column::build("Action").primary_key().id_string(72),
+                Column::build("Condition")
+                    .nullable()
+                    .category(Category::Condition)
+                    .string(255),
+                Column::build("Sequence")
+                    .nullable()
+                    .range(-4, 32767)
+                    .int16(),
+            ],
+        )?;
+        package.insert_rows(
+            Insert::into(table).rows(
+                actions
+                    .into_iter()
+                    .map(|(sequence, (action, condition))| {
+                        vec![Value::from(action), opt(&condition), Value::Int(sequence)]
+                    })
+                    .collect(),
+            ),
+        )?;
+    }
+
+    Ok(package.into_inner()?.into_inner())
+}
+
+fn opt(value: &Option<String>) -> Value {
+    value.as_deref().map(Value::from).unwrap_or(Value::Null)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use itertools::Itertools;
+
+    fn preprocessor() -> Preprocessor {
+        let mut vars = SortedMap::new();
+        vars.insert("Version".to_owned(), "1.2.3".to_owned());
+        Preprocessor {
+            vars,
+            build_arch: "arm64",
+        }
+    }
+
+    #[test]
+    fn evaluates_conditions() {
+        let pp = preprocessor();
+        assert!(pp
+            .eval("$(sys.BUILDARCH) = x64 or $(sys.BUILDARCH) = arm64")
+            .unwrap());
+        assert!(!pp.eval("$(sys.BUILDARCH) = x64").unwrap());
+        assert!(pp.eval("not ($(var.Version) != \"1.2.3\")").unwrap());
+        assert!(pp
+            .eval("$(sys.BUILDARCH) ~= ARM64 and $(Version) = 1.2.3")
+            .unwrap());
+        assert!(pp.eval("$(var.Nope) = 1").is_err());
+        assert!(pp.eval("$(var.Version) < 2").is_err());
+    }
+
+    #[test]
+    fn generates_file_names() {
+        assert_eq!(msi_file_name("bin", None), "bin");
+        assert_eq!(msi_file_name("app.exe", None), "app.exe");
+        assert_eq!(
+            msi_file_name("axolotlsay.exe", Some("axolot~1.exe".to_owned())),
+            "axolot~1.exe|axolotlsay.exe"
+        );
+        let generated = msi_file_name("axolotlsay.exe", None);
+        let (short, long) = generated.split_once('|').unwrap();
+        assert_eq!(long, "axolotlsay.exe");
+        assert!(is_short_name(short) && short.ends_with(".exe"));
+        assert_eq!(generated, msi_file_name("axolotlsay.exe", None));
+    }
+
+    #[test]
+    fn builds_msi() {
+        let tmp = temp_dir::TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(tmp.path()).unwrap();
+        LocalAsset::write_new_all("not really an exe", dir.join("bin/axolotlsay.exe")).unwrap();
+        let wxs = r#"<?xml version='1.0' encoding='windows-1252'?>
+<?if $(sys.BUILDARCH) = x64 or $(sys.BUILDARCH) = arm64 ?>
+    <?define PlatformProgramFilesFolder = "ProgramFiles64Folder" ?>
+<?else ?>
+    <?define PlatformProgramFilesFolder = "ProgramFilesFolder" ?>
+<?endif ?>
+<Wix xmlns='http://schemas.microsoft.com/wix/2006/wi'>
+    <Product Id='*' Name='axolotlsay' UpgradeCode='b36177bd-6fba-4f1e-8a2a-2a1e3b3a1c5e'
+        Manufacturer='axodotdev' Language='1033' Codepage='1252' Version='$(var.Version)'>
+        <Package Id='*' Keywords='Installer' Description='cute axolotl' Manufacturer='axodotdev'
+            InstallerVersion='450' Languages='1033' Compressed='yes' InstallScope='perMachine'
+            SummaryCodepage='1252'/>
+        <MajorUpgrade Schedule='afterInstallInitialize'
+            DowngradeErrorMessage='A newer version of [ProductName] is already installed.'/>
+        <Media Id='1' Cabinet='media1.cab' EmbedCab='yes' DiskPrompt='CD-ROM #1'/>
+        <Property Id='DiskPrompt' Value='axolotlsay Installation'/>
+        <Directory Id='TARGETDIR' Name='SourceDir'>
+            <Directory Id='$(var.PlatformProgramFilesFolder)' Name='PFiles'>
+                <Directory Id='APPLICATIONFOLDER' Name='axolotlsay'>
+                    <Directory Id='Bin' Name='bin'>
+                        <Component Id='Path' Guid='a2e6d8f8-0a1b-4a4e-9b8e-0c4b8a8a0e0f' KeyPath='yes'>
+                            <Environment Id='PATH' Name='PATH' Value='[Bin]' Permanent='no'
+                                Part='last' Action='set' System='yes'/>
+                        </Component>
+                        <Component Id='binary0' Guid='*'>
+                            <File Id='exe0' Name='axolotlsay.exe' DiskId='1'
+                                Source='$(var.CargoTargetBinDir)\axolotlsay.exe' KeyPath='yes'/>
+                        </Component>
+                    </Directory>
+                </Directory>
+            </Directory>
+        </Directory>
+        <Feature Id='Binaries' Title='Application' Level='1' ConfigurableDirectory='APPLICATIONFOLDER'
+            AllowAdvertise='no' Display='expand' Absent='disallow'>
+            <ComponentRef Id='binary0'/>
+            <Feature Id='Environment' Title='PATH Environment Variable' Level='1' Absent='allow'>
+                <ComponentRef Id='Path'/>
+            </Feature>
+        </Feature>
+        <SetProperty Id='ARPINSTALLLOCATION' Value='[APPLICATIONFOLDER]' After='CostFinalize'/>
+        <UI>
+            <UIRef Id='WixUI_FeatureTree'/>
+        </UI>
+    </Product>
+</Wix>
+"#;
+        let mut pp = preprocessor();
+        pp.vars
+            .insert("CargoTargetBinDir".to_owned(), dir.join("bin").to_string());
+        let model = read_wxs(wxs, dir, pp).unwrap();
+        assert_eq!(model.package.platform, "Arm64");
+        assert_eq!(model.package.installer_version, 500);
+        assert_eq!(model.environment[0].name, "=-*PATH");
+        assert_eq!(model.environment[0].value, "[~];[Bin]");
+
+        let payload = Payload::load(&model).unwrap();
+        let msi = write_msi(&model, &payload).unwrap();
+        let mut package = msi::Package::open(Cursor::new(msi)).unwrap();
+        assert_eq!(package.summary_info().arch(), Some("Arm64"));
+
+        let select = |package: &mut Package<_>, table: &str, column: &str| {
+            package
+                .select_rows(msi::Select::table(table))
+                .unwrap()
+                .map(|row| match &row[column] {
+                    Value::Str(val) => val.clone(),
+                    val => val.to_string(),
+                })
+                .sorted()
+                .collect::<Vec<_>>()
+        };
+        assert_eq!(
+            select(&mut package, "Directory", "Directory"),
+            [
+                "APPLICATIONFOLDER",
+                "Bin",
+                "ProgramFiles64Folder",
+                "TARGETDIR"
+            ]
+        );
+        assert_eq!(select(&mut package, "File", "FileSize"), ["17"]);
+        assert_eq!(
+            select(&mut package, "Component", "KeyPath"),
+            ["NULL", "exe0"]
+        );
+        assert_eq!(
+            select(&mut package, "Upgrade", "ActionProperty"),
+            [DOWNGRADE_DETECTED, UPGRADE_DETECTED]
+        );
+        let sequence = select(&mut package, "InstallExecuteSequence", "Action");
+        assert!(sequence.contains(&"SetARPINSTALLLOCATION".to_owned()));
+        assert!(sequence.contains(&"RemoveExistingProducts".to_owned()));
+        assert!(sequence.contains(&"WriteEnvironmentStrings".to_owned()));
+
+        let mut cabinet = Vec::new();
+        std::io::Read::read_to_end(
+            &mut package.read_stream("media1.cab").unwrap(),
+            &mut cabinet,
+        )
+        .unwrap();
+        let mut cabinet = cab::Cabinet::new(Cursor::new(cabinet)).unwrap();
+        let mut contents = String::new();
+        std::io::Read::read_to_string(&mut cabinet.read_file("exe0").unwrap(), &mut contents)
+            .unwrap();
+        assert_eq!(contents, "not really an exe");
+    }
+}
@@ -0,0 +1,1010 @@
+//! Code for generating RPM packages
+//!
+//! An RPM is, in order:
+//!
+//! * a 96 byte "lead", which is mostly vestigial
+//! * a signature header, holding digests of everything after it
+//! * the main header, holding the package metadata and the file list
+//! * the payload, a gzipped cpio archive of the files to install
+//!
+//! Both headers are the same binary format: an index of (tag, type, offset, count)
+//! entries followed by a data store those offsets point into. We write all of this
+//! ourselves so rpms can be built without `rpmbuild`.
+
+use std::collections::BTreeMap;
+use std::io::Write;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::TripleNameRef;
+use flate2::{write::GzEncoder, Compression};
+use sha2::{Digest, Sha256};
+use tracing::info;
+
+use crate::{tasks::StaticAssetKind, DistResult};
+
+use super::is_executable;
+
+/// Info about an RPM package installer
+#[derive(Debug, Clone)]
+pub struct RpmInstallerInfo {
+    /// Name of the RPM package
+    pub package_name: String,
+    /// Version of the RPM package
+    pub version: String,
+    /// RPM architecture (x86_64, aarch64)
+    pub arch: String,
+    /// A brief description of the app
+    pub description: Option<String>,
+    /// The Packager header
+    pub packager: Option<String>,
+    /// The app's license, as an SPDX expression
+    pub license: Option<String>,
+    /// The URL to the app's homepage
+    pub homepage: Option<String>,
+    /// The URL to the app's source repository
+    pub repository: Option<String>,
+    /// Packages this one requires, with an optional minimum version
+    pub requires: Vec<(String, Option<String>)>,
+    /// Final file path of the rpm
+    pub file_path: Utf8PathBuf,
+    /// Dir the binaries get copied to
+    pub package_dir: Utf8PathBuf,
+    /// Executables to install to /usr/bin (names in package_dir)
+    pub executables: Vec<String>,
+    /// Libraries to install to /usr/lib64 (names in package_dir)
+    pub libraries: Vec<String>,
+    /// Executable aliases
+    pub bin_aliases: BTreeMap<String, Vec<String>>,
+    /// Static assets to include (README, LICENSE, ...)
+    pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+    /// If set, stamp the build time and every file with this instead of now (reproducible-archives)
+    pub source_date_epoch: Option<u64>,
+}
+
+/// The release of every package we build, we only ever build one per version
+const RELEASE: &str = "1";
+
+// Header tags, see rpmtag.h
+const RPMTAG_HEADERSIGNATURES: u32 = 62;
+const RPMTAG_HEADERIMMUTABLE: u32 = 63;
+const RPMTAG_HEADERI18NTABLE: u32 = 100;
+const RPMTAG_NAME: u32 = 1000;
+const RPMTAG_VERSION: u32 = 1001;
+const RPMTAG_RELEASE: u32 = 1002;
+const RPMTAG_SUMMARY: u32 = 1004;
+const RPMTAG_DESCRIPTION: u32 = 1005;
+const RPMTAG_BUILDTIME: u32 = 1006;
+const RPMTAG_BUILDHOST: u32 = 1007;
+const RPMTAG_SIZE: u32 = 1009;
+const RPMTAG_LICENSE: u32 = 1014;
+const RPMTAG_PACKAGER: u32 = 1015;
+const RPMTAG_GROUP: u32 = 1016;
+const RPMTAG_URL: u32 = 1020;
+const RPMTAG_OS: u32 = 1021;
+const RPMTAG_ARCH: u32 = 1022;
+const RPMTAG_FILESIZES: u32 = 1028;
+const RPMTAG_FILEMODES: u32 = 1030;
+const RPMTAG_FILERDEVS: u32 = 1033;
+const RPMTAG_FILEMTIMES: u32 = 1034;
+const RPMTAG_FILEDIGESTS: u32 = 1035;
+const RPMTAG_FILELINKTOS: u32 = 1036;
+const RPMTAG_FILEFLAGS: u32 = 1037;
+const RPMTAG_FILEUSERNAME: u32 = 1039;
+const RPMTAG_FILEGROUPNAME: u32 = 1040;
+const RPMTAG_SOURCERPM: u32 = 1044;
+const RPMTAG_FILEVERIFYFLAGS: u32 = 1045;
+const RPMTAG_PROVIDENAME: u32 = 1047;
+const RPMTAG_REQUIREFLAGS: u32 = 1048;
+const RPMTAG_REQUIRENAME: u32 = 1049;
+const RPMTAG_REQUIREVERSION: u32 = 1050;
+const RPMTAG_FILEDEVICES: u32 = 1095;
+const RPMTAG_FILEINODES: u32 = 1096;
+const RPMTAG_FILELANGS: u32 = 1097;
+const RPMTAG_PROVIDEFLAGS: u32 = 1112;
+const RPMTAG_PROVIDEVERSION: u32 = 1113;
+const RPMTAG_DIRINDEXES: u32 = 1116;
+const RPMTAG_BASENAMES: u32 = 1117;
+const RPMTAG_DIRNAMES: u32 = 1118;
+const RPMTAG_PAYLOADFORMAT: u32 = 1124;
+const RPMTAG_PAYLOADCOMPRESSOR: u32 = 1125;
+const RPMTAG_PAYLOADFLAGS: u32 = 1126;
+const RPMTAG_FILEDIGESTALGO: u32 = 5011;
+const RPMTAG_ENCODING: u32 = 5062;
+const RPMTAG_PAYLOADDIGEST: u32 = 5092;
+const RPMTAG_PAYLOADDIGESTALGO: u32 = 5093;
+
+// Signature tags
+const RPMSIGTAG_SHA256: u32 = 273;
+const RPMSIGTAG_SIZE: u32 = 1000;
+const RPMSIGTAG_PAYLOADSIZE: u32 = 1007;
+
+// Dependency flags
+const RPMSENSE_LESS: u32 = 1 << 1;
+const RPMSENSE_GREATER: u32 = 1 << 2;
+const RPMSENSE_EQUAL: u32 = 1 << 3;
+const RPMSENSE_RPMLIB: u32 = 1 << 24;
+
+// File flags
+const RPMFILE_DOC: u32 = 1 << 1;
+const RPMFILE_LICENSE: u32 = 1 << 7;
+
+/// PGPHASHALGO_SHA256
+const DIGEST_ALGO_SHA256: u32 = 8;
+
+/// A file in the package
+struct RpmFile {
+    /// Absolute install path
+    path: String,
+    /// Full st_mode, including the file type
+    mode: u16,
+    /// File contents (or for symlinks, the target)
+    contents: Vec<u8>,
+    /// RPMFILE_* flags
+    flags: u32,
+}
+
+impl RpmFile {
+    fn is_dir(&self) -> bool {
+        self.mode & 0o170000 == 0o040000
+    }
+    fn is_symlink(&self) -> bool {
+        self.mode & 0o170000 == 0o120000
+    }
+}
+
+impl RpmInstallerInfo {
+    /// Build the rpm installer
+    pub fn build(&self) -> DistResult<()> {
+        info!("building an rpm: {}", self.file_path);
+
+        let mtime = self.source_date_epoch.unwrap_or_else(|| {
+            SystemTime::now()
+                .duration_since(UNIX_EPOCH)
+                .map(|d| d.as_secs())
+                .unwrap_or_default()
+        }) as u32;
+        let files = self.files()?;
+
+        let cpio = write_cpio(&files, mtime)?;
+        let mut payload = GzEncoder::new(Vec::new(), Compression::best());
+        payload.write_all(&cpio)?;
+        let payload = payload.finish()?;
+
+        let header = self
+            .header(&files, mtime, &payload)
+            .into_bytes(RPMTAG_HEADERIMMUTABLE);
+
+        let mut signature = Header::default();
+        signature.push(
+            RPMSIGTAG_SHA256,
+            Value::String(hex(&Sha256::digest(&header))),
+        );
+        signature.push(
+            RPMSIGTAG_SIZE,
+            Value::Int32(vec![(header.len() + payload.len()) as u32]),
+        );
+        signature.push(RPMSIGTAG_PAYLOADSIZE, Value::Int32(vec![cpio.len() as u32]));
+        let mut signature = signature.into_bytes(RPMTAG_HEADERSIGNATURES);
+        // The signature header is padded to 8 bytes, unlike the main header
+        signature.resize(signature.len().next_multiple_of(8), 0);
+
+        let mut rpm = self.lead();
+        rpm.extend_from_slice(&signature);
+        rpm.extend_from_slice(&header);
+        rpm.extend_from_slice(&payload);
+
+        if let Some(parent) = self.file_path.parent() {
+            LocalAsset::create_dir_all(parent)?;
+        }
+        std::fs::write(&self.file_path, rpm)?;
+
+        Ok(())
+    }
+
+    /// The lead, which rpm mostly ignores but still checks the magic of
+    fn lead(&self) -> Vec<u8> {
+        let mut lead = Vec::with_capacity(96);
+        lead.extend_from_slice(&[0xed, 0xab, 0xee, 0xdb]);
+        // format version 3.0
+        lead.extend_from_slice(&[3, 0]);
+        // binary package
+        lead.extend_from_slice(&0u16.to_be_bytes());
+        let archnum: u16 = if self.arch == "aarch64" { 19 } else { 1 };
+        lead.extend_from_slice(&archnum.to_be_bytes());
+        let mut name = format!("{}-{}-{RELEASE}", self.package_name, self.version).into_bytes();
+        name.resize(66, 0);
+        name[65] = 0;
+        lead.extend_from_slice(&name);
+        // linux
+        lead.extend_from_slice(&1u16.to_be_bytes());
+        // header-style signature
+        lead.extend_from_slice(&5u16.to_be_bytes());
+        lead.resize(96, 0);
+        lead
+    }
+
+    /// All the files to install, sorted by path
+    fn files(&self) -> DistResult<Vec<RpmFile>> {
+        let mut files = vec![];
+        let doc_dir = format!("/usr/share/doc/{}", self.package_name);
+        let license_dir = format!("/usr/share/licenses/{}", self.package_name);
+        let share_dir = format!("/usr/share/{}", self.package_name);
+
+        for exe in &self.executables {
+            files.push(RpmFile {
+                path: format!("/usr/bin/{exe}"),
+                mode: 0o100755,
+                contents: std::fs::read(self.package_dir.join(exe))?,
+                flags: 0,
+            });
+        }
+        for (bin, aliases) in &self.bin_aliases {
+            for alias in aliases {
+                files.push(RpmFile {
+                    path: format!("/usr/bin/{alias}"),
+                    mode: 0o120777,
+                    contents: bin.as_bytes().to_vec(),
+                    flags: 0,
+                });
+            }
+        }
+        for lib in &self.libraries {
+            files.push(RpmFile {
+                path: format!("/usr/lib64/{lib}"),
+                mode: 0o100755,
+                contents: std::fs::read(self.package_dir.join(lib))?,
+                flags: 0,
+            });
+        }
+
+        // Directories that are ours alone get owned by the package, so they're
+        // cleaned up when it's removed
+        let mut owned_dirs = vec![];
+        for (kind, src_path) in &self.static_assets {
+            let Some(file_name) = src_path.file_name() else {
+                continue;
+            };
+            let (dest_dir, flags) = match kind {
+                StaticAssetKind::Readme | StaticAssetKind::Changelog => (&doc_dir, RPMFILE_DOC),
+                StaticAssetKind::License => (&license_dir, RPMFILE_LICENSE),
+                StaticAssetKind::Other => (&share_dir, 0),
+            };
+            if !owned_dirs.contains(dest_dir) {
+                owned_dirs.push(dest_dir.clone());
+            }
+            add_path(
+                &mut files,
+                src_path,
+                format!("{dest_dir}/{file_name}"),
+                flags,
+            )?;
+        }
+        for dir in owned_dirs {
+            files.push(RpmFile {
+                path: dir,
+                mode: 0o040755,
+                contents: vec![],
+                flags: 0,
+            });
+        }
+
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+        Ok(files)
+    }
+
+    /// The main header
+    fn header(&self, files: &[RpmFile], mtime: u32, payload: &[u8]) -> Header {
+        let nevr = format!("{}-{}-{RELEASE}", self.package_name, self.version);
+        let summary = self
+            .description
+            .as_deref()
+            .and_then(|desc| desc.lines().next())
+            .map(str::trim)
+            .filter(|desc| !desc.is_empty())
+            .unwrap_or(&self.package_name)
+            .to_owned();
+        let description = self.description.clone().unwrap_or_else(|| summary.clone());
+
+        // Split paths into the "compressed" dirname/basename form
+        let mut dirnames: Vec<String> = vec![];
+        let mut dirindexes = vec![];
+        let mut basenames = vec![];
+        for file in files {
+            let (dir, base) = file.path.rsplit_once('/').unwrap_or(("", &file.path));
+            let dir = format!("{dir}/");
+            let idx = match dirnames.iter().position(|d| d == &dir) {
+                Some(idx) => idx,
+                None => {
+                    dirnames.push(dir);
+                    dirnames.len() - 1
+                }
+            };
+            dirindexes.push(idx as u32);
+            basenames.push(base.to_owned());
+        }
+
+        let mut requires = vec![
+            ("rpmlib(CompressedFileNames)", "3.0.4-1"),
+            ("rpmlib(FileDigests)", "4.6.0-1"),
+            ("rpmlib(PayloadFilesHavePrefix)", "4.0-1"),
+        ]
+        .into_iter()
+        .map(|(name, version)| {
+            (
+                name.to_owned(),
+                RPMSENSE_RPMLIB | RPMSENSE_LESS | RPMSENSE_EQUAL,
+                version.to_owned(),
+            )
+        })
+        .collect::<Vec<_>>();
+        if self.version.contains('~') {
+            requires.push((
+                "rpmlib(TildeInVersions)".to_owned(),
+                RPMSENSE_RPMLIB | RPMSENSE_LESS | RPMSENSE_EQUAL,
+                "4.10.0-1".to_owned(),
+            ));
+        }
+        for (name, version) in &self.requires {
+            match version {
+                Some(version) => requires.push((
+                    name.clone(),
+                    RPMSENSE_GREATER | RPMSENSE_EQUAL,
+                    version.clone(),
+                )),
+                None => requires.push((name.clone(), 0, String::new())),
+            }
+        }
+
+        let installed_size: u64 = files
+            .iter()
+            .filter(|f| !f.is_dir())
+            .map(|f| f.contents.len() as u64)
+            .sum();
+
+        let mut header = Header::default();
+        header.push(
+            RPMTAG_HEADERI18NTABLE,
+            Value::StringArray(vec!["C".to_owned()]),
+        );
+        header.push(RPMTAG_NAME, Value::String(self.package_name.clone()));
+        header.push(RPMTAG_VERSION, Value::String(self.version.clone()));
+        header.push(RPMTAG_RELEASE, Value::String(RELEASE.to_owned()));
+        header.push(RPMTAG_SUMMARY, Value::I18nString(summary));
+        header.push(RPMTAG_DESCRIPTION, Value::I18nString(description));
+        header.push(RPMTAG_BUILDTIME, Value::Int32(vec![mtime]));
+        header.push(RPMTAG_BUILDHOST, Value::String("localhost".to_owned()));
+        header.push(RPMTAG_SIZE, Value::Int32(vec![installed_size as u32]));
+        header.push(
+            RPMTAG_LICENSE,
+            Value::String(self.license.clone().unwrap_or_default()),
+        );
+        if let Some(packager) = &self.packager {
+            header.push(RPMTAG_PACKAGER, Value::String(packager.clone()));
+        }
+        header.push(RPMTAG_GROUP, Value::I18nString("Unspecified".to_owned()));
+        if let Some(url) = self.homepage.as_ref().or(self.repository.as_ref()) {
+            header.push(RPMTAG_URL, Value::String(url.clone()));
+        }
+        header.push(RPMTAG_OS, Value::String("linux".to_owned()));
+        header.push(RPMTAG_ARCH, Value::String(self.arch.clone()));
+        header.push(
+            RPMTAG_FILESIZES,
+            Value::Int32(
+                files
+                    .iter()
+                    .map(|f| {
+                        if f.is_dir() {
+                            4096
+                        } else {
+                            f.contents.len() as u32
+                        }
+                    })
+                    .collect(),
+            ),
+        );
+        header.push(
+            RPMTAG_FILEMODES,
+            Value::Int16(files.iter().map(|f| f.mode).collect()),
+        );
+        header.push(RPMTAG_FILERDEVS, Value::Int16(vec![0; files.len()]));
+        header.push(RPMTAG_FILEMTIMES, Value::Int32(vec![mtime; files.len()]));
+        header.push(
+            RPMTAG_FILEDIGESTS,
+            Value::StringArray(
+                files
+                    .iter()
+                    .map(|f| {
+                        if f.is_dir() || f.is_symlink() {
+                            String::new()
+                        } else {
+                            hex(&Sha256::digest(&f.contents))
+                        }
+                    })
+                    .collect(),
+            ),
+        );
+        header.push(
+            RPMTAG_FILELINKTOS,
+            Value::StringArray(
+                files
+                    .iter()
+                    .map(|f| {
+                        if f.is_symlink() {
+                            String::from_utf8_lossy(&f.contents).into_owned()
+                        } else {
+                            String::new()
+                        }
+                    })
+                    .collect(),
+            ),
+        );
+        header.push(
+            RPMTAG_FILEFLAGS,
+            Value::Int32(files.iter().map(|f| f.flags).collect()),
+        );
+        header.push(
+            RPMTAG_FILEUSERNAME,
+            Value::StringArray(vec!["root".to_owned(); files.len()]),
+        );
+        header.push(
+            RPMTAG_FILEGROUPNAME,
+            Value::StringArray(vec!["root".to_owned(); files.len()]),
+        );
+        header.push(RPMTAG_SOURCERPM, Value::String(format!("{nevr}.src.rpm")));
+        header.push(
+            RPMTAG_FILEVERIFYFLAGS,
+            Value::Int32(vec![u32::MAX; files.len()]),
+        );
+        header.push(
+            RPMTAG_PROVIDENAME,
+            Value::StringArray(vec![self.package_name.clone()]),
+        );
+        header.push(
+            RPMTAG_REQUIREFLAGS,
+            Value::Int32(requires.iter().map(|(_, flags, _)| *flags).collect()),
+        );
+        header.push(
+            RPMTAG_REQUIRENAME,
+            Value::StringArray(requires.iter().map(|(name, _, _)| name.clone()).collect()),
+        );
+        header.push(
+            RPMTAG_REQUIREVERSION,
+            Value::StringArray(
+                requires
+                    .iter()
+                    .map(|(_, _, version)| version.clone())
+                    .collect(),
+            ),
+        );
+        header.push(RPMTAG_FILEDEVICES, Value::Int32(vec![1; files.len()]));
+        header.push(
+            RPMTAG_FILEINODES,
+            Value::Int32((1..=files.len() as u32).collect()),
+        );
+        header.push(
+            RPMTAG_FILELANGS,
+            Value::StringArray(vec![String::new(); files.len()]),
+        );
+        header.push(RPMTAG_PROVIDEFLAGS, Value::Int32(vec![RPMSENSE_EQUAL]));
+        header.push(
+            RPMTAG_PROVIDEVERSION,
+            Value::StringArray(vec![format!("{}-{RELEASE}", self.version)]),
+        );
+        header.push(RPMTAG_DIRINDEXES, Value::Int32(dirindexes));
+        header.push(RPMTAG_BASENAMES, Value::StringArray(basenames));
+        header.push(RPMTAG_DIRNAMES, Value::StringArray(dirnames));
+        header.push(RPMTAG_PAYLOADFORMAT, Value::String("cpio".to_owned()));
+        header.push(RPMTAG_PAYLOADCOMPRESSOR, Value::String("gzip".to_owned()));
+        header.push(RPMTAG_PAYLOADFLAGS, Value::String("9".to_owned()));
+        header.push(
+            RPMTAG_FILEDIGESTALGO,
+            Value::Int32(vec![DIGEST_ALGO_SHA256]),
+        );
+        header.push(RPMTAG_ENCODING, Value::String("utf-8".to_owned()));
+        header.push(
+            RPMTAG_PAYLOADDIGEST,
+            Value::StringArray(vec![hex(&Sha256::digest(payload))]),
+        );
+        header.push(
+            RPMTAG_PAYLOADDIGESTALGO,
+            Value::Int32(vec![DIGEST_ALGO_SHA256]),
+        );
+        header
+    }
+}
+
+/// Add a file or (recursively) a directory from disk
+fn add_path(
+    files: &mut Vec<RpmFile>,
+    src_path: &Utf8Path,
+    path: String,
+    flags: u32,
+) -> DistResult<()> {
+    if src_path.is_dir() {
+        for entry in src_path.read_dir_utf8()? {
+            let entry = entry?;
+            add_path(
+                files,
+                entry.path(),
+                format!("{path}/{}", entry.file_name()),
+                flags,
+            )?;
+        }
+        files.push(RpmFile {
+            path,
+            mode: 0o040755,
+            contents: vec![],
+            flags,
+        });
+    } else {
+        let mode = if is_executable(src_path) {
+            0o100755
+        } else {
+            0o100644
+        };
+        files.push(RpmFile {
+            path,
+            mode,
+            contents: std::fs::read(src_path)?,
+            flags,
+        });
+    }
+    Ok(())
+}
+
+/// Write the payload, a "newc" cpio archive
+fn write_cpio(files: &[RpmFile], mtime: u32) -> DistResult<Vec<u8>> {
+    let mut cpio = Vec::new();
+    for (idx, file) in files.iter().enumerate() {
+        let nlink = if file.is_dir() { 2 } else { 1 };
+        let contents: &[u8] = if file.is_dir() { &[] } else { &file.contents };
+        write_cpio_entry(
+            &mut cpio,
+            &format!(".{}", file.path),
+            idx as u32 + 1,
+            file.mode as u32,
+            nlink,
+            mtime,
+            contents,
+        )?;
+    }
+    write_cpio_entry(&mut cpio, "TRAILER!!!", 0, 0, 1, 0, &[])?;
+    Ok(cpio)
+}
+
+fn write_cpio_entry(
+    cpio: &mut Vec<u8>,
+    name: &str,
+    ino: u32,
+    mode: u32,
+    nlink: u32,
+    mtime: u32,
+    contents: &[u8],
+) -> DistResult<()> {
+    // magic, ino, mode, uid, gid, nlink, mtime, filesize,
+    // devmajor, devminor, rdevmajor, rdevminor, namesize, check
+    write!(
+        cpio,
+        "070701{ino:08x}{mode:08x}{:08x}{:08x}{nlink:08x}{mtime:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}{:08x}",
+        0,
+        0,
+        contents.len(),
+        0,
+        1,
+        0,
+        0,
+        name.len() + 1,
+        0
+    )?;
+    cpio.extend_from_slice(name.as_bytes());
+    cpio.push(0);
+    cpio.resize(cpio.len().next_multiple_of(4), 0);
+    cpio.extend_from_slice(contents);
+    cpio.resize(cpio.len().next_multiple_of(4), 0);
+    Ok(())
+}
+
+/// A value in a header
+enum Value {
+    Int16(Vec<u16>),
+    Int32(Vec<u32>),
+    String(String),
+    Bin(Vec<u8>),
+    StringArray(Vec<String>),
+    I18nString(String),
+}
+
+impl Value {
+    fn type_and_count(&self) -> (u32, u32) {
+        match self {
+            Value::Int16(v) => (3, v.len() as u32),
+            Value::Int32(v) => (4, v.len() as u32),
+            Value::String(_) => (6, 1),
+            Value::Bin(v) => (7, v.len() as u32),
+            Value::StringArray(v) => (8, v.len() as u32),
+            Value::I18nString(_) => (9, 1),
+        }
+    }
+
+    fn alignment(&self) -> usize {
+        match self {
+            Value::Int16(_) => 2,
+            Value::Int32(_) => 4,
+            _ => 1,
+        }
+    }
+
+    fn write(&self, data: &mut Vec<u8>) {
+        match self {
+            Value::Int16(v) => v.iter().for_each(|n| data.extend(n.to_be_bytes())),
+            Value::Int32(v) => v.iter().for_each(|n| data.extend(n.to_be_bytes())),
+            Value::String(s) | Value::I18nString(s) => {
+                data.extend_from_slice(s.as_bytes());
+                data.push(0);
+            }
+            Value::Bin(v) => data.extend_from_slice(v),
+            Value::StringArray(v) => {
+                for s in v {
+                    data.extend_from_slice(s.as_bytes());
+                    data.push(0);
+                }
+            }
+        }
+    }
+}
+
+/// A header being built
+#[derive(Default)]
+struct Header {
+    entries: Vec<(u32, Value)>,
+}
+
+impl Header {
+    fn push(&mut self, tag: u32, value: Value) {
+        self.entries.push((tag, value));
+    }
+
+    /// Serialize the header, wrapped in an immutable region with the given tag
+    fn into_bytes(mut self, region_tag: u32) -> Vec<u8> {
+        self.entries.sort_by_key(|(tag, _)| *tag);
+        let count = self.entries.len() + 1;
+
+        let mut index = Vec::new();
+        let mut data = Vec::new();
+        for (tag, value) in &self.entries {
+            data.resize(data.len().next_multiple_of(value.alignment()), 0);
+            let (kind, count) = value.type_and_count();
+            index.extend(tag.to_be_bytes());
+            index.extend(kind.to_be_bytes());
+            index.extend((data.len() as u32).to_be_bytes());
+            index.extend(count.to_be_bytes());
+            value.write(&mut data);
+        }
+
+        // The region's data is a trailing index entry pointing back at the start of the index
+        let mut trailer = Vec::new();
+        trailer.extend(region_tag.to_be_bytes());
+        trailer.extend(7u32.to_be_bytes());
+        trailer.extend((-(count as i32 * 16)).to_be_bytes());
+        trailer.extend(16u32.to_be_bytes());
+        let region_offset = data.len() as u32;
+        Value::Bin(trailer).write(&mut data);
+
+        let mut bytes = Vec::new();
+        bytes.extend_from_slice(&[0x8e, 0xad, 0xe8, 0x01, 0, 0, 0, 0]);
+        bytes.extend((count as u32).to_be_bytes());
+        bytes.extend((data.len() as u32).to_be_bytes());
+        bytes.extend(region_tag.to_be_bytes());
+        bytes.extend(7u32.to_be_bytes());
+        bytes.extend(region_offset.to_be_bytes());
+        bytes.extend(16u32.to_be_bytes());
+        bytes.extend(index);
+        bytes.extend(data);
+        bytes
+    }
+}
+
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{b:02x}")).collect()
+}
+
+/// Get the RPM architecture name for a target triple
+pub fn rpm_arch(target: &TripleNameRef) -> Option<&'static str> {
+    match target.as_str().split('-').next() {
+        Some("x86_64") => Some("x86_64"),
+        Some("aarch64") => Some("aarch64"),
+        _ => None,
+    }
+}
+
+/// Convert a version to one that sorts correctly in RPM
+///
+/// RPM versions can't contain `-`, and sort `~` before the release like semver prereleases.
+pub fn rpm_version(version: &str) -> String {
+    let (version, build) = match version.split_once('+') {
+        Some((version, build)) => (version, Some(build)),
+        None => (version, None),
+    };
+    let mut result = match version.split_once('-') {
+        Some((release, pre)) => format!("{release}~{}", pre.replace('-', "_")),
+        None => version.to_owned(),
+    };
+    if let Some(build) = build {
+        result.push('+');
+        result.push_str(&build.replace('-', "_"));
+    }
+    result
+}
+
+/// Convert a package name to a valid RPM package name
+pub fn rpm_package_name(name: &str) -> String {
+    name.chars()
+        .map(|c| match c {
+            'a'..='z' | 'A'..='Z' | '0'..='9' | '.' | '_' | '+' | '-' => c,
+            _ => '-',
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use std::io::Read;
+
+    use super::*;
+
+    /// A parsed header: tag => (type, count, raw data starting at the entry's offset)
+    type ParsedHeader = BTreeMap<u32, (u32, u32, Vec<u8>)>;
+
+    /// Parse a header at the start of `bytes`, returning it and its length
+    fn parse_header(bytes: &[u8]) -> (ParsedHeader, usize) {
+        let be = |at: usize| u32::from_be_bytes(bytes[at..at + 4].try_into().unwrap());
+        assert_eq!(&bytes[..4], &[0x8e, 0xad, 0xe8, 0x01]);
+        let count = be(8) as usize;
+        let data_len = be(12) as usize;
+        let data_start = 16 + count * 16;
+        let mut entries = BTreeMap::new();
+        let mut last_tag = 0;
+        for i in 0..count {
+            let at = 16 + i * 16;
+            let (tag, kind, offset, n) = (be(at), be(at + 4), be(at + 8), be(at + 12));
+            assert!(tag > last_tag, "index isn't sorted");
+            last_tag = tag;
+            let offset = data_start + offset as usize;
+            match kind {
+                3 => assert_eq!(offset % 2, 0),
+                4 => assert_eq!((offset - data_start) % 4, 0),
+                _ => {}
+            }
+            entries.insert(
+                tag,
+                (kind, n, bytes[offset..data_start + data_len].to_vec()),
+            );
+        }
+        (entries, data_start + data_len)
+    }
+
+    fn strings(entry: &(u32, u32, Vec<u8>)) -> Vec<String> {
+        entry
+            .2
+            .split(|b| *b == 0)
+            .take(entry.1 as usize)
+            .map(|s| String::from_utf8(s.to_vec()).unwrap())
+            .collect()
+    }
+
+    fn int32s(entry: &(u32, u32, Vec<u8>)) -> Vec<u32> {
+        entry.2[..entry.1 as usize * 4]
+            .chunks(4)
+            .map(|c| u32::from_be_bytes(c.try_into().unwrap()))
+            .collect()
+    }
+
+    #[test]
+    fn converts_versions() {
+        assert_eq!(rpm_version("1.2.3"), "1.2.3");
+        assert_eq!(rpm_version("1.2.3-alpha.1"), "1.2.3~alpha.1");
+        assert_eq!(rpm_version("1.2.3-rc-1+build-5"), "1.2.3~rc_1+build_5");
+        assert_eq!(rpm_package_name("my app"), "my-app");
+        assert_eq!(
+            rpm_arch(TripleNameRef::from_str("aarch64-unknown-linux-musl")),
+            Some("aarch64")
+        );
+        assert_eq!(
+            rpm_arch(TripleNameRef::from_str("armv7-unknown-linux-gnueabihf")),
+            None
+        );
+    }
+
+    fn info(dir: &Utf8Path) -> RpmInstallerInfo {
+        RpmInstallerInfo {
+            package_name: "axolotlsay".to_owned(),
+            version: "0.1.0~beta.1".to_owned(),
+            arch: "x86_64".to_owned(),
+            description: Some("💬 a CLI for learning to distribute CLIs in rust".to_owned()),
+            packager: Some("Axo Developer <hello@axo.dev>".to_owned()),
+            license: Some("MIT OR Apache-2.0".to_owned()),
+            homepage: Some("https://axo.dev".to_owned()),
+            repository: None,
+            requires: vec![
+                ("openssl-libs".to_owned(), Some("3.0".to_owned())),
+                ("zlib".to_owned(), None),
+            ],
+            file_path: dir.join("axolotlsay.rpm"),
+            package_dir: dir.join("pkg"),
+            executables: vec!["axolotlsay".to_owned()],
+            libraries: vec![],
+            bin_aliases: BTreeMap::from([("axolotlsay".to_owned(), vec!["say".to_owned()])]),
+            static_assets: vec![(StaticAssetKind::License, dir.join("LICENSE"))],
+            source_date_epoch: None,
+        }
+    }
+
+    /// Write the files `info` packages
+    fn write_files(info: &RpmInstallerInfo, dir: &Utf8Path) {
+        LocalAsset::write_new_all("#!/bin/sh\necho hi\n", info.package_dir.join("axolotlsay"))
+            .unwrap();
+        LocalAsset::write_new_all("MIT\n", dir.join("LICENSE")).unwrap();
+    }
+
+    #[test]
+    fn builds_rpm() {
+        let tmp = temp_dir::TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(tmp.path()).unwrap();
+        let info = info(dir);
+        write_files(&info, dir);
+        info.build().unwrap();
+
+        let rpm = std::fs::read(&info.file_path).unwrap();
+        assert_eq!(&rpm[..4], &[0xed, 0xab, 0xee, 0xdb]);
+        assert!(rpm[10..76].starts_with(b"axolotlsay-0.1.0~beta.1-1\0"));
+
+        // Signature, then the header it signs
+        let (signature, signature_len) = parse_header(&rpm[96..]);
+        let header_start = 96 + signature_len.next_multiple_of(8);
+        let (header, header_len) = parse_header(&rpm[header_start..]);
+        let header_bytes = &rpm[header_start..header_start + header_len];
+        let payload = &rpm[header_start + header_len..];
+
+        assert_eq!(
+            strings(&signature[&RPMSIGTAG_SHA256]),
+            [hex(&Sha256::digest(header_bytes))]
+        );
+        assert_eq!(
+            int32s(&signature[&RPMSIGTAG_SIZE]),
+            [(header_len + payload.len()) as u32]
+        );
+        assert_eq!(
+            strings(&header[&RPMTAG_PAYLOADDIGEST]),
+            [hex(&Sha256::digest(payload))]
+        );
+
+        // Both headers are immutable regions whose trailer points back at the whole index
+        for (parsed, tag) in [
+            (&signature, RPMTAG_HEADERSIGNATURES),
+            (&header, RPMTAG_HEADERIMMUTABLE),
+        ] {
+            let (kind, count, data) = &parsed[&tag];
+            assert_eq!((*kind, *count, data.len()), (7, 16, 16));
+            assert_eq!(u32::from_be_bytes(data[..4].try_into().unwrap()), tag);
+            let offset = i32::from_be_bytes(data[8..12].try_into().unwrap());
+            assert_eq!(offset, -(parsed.len() as i32 * 16));
+        }
+
+        assert_eq!(strings(&header[&RPMTAG_NAME]), ["axolotlsay"]);
+        assert_eq!(strings(&header[&RPMTAG_LICENSE]), ["MIT OR Apache-2.0"]);
+        assert_eq!(strings(&header[&RPMTAG_URL]), ["https://axo.dev"]);
+        assert_eq!(
+            strings(&header[&RPMTAG_SUMMARY]),
+            ["💬 a CLI for learning to distribute CLIs in rust"]
+        );
+        assert_eq!(
+            strings(&header[&RPMTAG_REQUIRENAME]),
+            [
+                "rpmlib(CompressedFileNames)",
+                "rpmlib(FileDigests)",
+                "rpmlib(PayloadFilesHavePrefix)",
+                "rpmlib(TildeInVersions)",
+                "openssl-libs",
+                "zlib"
+            ]
+        );
+        assert_eq!(
+            int32s(&header[&RPMTAG_REQUIREFLAGS])[4..],
+            [RPMSENSE_GREATER | RPMSENSE_EQUAL, 0]
+        );
+        assert_eq!(
+            strings(&header[&RPMTAG_DIRNAMES]),
+            [
+                "/usr/bin/",
+                "/usr/share/licenses/",
+                "/usr/share/licenses/axolotlsay/"
+            ]
+        );
+        assert_eq!(
+            strings(&header[&RPMTAG_BASENAMES]),
+            ["axolotlsay", "say", "axolotlsay", "LICENSE"]
+        );
+        assert_eq!(int32s(&header[&RPMTAG_DIRINDEXES]), [0, 0, 1, 2]);
+        assert_eq!(
+            int32s(&header[&RPMTAG_FILEFLAGS]),
+            [0, 0, 0, RPMFILE_LICENSE]
+        );
+
+        // The payload is a gzipped cpio with ./-prefixed paths
+        let mut cpio = Vec::new();
+        flate2::read::GzDecoder::new(payload)
+            .read_to_end(&mut cpio)
+            .unwrap();
+        let mut names = vec![];
+        let mut pos = 0;
+        loop {
+            let header = std::str::from_utf8(&cpio[pos..pos + 110]).unwrap();
+            assert_eq!(&header[..6], "070701");
+            let field =
+                |i: usize| usize::from_str_radix(&header[6 + i * 8..14 + i * 8], 16).unwrap();
+            let (filesize, namesize) = (field(6), field(11));
+            let name = std::str::from_utf8(&cpio[pos + 110..pos + 110 + namesize - 1]).unwrap();
+            pos = (pos + 110 + namesize).next_multiple_of(4);
+            let contents = &cpio[pos..pos + filesize];
+            pos = (pos + filesize).next_multiple_of(4);
+            if name == "TRAILER!!!" {
+                break;
+            }
+            names.push(format!("{name} {:o} {}", field(1), contents.len()));
+        }
+        assert_eq!(pos, cpio.len());
+        assert_eq!(
+            names,
+            [
+                "./usr/bin/axolotlsay 100755 18",
+                "./usr/bin/say 120777 10",
+                "./usr/share/licenses/axolotlsay 40755 0",
+                "./usr/share/licenses/axolotlsay/LICENSE 100644 4",
+            ]
+        );
+    }
+
+    #[test]
+    fn builds_reproducible_rpm() {
+        const EPOCH: u64 = 1_700_000_000;
+        let tmp = temp_dir::TempDir::new().unwrap();
+        let dir = Utf8Path::from_path(tmp.path()).unwrap();
+        let mut info = info(dir);
+        info.source_date_epoch = Some(EPOCH);
+        write_files(&info, dir);
+        info.build().unwrap();
+        let first = std::fs::read(&info.file_path).unwrap();
+        std::thread::sleep(std::time::Duration::from_millis(1100));
+        info.build().unwrap();
+        let second = std::fs::read(&info.file_path).unwrap();
+        assert_eq!(first, second, "rpms differ");
+
+        let (signature, signature_len) = parse_header(&first[96..]);
+        let header_start = 96 + signature_len.next_multiple_of(8);
+        let (header, header_len) = parse_header(&first[header_start..]);
+        assert!(signature.contains_key(&RPMSIGTAG_SHA256));
+        assert_eq!(int32s(&header[&RPMTAG_BUILDTIME]), [EPOCH as u32]);
+        assert_eq!(int32s(&header[&RPMTAG_FILEMTIMES]), [EPOCH as u32; 4]);
+
+        let mut cpio = Vec::new();
+        flate2::read::GzDecoder::new(&first[header_start + header_len..])
+            .read_to_end(&mut cpio)
+            .unwrap();
+        let mut pos = 0;
+        while pos < cpio.len() {
+            let header = std::str::from_utf8(&cpio[pos..pos + 110]).unwrap();
+            let field =
+                |i: usize| usize::from_str_radix(&header[6 + i * 8..14 + i * 8], 16).unwrap();
+            let (mtime, filesize, namesize) = (field(5), field(6), field(11));
+            let name = std::str::from_utf8(&cpio[pos + 110..pos + 110 + namesize - 1]).unwrap();
+            pos = (pos + 110 + namesize).next_multiple_of(4);
+            pos = (pos + filesize).next_multiple_of(4);
+            if name == "TRAILER!!!" {
+                break;
+            }
+            assert_eq!(mtime, EPOCH as usize, "{name} isn't stamped with the epoch");
+        }
+    }
+}
@@ -0,0 +1,187 @@
+//! Code for generating Scoop manifests
+
+use axoasset::LocalAsset;
+use cargo_dist_schema::DistManifest;
+use serde::Serialize;
+
+use super::{ExecutableZipFragment, InstallerInfo, WindowsFragments};
+use crate::{
+    config::ZipStyle, errors::DistResult, platform::FetchableArchiveChecksum, tasks::DistGraph,
+    SortedMap,
+};
+
+/// Info about a Scoop manifest
+#[derive(Debug, Clone, Serialize)]
+pub struct ScoopInstallerInfo {
+    /// The application's name
+    pub name: String,
+    /// A brief description of the application
+    pub description: String,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// The application's license, in SPDX format
+    pub license: Option<String>,
+    /// A GitHub repository (Scoop bucket) to write the manifest to, in owner/name format
+    pub bucket: Option<String>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// A Scoop app manifest
+///
+/// <https://github.com/ScoopInstaller/Scoop/wiki/App-Manifests>
+#[derive(Debug, Clone, Serialize)]
+struct ScoopManifest {
+    version: String,
+    description: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    homepage: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    license: Option<String>,
+    /// Keyed by Scoop's architecture names ("64bit", "32bit", "arm64")
+    architecture: SortedMap<&'static str, ScoopArchitecture>,
+}
+
+/// The architecture-specific part of a Scoop manifest
+#[derive(Debug, Clone, Serialize)]
+struct ScoopArchitecture {
+    url: String,
+    /// Missing when building "just the installers" locally, since we didn't build the archives
+    #[serde(skip_serializing_if = "Option::is_none")]
+    hash: Option<String>,
+    /// Our tarballs have a root dir, zips don't
+    #[serde(skip_serializing_if = "Option::is_none")]
+    extract_dir: Option<String>,
+    bin: Vec<ScoopBin>,
+}
+
+/// An entry in the `bin` list of a Scoop manifest
+#[derive(Debug, Clone, Serialize)]
+#[serde(untagged)]
+enum ScoopBin {
+    /// Shim the executable under its own name
+    Executable(String),
+    /// Shim the executable under another name
+    Alias(String, String),
+}
+
+pub(crate) fn write_scoop_manifest(
+    dist: &DistGraph,
+    info: &ScoopInstallerInfo,
+    fragments: &WindowsFragments<ExecutableZipFragment>,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let platform_support = dist.release(info.inner.release).platform_support.clone();
+    let platform_support = if dist.local_builds_are_lies {
+        // if local builds are lies, the artifacts that are "fake-built" have a different
+        // checksum every time, so we can't use those in the generated manifest
+        platform_support
+    } else {
+        platform_support.with_checksums_from_manifest(manifest)
+    };
+    let base_url = info
+        .inner
+        .base_urls
+        .first()
+        .expect("returned empty list of artifact URLs!?");
+
+    let mut architecture = SortedMap::new();
+    for (arch, fragment) in [
+        ("64bit", &fragments.x86_64_windows),
+        ("32bit", &fragments.x86_windows),
+        ("arm64", &fragments.arm64_windows),
+    ] {
+        let Some(fragment) = fragment else {
+            continue;
+        };
+        let hash = platform_support
+            .archives
+            .iter()
+            .find(|archive| archive.id == fragment.id)
+            .and_then(|archive| archive.checksum.as_ref())
+            .and_then(scoop_hash);
+        let extract_dir = match fragment.zip_style {
+            ZipStyle::Tar(_) => fragment
+                .id
+                .as_str()
+                .strip_suffix(fragment.zip_style.ext())
+                .map(ToOwned::to_owned),
+            ZipStyle::Zip | ZipStyle::TempDir => None,
+        };
+        let aliases = info
+            .inner
+            .bin_aliases
+            .get(&fragment.target_triple)
+            .cloned()
+            .unwrap_or_default();
+        let mut bin = vec![];
+        for exe in &fragment.executables {
+            bin.push(ScoopBin::Executable(exe.clone()));
+            for alias in aliases.get(exe).into_iter().flatten() {
+                // Scoop adds the extension to the shim itself
+                let alias = alias.strip_suffix(".exe").unwrap_or(alias);
+                bin.push(ScoopBin::Alias(exe.clone(), alias.to_owned()));
+            }
+        }
+        architecture.insert(
+            arch,
+            ScoopArchitecture {
+                url: format!("{base_url}/{}", fragment.id),
+                hash,
+                extract_dir,
+                bin,
+            },
+        );
+    }
+
+    let scoop_manifest = ScoopManifest {
+        version: info.inner.app_version.clone(),
+        description: info.description.clone(),
+        homepage: info.homepage.clone(),
+        license: info.license.clone(),
+        architecture,
+    };
+    let mut contents = serde_json::to_string_pretty(&scoop_manifest).expect("serde_json failed");
+    contents.push('\n');
+    LocalAsset::write_new(&contents, &info.inner.dest_path)?;
+    Ok(())
+}
+
+/// Scoop assumes sha256, but also understands a few other algorithms with a prefix
+fn scoop_hash(checksum: &FetchableArchiveChecksum) -> Option<String> {
+    match checksum.style.as_str() {
+        "sha256" => Some(checksum.value.to_string()),
+        "sha512" => Some(format!("sha512:{}", checksum.value)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_dist_schema::{ChecksumExtension, ChecksumValue};
+
+    #[test]
+    fn test_scoop_bin_serialization() {
+        let bin = vec![
+            ScoopBin::Executable("axolotlsay.exe".to_owned()),
+            ScoopBin::Alias("axolotlsay.exe".to_owned(), "axo".to_owned()),
+        ];
+        let json = serde_json::to_string(&bin).unwrap();
+        assert_eq!(json, r#"["axolotlsay.exe",["axolotlsay.exe","axo"]]"#);
+    }
+
+    #[test]
+    fn test_scoop_hash() {
+        let checksum = |style: &str| FetchableArchiveChecksum {
+            style: ChecksumExtension::new(style.to_owned()),
+            value: ChecksumValue::new("abc123".to_owned()),
+        };
+        assert_eq!(scoop_hash(&checksum("sha256")).as_deref(), Some("abc123"));
+        assert_eq!(
+            scoop_hash(&checksum("sha512")).as_deref(),
+            Some("sha512:abc123")
+        );
+        assert_eq!(scoop_hash(&checksum("blake2b")), None);
+    }
+}
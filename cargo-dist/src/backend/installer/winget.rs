@@ -0,0 +1,213 @@
+//! Code for generating winget manifests
+
+use axoasset::LocalAsset;
+use cargo_dist_schema::{ChecksumValue, DistManifest};
+use serde::Serialize;
+
+use super::{ExecutableZipFragment, InstallerInfo, WindowsFragments};
+use crate::{
+    backend::templates::{
+        TEMPLATE_INSTALLER_WINGET_INSTALLER, TEMPLATE_INSTALLER_WINGET_LOCALE,
+        TEMPLATE_INSTALLER_WINGET_VERSION,
+    },
+    config::ChecksumStyle,
+    errors::DistResult,
+    tasks::DistGraph,
+};
+
+/// The version of the winget manifest schema we generate
+const WINGET_MANIFEST_VERSION: &str = "1.6.0";
+/// The locale we write package metadata for
+pub const WINGET_DEFAULT_LOCALE: &str = "en-US";
+
+/// Info about a winget package
+#[derive(Debug, Clone, Serialize)]
+pub struct WingetInstallerInfo {
+    /// The package identifier (Publisher.AppName)
+    pub package_identifier: String,
+    /// The application's name
+    pub package_name: String,
+    /// The publisher of the application
+    pub publisher: String,
+    /// The application's license, in SPDX format
+    pub license: String,
+    /// A brief description of the application
+    pub short_description: String,
+    /// The URL to the application's homepage
+    pub homepage: Option<String>,
+    /// A GitHub repository to write the manifests to, in owner/name format
+    pub repo: Option<String>,
+    /// Generic installer info
+    pub inner: InstallerInfo,
+}
+
+/// The winget manifests that make up a package
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WingetManifestType {
+    /// `{id}.yaml`, ties the other manifests together
+    Version,
+    /// `{id}.locale.en-US.yaml`, package metadata
+    DefaultLocale,
+    /// `{id}.installer.yaml`, the actual archives to install
+    Installer,
+}
+
+impl WingetManifestType {
+    /// All the manifests a package needs
+    pub const ALL: [Self; 3] = [Self::Version, Self::DefaultLocale, Self::Installer];
+
+    /// The filename of this manifest for the given package
+    pub fn file_name(self, package_identifier: &str) -> String {
+        match self {
+            Self::Version => format!("{package_identifier}.yaml"),
+            Self::DefaultLocale => {
+                format!("{package_identifier}.locale.{WINGET_DEFAULT_LOCALE}.yaml")
+            }
+            Self::Installer => format!("{package_identifier}.installer.yaml"),
+        }
+    }
+}
+
+pub(crate) fn write_winget_manifest(
+    dist: &DistGraph,
+    info: &WingetInstallerInfo,
+    manifest_type: WingetManifestType,
+    fragments: &WindowsFragments<ExecutableZipFragment>,
+    manifest: &DistManifest,
+) -> DistResult<()> {
+    let template = match manifest_type {
+        WingetManifestType::Version => TEMPLATE_INSTALLER_WINGET_VERSION,
+        WingetManifestType::DefaultLocale => TEMPLATE_INSTALLER_WINGET_LOCALE,
+        WingetManifestType::Installer => TEMPLATE_INSTALLER_WINGET_INSTALLER,
+    };
+
+    let platform_support = dist.release(info.inner.release).platform_support.clone();
+    let platform_support = if dist.local_builds_are_lies {
+        // if local builds are lies, the artifacts that are "fake-built" have a different
+        // checksum every time, so we can't use those in the generated manifest
+        platform_support
+    } else {
+        platform_support.with_checksums_from_manifest(manifest)
+    };
+    let base_url = info
+        .inner
+        .base_urls
+        .first()
+        .expect("returned empty list of artifact URLs!?");
+
+    let mut installers = vec![];
+    for (architecture, fragment) in [
+        ("x64", &fragments.x86_64_windows),
+        ("x86", &fragments.x86_windows),
+        ("arm64", &fragments.arm64_windows),
+    ] {
+        let Some(fragment) = fragment else {
+            continue;
+        };
+        let sha256 = platform_support
+            .archives
+            .iter()
+            .find(|archive| archive.id == fragment.id)
+            .and_then(|archive| archive.checksum.as_ref())
+            .filter(|checksum| checksum.style == ChecksumStyle::Sha256.ext())
+            .map(|checksum| checksum.value.clone());
+        let files = fragment
+            .executables
+            .iter()
+            .map(|exe| WingetNestedFile {
+                path: exe.clone(),
+                alias: exe.strip_suffix(".exe").unwrap_or(exe).to_owned(),
+            })
+            .collect();
+        installers.push(WingetInstaller {
+            architecture,
+            url: format!("{base_url}/{}", fragment.id),
+            sha256,
+            files,
+        });
+    }
+
+    let inputs = WingetTemplateInputs {
+        info: info.clone(),
+        manifest_version: WINGET_MANIFEST_VERSION,
+        default_locale: WINGET_DEFAULT_LOCALE,
+        installers,
+    };
+    let contents = dist
+        .templates
+        .render_file_to_clean_string(template, &inputs)?;
+    LocalAsset::write_new(&contents, &info.inner.dest_path)?;
+    Ok(())
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WingetTemplateInputs {
+    #[serde(flatten)]
+    info: WingetInstallerInfo,
+    manifest_version: &'static str,
+    default_locale: &'static str,
+    installers: Vec<WingetInstaller>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WingetInstaller {
+    /// winget's name for the architecture (x64, x86, arm64)
+    architecture: &'static str,
+    url: String,
+    /// SHA256 sum of the archive. When building "just the installers", like
+    /// when running `dist build --artifacts global` locally, we don't have
+    /// the SHA256 for the archive, since we didn't actually build it.
+    sha256: Option<ChecksumValue>,
+    files: Vec<WingetNestedFile>,
+}
+
+#[derive(Debug, Clone, Serialize)]
+struct WingetNestedFile {
+    /// Path of the executable inside the archive
+    path: String,
+    /// The command the executable is exposed as
+    alias: String,
+}
+
+/// Produce a valid segment of a winget package identifier from an arbitrary name
+///
+/// Segments can't contain whitespace, dots, or path-ish characters, and are at most 32 characters.
+pub fn to_identifier_segment(name: &str) -> String {
+    name.chars()
+        .filter(|c| {
+            !c.is_whitespace()
+                && !c.is_control()
+                && !matches!(
+                    c,
+                    '.' | '\\' | '/' | ':' | '*' | '?' | '"' | '<' | '>' | '|'
+                )
+        })
+        .take(32)
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_identifier_segment() {
+        assert_eq!(to_identifier_segment("axodotdev"), "axodotdev");
+        assert_eq!(to_identifier_segment("Axo Developer Co."), "AxoDeveloperCo");
+        assert_eq!(to_identifier_segment("a/b:c"), "abc");
+        assert_eq!(to_identifier_segment(&"x".repeat(40)).len(), 32);
+    }
+
+    #[test]
+    fn test_manifest_file_names() {
+        let names = WingetManifestType::ALL.map(|ty| ty.file_name("axodotdev.axolotlsay"));
+        assert_eq!(
+            names,
+            [
+                "axodotdev.axolotlsay.yaml",
+                "axodotdev.axolotlsay.locale.en-US.yaml",
+                "axodotdev.axolotlsay.installer.yaml",
+            ]
+        );
+    }
+}
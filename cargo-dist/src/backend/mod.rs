@@ -9,6 +9,7 @@ use newline_converter::dos2unix;
 use crate::errors::{DistError, DistResult};
 
 pub mod ci;
+pub mod desktop;
 pub mod installer;
 pub mod templates;
 
@@ -6,7 +6,10 @@ use minijinja::Environment;
 use newline_converter::dos2unix;
 use serde::Serialize;
 
-use crate::{errors::DistResult, SortedMap};
+use crate::{
+    errors::{DistError, DistResult},
+    SortedMap,
+};
 
 const TEMPLATE_DIR: Dir<'_> = include_dir!("$CARGO_MANIFEST_DIR/templates");
 /// Key used for looking up templates (relative path from the templates dir)
@@ -25,8 +28,18 @@ pub const TEMPLATE_INSTALLER_NPM_RUN_JS: TemplateId = "installer/npm/run.js";
 pub const TEMPLATE_INSTALLER_NPM_PACKAGE_JSON: TemplateId = "installer/package.json";
 /// Template key for the npm-shrinkwrap.json
 pub const TEMPLATE_INSTALLER_NPM_SHRINKWRAP: TemplateId = "installer/npm-shrinkwrap.json";
+/// Template key for the winget version manifest
+pub const TEMPLATE_INSTALLER_WINGET_VERSION: TemplateId = "installer/winget/version.yaml";
+/// Template key for the winget defaultLocale manifest
+pub const TEMPLATE_INSTALLER_WINGET_LOCALE: TemplateId = "installer/winget/locale.yaml";
+/// Template key for the winget installer manifest
+pub const TEMPLATE_INSTALLER_WINGET_INSTALLER: TemplateId = "installer/winget/installer.yaml";
+/// Template key for the GitHub/GitLab Release body
+pub const TEMPLATE_ANNOUNCE_GITHUB: TemplateId = "announce/github.md";
 /// Template key for the github ci.yml
 pub const TEMPLATE_CI_GITHUB: TemplateId = "ci/github/release.yml";
+/// Template key for the .gitlab-ci.yml
+pub const TEMPLATE_CI_GITLAB: TemplateId = "ci/gitlab/release.yml";
 
 /// ID used to look up an environment in [`Templates::envs`][]
 type EnvId = &'static str;
@@ -271,6 +284,29 @@ impl Templates {
         Ok(cleaned)
     }
 
+    /// Render a user-provided jinja template (rather than one baked into the binary)
+    /// to a string, cleaning all newlines to be unix-y
+    ///
+    /// The template gets the same environment, functions, and tests as our own templates.
+    pub fn render_user_file_to_clean_string(
+        &self,
+        path: &Utf8Path,
+        val: &impl Serialize,
+    ) -> DistResult<String> {
+        let source = axoasset::LocalAsset::load_string(path)?;
+        let mut env = self.envs[ENV_MISC].clone();
+        let mut rendered = env
+            .add_template_owned(path.to_string(), source)
+            .and_then(|_| env.get_template(path.as_str())?.render(val))
+            .map_err(|e| DistError::user_jinja(path, e))?;
+        // minijinja strips trailing newlines from templates
+        if !rendered.ends_with('\n') {
+            rendered.push('\n');
+        }
+        let cleaned = dos2unix(&rendered).into_owned();
+        Ok(cleaned)
+    }
+
     /// Render all the templates under a directory to a string, cleaning all newlines to be unix-y
     ///
     /// The output is a map from relpath => rendered_text, where relpath is the path of the file relative
@@ -383,6 +419,9 @@ mod test {
         let templates = Templates::new().unwrap();
 
         templates.get_template_file(TEMPLATE_INSTALLER_SH).unwrap();
+        templates
+            .get_template_file(TEMPLATE_ANNOUNCE_GITHUB)
+            .unwrap();
         templates.get_template_file(TEMPLATE_INSTALLER_RB).unwrap();
         templates.get_template_file(TEMPLATE_INSTALLER_PS1).unwrap();
         templates.get_template_dir(TEMPLATE_INSTALLER_NPM).unwrap();
@@ -391,5 +430,6 @@ mod test {
             .unwrap();
 
         templates.get_template_file(TEMPLATE_CI_GITHUB).unwrap();
+        templates.get_template_file(TEMPLATE_CI_GITLAB).unwrap();
     }
 }
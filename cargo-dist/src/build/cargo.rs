@@ -1,10 +1,11 @@
 //! Functionality required to invoke `cargo build` properly
 
 use std::env;
+use std::sync::RwLock;
 
 use axoprocess::Cmd;
 use axoproject::WorkspaceIdx;
-use cargo_dist_schema::target_lexicon::{Architecture, Environment, Triple};
+use cargo_dist_schema::target_lexicon::{Architecture, Environment, OperatingSystem, Triple};
 use cargo_dist_schema::{DistManifest, TripleName};
 use miette::{Context, IntoDiagnostic};
 use tracing::warn;
@@ -59,6 +60,10 @@ impl<'a> DistGraphBuilder<'a> {
             if self.workspaces.workspace_for_package(binary.pkg_idx) != workspace_idx {
                 continue;
             }
+            // Universal binaries are made out of other binaries, not built
+            if !binary.slices.is_empty() {
+                continue;
+            }
             if !binary.copy_exe_to.is_empty() || !binary.copy_symbols_to.is_empty() {
                 targets
                     .entry(binary.target.clone())
@@ -104,6 +109,14 @@ impl<'a> DistGraphBuilder<'a> {
                 rustflags.push_str(" -Ctarget-feature=+crt-static -Clink-self-contained=yes");
             }
 
+            // Split debuginfo gets filed under the binary's build-id, so make sure it has one
+            // (most linux toolchains do this by default, but not all of them)
+            if self.inner.config.builds.split_debuginfo
+                && target.operating_system == OperatingSystem::Linux
+            {
+                rustflags.push_str(" -Clink-arg=-Wl,--build-id");
+            }
+
             let host = cargo.host_target.parse()?;
 
             // If we're trying to cross-compile, ensure the rustup toolchain is set up!
@@ -249,7 +262,7 @@ pub fn make_build_cargo_target_command(
 /// Build a cargo target
 pub fn build_cargo_target(
     dist_graph: &DistGraph,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
     step: &CargoBuildStep,
 ) -> DistResult<()> {
     let cargo = dist_graph.tools.cargo()?;
@@ -0,0 +1,179 @@
+//! Split debuginfo for ELF binaries
+//!
+//! Rust binaries carry their DWARF around with them, which makes them huge. If asked
+//! (`split-debuginfo = true`) we use objcopy to move it out into a `.debug` file that
+//! ships as a symbols artifact, leaving a `.gnu_debuglink` behind so debuggers can find it.
+//!
+//! If a `symbol-store` is configured, the `.debug` files (and the binaries they belong to)
+//! are also laid out by build-id the way debuginfod serves them:
+//!
+//! ```text
+//! <symbol-store>/buildid/<build-id>/debuginfo
+//! <symbol-store>/buildid/<build-id>/executable
+//! ```
+//!
+//! so the directory can be served as-is (or uploaded somewhere that is).
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use camino::{Utf8Path, Utf8PathBuf};
+use goblin::elf::{note::NT_GNU_BUILD_ID, Elf};
+use tracing::warn;
+
+use crate::{
+    copy_file,
+    tasks::{ArtifactKind, SymbolKind, Symbols},
+    Binary, DistGraph, DistResult,
+};
+
+/// Split the debuginfo out of the copies of a binary we're shipping, if it wants that
+///
+/// This must run after the binary has been copied to its final homes, as the binary
+/// in the build output is left alone (so rebuilds don't find it already stripped).
+pub fn split_debuginfo(dist: &DistGraph, binary: &Binary, fake: bool) -> DistResult<()> {
+    let Some(symbols) = binary.symbols_artifact else {
+        return Ok(());
+    };
+    let ArtifactKind::Symbols(Symbols {
+        kind: SymbolKind::Debug,
+    }) = dist.artifact(symbols).kind
+    else {
+        return Ok(());
+    };
+    let (Some(debug_path), Some(exe_path)) =
+        (binary.copy_symbols_to.first(), binary.copy_exe_to.first())
+    else {
+        return Ok(());
+    };
+
+    if fake {
+        for dest in &binary.copy_symbols_to {
+            LocalAsset::write_new_all("", dest)?;
+        }
+        return Ok(());
+    }
+
+    let objcopy = dist.tools.objcopy(&binary.target)?;
+    let mut cmd = Cmd::new(&objcopy.cmd, "extract debuginfo");
+    cmd.arg("--only-keep-debug").arg(exe_path).arg(debug_path);
+    cmd.run()?;
+    // The debuglink records the .debug's file name (and checksum), so it needs to be
+    // made from the file we actually ship
+    for exe in &binary.copy_exe_to {
+        let mut cmd = Cmd::new(&objcopy.cmd, "strip debuginfo");
+        cmd.arg("--strip-debug")
+            .arg(format!("--add-gnu-debuglink={debug_path}"))
+            .arg(exe);
+        cmd.run()?;
+    }
+    for dest in &binary.copy_symbols_to[1..] {
+        copy_file(debug_path, dest)?;
+    }
+
+    if let Some(store) = &dist.config.builds.symbol_store {
+        match build_id(exe_path)? {
+            Some(build_id) => {
+                let (debuginfo, executable) = store_paths(store, &build_id);
+                if let Some(dir) = debuginfo.parent() {
+                    LocalAsset::create_dir_all(dir)?;
+                }
+                copy_file(debug_path, &debuginfo)?;
+                copy_file(exe_path, &executable)?;
+            }
+            None => warn!(
+                "{} has no build-id, so it can't be added to the symbol store",
+                binary.name
+            ),
+        }
+    }
+
+    Ok(())
+}
+
+/// Get the GNU build-id of an ELF binary, as hex
+pub fn build_id(path: &Utf8Path) -> DistResult<Option<String>> {
+    let data = std::fs::read(path)?;
+    Ok(build_id_of(&data))
+}
+
+fn build_id_of(data: &[u8]) -> Option<String> {
+    let elf = Elf::parse(data).ok()?;
+    // Linked binaries should have it in a PT_NOTE, but look at the sections too just in case
+    let notes = elf
+        .iter_note_headers(data)
+        .into_iter()
+        .flatten()
+        .chain(elf.iter_note_sections(data, None).into_iter().flatten());
+    for note in notes.flatten() {
+        if note.n_type == NT_GNU_BUILD_ID && note.name == "GNU" && !note.desc.is_empty() {
+            return Some(note.desc.iter().map(|b| format!("{b:02x}")).collect());
+        }
+    }
+    None
+}
+
+/// Where debuginfod expects the debuginfo and executable with this build-id to be
+fn store_paths(store: &Utf8Path, build_id: &str) -> (Utf8PathBuf, Utf8PathBuf) {
+    let dir = store.join("buildid").join(build_id);
+    (dir.join("debuginfo"), dir.join("executable"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A minimal 64-bit ELF executable with one PT_NOTE segment holding a build-id
+    fn elf_with_build_id(build_id: &[u8]) -> Vec<u8> {
+        let note_len = 12 + 4 + build_id.len() as u64;
+        let mut out = b"\x7fELF\x02\x01\x01".to_vec();
+        out.resize(16, 0);
+        // e_type, e_machine, e_version, e_entry, e_phoff, e_shoff, e_flags
+        out.extend(2u16.to_le_bytes());
+        out.extend(62u16.to_le_bytes());
+        out.extend(1u32.to_le_bytes());
+        out.extend(0u64.to_le_bytes());
+        out.extend(64u64.to_le_bytes());
+        out.extend(0u64.to_le_bytes());
+        out.extend(0u32.to_le_bytes());
+        // e_ehsize, e_phentsize, e_phnum, e_shentsize, e_shnum, e_shstrndx
+        for field in [64u16, 56, 1, 64, 0, 0] {
+            out.extend(field.to_le_bytes());
+        }
+        // p_type (PT_NOTE), p_flags
+        out.extend(4u32.to_le_bytes());
+        out.extend(4u32.to_le_bytes());
+        // p_offset, p_vaddr, p_paddr, p_filesz, p_memsz, p_align
+        for field in [120u64, 0, 0, note_len, note_len, 4] {
+            out.extend(field.to_le_bytes());
+        }
+        // The note itself: namesz, descsz, type, name, desc
+        out.extend(4u32.to_le_bytes());
+        out.extend((build_id.len() as u32).to_le_bytes());
+        out.extend(NT_GNU_BUILD_ID.to_le_bytes());
+        out.extend(b"GNU\0");
+        out.extend(build_id);
+        out
+    }
+
+    #[test]
+    fn finds_build_id() {
+        let id = [0x6e, 0xc6, 0x42, 0x11, 0x77, 0x78, 0x06, 0x85];
+        assert_eq!(
+            build_id_of(&elf_with_build_id(&id)).as_deref(),
+            Some("6ec6421177780685")
+        );
+    }
+
+    #[test]
+    fn no_build_id() {
+        assert_eq!(build_id_of(&elf_with_build_id(&[])), None);
+        assert_eq!(build_id_of(b"not an elf at all"), None);
+    }
+
+    #[test]
+    fn debuginfod_layout() {
+        let (debuginfo, executable) = store_paths(Utf8Path::new("symbols"), "6ec64211");
+        assert_eq!(debuginfo, "symbols/buildid/6ec64211/debuginfo");
+        assert_eq!(executable, "symbols/buildid/6ec64211/executable");
+    }
+}
@@ -3,11 +3,13 @@
 //! used by --artifacts=lies to reproduce as much of our builds as possible
 //! without needing to actually run platform-specific builds
 
+use std::sync::RwLock;
+
 use axoasset::LocalAsset;
 use camino::Utf8PathBuf;
 use cargo_dist_schema::DistManifest;
 
-use crate::{BinaryIdx, CargoBuildStep, DistGraph, DistResult, GenericBuildStep};
+use crate::{BinaryIdx, CargoBuildStep, DistGraph, DistResult, GenericBuildStep, LipoStep};
 
 use super::BuildExpectations;
 
@@ -16,7 +18,7 @@ use super::BuildExpectations;
 /// This produces empty binaries but otherwise emulates the build process as much as possible.
 pub fn build_fake_cargo_target(
     dist: &DistGraph,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
     target: &CargoBuildStep,
 ) -> DistResult<()> {
     build_fake_binaries(dist, manifest, &target.expected_binaries)
@@ -27,16 +29,27 @@ pub fn build_fake_cargo_target(
 /// This produces empty binaries but otherwise emulates the build process as much as possible.
 pub fn build_fake_generic_target(
     dist: &DistGraph,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
     target: &GenericBuildStep,
 ) -> DistResult<()> {
     build_fake_binaries(dist, manifest, &target.expected_binaries)
 }
 
+/// build a fake universal binary
+///
+/// This produces an empty binary but otherwise emulates the build process as much as possible.
+pub fn build_fake_universal_binary(
+    dist: &DistGraph,
+    manifest: &RwLock<DistManifest>,
+    step: &LipoStep,
+) -> DistResult<()> {
+    build_fake_binaries(dist, manifest, &[step.binary])
+}
+
 /// build fake binaries, and emulate the build process as much as possible
 fn build_fake_binaries(
     dist: &DistGraph,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
     binaries: &[BinaryIdx],
 ) -> DistResult<()> {
     // Shove these in a temp dir inside the dist dir, where it's safe for us to do whatever
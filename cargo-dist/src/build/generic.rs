@@ -1,6 +1,6 @@
 //! Functionality required to invoke a generic build's `build-command`
 
-use std::{env, process::ExitStatus};
+use std::{env, process::ExitStatus, sync::RwLock};
 
 use axoprocess::Cmd;
 use axoproject::WorkspaceIdx;
@@ -28,6 +28,10 @@ impl<'a> DistGraphBuilder<'a> {
             if self.workspaces.workspace_for_package(binary.pkg_idx) != workspace_idx {
                 continue;
             }
+            // Universal binaries are made out of other binaries, not built
+            if !binary.slices.is_empty() {
+                continue;
+            }
             if !binary.copy_exe_to.is_empty() || !binary.copy_symbols_to.is_empty() {
                 targets
                     .entry(binary.target.clone())
@@ -211,7 +215,7 @@ fn run_build(
 /// Build a generic targets
 pub fn build_generic_target(
     dist_graph: &DistGraph,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
     target: &GenericBuildStep,
 ) -> DistResult<()> {
     eprintln!(
@@ -0,0 +1,440 @@
+//! Running build steps as a dependency graph
+//!
+//! Every [`BuildStep`][] declares what it reads and writes: files and directories, but
+//! also the cargo toolchain (which has lots of global state, like the target dir) and
+//! the [`DistManifest`][cargo_dist_schema::DistManifest] we're filling in. A step
+//! depends on every earlier step it conflicts with, which is exactly the ordering the
+//! old one-step-at-a-time loop guaranteed, so any schedule that respects those edges
+//! produces the same results.
+//!
+//! Notably every step that invokes cargo (or a generic/extra build, which may well invoke
+//! cargo) writes the toolchain, so those are always run one at a time, in order.
+
+use std::collections::BTreeSet;
+use std::sync::{Condvar, Mutex};
+
+use camino::{Utf8Path, Utf8PathBuf};
+
+use crate::{
+    backend::installer::{HomebrewImpl, InstallerImpl, ScoopImpl, WingetImpl},
+    errors::DistError,
+    tasks::{
+        ChecksumImpl, CopyStep, DesktopEntryStep, OmniborArtifactIdImpl, SignatureImpl,
+        SourceTarballStep, UnifiedChecksumStep, ZipDirStep,
+    },
+    BuildStep, DistGraph, DistResult,
+};
+
+/// Something a build step can touch
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum Resource {
+    /// cargo and friends, which can't safely run concurrently with themselves
+    Toolchain,
+    /// The DistManifest we're building up
+    Manifest,
+    /// A file or directory (and everything under it)
+    Path(Utf8PathBuf),
+}
+
+impl Resource {
+    fn overlaps(&self, other: &Resource) -> bool {
+        match (self, other) {
+            (Resource::Path(a), Resource::Path(b)) => a.starts_with(b) || b.starts_with(a),
+            (a, b) => a == b,
+        }
+    }
+}
+
+/// How a build step touches a [`Resource`][]
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Access {
+    /// Only looks at it
+    Read,
+    /// Modifies it in a way that commutes with other updates
+    /// (e.g. inserting an entry in the manifest keyed by artifact id)
+    Update,
+    /// Modifies it
+    Write,
+}
+
+type StepAccess = Vec<(Resource, Access)>;
+
+fn conflicts(a: &StepAccess, b: &StepAccess) -> bool {
+    a.iter().any(|(res_a, access_a)| {
+        b.iter().any(|(res_b, access_b)| {
+            let compatible = matches!(
+                (access_a, access_b),
+                (Access::Read, Access::Read) | (Access::Update, Access::Update)
+            );
+            !compatible && res_a.overlaps(res_b)
+        })
+    })
+}
+
+/// Work out what a build step touches
+fn step_access(dist: &DistGraph, step: &BuildStep) -> StepAccess {
+    use Access::*;
+    let read = |path: &Utf8Path| (Resource::Path(path.to_owned()), Read);
+    let write = |path: &Utf8Path| (Resource::Path(path.to_owned()), Write);
+    let binary_outputs = |binaries: &[crate::BinaryIdx]| {
+        binaries
+            .iter()
+            .flat_map(|&idx| {
+                let binary = dist.binary(idx);
+                binary.copy_exe_to.iter().chain(&binary.copy_symbols_to)
+            })
+            .map(|path| write(path))
+            .collect::<Vec<_>>()
+    };
+
+    let mut access = vec![];
+    match step {
+        BuildStep::Generic(step) => {
+            access.push((Resource::Toolchain, Write));
+            access.push((Resource::Manifest, Update));
+            access.push(write(&step.out_dir));
+            access.extend(binary_outputs(&step.expected_binaries));
+        }
+        BuildStep::Cargo(step) => {
+            access.push((Resource::Toolchain, Write));
+            access.push((Resource::Manifest, Update));
+            access.extend(binary_outputs(&step.expected_binaries));
+        }
+        BuildStep::Extra(step) => {
+            access.push((Resource::Toolchain, Write));
+            for relpath in &step.artifact_relpaths {
+                access.push(write(&dist.dist_dir.join(relpath)));
+                if let Some(name) = relpath.file_name() {
+                    access.push(write(&dist.dist_dir.join(name)));
+                }
+            }
+        }
+        BuildStep::Lipo(step) => {
+            access.push((Resource::Manifest, Update));
+            access.extend(step.src_paths.iter().map(|path| read(path)));
+            access.push(write(&step.dest_path));
+            access.extend(binary_outputs(&[step.binary]));
+        }
+        BuildStep::Rustup(_) => access.push((Resource::Toolchain, Write)),
+        BuildStep::CopyFile(CopyStep {
+            src_path,
+            dest_path,
+        })
+        | BuildStep::CopyDir(CopyStep {
+            src_path,
+            dest_path,
+        })
+        | BuildStep::CopyFileOrDir(CopyStep {
+            src_path,
+            dest_path,
+        })
+        | BuildStep::Zip(ZipDirStep {
+            src_path,
+            dest_path,
+            ..
+        })
+        | BuildStep::Signature(SignatureImpl {
+            src_path,
+            dest_path,
+        })
+        | BuildStep::OmniborArtifactId(OmniborArtifactIdImpl {
+            src_path,
+            dest_path,
+        }) => {
+            access.push(read(src_path));
+            access.push(write(dest_path));
+        }
+        BuildStep::DesktopEntry(DesktopEntryStep { dest_dir, .. }) => access.push(write(dest_dir)),
+        BuildStep::GenerateInstaller(installer) => {
+            access.push((Resource::Manifest, Read));
+            match installer {
+                InstallerImpl::Shell(info) | InstallerImpl::Powershell(info) => {
+                    access.push(write(&info.dest_path))
+                }
+                InstallerImpl::Npm(info) => {
+                    access.push(write(&info.package_dir));
+                    access.push(write(&info.inner.dest_path));
+                }
+                InstallerImpl::Homebrew(HomebrewImpl { info, .. }) => {
+                    access.push(write(&info.inner.dest_path))
+                }
+                InstallerImpl::Scoop(ScoopImpl { info, .. }) => {
+                    access.push(write(&info.inner.dest_path))
+                }
+                InstallerImpl::Winget(WingetImpl { info, .. }) => {
+                    access.push(write(&info.inner.dest_path))
+                }
+                InstallerImpl::Msi(info) => {
+                    // cargo-wix picks the binaries up out of the target dir
+                    access.push((Resource::Toolchain, Read));
+                    access.push(read(&info.package_dir));
+                    access.push(read(&info.wxs_path));
+                    access.push(write(&info.file_path));
+                }
+                InstallerImpl::Pkg(info) => {
+                    access.push(read(&info.package_dir));
+                    access.push(write(&info.file_path));
+                }
+                InstallerImpl::Deb(info) => {
+                    access.push(read(&info.package_dir));
+                    access.push(write(&info.file_path));
+                }
+                InstallerImpl::Rpm(info) => {
+                    access.push(read(&info.package_dir));
+                    access.push(write(&info.file_path));
+                }
+            }
+        }
+        BuildStep::GenerateSourceTarball(SourceTarballStep { target, .. }) => {
+            access.push(write(target))
+        }
+        BuildStep::Checksum(ChecksumImpl {
+            src_path,
+            dest_path,
+            for_artifact,
+            ..
+        }) => {
+            access.push(read(src_path));
+            if let Some(dest_path) = dest_path {
+                access.push(write(dest_path));
+            }
+            if for_artifact.is_some() {
+                access.push((Resource::Manifest, Update));
+            }
+        }
+        BuildStep::UnifiedChecksum(UnifiedChecksumStep { dest_path, .. }) => {
+            access.push((Resource::Manifest, Read));
+            access.push(write(dest_path));
+        }
+        BuildStep::Provenance(provenance) => {
+            // Provenance describes (and may hash) everything else in the release
+            access.push((Resource::Manifest, Read));
+            access.push(read(&dist.dist_dir));
+            access.push(write(&provenance.dest_path));
+        }
+        BuildStep::Updater(updater) => {
+            access.push(write(&dist.target_dir.join(&updater.target_filename)))
+        }
+    }
+    access
+}
+
+/// The dependencies between a list of build steps
+pub struct BuildGraph {
+    /// For each step, the earlier steps it has to wait for
+    deps: Vec<Vec<usize>>,
+}
+
+impl BuildGraph {
+    /// Compute the dependencies between these steps (which are in the order
+    /// they would be run serially)
+    pub fn new(dist: &DistGraph, steps: &[&BuildStep]) -> Self {
+        let accesses = steps
+            .iter()
+            .map(|step| step_access(dist, step))
+            .collect::<Vec<_>>();
+        Self::from_accesses(&accesses)
+    }
+
+    fn from_accesses(accesses: &[StepAccess]) -> Self {
+        let deps = accesses
+            .iter()
+            .enumerate()
+            .map(|(idx, access)| {
+                accesses[..idx]
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, earlier)| conflicts(earlier, access))
+                    .map(|(dep_idx, _)| dep_idx)
+                    .collect()
+            })
+            .collect();
+        Self { deps }
+    }
+
+    /// The earlier steps that step `idx` has to wait for
+    pub fn deps(&self, idx: usize) -> &[usize] {
+        &self.deps[idx]
+    }
+
+    /// Run every step, at most `jobs` at a time
+    ///
+    /// With a single job this is just the steps in order. Otherwise, whenever a worker
+    /// is free it picks the earliest step whose dependencies are all done. If a step fails
+    /// no new steps are started, and the error of the earliest failed step is returned.
+    pub fn run(
+        &self,
+        jobs: usize,
+        run_step: impl Fn(usize) -> DistResult<()> + Sync,
+    ) -> DistResult<()> {
+        let num_steps = self.deps.len();
+        if jobs <= 1 || num_steps <= 1 {
+            return (0..num_steps).try_for_each(run_step);
+        }
+
+        struct State {
+            ready: BTreeSet<usize>,
+            waiting_on: Vec<usize>,
+            remaining: usize,
+            failed: Option<(usize, DistError)>,
+        }
+
+        let mut dependents = vec![vec![]; num_steps];
+        for (idx, deps) in self.deps.iter().enumerate() {
+            for &dep in deps {
+                dependents[dep].push(idx);
+            }
+        }
+        let waiting_on = self.deps.iter().map(Vec::len).collect::<Vec<_>>();
+        let ready = (0..num_steps).filter(|&idx| waiting_on[idx] == 0).collect();
+        let state = Mutex::new(State {
+            ready,
+            waiting_on,
+            remaining: num_steps,
+            failed: None,
+        });
+        let wakeup = Condvar::new();
+        // Steps may need to block on async work, so let the workers see our runtime
+        let runtime = tokio::runtime::Handle::try_current().ok();
+
+        std::thread::scope(|scope| {
+            for _ in 0..jobs.min(num_steps) {
+                let (state, wakeup, dependents) = (&state, &wakeup, &dependents);
+                let (runtime, run_step) = (&runtime, &run_step);
+                scope.spawn(move || {
+                    let _runtime = runtime.as_ref().map(|runtime| runtime.enter());
+                    loop {
+                        let idx = {
+                            let mut state = state.lock().unwrap();
+                            loop {
+                                if state.failed.is_some() || state.remaining == 0 {
+                                    return;
+                                }
+                                if let Some(idx) = state.ready.pop_first() {
+                                    break idx;
+                                }
+                                state = wakeup.wait(state).unwrap();
+                            }
+                        };
+
+                        let result = run_step(idx);
+
+                        let mut state = state.lock().unwrap();
+                        state.remaining -= 1;
+                        match result {
+                            Ok(()) => {
+                                for &dependent in &dependents[idx] {
+                                    state.waiting_on[dependent] -= 1;
+                                    if state.waiting_on[dependent] == 0 {
+                                        state.ready.insert(dependent);
+                                    }
+                                }
+                            }
+                            Err(e) => {
+                                if state.failed.as_ref().map_or(true, |(prev, _)| idx < *prev) {
+                                    state.failed = Some((idx, e));
+                                }
+                            }
+                        }
+                        wakeup.notify_all();
+                    }
+                });
+            }
+        });
+
+        match state.into_inner().unwrap().failed {
+            Some((_, e)) => Err(e),
+            None => Ok(()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn path(path: &str) -> Resource {
+        Resource::Path(Utf8PathBuf::from(path))
+    }
+
+    #[test]
+    fn deps_follow_hazards() {
+        use Access::*;
+        let graph = BuildGraph::from_accesses(&[
+            // 0: build a
+            vec![(Resource::Toolchain, Write), (path("/dist/a/app"), Write)],
+            // 1: build b
+            vec![(Resource::Toolchain, Write), (path("/dist/b/app"), Write)],
+            // 2: zip a
+            vec![(path("/dist/a"), Read), (path("/dist/a.tar.xz"), Write)],
+            // 3: checksum a
+            vec![(path("/dist/a.tar.xz"), Read), (Resource::Manifest, Update)],
+            // 4: zip b
+            vec![(path("/dist/b"), Read), (path("/dist/b.tar.xz"), Write)],
+            // 5: checksum b
+            vec![(path("/dist/b.tar.xz"), Read), (Resource::Manifest, Update)],
+            // 6: installer
+            vec![
+                (Resource::Manifest, Read),
+                (path("/dist/installer.sh"), Write),
+            ],
+        ]);
+
+        // cargo invocations stay in order
+        assert_eq!(graph.deps(1), [0]);
+        // archives only wait for their own build
+        assert_eq!(graph.deps(2), [0]);
+        assert_eq!(graph.deps(4), [1]);
+        // checksums don't wait on each other
+        assert_eq!(graph.deps(3), [2]);
+        assert_eq!(graph.deps(5), [4]);
+        // but anything reading the manifest waits for them
+        assert_eq!(graph.deps(6), [3, 5]);
+    }
+
+    #[test]
+    fn runs_in_dependency_order() {
+        use Access::*;
+        let accesses = (0..20)
+            .map(|idx| {
+                vec![
+                    (path(&format!("/dist/{}", idx / 4)), Write),
+                    (path(&format!("/dist/{}", idx / 4 + 1)), Read),
+                ]
+            })
+            .collect::<Vec<_>>();
+        let graph = BuildGraph::from_accesses(&accesses);
+
+        let finished = Mutex::new(vec![]);
+        graph
+            .run(4, |idx| {
+                let mut finished = finished.lock().unwrap();
+                for dep in graph.deps(idx) {
+                    assert!(finished.contains(dep), "{idx} ran before {dep}");
+                }
+                finished.push(idx);
+                Ok(())
+            })
+            .unwrap();
+        assert_eq!(finished.into_inner().unwrap().len(), 20);
+    }
+
+    #[test]
+    fn reports_earliest_failure() {
+        let accesses = vec![vec![]; 8];
+        let graph = BuildGraph::from_accesses(&accesses);
+
+        let err = graph
+            .run(3, |idx| {
+                if idx >= 2 {
+                    Err(DistError::ToolMissing {
+                        tool: format!("step {idx}"),
+                    })
+                } else {
+                    Ok(())
+                }
+            })
+            .unwrap_err();
+        assert!(matches!(err, DistError::ToolMissing { tool } if tool == "step 2"));
+    }
+}
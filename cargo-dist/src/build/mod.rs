@@ -1,18 +1,25 @@
 //! Compiling Things
 
+use std::sync::{RwLock, RwLockReadGuard, RwLockWriteGuard};
+
 use axoproject::PackageId;
 use camino::Utf8PathBuf;
 use cargo_dist_schema::{AssetInfo, DistManifest, TripleNameRef};
-use tracing::info;
+use tracing::{info, warn};
 
 use crate::{
-    copy_file, linkage::determine_linkage, Binary, BinaryIdx, BinaryKind, DistError, DistGraph,
-    DistResult, SortedMap,
+    copy_file,
+    linkage::{check_glibc_ceiling, determine_linkage, try_determine_linkage},
+    platform::min_glibc_version_for_target,
+    Binary, BinaryIdx, BinaryKind, DistError, DistGraph, DistResult, SortedMap,
 };
 
 pub mod cargo;
+pub mod debuginfo;
 pub mod fake;
 pub mod generic;
+pub mod graph;
+pub mod universal;
 
 /// Output expectations for builds, and computed facts (all packages)
 pub struct BuildExpectations {
@@ -97,6 +104,7 @@ impl BuildExpectations {
         let (maybe_symbols, maybe_bins): (Vec<_>, Vec<_>) = filenames
             .into_iter()
             // FIXME: unhardcode this when we add support for other symbol kinds!
+            // (split ELF debuginfo doesn't come out of the build, it's made after it in process_bins)
             .partition(|f| f.extension().map(|e| e == "pdb").unwrap_or(false));
 
         // lookup the package
@@ -152,12 +160,20 @@ impl BuildExpectations {
     /// * checking src_path was set by found_bin
     /// * computing linkage for the binary
     /// * copying the binary and symbols to their final homes
+    /// * splitting out debuginfo (for ELF binaries, if enabled)
     ///
     /// In the future this may also include:
     ///
     /// * code signing / hashing
     /// * stripping
-    pub fn process_bins(&self, dist: &DistGraph, manifest: &mut DistManifest) -> DistResult<()> {
+    ///
+    /// Build steps run in parallel, so the manifest is only locked to record each
+    /// binary's linkage once everything else about it is done.
+    pub fn process_bins(
+        &self,
+        dist: &DistGraph,
+        manifest: &RwLock<DistManifest>,
+    ) -> DistResult<()> {
         let mut missing = vec![];
         for (pkg_id, pkg) in &self.packages {
             for (bin_name, result_bin) in &pkg.binaries {
@@ -173,10 +189,17 @@ impl BuildExpectations {
                 let bin = dist.binary(result_bin.idx);
 
                 // compute linkage for the binary
-                self.compute_linkage_and_sign(dist, manifest, result_bin, &bin.target)?;
+                let asset = self.compute_linkage_and_sign(dist, result_bin, &bin.target)?;
 
                 // copy files to their final homes
                 self.copy_assets(result_bin, bin)?;
+
+                // split out the debuginfo of what we just copied, if requested
+                debuginfo::split_debuginfo(dist, bin, self.fake)?;
+
+                write_manifest(manifest)?
+                    .assets
+                    .insert(asset.id.clone(), asset);
             }
         }
 
@@ -193,10 +216,9 @@ impl BuildExpectations {
     fn compute_linkage_and_sign(
         &self,
         dist: &DistGraph,
-        manifest: &mut DistManifest,
         src: &ExpectedBinary,
         target: &TripleNameRef,
-    ) -> DistResult<()> {
+    ) -> DistResult<AssetInfo> {
         let src_path = src
             .src_path
             .as_ref()
@@ -204,6 +226,30 @@ impl BuildExpectations {
 
         dist.signer.sign(src_path)?;
 
+        let bin = dist.binary(src.idx);
+        // If asked, we make sure the binary doesn't need a newer glibc than we advertise
+        let glibc_ceiling = if !self.fake
+            && dist.config.builds.enforce_min_glibc_version
+            && target.is_linux()
+            && !target.is_linux_musl()
+        {
+            let ceiling = dist
+                .config
+                .builds
+                .min_glibc_version
+                .as_ref()
+                .and_then(|vmap| min_glibc_version_for_target(vmap, target));
+            if ceiling.is_none() {
+                warn!(
+                    "enforce-min-glibc-version is set, but there's no min-glibc-version for {target}, so {} won't be checked",
+                    bin.name
+                );
+            }
+            ceiling
+        } else {
+            None
+        };
+
         // If we're faking it, don't run the linkage stuff
         let linkage = if self.fake {
             // FIXME: fake this more interestingly!
@@ -214,22 +260,23 @@ impl BuildExpectations {
                 package_manager: None,
             });
             linkage
+        } else if let Some(ceiling) = glibc_ceiling {
+            // The gate is only as good as the linkage, so a binary we failed to
+            // read is an error rather than a pass
+            let linkage = try_determine_linkage(src_path, target)?;
+            check_glibc_ceiling(&bin.name, target, &linkage, ceiling)?;
+            linkage
         } else {
             determine_linkage(src_path, target)
         };
 
-        let bin = dist.binary(src.idx);
-        manifest.assets.insert(
-            bin.id.clone(),
-            AssetInfo {
-                id: bin.id.clone(),
-                name: bin.name.clone(),
-                system: dist.system_id.clone(),
-                linkage: Some(linkage),
-                target_triples: vec![target.to_owned()],
-            },
-        );
-        Ok(())
+        Ok(AssetInfo {
+            id: bin.id.clone(),
+            name: bin.name.clone(),
+            system: dist.system_id.clone(),
+            linkage: Some(linkage),
+            target_triples: vec![target.to_owned()],
+        })
     }
 
     // Copy the assets for this binary
@@ -254,6 +301,47 @@ impl BuildExpectations {
     }
 }
 
+/// Lock the manifest to read it, failing if a build step panicked while writing it
+pub(crate) fn read_manifest(
+    manifest: &RwLock<DistManifest>,
+) -> DistResult<RwLockReadGuard<'_, DistManifest>> {
+    manifest.read().map_err(|_| DistError::ManifestPoisoned)
+}
+
+/// Lock the manifest to update it, failing if a build step panicked while writing it
+pub(crate) fn write_manifest(
+    manifest: &RwLock<DistManifest>,
+) -> DistResult<RwLockWriteGuard<'_, DistManifest>> {
+    manifest.write().map_err(|_| DistError::ManifestPoisoned)
+}
+
 fn package_id_string(id: Option<&PackageId>) -> String {
     id.map(ToString::to_string).unwrap_or_default()
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn poisoned_manifest_is_an_error() {
+        let manifest = RwLock::new(DistManifest::new(vec![], Default::default()));
+        assert!(read_manifest(&manifest).is_ok());
+        let _ = std::thread::scope(|scope| {
+            scope
+                .spawn(|| {
+                    let _guard = write_manifest(&manifest).unwrap();
+                    panic!("a build step blew up");
+                })
+                .join()
+        });
+        assert!(matches!(
+            read_manifest(&manifest),
+            Err(DistError::ManifestPoisoned)
+        ));
+        assert!(matches!(
+            write_manifest(&manifest),
+            Err(DistError::ManifestPoisoned)
+        ));
+    }
+}
@@ -0,0 +1,213 @@
+//! Universal (fat) macOS binaries
+//!
+//! A universal binary is just several single-architecture Mach-O binaries stapled
+//! together behind a small header that says which architecture lives where. Apple's
+//! `lipo` makes these, but it only exists on macOS, so we write them ourselves
+//! (which also means this can all be tested anywhere).
+
+use std::sync::RwLock;
+
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::DistManifest;
+use goblin::mach::Mach;
+
+use super::{package_id_string, BuildExpectations};
+use crate::{errors::*, DistGraph, LipoStep};
+
+/// The magic number of a fat binary (stored big-endian, unlike everything else in Mach-O)
+const FAT_MAGIC: u32 = 0xcafe_babe;
+/// CPU_TYPE_ARM64
+const CPU_TYPE_ARM64: u32 = 0x0100_000c;
+/// The capability bits of a cpusubtype, which don't belong in the fat header
+const CPU_SUBTYPE_MASK: u32 = 0xff00_0000;
+
+/// Merge the slices of a universal binary, and then process it like any other built binary
+pub fn build_universal_binary(
+    dist: &DistGraph,
+    manifest: &RwLock<DistManifest>,
+    step: &LipoStep,
+) -> DistResult<()> {
+    let binary = dist.binary(step.binary);
+    eprintln!("building universal binary {}", binary.id);
+
+    lipo(&step.src_paths, &step.dest_path)?;
+
+    let mut expected = BuildExpectations::new(dist, &[step.binary]);
+    expected.found_bins(
+        package_id_string(binary.pkg_id.as_ref()),
+        vec![step.dest_path.clone()],
+    );
+    expected.process_bins(dist, manifest)?;
+
+    Ok(())
+}
+
+/// Merge Mach-O binaries into one universal binary at dest_path
+pub fn lipo(src_paths: &[Utf8PathBuf], dest_path: &Utf8Path) -> DistResult<()> {
+    let slices = src_paths
+        .iter()
+        .map(|path| Ok((path.as_path(), std::fs::read(path)?)))
+        .collect::<DistResult<Vec<_>>>()?;
+    let fat = fat_binary(&slices)?;
+
+    if let Some(parent) = dest_path.parent() {
+        std::fs::create_dir_all(parent)?;
+    }
+    std::fs::write(dest_path, fat)?;
+    // The slices are all executable (or not) in the same way, so take the first's permissions
+    if let Some(first) = src_paths.first() {
+        std::fs::set_permissions(dest_path, std::fs::metadata(first)?.permissions())?;
+    }
+
+    Ok(())
+}
+
+/// One architecture's worth of a universal binary
+struct Slice<'a> {
+    cputype: u32,
+    cpusubtype: u32,
+    /// log2 of the alignment this slice needs in the file
+    align: u32,
+    data: &'a [u8],
+}
+
+/// Lay out a fat binary containing every architecture of the given binaries
+/// (which can themselves be thin or fat)
+fn fat_binary(binaries: &[(&Utf8Path, Vec<u8>)]) -> DistResult<Vec<u8>> {
+    let mut slices: Vec<Slice> = vec![];
+    for (path, data) in binaries {
+        let not_macho = || DistError::NotMachO {
+            path: path.to_path_buf(),
+        };
+        let mut add = |cputype: u32, cpusubtype: u32, data| {
+            let cpusubtype = cpusubtype & !CPU_SUBTYPE_MASK;
+            if slices
+                .iter()
+                .any(|s| s.cputype == cputype && s.cpusubtype == cpusubtype)
+            {
+                return Err(DistError::DuplicateMachOArch {
+                    path: path.to_path_buf(),
+                });
+            }
+            // Match lipo: arm64 wants 16K pages, everything else we care about is fine with 4K
+            let align = if cputype == CPU_TYPE_ARM64 { 14 } else { 12 };
+            slices.push(Slice {
+                cputype,
+                cpusubtype,
+                align,
+                data,
+            });
+            Ok(())
+        };
+        match Mach::parse(data).map_err(|_| not_macho())? {
+            Mach::Binary(macho) => add(macho.header.cputype, macho.header.cpusubtype, data)?,
+            Mach::Fat(fat) => {
+                for arch in fat.iter_arches() {
+                    let arch = arch?;
+                    let slice = arch.slice(data);
+                    if slice.is_empty() {
+                        return Err(not_macho());
+                    }
+                    add(arch.cputype, arch.cpusubtype, slice)?;
+                }
+            }
+        }
+    }
+    // Keep the output stable regardless of the order we were given things in
+    slices.sort_by_key(|s| (s.cputype, s.cpusubtype));
+
+    // The header: magic, count, then (cputype, cpusubtype, offset, size, align) per slice
+    let header_len = 8 + 20 * slices.len();
+    let mut out = Vec::with_capacity(header_len);
+    out.extend(FAT_MAGIC.to_be_bytes());
+    out.extend((slices.len() as u32).to_be_bytes());
+    let mut offset = header_len;
+    let mut offsets = vec![];
+    for slice in &slices {
+        offset = offset.next_multiple_of(1 << slice.align);
+        offsets.push(offset);
+        for field in [
+            slice.cputype,
+            slice.cpusubtype,
+            offset as u32,
+            slice.data.len() as u32,
+            slice.align,
+        ] {
+            out.extend(field.to_be_bytes());
+        }
+        offset += slice.data.len();
+    }
+    for (slice, offset) in slices.iter().zip(offsets) {
+        out.resize(offset, 0);
+        out.extend(slice.data);
+    }
+
+    Ok(out)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CPU_TYPE_X86_64: u32 = 0x0100_0007;
+
+    /// A minimal 64-bit Mach-O executable with no load commands
+    fn thin_macho(cputype: u32, cpusubtype: u32, payload: &[u8]) -> Vec<u8> {
+        let mut out = vec![];
+        for field in [0xfeed_facf, cputype, cpusubtype, 2, 0, 0, 0, 0] {
+            out.extend(u32::to_le_bytes(field));
+        }
+        out.extend(payload);
+        out
+    }
+
+    #[test]
+    fn merges_slices() {
+        let arm64 = thin_macho(CPU_TYPE_ARM64, 0, b"arm64 code");
+        // x86_64 executables set CPU_SUBTYPE_LIB64 in their header
+        let x86_64 = thin_macho(CPU_TYPE_X86_64, 0x8000_0003, b"x86_64 code");
+        let fat = fat_binary(&[
+            (Utf8Path::new("arm64"), arm64.clone()),
+            (Utf8Path::new("x86_64"), x86_64.clone()),
+        ])
+        .unwrap();
+
+        let Mach::Fat(parsed) = Mach::parse(&fat).unwrap() else {
+            panic!("didn't make a fat binary");
+        };
+        let arches = parsed.arches().unwrap();
+        assert_eq!(arches.len(), 2);
+
+        assert_eq!(arches[0].cputype, CPU_TYPE_X86_64);
+        assert_eq!(arches[0].cpusubtype, 3);
+        assert_eq!(arches[0].offset % (1 << 12), 0);
+        assert_eq!(arches[0].slice(&fat), &x86_64[..]);
+
+        assert_eq!(arches[1].cputype, CPU_TYPE_ARM64);
+        assert_eq!(arches[1].offset % (1 << 14), 0);
+        assert_eq!(arches[1].slice(&fat), &arm64[..]);
+
+        // Merging a fat binary again gives the same result
+        let again = fat_binary(&[(Utf8Path::new("fat"), fat.clone())]).unwrap();
+        assert_eq!(again, fat);
+    }
+
+    #[test]
+    fn rejects_duplicate_arches() {
+        let arm64 = thin_macho(CPU_TYPE_ARM64, 0, b"one");
+        let arm64_again = thin_macho(CPU_TYPE_ARM64, 0, b"two");
+        let err = fat_binary(&[
+            (Utf8Path::new("one"), arm64),
+            (Utf8Path::new("two"), arm64_again),
+        ])
+        .unwrap_err();
+        assert!(matches!(err, DistError::DuplicateMachOArch { path } if path == "two"));
+    }
+
+    #[test]
+    fn rejects_non_macho() {
+        let err =
+            fat_binary(&[(Utf8Path::new("elf"), b"\x7fELF\x02\x01\x01".to_vec())]).unwrap_err();
+        assert!(matches!(err, DistError::NotMachO { path } if path == "elf"));
+    }
+}
@@ -180,6 +180,22 @@ pub enum Commands {
     #[clap(disable_version_flag = true)]
     Host(HostArgs),
 
+    /// Check downloaded release artifacts against a dist-manifest.json
+    ///
+    /// Every artifact in the manifest that has a checksum is hashed again and
+    /// compared, as are the entries of any unified checksum file (like sha256.sum)
+    /// in the directory. Missing, extra, and mismatched files are all reported.
+    #[clap(disable_version_flag = true)]
+    Verify(VerifyArgs),
+
+    /// Publish built artifacts to package managers
+    ///
+    /// Reads the dist-manifest.json next to the artifacts, and publishes each
+    /// package to the Homebrew tap and npm registry it's configured for. Releases
+    /// that are prereleases are skipped unless that package opts into publishing them.
+    #[clap(disable_version_flag = true)]
+    Publish(PublishArgs),
+
     /// Performs a self-update, if a new version is available, and then 'init'
     #[clap(disable_version_flag = true)]
     Selfupdate(UpdateArgs),
@@ -214,6 +230,16 @@ pub struct BuildArgs {
     /// * linkage: prints information on dynamic libraries used by build artifacts
     #[clap(long, short, value_delimiter(','))]
     pub print: Vec<String>,
+
+    /// How many build steps to run at once
+    ///
+    /// Build steps (zipping, checksumming, generating installers...) are run as a
+    /// dependency graph, so with more than one job they can happen in parallel as
+    /// soon as whatever they need is ready. Cargo invocations are always run one at
+    /// a time, and the results are the same no matter how many jobs are used.
+    #[clap(long, short)]
+    #[clap(default_value = "1")]
+    pub jobs: std::num::NonZeroUsize,
 }
 
 /// How we should select the artifacts to build
@@ -387,6 +413,8 @@ pub struct UpdateArgs {
 pub enum CiStyle {
     /// Generate github CI that uploads to github releases
     Github,
+    /// Generate gitlab CI
+    Gitlab,
 }
 
 impl CiStyle {
@@ -394,6 +422,7 @@ impl CiStyle {
     pub fn to_lib(self) -> cargo_dist::config::CiStyle {
         match self {
             CiStyle::Github => cargo_dist::config::CiStyle::Github,
+            CiStyle::Gitlab => cargo_dist::config::CiStyle::Gitlab,
         }
     }
 }
@@ -411,6 +440,14 @@ pub enum InstallerStyle {
     Homebrew,
     /// Generates an msi for each windows platform
     Msi,
+    /// Generates a .deb for each linux platform
+    Deb,
+    /// Generates an .rpm for each x86_64/aarch64 linux platform
+    Rpm,
+    /// Generates a Scoop manifest
+    Scoop,
+    /// Generates winget manifests
+    Winget,
 }
 
 impl InstallerStyle {
@@ -422,6 +459,10 @@ impl InstallerStyle {
             InstallerStyle::Npm => cargo_dist::config::InstallerStyle::Npm,
             InstallerStyle::Homebrew => cargo_dist::config::InstallerStyle::Homebrew,
             InstallerStyle::Msi => cargo_dist::config::InstallerStyle::Msi,
+            InstallerStyle::Deb => cargo_dist::config::InstallerStyle::Deb,
+            InstallerStyle::Rpm => cargo_dist::config::InstallerStyle::Rpm,
+            InstallerStyle::Scoop => cargo_dist::config::InstallerStyle::Scoop,
+            InstallerStyle::Winget => cargo_dist::config::InstallerStyle::Winget,
         }
     }
 }
@@ -456,6 +497,50 @@ pub struct PrintUploadFilesFromManifestArgs {
     pub manifest: String,
 }
 
+#[derive(Args, Clone, Debug)]
+pub struct VerifyArgs {
+    /// A path or URL for the dist-manifest.json of the release
+    pub manifest: String,
+    /// The directory containing the downloaded artifacts
+    #[clap(default_value = ".")]
+    pub dir: Utf8PathBuf,
+}
+
+#[derive(Args, Clone, Debug)]
+pub struct PublishArgs {
+    /// The directory containing the built artifacts
+    #[clap(default_value = "target/distrib")]
+    pub dir: Utf8PathBuf,
+    /// The dist-manifest.json to publish (defaults to the one in the artifact directory)
+    #[clap(long)]
+    pub manifest: Option<Utf8PathBuf>,
+    /// Only publish to these package managers (defaults to all of them)
+    #[clap(long, value_delimiter(','))]
+    pub publishers: Vec<PublisherStyle>,
+    /// Print what would be published without publishing it
+    #[clap(long)]
+    pub dry_run: bool,
+}
+
+impl PublisherStyle {
+    /// Convert the application version of this enum to the library version
+    pub fn to_lib(self) -> cargo_dist::config::PublishStyle {
+        match self {
+            PublisherStyle::Homebrew => cargo_dist::config::PublishStyle::Homebrew,
+            PublisherStyle::Npm => cargo_dist::config::PublishStyle::Npm,
+        }
+    }
+}
+
+/// Package managers `dist publish` can publish to
+#[derive(Copy, Clone, Debug, PartialEq, Eq, PartialOrd, Ord, ValueEnum)]
+pub enum PublisherStyle {
+    /// Commit Homebrew formulae to their taps
+    Homebrew,
+    /// Publish npm packages to their registries
+    Npm,
+}
+
 #[derive(Args, Clone, Debug)]
 pub struct HostArgs {
     /// The hosting steps to perform
@@ -496,6 +581,7 @@ impl HostingStyle {
     pub fn to_lib(self) -> cargo_dist::config::HostingStyle {
         match self {
             HostingStyle::Github => cargo_dist::config::HostingStyle::Github,
+            HostingStyle::Gitlab => cargo_dist::config::HostingStyle::Gitlab,
         }
     }
 }
@@ -505,12 +591,15 @@ impl HostingStyle {
 pub enum HostingStyle {
     /// Host on Github Releases
     Github,
+    /// Host on GitLab Releases
+    Gitlab,
 }
 
 impl std::fmt::Display for HostingStyle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self {
             HostingStyle::Github => "github",
+            HostingStyle::Gitlab => "gitlab",
         };
         string.fmt(f)
     }
@@ -4,14 +4,18 @@ use std::collections::BTreeMap;
 
 use axoasset::{toml_edit, SourceFile};
 use axoproject::local_repo::LocalRepo;
+use axoproject::WorkspaceGraph;
 use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{
-    AptPackageName, ChecksumExtensionRef, ChocolateyPackageName, GithubAttestationsFilters,
-    GithubAttestationsPhase, HomebrewPackageName, PackageVersion, TripleName, TripleNameRef,
+    AptPackageName, ChecksumExtensionRef, ChocolateyPackageName, DnfPackageName,
+    GithubAttestationsFilters, GithubAttestationsPhase, HomebrewPackageName, PackageVersion,
+    TripleName, TripleNameRef,
 };
+use itertools::Itertools;
 use serde::{Deserialize, Serialize};
 
 use crate::announce::TagSettings;
+use crate::platform::targets::{known_targets_for_os, TARGET_GROUP_OSES};
 use crate::SortedMap;
 use crate::{
     errors::{DistError, DistResult},
@@ -64,6 +68,8 @@ pub struct Config {
     pub installers: Vec<InstallerStyle>,
     /// What command was being invoked here, used for SystemIds
     pub root_cmd: String,
+    /// How many build steps can run at once (cargo invocations are always run one at a time)
+    pub jobs: usize,
 }
 
 /// How we should select the artifacts to build
@@ -100,12 +106,15 @@ impl std::fmt::Display for ArtifactMode {
 pub enum CiStyle {
     /// Generate Github CI
     Github,
+    /// Generate GitLab CI
+    Gitlab,
 }
 impl CiStyle {
     /// If the CI provider provides a native release hosting system, get it
     pub(crate) fn native_hosting(&self) -> Option<HostingStyle> {
         match self {
             CiStyle::Github => Some(HostingStyle::Github),
+            CiStyle::Gitlab => Some(HostingStyle::Gitlab),
         }
     }
 }
@@ -114,6 +123,7 @@ impl std::fmt::Display for CiStyle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self {
             CiStyle::Github => "github",
+            CiStyle::Gitlab => "gitlab",
         };
         string.fmt(f)
     }
@@ -124,6 +134,7 @@ impl std::str::FromStr for CiStyle {
     fn from_str(val: &str) -> DistResult<Self> {
         let res = match val {
             "github" => CiStyle::Github,
+            "gitlab" => CiStyle::Gitlab,
             s => {
                 return Err(DistError::UnrecognizedCiStyle {
                     style: s.to_string(),
@@ -187,6 +198,14 @@ pub enum InstallerStyle {
     Msi,
     /// Generate an Apple pkg installer that embeds the binary
     Pkg,
+    /// Generate a Debian package that embeds the binary
+    Deb,
+    /// Generate an RPM package that embeds the binary
+    Rpm,
+    /// Generate a Scoop manifest that fetches from [`cargo_dist_schema::Release::artifact_download_urls`][]
+    Scoop,
+    /// Generate winget manifests that fetch from [`cargo_dist_schema::Release::artifact_download_urls`][]
+    Winget,
 }
 
 impl std::fmt::Display for InstallerStyle {
@@ -198,6 +217,32 @@ impl std::fmt::Display for InstallerStyle {
             InstallerStyle::Homebrew => "homebrew",
             InstallerStyle::Msi => "msi",
             InstallerStyle::Pkg => "pkg",
+            InstallerStyle::Deb => "deb",
+            InstallerStyle::Rpm => "rpm",
+            InstallerStyle::Scoop => "scoop",
+            InstallerStyle::Winget => "winget",
+        };
+        string.fmt(f)
+    }
+}
+
+/// The tool we should use to turn `wix/main.wxs` into an msi
+#[derive(Clone, Copy, Debug, Default, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(rename_all = "kebab-case")]
+pub enum MsiBuilder {
+    /// Run the WiX toolset (via cargo-wix), which only works on Windows
+    #[default]
+    Wix,
+    /// Use dist's builtin msi writer, which works on any platform but only
+    /// supports the subset of WiX that dist's generated main.wxs uses
+    Native,
+}
+
+impl std::fmt::Display for MsiBuilder {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let string = match self {
+            MsiBuilder::Wix => "wix",
+            MsiBuilder::Native => "native",
         };
         string.fmt(f)
     }
@@ -233,7 +278,9 @@ impl std::fmt::Display for GithubReleasePhase {
 pub enum HostingStyle {
     /// Host on Github Releases
     Github,
-    /// Hosted on a simple static file server (currently download-only)
+    /// Host on GitLab Releases (with files in the Generic Package Registry)
+    Gitlab,
+    /// Hosted on a simple static file server, optionally uploaded to S3-compatible storage
     Simple,
 }
 
@@ -241,6 +288,7 @@ impl std::fmt::Display for HostingStyle {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         let string = match self {
             HostingStyle::Github => "github",
+            HostingStyle::Gitlab => "gitlab",
             HostingStyle::Simple => "simple",
         };
         string.fmt(f)
@@ -252,6 +300,7 @@ impl std::str::FromStr for HostingStyle {
     fn from_str(val: &str) -> DistResult<Self> {
         let res = match val {
             "github" => HostingStyle::Github,
+            "gitlab" => HostingStyle::Gitlab,
             "simple" => HostingStyle::Simple,
             s => {
                 return Err(DistError::UnrecognizedHostingStyle {
@@ -271,6 +320,10 @@ pub enum PublishStyle {
     Homebrew,
     /// Publish an npm pkg to the global npm registry
     Npm,
+    /// Publish a Scoop manifest to a bucket repository
+    Scoop,
+    /// Publish winget manifests to a winget-pkgs style repository
+    Winget,
     /// User-supplied value
     User(String),
 }
@@ -284,6 +337,10 @@ impl std::str::FromStr for PublishStyle {
             Ok(Self::Homebrew)
         } else if s == "npm" {
             Ok(Self::Npm)
+        } else if s == "scoop" {
+            Ok(Self::Scoop)
+        } else if s == "winget" {
+            Ok(Self::Winget)
         } else {
             Err(DistError::UnrecognizedJobStyle {
                 style: s.to_owned(),
@@ -309,6 +366,8 @@ impl std::fmt::Display for PublishStyle {
         match self {
             PublishStyle::Homebrew => write!(f, "homebrew"),
             PublishStyle::Npm => write!(f, "npm"),
+            PublishStyle::Scoop => write!(f, "scoop"),
+            PublishStyle::Winget => write!(f, "winget"),
             PublishStyle::User(s) => write!(f, "./{s}"),
         }
     }
@@ -676,6 +735,20 @@ impl ChecksumStyle {
             ChecksumStyle::False => "false",
         })
     }
+
+    /// Get the checksum style for an extension (the inverse of [`ChecksumStyle::ext`][])
+    pub fn from_ext(ext: &str) -> Option<Self> {
+        [
+            ChecksumStyle::Sha256,
+            ChecksumStyle::Sha512,
+            ChecksumStyle::Sha3_256,
+            ChecksumStyle::Sha3_512,
+            ChecksumStyle::Blake2s,
+            ChecksumStyle::Blake2b,
+        ]
+        .into_iter()
+        .find(|style| style.ext().as_str() == ext)
+    }
 }
 
 /// Which style(s) of configuration to generate
@@ -762,6 +835,14 @@ pub struct SystemDependencies {
     #[serde(default)]
     #[serde(skip_serializing_if = "BTreeMap::is_empty")]
     pub chocolatey: BTreeMap<ChocolateyPackageName, SystemDependency>,
+
+    /// Packages to install in dnf
+    ///
+    /// These aren't installed during builds, but `run` dependencies are
+    /// declared as dependencies of rpm installers.
+    #[serde(default)]
+    #[serde(skip_serializing_if = "BTreeMap::is_empty")]
+    pub dnf: BTreeMap<DnfPackageName, SystemDependency>,
 }
 
 impl SystemDependencies {
@@ -770,6 +851,7 @@ impl SystemDependencies {
         self.homebrew.append(&mut other.homebrew);
         self.apt.append(&mut other.apt);
         self.chocolatey.append(&mut other.chocolatey);
+        self.dnf.append(&mut other.dnf);
     }
 }
 
@@ -929,6 +1011,219 @@ impl std::fmt::Display for ProductionMode {
     }
 }
 
+/// An entry in `targets`: either a target triple, or a shorthand for several of them
+#[derive(Clone, Debug, PartialEq, Eq, Serialize)]
+#[serde(untagged)]
+pub enum TargetSelector {
+    /// A single target triple, like `x86_64-unknown-linux-gnu`
+    Triple(TripleName),
+    /// Every combination of some os/arch/libc values, like `{ os = "linux", arch = "x86_64" }`
+    Group(TargetGroup),
+}
+
+impl std::fmt::Display for TargetSelector {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            TargetSelector::Triple(triple) => triple.fmt(f),
+            TargetSelector::Group(group) => group.fmt(f),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for TargetSelector {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        // Hand-rolled instead of `#[serde(untagged)]` so that mistakes in a group
+        // get reported as such, instead of as "didn't match any variant"
+        struct TargetSelectorVisitor;
+
+        impl<'de> serde::de::Visitor<'de> for TargetSelectorVisitor {
+            type Value = TargetSelector;
+
+            fn expecting(&self, formatter: &mut std::fmt::Formatter) -> std::fmt::Result {
+                formatter.write_str("a target triple, or a target group like { os = \"linux\" }")
+            }
+
+            fn visit_str<E>(self, s: &str) -> Result<Self::Value, E>
+            where
+                E: serde::de::Error,
+            {
+                Ok(TargetSelector::Triple(TripleName::new(s.to_owned())))
+            }
+
+            fn visit_map<M>(self, map: M) -> Result<Self::Value, M::Error>
+            where
+                M: serde::de::MapAccess<'de>,
+            {
+                TargetGroup::deserialize(serde::de::value::MapAccessDeserializer::new(map))
+                    .map(TargetSelector::Group)
+            }
+        }
+
+        deserializer.deserialize_any(TargetSelectorVisitor)
+    }
+}
+
+/// A shorthand for every known target matching some os/arch/libc values
+///
+/// A missing `os` means linux, macos, and windows. A missing `arch` means
+/// x86_64 and aarch64. A missing `libc` means each os's usual one.
+#[derive(Clone, Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct TargetGroup {
+    /// Operating systems: "linux", "macos", or "windows"
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, with = "opt_string_or_vec")]
+    pub os: Option<Vec<String>>,
+    /// Architectures, as they appear at the start of a target triple (e.g. "aarch64")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, with = "opt_string_or_vec")]
+    pub arch: Option<Vec<String>>,
+    /// libcs: "gnu" or "musl" for linux, "msvc" or "gnu" for windows (ignored for macos)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default, with = "opt_string_or_vec")]
+    pub libc: Option<Vec<String>>,
+}
+
+impl TargetGroup {
+    /// Get the target triples this group stands for
+    pub fn expand(&self) -> DistResult<Vec<TripleName>> {
+        let oses = self
+            .os
+            .clone()
+            .unwrap_or_else(|| vec!["linux".to_owned(), "macos".to_owned(), "windows".to_owned()]);
+        let arches = self
+            .arch
+            .clone()
+            .unwrap_or_else(|| vec!["x86_64".to_owned(), "aarch64".to_owned()]);
+
+        let mut triples = vec![];
+        for os in &oses {
+            let Some((_, known_libcs)) = TARGET_GROUP_OSES.iter().find(|(name, _)| name == os)
+            else {
+                return Err(DistError::UnknownTargetGroupOs { os: os.clone() });
+            };
+            let libcs = match (known_libcs.first(), &self.libc) {
+                // Only one libc to pick from, so it doesn't matter what was asked for
+                (None, _) => vec![""],
+                (Some(default), None) => vec![*default],
+                (Some(_), Some(libcs)) => libcs.iter().map(|libc| libc.as_str()).collect(),
+            };
+            for libc in libcs {
+                if !libc.is_empty() && !known_libcs.contains(&libc) {
+                    return Err(DistError::UnknownTargetGroupLibc {
+                        os: os.clone(),
+                        libc: libc.to_owned(),
+                        known: known_libcs.join(", "),
+                    });
+                }
+                let known = known_targets_for_os(os, libc);
+                let arch_of = |triple: &TripleNameRef| {
+                    triple
+                        .as_str()
+                        .split('-')
+                        .next()
+                        .unwrap_or_default()
+                        .to_owned()
+                };
+                for arch in &arches {
+                    let Some(triple) = known.iter().find(|triple| arch_of(triple) == *arch) else {
+                        return Err(DistError::UnknownTargetGroupArch {
+                            platform: if libc.is_empty() {
+                                os.clone()
+                            } else {
+                                format!("{os} ({libc})")
+                            },
+                            arch: arch.clone(),
+                            known: known
+                                .iter()
+                                .map(|triple| arch_of(triple))
+                                .unique()
+                                .join(", "),
+                        });
+                    };
+                    triples.push((*triple).to_owned());
+                }
+            }
+        }
+        Ok(triples)
+    }
+}
+
+impl std::fmt::Display for TargetGroup {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        // Written the same way it would be in a toml file
+        let fields = [("os", &self.os), ("arch", &self.arch), ("libc", &self.libc)]
+            .into_iter()
+            .filter_map(|(key, vals)| {
+                let vals = vals.as_ref()?;
+                let vals = if let [val] = &vals[..] {
+                    format!("{val:?}")
+                } else {
+                    format!("[{}]", vals.iter().map(|val| format!("{val:?}")).join(", "))
+                };
+                Some(format!("{key} = {vals}"))
+            })
+            .collect::<Vec<_>>();
+        if fields.is_empty() {
+            write!(f, "{{}}")
+        } else {
+            write!(f, "{{ {} }}", fields.join(", "))
+        }
+    }
+}
+
+/// Expand any target groups in a `targets` list into the triples they stand for
+pub fn expand_targets(targets: &[TargetSelector]) -> DistResult<Vec<TripleName>> {
+    let mut triples = vec![];
+    for target in targets {
+        let expanded = match target {
+            TargetSelector::Triple(triple) => vec![triple.clone()],
+            TargetSelector::Group(group) => group.expand()?,
+        };
+        for triple in expanded {
+            if !triples.contains(&triple) {
+                triples.push(triple);
+            }
+        }
+    }
+    Ok(triples)
+}
+
+/// Find every target group in the workspace's config, along with what it expands to
+pub fn target_group_expansions(
+    workspaces: &WorkspaceGraph,
+) -> DistResult<Vec<(TargetGroup, Vec<TripleName>)>> {
+    let root_workspace = workspaces.root_workspace();
+    let mut metadatas = vec![parse_metadata_table_or_manifest(
+        &root_workspace.manifest_path,
+        root_workspace.dist_manifest_path.as_deref(),
+        root_workspace.cargo_metadata_table.as_ref(),
+    )?];
+    for (_idx, package) in workspaces.all_packages() {
+        metadatas.push(parse_metadata_table_or_manifest(
+            &package.manifest_path,
+            package.dist_manifest_path.as_deref(),
+            package.cargo_metadata_table.as_ref(),
+        )?);
+    }
+
+    let mut expansions = Vec::<(TargetGroup, Vec<TripleName>)>::new();
+    for target in metadatas
+        .iter()
+        .flat_map(|meta| meta.targets.iter().flatten())
+    {
+        if let TargetSelector::Group(group) = target {
+            if !expansions.iter().any(|(known, _)| known == group) {
+                expansions.push((group.clone(), group.expand()?));
+            }
+        }
+    }
+    Ok(expansions)
+}
+
 pub(crate) fn parse_metadata_table_or_manifest(
     manifest_path: &Utf8Path,
     dist_manifest_path: Option<&Utf8Path>,
@@ -114,6 +114,26 @@ pub struct DistMetadata {
     /// Customize the name of the Homebrew formula
     pub formula: Option<String>,
 
+    /// A GitHub repo (Scoop bucket) to push the Scoop manifest to, if built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub scoop_bucket: Option<String>,
+    /// A GitHub repo (laid out like microsoft/winget-pkgs) to push winget manifests to, if built
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winget_repo: Option<String>,
+    /// The publisher name to use in winget manifests
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winget_publisher: Option<String>,
+    /// Customize the winget package identifier (defaults to `Publisher.AppName`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub winget_package_identifier: Option<String>,
+
+    /// How to build msi installers (defaults to "wix")
+    ///
+    /// "native" builds them with dist's builtin msi writer, so they can be made
+    /// on any platform (e.g. Linux runners cross-compiling for Windows).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub msi_builder: Option<MsiBuilder>,
+
     /// A set of packages to install before building
     #[serde(rename = "dependencies")]
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -126,13 +146,15 @@ pub struct DistMetadata {
     /// The inputs should be valid rustc target triples (see `rustc --print target-list`) such
     /// as `x86_64-pc-windows-msvc`, `aarch64-apple-darwin`, or `x86_64-unknown-linux-gnu`.
     ///
-    /// FIXME: We should also accept one magic target: `universal2-apple-darwin`. This will induce
+    /// We also accept one magic target: `universal2-apple-darwin`. This will induce
     /// us to build `x86_64-apple-darwin` and `aarch64-apple-darwin` (arm64) and then combine
-    /// them into a "universal" binary that can run on either arch (using apple's `lipo` tool).
+    /// them into a "universal" binary that can run on either arch.
     ///
-    /// FIXME: Allow higher level requests like "[macos, windows, linux] x [x86_64, aarch64]"?
+    /// Entries can also be target groups like `{ os = ["linux", "macos"], arch = "aarch64" }`,
+    /// which stand for every known target matching the given os/arch/libc values
+    /// (see [`TargetGroup`][]).
     #[serde(skip_serializing_if = "Option::is_none")]
-    pub targets: Option<Vec<TripleName>>,
+    pub targets: Option<Vec<TargetSelector>>,
 
     /// Include the following static files in bundles like archives.
     ///
@@ -149,6 +171,25 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub auto_includes: Option<bool>,
 
+    /// A FreeDesktop desktop entry (`.desktop` file) to bundle with linux builds
+    ///
+    /// Path is relative to the Cargo.toml this is defined in.
+    ///
+    /// If this isn't set but `desktop-icon` or `desktop-mime-types` are, a desktop
+    /// entry will be generated for the app.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_entry: Option<Utf8PathBuf>,
+
+    /// An icon (`.png` or `.svg`) to bundle with linux builds for the app's desktop entry
+    ///
+    /// Path is relative to the Cargo.toml this is defined in.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_icon: Option<Utf8PathBuf>,
+
+    /// MIME types the app's desktop entry can open (e.g. `image/png`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_mime_types: Option<Vec<String>>,
+
     /// Whether msvc targets should statically link the crt
     ///
     /// Defaults to true.
@@ -177,6 +218,11 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub npm_scope: Option<String>,
 
+    /// The npm registry to publish the npm package to, instead of npmjs.com
+    /// (used by `dist publish`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub npm_registry: Option<String>,
+
     /// Which checksum algorithm to use, from: sha256, sha512, sha3-256,
     /// sha3-512, blake2s, blake2b, or false (to disable checksums)
     #[serde(skip_serializing_if = "Option::is_none")]
@@ -329,6 +375,18 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub recursive_tarball: Option<bool>,
 
+    /// Whether to generate an in-toto SLSA provenance statement for each release
+    ///
+    /// (defaults to false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slsa_provenance: Option<bool>,
+
+    /// Whether archives should be byte-for-byte reproducible
+    ///
+    /// (defaults to false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reproducible_archives: Option<bool>,
+
     /// Host jobs to run in CI
     ///
     /// The core build job is always run, but this allows additional hooks
@@ -367,6 +425,35 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_latest: Option<bool>,
 
+    /// Generate the announcement's changelog from the Conventional Commits since the
+    /// previous release, for projects that don't keep a CHANGELOG
+    ///
+    /// (defaults to false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub changelog_from_commits: Option<bool>,
+
+    /// A template for the version of untagged releases (see `release-branch`), using
+    /// `{version}`, `{date}`, `{short_sha}`, `{commit_count}`, and `{branch}`
+    ///
+    /// (defaults to `{version}-alpha.{timestamp}`, or
+    /// `{version}-nightly.{date}.{commit_count}` when `snapshot-tag` is set)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub snapshot_version: Option<String>,
+
+    /// A rolling tag (like "nightly") to announce untagged releases under,
+    /// replacing the previous release at that tag instead of making a new one
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub snapshot_tag: Option<String>,
+
+    /// A jinja2 template to render the GitHub/GitLab Release body with,
+    /// instead of the built-in layout (path relative to this Cargo.toml's dir)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub announcement_template: Option<Utf8PathBuf>,
+
     /// Whether we should create the Github Release for you when you push a tag.
     ///
     /// If true (default), dist will create a new Github Release and generate
@@ -401,6 +488,12 @@ pub struct DistMetadata {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub macos_sign: Option<bool>,
 
+    /// A minisign public key to make detached signatures of artifacts with
+    ///
+    /// The matching secret key is read from the MINISIGN_SECRET_KEY environment variable.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minisign_public_key: Option<String>,
+
     /// Whether GitHub Attestations is enabled (default false)
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github_attestations: Option<bool>,
@@ -427,6 +520,10 @@ pub struct DistMetadata {
     pub github_custom_runners:
         Option<SortedMap<TripleName, StringLikeOr<GithubRunner, GithubRunnerConfigInput>>>,
 
+    /// Custom GitLab runner tags, mapped by triple target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gitlab_custom_runners: Option<SortedMap<TripleName, String>>,
+
     /// Custom permissions for jobs
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github_custom_job_permissions: Option<SortedMap<String, GithubPermissionMap>>,
@@ -484,6 +581,21 @@ pub struct DistMetadata {
     #[serde(default)]
     pub min_glibc_version: Option<MinGlibcVersion>,
 
+    /// Fail the build if a binary requires a newer glibc than min-glibc-version
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub enforce_min_glibc_version: Option<bool>,
+
+    /// Split the debuginfo of Linux binaries out into .debug files, and ship them as symbols
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub split_debuginfo: Option<bool>,
+
+    /// A directory to also lay out split debuginfo in by build-id, like a debuginfod server
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub symbol_store: Option<Utf8PathBuf>,
+
     /// Overrides for platform binaries, same syntax as min_glibc_version
     #[serde(skip_serializing_if = "Option::is_none")]
     #[serde(default)]
@@ -506,6 +618,26 @@ pub struct DistMetadata {
 
     /// Where to download artifacts from on the Simple host
     pub simple_download_url: Option<String>,
+
+    /// The S3-compatible endpoint to upload artifacts to for the Simple host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub simple_s3_endpoint: Option<String>,
+
+    /// The S3 bucket to upload artifacts to for the Simple host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub simple_s3_bucket: Option<String>,
+
+    /// The prefix of the S3 keys to upload artifacts to for the Simple host
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub simple_s3_prefix: Option<String>,
+
+    /// The S3 region to sign requests for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    #[serde(default)]
+    pub simple_s3_region: Option<String>,
 }
 
 impl DistMetadata {
@@ -515,6 +647,10 @@ impl DistMetadata {
         let DistMetadata {
             include,
             extra_artifacts,
+            desktop_entry,
+            desktop_icon,
+            announcement_template,
+            symbol_store,
             // The rest of these don't include relative paths
             cargo_dist_version: _,
             cargo_dist_url_override: _,
@@ -525,14 +661,21 @@ impl DistMetadata {
             install_success_msg: _,
             tap: _,
             formula: _,
+            scoop_bucket: _,
+            winget_repo: _,
+            winget_publisher: _,
+            winget_package_identifier: _,
+            msi_builder: _,
             system_dependencies: _,
             targets: _,
             auto_includes: _,
+            desktop_mime_types: _,
             windows_archive: _,
             unix_archive: _,
             npm_package: _,
             npm_scope: _,
             npm_shrinkwrap: _,
+            npm_registry: _,
             checksum: _,
             precise_builds: _,
             fail_fast: _,
@@ -550,23 +693,30 @@ impl DistMetadata {
             global_artifacts_jobs: _,
             source_tarball: _,
             recursive_tarball: _,
+            slsa_provenance: _,
+            reproducible_archives: _,
             host_jobs: _,
             publish_jobs: _,
             post_announce_jobs: _,
             publish_prereleases: _,
             force_latest: _,
+            changelog_from_commits: _,
+            snapshot_version: _,
+            snapshot_tag: _,
             create_release: _,
             pr_run_mode: _,
             allow_dirty: _,
             github_release: _,
             ssldotcom_windows_sign: _,
             macos_sign: _,
+            minisign_public_key: _,
             github_attestations: _,
             github_attestations_filters: _,
             github_attestations_phase: _,
             msvc_crt_static: _,
             hosting: _,
             github_custom_runners: _,
+            gitlab_custom_runners: _,
             github_custom_job_permissions: _,
             github_action_commits: _,
             bin_aliases: _,
@@ -582,17 +732,35 @@ impl DistMetadata {
             github_build_setup: _,
             mac_pkg_config: _,
             min_glibc_version: _,
+            enforce_min_glibc_version: _,
+            split_debuginfo: _,
             binaries: _,
             cargo_auditable: _,
             cargo_cyclonedx: _,
             omnibor: _,
             simple_download_url: _,
+            simple_s3_endpoint: _,
+            simple_s3_bucket: _,
+            simple_s3_prefix: _,
+            simple_s3_region: _,
         } = self;
         if let Some(include) = include {
             for include in include {
                 *include = base_path.join(&*include);
             }
         }
+        if let Some(desktop_entry) = desktop_entry {
+            *desktop_entry = base_path.join(&*desktop_entry);
+        }
+        if let Some(desktop_icon) = desktop_icon {
+            *desktop_icon = base_path.join(&*desktop_icon);
+        }
+        if let Some(announcement_template) = announcement_template {
+            *announcement_template = base_path.join(&*announcement_template);
+        }
+        if let Some(symbol_store) = symbol_store {
+            *symbol_store = base_path.join(&*symbol_store);
+        }
         if let Some(extra_artifacts) = extra_artifacts {
             for extra in extra_artifacts {
                 // We update the working_dir to be relative to this file
@@ -632,15 +800,24 @@ impl DistMetadata {
             install_success_msg,
             tap,
             formula,
+            scoop_bucket,
+            winget_repo,
+            winget_publisher,
+            winget_package_identifier,
+            msi_builder,
             system_dependencies,
             targets,
             include,
             auto_includes,
+            desktop_entry,
+            desktop_icon,
+            desktop_mime_types,
             windows_archive,
             unix_archive,
             npm_package,
             npm_scope,
             npm_shrinkwrap,
+            npm_registry,
             checksum,
             precise_builds,
             merge_tasks,
@@ -658,17 +835,24 @@ impl DistMetadata {
             global_artifacts_jobs,
             source_tarball,
             recursive_tarball,
+            slsa_provenance,
+            reproducible_archives,
             host_jobs,
             publish_jobs,
             post_announce_jobs,
             publish_prereleases,
             force_latest,
+            changelog_from_commits,
+            snapshot_version,
+            snapshot_tag,
+            announcement_template,
             create_release,
             pr_run_mode,
             allow_dirty,
             github_release,
             ssldotcom_windows_sign,
             macos_sign,
+            minisign_public_key,
             github_attestations,
             github_attestations_filters,
             github_attestations_phase,
@@ -676,6 +860,7 @@ impl DistMetadata {
             hosting,
             extra_artifacts,
             github_custom_runners,
+            gitlab_custom_runners,
             github_custom_job_permissions,
             github_action_commits,
             bin_aliases,
@@ -691,11 +876,18 @@ impl DistMetadata {
             github_build_setup,
             mac_pkg_config,
             min_glibc_version,
+            enforce_min_glibc_version,
+            split_debuginfo,
+            symbol_store,
             binaries,
             cargo_auditable,
             cargo_cyclonedx,
             omnibor,
             simple_download_url,
+            simple_s3_endpoint,
+            simple_s3_bucket,
+            simple_s3_prefix,
+            simple_s3_region,
         } = self;
 
         // Check for global settings on local packages
@@ -752,6 +944,18 @@ impl DistMetadata {
         if force_latest.is_some() {
             warn!("package.metadata.dist.force-stable is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if changelog_from_commits.is_some() {
+            warn!("package.metadata.dist.changelog-from-commits is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if snapshot_version.is_some() {
+            warn!("package.metadata.dist.snapshot-version is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if snapshot_tag.is_some() {
+            warn!("package.metadata.dist.snapshot-tag is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if announcement_template.is_some() {
+            warn!("package.metadata.dist.announcement-template is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
         if pr_run_mode.is_some() {
             warn!("package.metadata.dist.pr-run-mode is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
@@ -761,6 +965,9 @@ impl DistMetadata {
         if macos_sign.is_some() {
             warn!("package.metadata.dist.macos-sign is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if minisign_public_key.is_some() {
+            warn!("package.metadata.dist.minisign-public-key is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
         if github_attestations.is_some() {
             warn!("package.metadata.dist.github-attestations is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
@@ -791,6 +998,12 @@ impl DistMetadata {
         if recursive_tarball.is_some() {
             warn!("package.metadata.dist.recursive-tarball is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if slsa_provenance.is_some() {
+            warn!("package.metadata.dist.slsa-provenance is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if reproducible_archives.is_some() {
+            warn!("package.metadata.dist.reproducible-archives is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
         if host_jobs.is_some() {
             warn!("package.metadata.dist.host-jobs is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
@@ -815,12 +1028,27 @@ impl DistMetadata {
         if github_custom_runners.is_some() {
             warn!("package.metadata.dist.github-custom-runners is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if gitlab_custom_runners.is_some() {
+            warn!("package.metadata.dist.gitlab-custom-runners is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
         if github_build_setup.is_some() {
             warn!("package.metadata.dist.github-build-setup is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
         if simple_download_url.is_some() {
             warn!("package.metadata.dist.simple-download-url is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
         }
+        if simple_s3_endpoint.is_some() {
+            warn!("package.metadata.dist.simple-s3-endpoint is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if simple_s3_bucket.is_some() {
+            warn!("package.metadata.dist.simple-s3-bucket is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if simple_s3_prefix.is_some() {
+            warn!("package.metadata.dist.simple-s3-prefix is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
+        if simple_s3_region.is_some() {
+            warn!("package.metadata.dist.simple-s3-region is set, but this is only accepted in workspace.metadata (value is being ignored): {}", package_manifest_path);
+        }
 
         // Merge non-global settings
         if installers.is_none() {
@@ -835,6 +1063,15 @@ impl DistMetadata {
         if auto_includes.is_none() {
             *auto_includes = workspace_config.auto_includes;
         }
+        if desktop_entry.is_none() {
+            desktop_entry.clone_from(&workspace_config.desktop_entry);
+        }
+        if desktop_icon.is_none() {
+            desktop_icon.clone_from(&workspace_config.desktop_icon);
+        }
+        if desktop_mime_types.is_none() {
+            desktop_mime_types.clone_from(&workspace_config.desktop_mime_types);
+        }
         if windows_archive.is_none() {
             *windows_archive = workspace_config.windows_archive;
         }
@@ -850,6 +1087,9 @@ impl DistMetadata {
         if npm_shrinkwrap.is_none() {
             npm_shrinkwrap.clone_from(&workspace_config.npm_shrinkwrap);
         }
+        if npm_registry.is_none() {
+            npm_registry.clone_from(&workspace_config.npm_registry);
+        }
         if checksum.is_none() {
             *checksum = workspace_config.checksum;
         }
@@ -874,6 +1114,21 @@ impl DistMetadata {
         if formula.is_none() {
             formula.clone_from(&workspace_config.formula);
         }
+        if scoop_bucket.is_none() {
+            scoop_bucket.clone_from(&workspace_config.scoop_bucket);
+        }
+        if winget_repo.is_none() {
+            winget_repo.clone_from(&workspace_config.winget_repo);
+        }
+        if winget_publisher.is_none() {
+            winget_publisher.clone_from(&workspace_config.winget_publisher);
+        }
+        if winget_package_identifier.is_none() {
+            winget_package_identifier.clone_from(&workspace_config.winget_package_identifier);
+        }
+        if msi_builder.is_none() {
+            *msi_builder = workspace_config.msi_builder;
+        }
         if system_dependencies.is_none() {
             system_dependencies.clone_from(&workspace_config.system_dependencies);
         }
@@ -907,6 +1162,15 @@ impl DistMetadata {
         if min_glibc_version.is_none() {
             min_glibc_version.clone_from(&workspace_config.min_glibc_version);
         }
+        if enforce_min_glibc_version.is_none() {
+            *enforce_min_glibc_version = workspace_config.enforce_min_glibc_version;
+        }
+        if split_debuginfo.is_none() {
+            *split_debuginfo = workspace_config.split_debuginfo;
+        }
+        if symbol_store.is_none() {
+            symbol_store.clone_from(&workspace_config.symbol_store);
+        }
         if binaries.is_none() {
             binaries.clone_from(&workspace_config.binaries);
         }
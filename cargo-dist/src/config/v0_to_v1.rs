@@ -5,24 +5,32 @@ use artifacts::ArtifactLayer;
 use builds::cargo::CargoBuildLayer;
 use builds::{BuildLayer, CommonBuildLayer};
 use ci::github::GithubCiLayer;
+use ci::gitlab::GitlabCiLayer;
 use ci::{CiLayer, CommonCiLayer};
 use hosts::github::GithubHostLayer;
 use hosts::{CommonHostLayer, HostLayer};
 use installers::homebrew::HomebrewInstallerLayer;
+use installers::msi::MsiInstallerLayer;
 use installers::npm::NpmInstallerLayer;
 use installers::pkg::PkgInstallerLayer;
+use installers::scoop::ScoopInstallerLayer;
+use installers::winget::WingetInstallerLayer;
 use installers::{CommonInstallerLayer, InstallerLayer};
 use layer::BoolOr;
 use publishers::{CommonPublisherLayer, PublisherLayer};
 
-use crate::config::v1::hosts::simple::SimpleHostLayer;
+use crate::config::v1::hosts::simple::{SimpleHostLayer, SimpleS3Layer};
+use crate::DistResult;
 
 use super::v0::DistMetadata;
-use super::{v1::*, CiStyle, HostingStyle, InstallerStyle, JobStyle, MacPkgConfig, PublishStyle};
+use super::{
+    expand_targets, v1::*, CiStyle, HostingStyle, InstallerStyle, JobStyle, MacPkgConfig,
+    PublishStyle,
+};
 
 impl DistMetadata {
     /// Convert the v0 config format to v1
-    pub fn to_toml_layer(&self, is_global: bool) -> TomlLayer {
+    pub fn to_toml_layer(&self, is_global: bool) -> DistResult<TomlLayer> {
         let DistMetadata {
             cargo_dist_version,
             cargo_dist_url_override,
@@ -35,15 +43,24 @@ impl DistMetadata {
             install_success_msg,
             tap,
             formula,
+            scoop_bucket,
+            winget_repo,
+            winget_publisher,
+            winget_package_identifier,
+            msi_builder,
             system_dependencies,
             targets,
             include,
             auto_includes,
+            desktop_entry,
+            desktop_icon,
+            desktop_mime_types,
             msvc_crt_static,
             windows_archive,
             unix_archive,
             npm_package,
             npm_shrinkwrap,
+            npm_registry,
             npm_scope,
             checksum,
             precise_builds,
@@ -62,11 +79,17 @@ impl DistMetadata {
             global_artifacts_jobs,
             source_tarball,
             recursive_tarball,
+            slsa_provenance,
+            reproducible_archives,
             host_jobs,
             publish_jobs,
             post_announce_jobs,
             publish_prereleases,
             force_latest,
+            changelog_from_commits,
+            snapshot_version,
+            snapshot_tag,
+            announcement_template,
             create_release,
             github_releases_repo,
             github_releases_submodule_path,
@@ -74,6 +97,7 @@ impl DistMetadata {
             github_action_commits,
             ssldotcom_windows_sign,
             macos_sign,
+            minisign_public_key,
             mac_pkg_config,
             github_attestations,
             github_attestations_filters,
@@ -81,6 +105,7 @@ impl DistMetadata {
             hosting,
             extra_artifacts,
             github_custom_runners,
+            gitlab_custom_runners,
             github_custom_job_permissions,
             bin_aliases,
             tag_namespace,
@@ -92,20 +117,34 @@ impl DistMetadata {
             install_libraries,
             github_build_setup,
             min_glibc_version,
+            enforce_min_glibc_version,
+            split_debuginfo,
+            symbol_store,
             binaries,
             cargo_auditable,
             cargo_cyclonedx,
             omnibor,
             simple_download_url,
+            simple_s3_endpoint,
+            simple_s3_bucket,
+            simple_s3_prefix,
+            simple_s3_region,
         } = self.clone();
 
+        let targets = targets
+            .map(|targets| expand_targets(&targets))
+            .transpose()?;
+
         // Archives
         let needs_archive_layer = include.is_some()
             || auto_includes.is_some()
             || windows_archive.is_some()
             || unix_archive.is_some()
             || package_libraries.is_some()
-            || binaries.is_some();
+            || binaries.is_some()
+            || desktop_entry.is_some()
+            || desktop_icon.is_some()
+            || desktop_mime_types.is_some();
         let archive_layer = needs_archive_layer.then_some(ArchiveLayer {
             include,
             auto_includes,
@@ -113,16 +152,23 @@ impl DistMetadata {
             unix_archive,
             package_libraries,
             binaries,
+            desktop_entry,
+            desktop_icon,
+            desktop_mime_types,
         });
         let needs_artifacts = archive_layer.is_some()
             || source_tarball.is_some()
             || recursive_tarball.is_some()
+            || slsa_provenance.is_some()
+            || reproducible_archives.is_some()
             || extra_artifacts.is_some()
             || checksum.is_some();
         let artifacts_layer = needs_artifacts.then_some(ArtifactLayer {
             archives: archive_layer,
             source_tarball,
             recursive_tarball,
+            slsa_provenance,
+            reproducible_archives,
             extra: extra_artifacts,
             checksum,
         });
@@ -151,17 +197,25 @@ impl DistMetadata {
             || system_dependencies.is_some()
             || ssldotcom_windows_sign.is_some()
             || macos_sign.is_some()
+            || minisign_public_key.is_some()
             || msvc_crt_static.is_some()
             || min_glibc_version.is_some()
+            || enforce_min_glibc_version.is_some()
+            || split_debuginfo.is_some()
+            || symbol_store.is_some()
             || omnibor.is_some();
         let build_layer = needs_build_layer.then_some(BuildLayer {
             common: CommonBuildLayer {},
             ssldotcom_windows_sign,
             macos_sign,
+            minisign_public_key,
             system_dependencies,
             cargo: cargo_layer,
             generic: None,
             min_glibc_version,
+            enforce_min_glibc_version,
+            split_debuginfo,
+            symbol_store,
             omnibor,
         });
 
@@ -184,6 +238,13 @@ impl DistMetadata {
             }
         });
         let has_github_ci = github_ci_layer.is_some();
+        let gitlab_ci_layer = list_to_bool_layer(is_global, &ci, CiStyle::Gitlab, || {
+            gitlab_custom_runners.map(|runners| GitlabCiLayer {
+                common: CommonCiLayer::default(),
+                runners: Some(runners),
+            })
+        });
+        let has_gitlab_ci = gitlab_ci_layer.is_some();
         let custom_publish_jobs = publish_jobs.as_ref().map(|jobs| {
             jobs.iter()
                 .filter_map(|p| {
@@ -205,6 +266,7 @@ impl DistMetadata {
             custom_publish_jobs
         };
         let needs_ci_layer = github_ci_layer.is_some()
+            || gitlab_ci_layer.is_some()
             || merge_tasks.is_some()
             || fail_fast.is_some()
             || cache_builds.is_some()
@@ -236,6 +298,7 @@ impl DistMetadata {
                 post_announce_jobs,
             },
             github: github_ci_layer,
+            gitlab: gitlab_ci_layer,
         });
 
         // hosts
@@ -264,12 +327,28 @@ impl DistMetadata {
         if github_host_layer.is_none() && has_github_ci {
             github_host_layer = Some(BoolOr::Bool(true));
         }
+        let mut gitlab_host_layer =
+            list_to_bool_layer(is_global, &hosting, HostingStyle::Gitlab, || None);
+        if gitlab_host_layer.is_none() && has_gitlab_ci && hosting.is_none() {
+            gitlab_host_layer = Some(BoolOr::Bool(true));
+        }
         let simple_host_layer =
             list_to_bool_layer(is_global, &hosting, HostingStyle::Simple, || {
-                if simple_download_url.is_some() {
+                let needs_s3_layer = simple_s3_endpoint.is_some()
+                    || simple_s3_bucket.is_some()
+                    || simple_s3_prefix.is_some()
+                    || simple_s3_region.is_some();
+                let s3 = needs_s3_layer.then_some(SimpleS3Layer {
+                    endpoint: simple_s3_endpoint,
+                    bucket: simple_s3_bucket,
+                    prefix: simple_s3_prefix,
+                    region: simple_s3_region,
+                });
+                if simple_download_url.is_some() || s3.is_some() {
                     Some(SimpleHostLayer {
                         common: CommonHostLayer::default(),
                         download_url: simple_download_url,
+                        s3,
                     })
                 } else {
                     None
@@ -277,16 +356,26 @@ impl DistMetadata {
             });
 
         let needs_host_layer = github_host_layer.is_some()
+            || gitlab_host_layer.is_some()
             || simple_host_layer.is_some()
             || force_latest.is_some()
+            || changelog_from_commits.is_some()
+            || snapshot_version.is_some()
+            || snapshot_tag.is_some()
+            || announcement_template.is_some()
             || display.is_some()
             || display_name.is_some();
         let host_layer = needs_host_layer.then_some(HostLayer {
             common: CommonHostLayer {},
             order: hosting,
             github: github_host_layer,
+            gitlab: gitlab_host_layer,
             simple: simple_host_layer,
             force_latest,
+            changelog_from_commits,
+            snapshot_version,
+            snapshot_tag,
+            announcement_template,
             display,
             display_name,
         });
@@ -306,19 +395,33 @@ impl DistMetadata {
             });
         let npm_installer_layer =
             list_to_bool_layer(is_global, &installers, InstallerStyle::Npm, || {
-                if npm_package.is_some() || npm_scope.is_some() || npm_shrinkwrap.is_some() {
+                if npm_package.is_some()
+                    || npm_scope.is_some()
+                    || npm_shrinkwrap.is_some()
+                    || npm_registry.is_some()
+                {
                     Some(NpmInstallerLayer {
                         common: CommonInstallerLayer::default(),
                         package: npm_package,
                         scope: npm_scope,
                         shrinkwrap: npm_shrinkwrap,
+                        registry: npm_registry,
                     })
                 } else {
                     None
                 }
             });
         let msi_installer_layer =
-            list_to_bool_layer(is_global, &installers, InstallerStyle::Msi, || None);
+            list_to_bool_layer(is_global, &installers, InstallerStyle::Msi, || {
+                if msi_builder.is_some() {
+                    Some(MsiInstallerLayer {
+                        common: CommonInstallerLayer::default(),
+                        builder: msi_builder,
+                    })
+                } else {
+                    None
+                }
+            });
         let pkg_installer_layer =
             list_to_bool_layer(is_global, &installers, InstallerStyle::Pkg, || {
                 let MacPkgConfig {
@@ -335,6 +438,37 @@ impl DistMetadata {
                     None
                 }
             });
+        let deb_installer_layer =
+            list_to_bool_layer(is_global, &installers, InstallerStyle::Deb, || None);
+        let rpm_installer_layer =
+            list_to_bool_layer(is_global, &installers, InstallerStyle::Rpm, || None);
+        let scoop_installer_layer =
+            list_to_bool_layer(is_global, &installers, InstallerStyle::Scoop, || {
+                if scoop_bucket.is_some() {
+                    Some(ScoopInstallerLayer {
+                        common: CommonInstallerLayer::default(),
+                        bucket: scoop_bucket,
+                    })
+                } else {
+                    None
+                }
+            });
+        let winget_installer_layer =
+            list_to_bool_layer(is_global, &installers, InstallerStyle::Winget, || {
+                if winget_repo.is_some()
+                    || winget_publisher.is_some()
+                    || winget_package_identifier.is_some()
+                {
+                    Some(WingetInstallerLayer {
+                        common: CommonInstallerLayer::default(),
+                        repo: winget_repo,
+                        publisher: winget_publisher,
+                        package_identifier: winget_package_identifier,
+                    })
+                } else {
+                    None
+                }
+            });
         let powershell_installer_layer =
             list_to_bool_layer(is_global, &installers, InstallerStyle::Powershell, || None);
         let shell_installer_layer =
@@ -345,6 +479,10 @@ impl DistMetadata {
             || powershell_installer_layer.is_some()
             || shell_installer_layer.is_some()
             || pkg_installer_layer.is_some()
+            || deb_installer_layer.is_some()
+            || rpm_installer_layer.is_some()
+            || scoop_installer_layer.is_some()
+            || winget_installer_layer.is_some()
             || install_path.is_some()
             || install_success_msg.is_some()
             || install_libraries.is_some()
@@ -364,6 +502,10 @@ impl DistMetadata {
             powershell: powershell_installer_layer,
             shell: shell_installer_layer,
             pkg: pkg_installer_layer,
+            deb: deb_installer_layer,
+            rpm: rpm_installer_layer,
+            scoop: scoop_installer_layer,
+            winget: winget_installer_layer,
             updater: install_updater,
             always_use_latest_updater,
         });
@@ -373,11 +515,17 @@ impl DistMetadata {
             list_to_bool_layer(is_global, &publish_jobs, PublishStyle::Homebrew, || None);
         let npm_publisher_layer =
             list_to_bool_layer(is_global, &publish_jobs, PublishStyle::Npm, || None);
+        let scoop_publisher_layer =
+            list_to_bool_layer(is_global, &publish_jobs, PublishStyle::Scoop, || None);
+        let winget_publisher_layer =
+            list_to_bool_layer(is_global, &publish_jobs, PublishStyle::Winget, || None);
         let user_layer = list_to_bool_layer_predicate(is_global, &publish_jobs, |job| {
             matches!(job, PublishStyle::User(_))
         });
         let needs_publisher_layer = homebrew_publisher_layer.is_some()
             || npm_publisher_layer.is_some()
+            || scoop_publisher_layer.is_some()
+            || winget_publisher_layer.is_some()
             || user_layer.is_some()
             || publish_prereleases.is_some();
         let publisher_layer = needs_publisher_layer.then_some(PublisherLayer {
@@ -386,12 +534,14 @@ impl DistMetadata {
             },
             homebrew: homebrew_publisher_layer,
             npm: npm_publisher_layer,
+            scoop: scoop_publisher_layer,
+            winget: winget_publisher_layer,
             user: user_layer,
         });
 
         // done!
 
-        TomlLayer {
+        Ok(TomlLayer {
             dist_version: cargo_dist_version,
             dist_url_override: cargo_dist_url_override,
             dist,
@@ -403,7 +553,7 @@ impl DistMetadata {
             hosts: host_layer,
             installers: installer_layer,
             publishers: publisher_layer,
-        }
+        })
     }
 }
 
@@ -17,6 +17,12 @@ pub struct ArchiveConfig {
     pub package_libraries: Vec<LibraryStyle>,
     /// Binaries for a given platform
     pub binaries: SortedMap<String, Vec<String>>,
+    /// A desktop entry to bundle with linux builds, instead of a generated one
+    pub desktop_entry: Option<Utf8PathBuf>,
+    /// An icon to bundle with linux builds
+    pub desktop_icon: Option<Utf8PathBuf>,
+    /// MIME types the app's desktop entry can open
+    pub desktop_mime_types: Vec<String>,
 }
 
 /// archive config (raw from config file)
@@ -54,6 +60,21 @@ pub struct ArchiveLayer {
     /// Binaries for a given platform
     #[serde(skip_serializing_if = "Option::is_none")]
     pub binaries: Option<SortedMap<String, Vec<String>>>,
+
+    /// A FreeDesktop desktop entry (`.desktop` file) to bundle with linux builds
+    ///
+    /// If this isn't set but `desktop-icon` or `desktop-mime-types` are, a desktop
+    /// entry will be generated for the app.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_entry: Option<Utf8PathBuf>,
+
+    /// An icon (`.png` or `.svg`) to bundle with linux builds for the app's desktop entry
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_icon: Option<Utf8PathBuf>,
+
+    /// MIME types the app's desktop entry can open (e.g. `image/png`)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub desktop_mime_types: Option<Vec<String>>,
 }
 
 impl ArchiveConfig {
@@ -66,6 +87,9 @@ impl ArchiveConfig {
             unix_archive: ZipStyle::Tar(CompressionImpl::Xzip),
             package_libraries: vec![],
             binaries: SortedMap::default(),
+            desktop_entry: None,
+            desktop_icon: None,
+            desktop_mime_types: vec![],
         }
     }
 }
@@ -81,6 +105,9 @@ impl ApplyLayer for ArchiveConfig {
             unix_archive,
             package_libraries,
             binaries,
+            desktop_entry,
+            desktop_icon,
+            desktop_mime_types,
         }: Self::Layer,
     ) {
         self.include.apply_val(include);
@@ -89,6 +116,9 @@ impl ApplyLayer for ArchiveConfig {
         self.unix_archive.apply_val(unix_archive);
         self.package_libraries.apply_val(package_libraries);
         self.binaries.apply_val(binaries);
+        self.desktop_entry.apply_opt(desktop_entry);
+        self.desktop_icon.apply_opt(desktop_icon);
+        self.desktop_mime_types.apply_val(desktop_mime_types);
     }
 }
 impl ApplyLayer for ArchiveLayer {
@@ -102,6 +132,9 @@ impl ApplyLayer for ArchiveLayer {
             unix_archive,
             package_libraries,
             binaries,
+            desktop_entry,
+            desktop_icon,
+            desktop_mime_types,
         }: Self::Layer,
     ) {
         self.include.apply_opt(include);
@@ -110,5 +143,8 @@ impl ApplyLayer for ArchiveLayer {
         self.unix_archive.apply_opt(unix_archive);
         self.package_libraries.apply_opt(package_libraries);
         self.binaries.apply_opt(binaries);
+        self.desktop_entry.apply_opt(desktop_entry);
+        self.desktop_icon.apply_opt(desktop_icon);
+        self.desktop_mime_types.apply_opt(desktop_mime_types);
     }
 }
@@ -21,6 +21,10 @@ pub struct WorkspaceArtifactConfig {
     pub source_tarball: bool,
     /// Whether tarballs should include submodules
     pub recursive_tarball: bool,
+    /// Whether to generate an in-toto SLSA provenance statement
+    pub slsa_provenance: bool,
+    /// Whether archives should be byte-for-byte reproducible
+    pub reproducible_archives: bool,
     /// How to checksum
     pub checksum: ChecksumStyle,
 }
@@ -44,6 +48,18 @@ pub struct ArtifactLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub recursive_tarball: Option<bool>,
 
+    /// Whether to generate an in-toto SLSA provenance statement for each release
+    ///
+    /// (defaults to false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub slsa_provenance: Option<bool>,
+
+    /// Whether archives should be byte-for-byte reproducible
+    ///
+    /// (defaults to false)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub reproducible_archives: Option<bool>,
+
     /// Any extra artifacts and their buildscripts
     #[serde(skip_serializing_if = "Option::is_none")]
     pub extra: Option<Vec<ExtraArtifact>>,
@@ -68,6 +84,8 @@ impl WorkspaceArtifactConfig {
         Self {
             source_tarball: true,
             recursive_tarball: false,
+            slsa_provenance: false,
+            reproducible_archives: false,
             checksum: ChecksumStyle::Sha256,
         }
     }
@@ -83,6 +101,8 @@ impl ApplyLayer for AppArtifactConfig {
             // these are all workspace-only
             source_tarball: _,
             recursive_tarball: _,
+            slsa_provenance: _,
+            reproducible_archives: _,
             checksum: _,
         }: Self::Layer,
     ) {
@@ -98,6 +118,8 @@ impl ApplyLayer for WorkspaceArtifactConfig {
         Self::Layer {
             source_tarball,
             recursive_tarball,
+            slsa_provenance,
+            reproducible_archives,
             checksum,
             // these are all app-only
             archives: _,
@@ -106,6 +128,8 @@ impl ApplyLayer for WorkspaceArtifactConfig {
     ) {
         self.source_tarball.apply_val(source_tarball);
         self.recursive_tarball.apply_val(recursive_tarball);
+        self.slsa_provenance.apply_val(slsa_provenance);
+        self.reproducible_archives.apply_val(reproducible_archives);
         self.checksum.apply_val(checksum);
     }
 }
@@ -17,8 +17,16 @@ pub struct WorkspaceBuildConfig {
     pub ssldotcom_windows_sign: Option<ProductionMode>,
     /// whether to sign macos binaries with apple
     pub macos_sign: bool,
+    /// minisign public key to make detached signatures of artifacts with
+    pub minisign_public_key: Option<String>,
     /// Overrides the minimum supported glibc version.
     pub min_glibc_version: Option<MinGlibcVersion>,
+    /// Whether to fail the build if a binary requires a newer glibc than min_glibc_version.
+    pub enforce_min_glibc_version: bool,
+    /// Whether to split the debuginfo of Linux binaries out into .debug symbols artifacts.
+    pub split_debuginfo: bool,
+    /// A directory to lay out split debuginfo in by build-id (debuginfod-style).
+    pub symbol_store: Option<Utf8PathBuf>,
     /// Whether to generate OmniBOR artifact IDs.
     pub omnibor: bool,
 }
@@ -47,6 +55,8 @@ pub struct BuildConfigInheritable {
     pub ssldotcom_windows_sign: Option<ProductionMode>,
     /// whether to sign macos binaries with apple
     pub macos_sign: Option<bool>,
+    /// minisign public key to make detached signatures of artifacts with
+    pub minisign_public_key: Option<String>,
     /// cargo builds
     pub cargo: Option<CargoBuildLayer>,
     /// generic builds
@@ -55,6 +65,12 @@ pub struct BuildConfigInheritable {
     pub system_dependencies: SystemDependencies,
     /// Overrides the minimum supported glibc version.
     pub min_glibc_version: Option<MinGlibcVersion>,
+    /// Whether to fail the build if a binary requires a newer glibc than min_glibc_version.
+    pub enforce_min_glibc_version: Option<bool>,
+    /// Whether to split the debuginfo of Linux binaries out into .debug symbols artifacts.
+    pub split_debuginfo: Option<bool>,
+    /// A directory to lay out split debuginfo in by build-id (debuginfod-style).
+    pub symbol_store: Option<Utf8PathBuf>,
     /// Whether to generate OmniBOR artifact IDs.
     pub omnibor: Option<bool>,
 }
@@ -75,6 +91,10 @@ pub struct BuildLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub macos_sign: Option<bool>,
 
+    /// minisign public key to make detached signatures of artifacts with
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub minisign_public_key: Option<String>,
+
     /// cargo builds
     #[serde(skip_serializing_if = "Option::is_none")]
     pub cargo: Option<BoolOr<CargoBuildLayer>>,
@@ -88,6 +108,15 @@ pub struct BuildLayer {
     /// Overrides the minimum supported glibc version.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub min_glibc_version: Option<MinGlibcVersion>,
+    /// Whether to fail the build if a binary requires a newer glibc than min_glibc_version.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub enforce_min_glibc_version: Option<bool>,
+    /// Whether to split the debuginfo of Linux binaries out into .debug symbols artifacts.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub split_debuginfo: Option<bool>,
+    /// A directory to lay out split debuginfo in by build-id (debuginfod-style).
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub symbol_store: Option<Utf8PathBuf>,
     /// Whether to generate OmniBOR artifact IDs.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub omnibor: Option<bool>,
@@ -102,7 +131,11 @@ impl BuildConfigInheritable {
             system_dependencies: Default::default(),
             ssldotcom_windows_sign: None,
             macos_sign: None,
+            minisign_public_key: None,
             min_glibc_version: None,
+            enforce_min_glibc_version: None,
+            split_debuginfo: None,
+            symbol_store: None,
             omnibor: None,
         }
     }
@@ -115,7 +148,11 @@ impl BuildConfigInheritable {
             system_dependencies: Default::default(),
             ssldotcom_windows_sign: None,
             macos_sign: None,
+            minisign_public_key: None,
             min_glibc_version: None,
+            enforce_min_glibc_version: None,
+            split_debuginfo: None,
+            symbol_store: None,
             omnibor: None,
         }
     }
@@ -129,7 +166,11 @@ impl BuildConfigInheritable {
             cargo,
             ssldotcom_windows_sign,
             macos_sign,
+            minisign_public_key,
             min_glibc_version,
+            enforce_min_glibc_version,
+            split_debuginfo,
+            symbol_store,
             omnibor,
             // local-only
             generic: _,
@@ -142,8 +183,12 @@ impl BuildConfigInheritable {
         WorkspaceBuildConfig {
             cargo: cargo_out,
             macos_sign: macos_sign.unwrap_or(false),
+            minisign_public_key,
             ssldotcom_windows_sign,
             min_glibc_version,
+            enforce_min_glibc_version: enforce_min_glibc_version.unwrap_or(false),
+            split_debuginfo: split_debuginfo.unwrap_or(false),
+            symbol_store,
             omnibor: omnibor.unwrap_or(false),
         }
     }
@@ -163,6 +208,10 @@ impl BuildConfigInheritable {
             // local-only
             ssldotcom_windows_sign: _,
             macos_sign: _,
+            minisign_public_key: _,
+            enforce_min_glibc_version: _,
+            split_debuginfo: _,
+            symbol_store: _,
         } = self;
         let mut cargo_out = AppCargoBuildConfig::defaults_for_package(workspaces, pkg_idx, &common);
         if let Some(cargo) = cargo {
@@ -194,7 +243,11 @@ impl ApplyLayer for BuildConfigInheritable {
             system_dependencies,
             ssldotcom_windows_sign,
             macos_sign,
+            minisign_public_key,
             min_glibc_version,
+            enforce_min_glibc_version,
+            split_debuginfo,
+            symbol_store,
             omnibor,
         }: Self::Layer,
     ) {
@@ -205,7 +258,12 @@ impl ApplyLayer for BuildConfigInheritable {
         self.ssldotcom_windows_sign
             .apply_opt(ssldotcom_windows_sign);
         self.macos_sign.apply_opt(macos_sign);
+        self.minisign_public_key.apply_opt(minisign_public_key);
         self.min_glibc_version.apply_opt(min_glibc_version);
+        self.enforce_min_glibc_version
+            .apply_opt(enforce_min_glibc_version);
+        self.split_debuginfo.apply_opt(split_debuginfo);
+        self.symbol_store.apply_opt(symbol_store);
         self.omnibor.apply_opt(omnibor);
     }
 }
@@ -0,0 +1,60 @@
+//! gitlab ci config
+
+use cargo_dist_schema::TripleName;
+
+use super::*;
+
+/// gitlab ci config (raw from file)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GitlabCiLayer {
+    /// Common options
+    #[serde(flatten)]
+    pub common: CommonCiLayer,
+
+    /// Custom GitLab runner tags, mapped by triple target
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub runners: Option<SortedMap<TripleName, String>>,
+}
+
+/// gitlab ci config (final)
+#[derive(Debug, Default, Clone)]
+pub struct GitlabCiConfig {
+    /// Common options
+    pub common: CommonCiConfig,
+
+    /// Custom GitLab runner tags, mapped by triple target
+    pub runners: SortedMap<TripleName, String>,
+}
+
+impl GitlabCiConfig {
+    /// Get defaults for the given package
+    pub fn defaults_for_workspace(_workspaces: &WorkspaceGraph, common: &CommonCiConfig) -> Self {
+        Self {
+            common: common.clone(),
+            runners: Default::default(),
+        }
+    }
+}
+
+impl ApplyLayer for GitlabCiConfig {
+    type Layer = GitlabCiLayer;
+    fn apply_layer(&mut self, Self::Layer { common, runners }: Self::Layer) {
+        self.common.apply_layer(common);
+        self.runners.apply_val(runners);
+    }
+}
+impl ApplyLayer for GitlabCiLayer {
+    type Layer = GitlabCiLayer;
+    fn apply_layer(&mut self, Self::Layer { common, runners }: Self::Layer) {
+        self.common.apply_layer(common);
+        self.runners.apply_opt(runners);
+    }
+}
+
+impl std::ops::Deref for GitlabCiConfig {
+    type Target = CommonCiConfig;
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
@@ -0,0 +1,48 @@
+//! GitLab host
+
+use super::*;
+
+/// GitLab host config (raw)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct GitlabHostLayer {
+    /// Common options
+    #[serde(flatten)]
+    pub common: CommonHostLayer,
+}
+
+/// GitLab host config (final)
+#[derive(Debug, Default, Clone)]
+pub struct GitlabHostConfig {
+    /// Common options
+    pub common: CommonHostConfig,
+}
+
+impl GitlabHostConfig {
+    /// Get defaults for the given package
+    pub fn defaults_for_workspace(_workspaces: &WorkspaceGraph, common: &CommonHostConfig) -> Self {
+        Self {
+            common: common.clone(),
+        }
+    }
+}
+
+impl ApplyLayer for GitlabHostConfig {
+    type Layer = GitlabHostLayer;
+    fn apply_layer(&mut self, Self::Layer { common }: Self::Layer) {
+        self.common.apply_layer(common);
+    }
+}
+impl ApplyLayer for GitlabHostLayer {
+    type Layer = GitlabHostLayer;
+    fn apply_layer(&mut self, Self::Layer { common }: Self::Layer) {
+        self.common.apply_layer(common);
+    }
+}
+
+impl std::ops::Deref for GitlabHostConfig {
+    type Target = CommonHostConfig;
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
@@ -1,11 +1,13 @@
 //! host config
 
 pub mod github;
+pub mod gitlab;
 pub mod simple;
 
 use super::*;
 
 use github::*;
+use gitlab::*;
 use simple::*;
 
 #[derive(Debug, Clone)]
@@ -22,10 +24,20 @@ pub struct AppHostConfig {
 pub struct WorkspaceHostConfig {
     /// Always regard releases as stable
     pub force_latest: bool,
+    /// Generate the announcement's changelog from Conventional Commits
+    pub changelog_from_commits: bool,
+    /// A template for the version of untagged (snapshot) releases
+    pub snapshot_version: Option<String>,
+    /// A rolling tag to announce untagged (snapshot) releases under
+    pub snapshot_tag: Option<String>,
+    /// A template to render the GitHub/GitLab Release body with
+    pub announcement_template: Option<Utf8PathBuf>,
     /// The order the hosts are preferred in for downloads
     pub order: Vec<HostingStyle>,
     /// github host config (github releases)
     pub github: Option<GithubHostConfig>,
+    /// gitlab host config (gitlab releases)
+    pub gitlab: Option<GitlabHostConfig>,
     /// simple host config
     pub simple: Option<SimpleHostConfig>,
 }
@@ -36,6 +48,14 @@ pub struct HostConfigInheritable {
     pub common: CommonHostConfig,
     /// Always regard releases as stable
     pub force_latest: Option<bool>,
+    /// Generate the announcement's changelog from Conventional Commits
+    pub changelog_from_commits: Option<bool>,
+    /// A template for the version of untagged (snapshot) releases
+    pub snapshot_version: Option<String>,
+    /// A rolling tag to announce untagged (snapshot) releases under
+    pub snapshot_tag: Option<String>,
+    /// A template to render the GitHub/GitLab Release body with
+    pub announcement_template: Option<Utf8PathBuf>,
     /// Whether artifacts/installers for this app should be displayed in release bodies
     pub display: Option<bool>,
     /// How to refer to the app in release bodies
@@ -44,6 +64,8 @@ pub struct HostConfigInheritable {
     pub order: Option<Vec<HostingStyle>>,
     /// github hosting
     pub github: Option<GithubHostLayer>,
+    /// gitlab hosting
+    pub gitlab: Option<GitlabHostLayer>,
     /// simple hosting
     pub simple: Option<SimpleHostLayer>,
 }
@@ -68,6 +90,52 @@ pub struct HostLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub force_latest: Option<bool>,
 
+    /// Generate the announcement's changelog from Conventional Commits
+    ///
+    /// (defaults to false)
+    ///
+    /// Ordinarily, dist looks for the version being announced in your
+    /// CHANGELOG/RELEASES file. If you don't keep one, this builds the
+    /// release notes from the features, fixes, performance improvements,
+    /// and breaking changes committed since the previous release's tag.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub changelog_from_commits: Option<bool>,
+
+    /// A template for the version of untagged (snapshot) releases
+    ///
+    /// (defaults to `{version}-nightly.{date}.{commit_count}` when `snapshot-tag`
+    /// is set, and otherwise to adding a unix timestamp to the prerelease of
+    /// `{version}`, like `1.2.0-alpha.1760659200`)
+    ///
+    /// When releasing untagged pushes (see `release-branch`), every release
+    /// needs a unique prerelease version. This template can use `{version}`
+    /// (the highest version being released), `{date}` (the commit's date as
+    /// YYYYMMDD), `{short_sha}`, `{commit_count}`, and `{branch}`.
+    ///
+    /// `{short_sha}` is prefixed with a `g` (like `git describe`), since an
+    /// all-digit sha with a leading zero isn't a valid prerelease identifier.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_version: Option<String>,
+
+    /// A rolling tag to announce untagged (snapshot) releases under
+    ///
+    /// (defaults to none, giving each snapshot its own versioned tag)
+    ///
+    /// With this set (usually to `"nightly"`), untagged pushes replace the
+    /// release at this tag instead of making a new one, and installers
+    /// download from it.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub snapshot_tag: Option<String>,
+
+    /// A jinja2 template to render the GitHub/GitLab Release body with
+    ///
+    /// (defaults to dist's built-in layout)
+    ///
+    /// The template is given the dist-manifest and a digest of the releases
+    /// being announced, with their installers, artifacts, download urls, and checksums.
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub announcement_template: Option<Utf8PathBuf>,
+
     /// Whether artifacts/installers for this app should be displayed in release bodies
     #[serde(skip_serializing_if = "Option::is_none")]
     pub display: Option<bool>,
@@ -83,6 +151,10 @@ pub struct HostLayer {
     #[serde(skip_serializing_if = "Option::is_none")]
     pub github: Option<BoolOr<GithubHostLayer>>,
 
+    /// gitlab hosting
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub gitlab: Option<BoolOr<GitlabHostLayer>>,
+
     /// simple hosting
     #[serde(skip_serializing_if = "Option::is_none")]
     pub simple: Option<BoolOr<SimpleHostLayer>>,
@@ -93,9 +165,14 @@ impl HostConfigInheritable {
         Self {
             common: CommonHostConfig::defaults_for_package(workspaces, pkg_idx),
             github: None,
+            gitlab: None,
             simple: None,
             order: None,
             force_latest: None,
+            changelog_from_commits: None,
+            snapshot_version: None,
+            snapshot_tag: None,
+            announcement_template: None,
             display: None,
             display_name: None,
         }
@@ -105,9 +182,14 @@ impl HostConfigInheritable {
         Self {
             common: CommonHostConfig::defaults_for_workspace(workspaces),
             github: None,
+            gitlab: None,
             simple: None,
             order: None,
             force_latest: None,
+            changelog_from_commits: None,
+            snapshot_version: None,
+            snapshot_tag: None,
+            announcement_template: None,
             display: None,
             display_name: None,
         }
@@ -121,9 +203,14 @@ impl HostConfigInheritable {
         let Self {
             common: _,
             github: _,
+            gitlab: _,
             simple: _,
             order: _,
             force_latest: _,
+            changelog_from_commits: _,
+            snapshot_version: _,
+            snapshot_tag: _,
+            announcement_template: _,
             display,
             display_name,
         } = self;
@@ -142,9 +229,14 @@ impl HostConfigInheritable {
         let Self {
             common,
             github,
+            gitlab,
             simple,
             order,
             force_latest,
+            changelog_from_commits,
+            snapshot_version,
+            snapshot_tag,
+            announcement_template,
             display: _,
             display_name: _,
         } = self;
@@ -153,6 +245,11 @@ impl HostConfigInheritable {
             default.apply_layer(github);
             default
         });
+        let gitlab = gitlab.map(|gitlab| {
+            let mut default = GitlabHostConfig::defaults_for_workspace(workspaces, &common);
+            default.apply_layer(gitlab);
+            default
+        });
         let simple = simple.map(|simple| {
             let mut default = SimpleHostConfig::defaults_for_workspace(workspaces, &common);
             default.apply_layer(simple);
@@ -160,9 +257,14 @@ impl HostConfigInheritable {
         });
         WorkspaceHostConfig {
             github,
+            gitlab,
             simple,
             order: order.unwrap_or_default(),
             force_latest: force_latest.unwrap_or(false),
+            changelog_from_commits: changelog_from_commits.unwrap_or(false),
+            snapshot_version,
+            snapshot_tag,
+            announcement_template,
         }
     }
 }
@@ -175,17 +277,28 @@ impl ApplyLayer for HostConfigInheritable {
             common,
             order,
             github,
+            gitlab,
             simple,
             force_latest,
+            changelog_from_commits,
+            snapshot_version,
+            snapshot_tag,
+            announcement_template,
             display,
             display_name,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.github.apply_bool_layer(github);
+        self.gitlab.apply_bool_layer(gitlab);
         self.simple.apply_bool_layer(simple);
         self.order.apply_opt(order);
         self.force_latest.apply_opt(force_latest);
+        self.changelog_from_commits
+            .apply_opt(changelog_from_commits);
+        self.snapshot_version.apply_opt(snapshot_version);
+        self.snapshot_tag.apply_opt(snapshot_tag);
+        self.announcement_template.apply_opt(announcement_template);
         self.display.apply_opt(display);
         self.display_name.apply_opt(display_name);
     }
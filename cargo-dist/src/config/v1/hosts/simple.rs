@@ -12,6 +12,10 @@ pub struct SimpleHostLayer {
 
     /// URL to download from
     pub download_url: Option<String>,
+
+    /// S3-compatible object storage to upload to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub s3: Option<SimpleS3Layer>,
 }
 
 /// Simple host config (final)
@@ -22,6 +26,70 @@ pub struct SimpleHostConfig {
 
     /// URL to download from
     pub download_url: String,
+
+    /// S3-compatible object storage to upload to
+    pub s3: Option<SimpleS3Config>,
+}
+
+/// S3-compatible object storage for the simple host (raw)
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SimpleS3Layer {
+    /// The URL of the S3 API, e.g. `https://s3.us-east-1.amazonaws.com`
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub endpoint: Option<String>,
+    /// The bucket to upload to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub bucket: Option<String>,
+    /// The prefix of the keys to upload to
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub prefix: Option<String>,
+    /// The region to sign requests for
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub region: Option<String>,
+}
+
+/// S3-compatible object storage for the simple host (final)
+#[derive(Debug, Clone)]
+pub struct SimpleS3Config {
+    /// The URL of the S3 API, e.g. `https://s3.us-east-1.amazonaws.com`
+    pub endpoint: String,
+    /// The bucket to upload to
+    pub bucket: String,
+    /// The prefix of the keys to upload to (no leading or trailing `/`)
+    pub prefix: String,
+    /// The region to sign requests for
+    pub region: String,
+}
+
+impl Default for SimpleS3Config {
+    fn default() -> Self {
+        Self {
+            endpoint: String::new(),
+            bucket: String::new(),
+            prefix: String::new(),
+            // What MinIO and most other S3-compatible stores expect
+            region: "us-east-1".to_owned(),
+        }
+    }
+}
+
+impl SimpleS3Config {
+    /// The key of a file uploaded for the release `dir` (e.g. the tag, or `latest`)
+    pub fn key(&self, dir: &str, file_name: &str) -> String {
+        if self.prefix.is_empty() {
+            format!("{dir}/{file_name}")
+        } else {
+            format!("{}/{dir}/{file_name}", self.prefix)
+        }
+    }
+
+    /// The (path-style) URL files for the release `dir` can be downloaded from
+    pub fn download_url(&self, dir: &str) -> String {
+        let key = self.key(dir, "");
+        let key = key.trim_end_matches('/');
+        format!("{}/{}/{key}", self.endpoint, self.bucket)
+    }
 }
 
 impl SimpleHostConfig {
@@ -30,6 +98,7 @@ impl SimpleHostConfig {
         Self {
             common: common.clone(),
             download_url: String::new(),
+            s3: None,
         }
     }
 }
@@ -41,10 +110,14 @@ impl ApplyLayer for SimpleHostConfig {
         Self::Layer {
             common,
             download_url,
+            s3,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.download_url.apply_val(download_url);
+        if let Some(s3) = s3 {
+            self.s3.get_or_insert_with(Default::default).apply_layer(s3);
+        }
     }
 }
 impl ApplyLayer for SimpleHostLayer {
@@ -54,10 +127,51 @@ impl ApplyLayer for SimpleHostLayer {
         Self::Layer {
             common,
             download_url,
+            s3,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.download_url.apply_opt(download_url);
+        if let Some(s3) = s3 {
+            self.s3.get_or_insert_with(Default::default).apply_layer(s3);
+        }
+    }
+}
+
+impl ApplyLayer for SimpleS3Config {
+    type Layer = SimpleS3Layer;
+    fn apply_layer(
+        &mut self,
+        Self::Layer {
+            endpoint,
+            bucket,
+            prefix,
+            region,
+        }: Self::Layer,
+    ) {
+        self.endpoint
+            .apply_val(endpoint.map(|e| e.trim_end_matches('/').to_owned()));
+        self.bucket.apply_val(bucket);
+        self.prefix
+            .apply_val(prefix.map(|p| p.trim_matches('/').to_owned()));
+        self.region.apply_val(region);
+    }
+}
+impl ApplyLayer for SimpleS3Layer {
+    type Layer = SimpleS3Layer;
+    fn apply_layer(
+        &mut self,
+        Self::Layer {
+            endpoint,
+            bucket,
+            prefix,
+            region,
+        }: Self::Layer,
+    ) {
+        self.endpoint.apply_opt(endpoint);
+        self.bucket.apply_opt(bucket);
+        self.prefix.apply_opt(prefix);
+        self.region.apply_opt(region);
     }
 }
 
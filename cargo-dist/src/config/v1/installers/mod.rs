@@ -1,20 +1,28 @@
 //! installer config
 
+pub mod deb;
 pub mod homebrew;
 pub mod msi;
 pub mod npm;
 pub mod pkg;
 pub mod powershell;
+pub mod rpm;
+pub mod scoop;
 pub mod shell;
+pub mod winget;
 
 use super::*;
 
+use deb::*;
 use homebrew::*;
 use msi::*;
 use npm::*;
 use pkg::*;
 use powershell::*;
+use rpm::*;
+use scoop::*;
 use shell::*;
+use winget::*;
 
 /// workspace installer config (final)
 #[derive(Debug, Default, Clone)]
@@ -37,8 +45,16 @@ pub struct AppInstallerConfig {
     pub powershell: Option<PowershellInstallerConfig>,
     /// shell installer
     pub shell: Option<ShellInstallerConfig>,
-    /// shell installer
+    /// pkg installer
     pub pkg: Option<PkgInstallerConfig>,
+    /// deb installer
+    pub deb: Option<DebInstallerConfig>,
+    /// rpm installer
+    pub rpm: Option<RpmInstallerConfig>,
+    /// scoop installer
+    pub scoop: Option<ScoopInstallerConfig>,
+    /// winget installer
+    pub winget: Option<WingetInstallerConfig>,
 }
 
 /// installer config (inheritance not yet applied)
@@ -58,6 +74,14 @@ pub struct InstallerConfigInheritable {
     pub shell: Option<ShellInstallerLayer>,
     /// pkg installer
     pub pkg: Option<PkgInstallerLayer>,
+    /// deb installer
+    pub deb: Option<DebInstallerLayer>,
+    /// rpm installer
+    pub rpm: Option<RpmInstallerLayer>,
+    /// scoop installer
+    pub scoop: Option<ScoopInstallerLayer>,
+    /// winget installer
+    pub winget: Option<WingetInstallerLayer>,
     /// Whether to install an updater program alongside the software
     pub updater: bool,
     /// Whether to always use the latest version instead of a fixed version
@@ -83,6 +107,14 @@ pub struct InstallerLayer {
     pub shell: Option<BoolOr<ShellInstallerLayer>>,
     /// pkg installer
     pub pkg: Option<BoolOr<PkgInstallerLayer>>,
+    /// deb installer
+    pub deb: Option<BoolOr<DebInstallerLayer>>,
+    /// rpm installer
+    pub rpm: Option<BoolOr<RpmInstallerLayer>>,
+    /// scoop installer
+    pub scoop: Option<BoolOr<ScoopInstallerLayer>>,
+    /// winget installer
+    pub winget: Option<BoolOr<WingetInstallerLayer>>,
     /// Whether to install an updater program alongside the software
     #[serde(skip_serializing_if = "Option::is_none")]
     pub updater: Option<bool>,
@@ -109,6 +141,10 @@ impl InstallerConfigInheritable {
             powershell: None,
             shell: None,
             pkg: None,
+            deb: None,
+            rpm: None,
+            scoop: None,
+            winget: None,
             updater: false,
             always_use_latest_updater: false,
         }
@@ -130,6 +166,10 @@ impl InstallerConfigInheritable {
             powershell: _,
             shell: _,
             pkg: _,
+            deb: _,
+            rpm: _,
+            scoop: _,
+            winget: _,
         } = self;
 
         WorkspaceInstallerConfig {
@@ -151,6 +191,10 @@ impl InstallerConfigInheritable {
             powershell,
             shell,
             pkg,
+            deb,
+            rpm,
+            scoop,
+            winget,
             // global-only
             updater: _,
             always_use_latest_updater: _,
@@ -191,6 +235,30 @@ impl InstallerConfigInheritable {
             default.apply_layer(pkg);
             default
         });
+        let deb = deb.map(|deb| {
+            let mut default =
+                DebInstallerConfig::defaults_for_package(workspaces, pkg_idx, &common);
+            default.apply_layer(deb);
+            default
+        });
+        let rpm = rpm.map(|rpm| {
+            let mut default =
+                RpmInstallerConfig::defaults_for_package(workspaces, pkg_idx, &common);
+            default.apply_layer(rpm);
+            default
+        });
+        let scoop = scoop.map(|scoop| {
+            let mut default =
+                ScoopInstallerConfig::defaults_for_package(workspaces, pkg_idx, &common);
+            default.apply_layer(scoop);
+            default
+        });
+        let winget = winget.map(|winget| {
+            let mut default =
+                WingetInstallerConfig::defaults_for_package(workspaces, pkg_idx, &common);
+            default.apply_layer(winget);
+            default
+        });
         AppInstallerConfig {
             homebrew,
             msi,
@@ -198,6 +266,10 @@ impl InstallerConfigInheritable {
             powershell,
             shell,
             pkg,
+            deb,
+            rpm,
+            scoop,
+            winget,
         }
     }
 }
@@ -213,6 +285,10 @@ impl ApplyLayer for InstallerConfigInheritable {
             powershell,
             shell,
             pkg,
+            deb,
+            rpm,
+            scoop,
+            winget,
             updater,
             always_use_latest_updater,
         }: Self::Layer,
@@ -224,6 +300,10 @@ impl ApplyLayer for InstallerConfigInheritable {
         self.powershell.apply_bool_layer(powershell);
         self.shell.apply_bool_layer(shell);
         self.pkg.apply_bool_layer(pkg);
+        self.deb.apply_bool_layer(deb);
+        self.rpm.apply_bool_layer(rpm);
+        self.scoop.apply_bool_layer(scoop);
+        self.winget.apply_bool_layer(winget);
         self.updater.apply_val(updater);
         self.always_use_latest_updater
             .apply_val(always_use_latest_updater);
@@ -9,12 +9,17 @@ pub struct MsiInstallerLayer {
     /// Common options
     #[serde(flatten)]
     pub common: CommonInstallerLayer,
+    /// How to build the msi (defaults to "wix")
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub builder: Option<MsiBuilder>,
 }
 /// Options for msi installer
 #[derive(Debug, Default, Clone)]
 pub struct MsiInstallerConfig {
     /// Common options
     pub common: CommonInstallerConfig,
+    /// How to build the msi
+    pub builder: MsiBuilder,
 }
 
 impl MsiInstallerConfig {
@@ -26,20 +31,23 @@ impl MsiInstallerConfig {
     ) -> Self {
         Self {
             common: common.clone(),
+            builder: MsiBuilder::default(),
         }
     }
 }
 
 impl ApplyLayer for MsiInstallerConfig {
     type Layer = MsiInstallerLayer;
-    fn apply_layer(&mut self, Self::Layer { common }: Self::Layer) {
+    fn apply_layer(&mut self, Self::Layer { common, builder }: Self::Layer) {
         self.common.apply_layer(common);
+        self.builder.apply_val(builder);
     }
 }
 impl ApplyLayer for MsiInstallerLayer {
     type Layer = MsiInstallerLayer;
-    fn apply_layer(&mut self, Self::Layer { common }: Self::Layer) {
+    fn apply_layer(&mut self, Self::Layer { common, builder }: Self::Layer) {
         self.common.apply_layer(common);
+        self.builder.apply_opt(builder);
     }
 }
 
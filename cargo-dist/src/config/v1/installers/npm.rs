@@ -21,6 +21,10 @@ pub struct NpmInstallerLayer {
     /// Whether to generate an npm-shrinkwrap.json
     #[serde(skip_serializing_if = "Option::is_none")]
     pub shrinkwrap: Option<bool>,
+
+    /// The npm registry to publish to, instead of npmjs.com
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub registry: Option<String>,
 }
 
 /// Options for npm installer (final)
@@ -37,6 +41,9 @@ pub struct NpmInstallerConfig {
 
     /// Whether to generate an npm-shrinkwrap.json
     pub shrinkwrap: bool,
+
+    /// The npm registry to publish to, instead of npmjs.com
+    pub registry: Option<String>,
 }
 
 impl NpmInstallerConfig {
@@ -52,6 +59,7 @@ impl NpmInstallerConfig {
             package: pkg.name.clone(),
             scope: pkg.npm_scope.clone(),
             shrinkwrap: true,
+            registry: None,
         }
     }
 }
@@ -65,12 +73,14 @@ impl ApplyLayer for NpmInstallerConfig {
             scope,
             package,
             shrinkwrap,
+            registry,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.scope.apply_opt(scope);
         self.package.apply_val(package);
         self.shrinkwrap.apply_val(shrinkwrap);
+        self.registry.apply_opt(registry);
     }
 }
 impl ApplyLayer for NpmInstallerLayer {
@@ -82,12 +92,14 @@ impl ApplyLayer for NpmInstallerLayer {
             scope,
             package,
             shrinkwrap,
+            registry,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.scope.apply_opt(scope);
         self.package.apply_opt(package);
         self.shrinkwrap.apply_opt(shrinkwrap);
+        self.registry.apply_opt(registry);
     }
 }
 
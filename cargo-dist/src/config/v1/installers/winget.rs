@@ -0,0 +1,88 @@
+//! winget installer config
+
+use super::*;
+
+/// Options for winget installer
+#[derive(Debug, Default, Clone, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WingetInstallerLayer {
+    /// Common options
+    #[serde(flatten)]
+    pub common: CommonInstallerLayer,
+    /// A GitHub repository (in the layout of microsoft/winget-pkgs) to push the manifests to, if built
+    pub repo: Option<String>,
+    /// The publisher name to use in the manifests
+    pub publisher: Option<String>,
+    /// Customize the winget package identifier (defaults to `Publisher.AppName`)
+    pub package_identifier: Option<String>,
+}
+/// Options for winget installer
+#[derive(Debug, Default, Clone)]
+pub struct WingetInstallerConfig {
+    /// Common options
+    pub common: CommonInstallerConfig,
+    /// A GitHub repository (in the layout of microsoft/winget-pkgs) to push the manifests to, if built
+    pub repo: Option<String>,
+    /// The publisher name to use in the manifests
+    pub publisher: Option<String>,
+    /// Customize the winget package identifier (defaults to `Publisher.AppName`)
+    pub package_identifier: Option<String>,
+}
+
+impl WingetInstallerConfig {
+    /// Get defaults for the given package
+    pub fn defaults_for_package(
+        _workspaces: &WorkspaceGraph,
+        _pkg_idx: PackageIdx,
+        common: &CommonInstallerConfig,
+    ) -> Self {
+        Self {
+            common: common.clone(),
+            repo: None,
+            publisher: None,
+            package_identifier: None,
+        }
+    }
+}
+
+impl ApplyLayer for WingetInstallerConfig {
+    type Layer = WingetInstallerLayer;
+    fn apply_layer(
+        &mut self,
+        Self::Layer {
+            common,
+            repo,
+            publisher,
+            package_identifier,
+        }: Self::Layer,
+    ) {
+        self.common.apply_layer(common);
+        self.repo.apply_opt(repo);
+        self.publisher.apply_opt(publisher);
+        self.package_identifier.apply_opt(package_identifier);
+    }
+}
+impl ApplyLayer for WingetInstallerLayer {
+    type Layer = WingetInstallerLayer;
+    fn apply_layer(
+        &mut self,
+        Self::Layer {
+            common,
+            repo,
+            publisher,
+            package_identifier,
+        }: Self::Layer,
+    ) {
+        self.common.apply_layer(common);
+        self.repo.apply_opt(repo);
+        self.publisher.apply_opt(publisher);
+        self.package_identifier.apply_opt(package_identifier);
+    }
+}
+
+impl std::ops::Deref for WingetInstallerConfig {
+    type Target = CommonInstallerConfig;
+    fn deref(&self) -> &Self::Target {
+        &self.common
+    }
+}
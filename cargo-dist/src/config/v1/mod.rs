@@ -413,11 +413,11 @@ pub struct TomlLayer {
     /// The inputs should be valid rustc target triples (see `rustc --print target-list`) such
     /// as `x86_64-pc-windows-msvc`, `aarch64-apple-darwin`, or `x86_64-unknown-linux-gnu`.
     ///
-    /// FIXME: We should also accept one magic target: `universal2-apple-darwin`. This will induce
+    /// We also accept one magic target: `universal2-apple-darwin`. This will induce
     /// us to build `x86_64-apple-darwin` and `aarch64-apple-darwin` (arm64) and then combine
-    /// them into a "universal" binary that can run on either arch (using apple's `lipo` tool).
+    /// them into a "universal" binary that can run on either arch.
     ///
-    /// FIXME: Allow higher level requests like "[macos, windows, linux] x [x86_64, aarch64]"?
+    /// Any target groups in the original config have already been expanded into triples.
     #[serde(skip_serializing_if = "Option::is_none")]
     pub targets: Option<Vec<TripleName>>,
 
@@ -468,6 +468,9 @@ impl TomlLayer {
             }
         }
         if let Some(hosts) = &mut self.hosts {
+            if let Some(path) = &mut hosts.announcement_template {
+                make_path_relative_to(path, base_path);
+            }
             if let Some(BoolOr::Val(github)) = &mut hosts.github {
                 if let Some(path) = &mut github.submodule_path {
                     make_path_relative_to(path, base_path);
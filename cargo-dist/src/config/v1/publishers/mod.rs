@@ -2,13 +2,17 @@
 
 pub mod homebrew;
 pub mod npm;
+pub mod scoop;
 pub mod user;
+pub mod winget;
 
 use super::*;
 
 use homebrew::*;
 use npm::*;
+use scoop::*;
 use user::*;
+use winget::*;
 
 /// the final publisher config
 #[derive(Debug, Default, Clone, PartialEq, Eq)]
@@ -17,6 +21,10 @@ pub struct PublisherConfig {
     pub homebrew: Option<HomebrewPublisherConfig>,
     /// npm publisher
     pub npm: Option<NpmPublisherConfig>,
+    /// scoop publisher
+    pub scoop: Option<ScoopPublisherConfig>,
+    /// winget publisher
+    pub winget: Option<WingetPublisherConfig>,
     /// user specified publisher
     pub user: Option<UserPublisherConfig>,
 }
@@ -32,6 +40,10 @@ pub struct PublisherConfigInheritable {
     pub homebrew: Option<HomebrewPublisherLayer>,
     /// npm publisher
     pub npm: Option<NpmPublisherLayer>,
+    /// scoop publisher
+    pub scoop: Option<ScoopPublisherLayer>,
+    /// winget publisher
+    pub winget: Option<WingetPublisherLayer>,
     /// user specified publisher
     pub user: Option<UserPublisherLayer>,
 }
@@ -46,6 +58,10 @@ pub struct PublisherLayer {
     pub homebrew: Option<BoolOr<HomebrewPublisherLayer>>,
     /// npm publisher
     pub npm: Option<BoolOr<NpmPublisherLayer>>,
+    /// scoop publisher
+    pub scoop: Option<BoolOr<ScoopPublisherLayer>>,
+    /// winget publisher
+    pub winget: Option<BoolOr<WingetPublisherLayer>>,
     /// user-specified publisher
     pub user: Option<BoolOr<UserPublisherLayer>>,
 }
@@ -56,6 +72,8 @@ impl PublisherConfigInheritable {
             common: CommonPublisherConfig::defaults_for_package(workspaces, pkg_idx),
             homebrew: None,
             npm: None,
+            scoop: None,
+            winget: None,
             user: None,
         }
     }
@@ -69,6 +87,8 @@ impl PublisherConfigInheritable {
             common,
             homebrew,
             npm,
+            scoop,
+            winget,
             user,
         } = self;
         let homebrew = homebrew.map(|homebrew| {
@@ -83,6 +103,18 @@ impl PublisherConfigInheritable {
             default.apply_layer(npm);
             default
         });
+        let scoop = scoop.map(|scoop| {
+            let mut default =
+                ScoopPublisherConfig::defaults_for_package(workspaces, pkg_idx, &common);
+            default.apply_layer(scoop);
+            default
+        });
+        let winget = winget.map(|winget| {
+            let mut default =
+                WingetPublisherConfig::defaults_for_package(workspaces, pkg_idx, &common);
+            default.apply_layer(winget);
+            default
+        });
         let user = user.map(|user| {
             let mut default =
                 UserPublisherConfig::defaults_for_package(workspaces, pkg_idx, &common);
@@ -92,6 +124,8 @@ impl PublisherConfigInheritable {
         PublisherConfig {
             homebrew,
             npm,
+            scoop,
+            winget,
             user,
         }
     }
@@ -104,12 +138,16 @@ impl ApplyLayer for PublisherConfigInheritable {
             common,
             homebrew,
             npm,
+            scoop,
+            winget,
             user,
         }: Self::Layer,
     ) {
         self.common.apply_layer(common);
         self.homebrew.apply_bool_layer(homebrew);
         self.npm.apply_bool_layer(npm);
+        self.scoop.apply_bool_layer(scoop);
+        self.winget.apply_bool_layer(winget);
         self.user.apply_bool_layer(user);
     }
 }
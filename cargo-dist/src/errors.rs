@@ -8,7 +8,7 @@
 
 use axoproject::errors::AxoprojectError;
 use backtrace::Backtrace;
-use camino::Utf8PathBuf;
+use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{target_lexicon::Triple, ArtifactId, TripleName};
 use color_backtrace::BacktracePrinter;
 use console::style;
@@ -86,6 +86,23 @@ pub enum DistError {
         backtrace: JinjaErrorWithBacktrace,
     },
 
+    /// A problem with a user-provided jinja template
+    #[error("Failed to render your template {path}")]
+    #[diagnostic(help("see the docs for the values your template can use"))]
+    UserJinja {
+        /// The template
+        path: Utf8PathBuf,
+        /// The contents of the template
+        #[source_code]
+        source: String,
+        /// The range the error was found on
+        #[label]
+        span: Option<miette::SourceSpan>,
+        /// Details of the error
+        #[source]
+        details: minijinja::Error,
+    },
+
     /// Error from (cargo-)wix
     #[error("WiX returned an error while building {msi}")]
     Wix {
@@ -155,6 +172,85 @@ pub enum DistError {
         inner: AxoprojectError,
     },
 
+    /// We got a repository URL but couldn't interpret it as a GitLab repo
+    #[error("GitLab hosting requires a GitLab repository")]
+    CantEnableGitlabUrlNotGitlab {
+        /// inner error that caught this
+        #[diagnostic_source]
+        inner: AxoprojectError,
+    },
+
+    /// GitLab hosting needs credentials to talk to the GitLab API
+    #[error("GitLab hosting requires an API token, but none was found")]
+    #[diagnostic(help(
+        "Set GITLAB_TOKEN to a token with the api scope, or run this inside GitLab CI so CI_JOB_TOKEN is available"
+    ))]
+    GitlabNoToken {},
+
+    /// S3 uploads for the simple host were configured without somewhere to upload to
+    #[error("uploading to S3 requires simple-s3-{field} to be set")]
+    SimpleS3Incomplete {
+        /// The missing field
+        field: String,
+    },
+
+    /// The S3 endpoint isn't a URL we can send requests to
+    #[error("couldn't parse the S3 endpoint {endpoint}")]
+    #[diagnostic(help("It should be a URL like https://s3.us-east-1.amazonaws.com"))]
+    S3BadEndpoint {
+        /// The configured endpoint
+        endpoint: String,
+    },
+
+    /// Uploading to S3 needs credentials
+    #[error("uploading to S3 requires credentials, but none were found")]
+    #[diagnostic(help("Set AWS_ACCESS_KEY_ID and AWS_SECRET_ACCESS_KEY"))]
+    S3NoCredentials {},
+
+    /// A request to the S3 API failed to complete
+    #[error("failed to {action} with the S3 API")]
+    S3Request {
+        /// What we were trying to do
+        action: String,
+        /// inner error that caught this
+        #[source]
+        details: axoasset::reqwest::Error,
+    },
+
+    /// The S3 API rejected a request
+    #[error("failed to {action} with the S3 API (HTTP {status})")]
+    #[diagnostic(help("The server responded with: {body}"))]
+    S3Api {
+        /// What we were trying to do
+        action: String,
+        /// The HTTP status code
+        status: u16,
+        /// The body of the response
+        body: String,
+    },
+
+    /// A request to the GitLab API failed to complete
+    #[error("failed to {action} with the GitLab API")]
+    GitlabRequest {
+        /// What we were trying to do
+        action: String,
+        /// inner error that caught this
+        #[source]
+        details: axoasset::reqwest::Error,
+    },
+
+    /// The GitLab API rejected a request
+    #[error("failed to {action} with the GitLab API (HTTP {status})")]
+    #[diagnostic(help("GitLab responded with: {body}"))]
+    GitlabApi {
+        /// What we were trying to do
+        action: String,
+        /// The HTTP status code
+        status: u16,
+        /// The body of the response
+        body: String,
+    },
+
     /// User supplied an illegal npm scope
     #[error("The npm-scope field must be an all-lowercase value; the supplied value was {scope}")]
     ScopeMustBeLowercase {
@@ -200,10 +296,18 @@ pub enum DistError {
         packages: Vec<camino::Utf8PathBuf>,
     },
 
-    /// packages disagreed on homebrew taps
-    #[error("different homebrew taps were set in your workspace, this is currently unsupported")]
+    /// packages disagreed on scoop buckets
+    #[error("different scoop buckets were set in your workspace, this is currently unsupported")]
     #[diagnostic(help("these packages disagree:\n{packages:#?}"))]
-    MismatchedTaps {
+    MismatchedScoopBuckets {
+        /// paths of problem manifests
+        packages: Vec<camino::Utf8PathBuf>,
+    },
+
+    /// packages disagreed on winget repos
+    #[error("different winget repos were set in your workspace, this is currently unsupported")]
+    #[diagnostic(help("these packages disagree:\n{packages:#?}"))]
+    MismatchedWingetRepos {
         /// paths of problem manifests
         packages: Vec<camino::Utf8PathBuf>,
     },
@@ -218,6 +322,7 @@ pub enum DistError {
 
     /// publishers disagreed on prereleases
     #[error("different publisher 'prereleases' settings were in your workspace, this is currently unsupported")]
+    #[diagnostic(help("The scoop, winget, and custom publish jobs must agree on 'prereleases', and can't turn them off if any homebrew or npm publisher turns them on"))]
     MismatchedPrereleases,
 
     /// parse_tag concluded there was nothing to release
@@ -318,13 +423,22 @@ pub enum DistError {
         style: String,
     },
 
-    /// Linkage report can't be run for this combination of OS and target
-    #[error("unable to run linkage report for {target} on {host}")]
-    LinkageCheckInvalidOS {
-        /// The OS the check was run on
-        host: String,
-        /// The OS being checked
+    /// A binary requires a newer glibc than the configured min-glibc-version
+    #[error("{bin_name} for {target} requires glibc {required}, which is newer than the configured min-glibc-version of {ceiling}")]
+    #[diagnostic(help(
+        "These symbols need a newer glibc:\n{symbols}\n\nTry building on an older system, or raise min-glibc-version."
+    ))]
+    GlibcCeilingExceeded {
+        /// The binary
+        bin_name: String,
+        /// The target it was built for
         target: TripleName,
+        /// The configured min-glibc-version
+        ceiling: String,
+        /// The newest glibc version the binary requires
+        required: String,
+        /// The offending symbols, one per line
+        symbols: String,
     },
 
     /// Linkage report can't be run for this target
@@ -473,6 +587,38 @@ pub enum DistError {
         target: TripleName,
     },
 
+    /// A target group asked for an os we don't know
+    #[error("Unrecognized os in target group: {os}")]
+    #[diagnostic(help("recognized values are: linux, macos, windows"))]
+    UnknownTargetGroupOs {
+        /// The os in question
+        os: String,
+    },
+
+    /// A target group asked for a libc that doesn't exist for the os
+    #[error("Unrecognized libc in target group: there are no {os} targets using {libc}")]
+    #[diagnostic(help("{os} supports: {known}"))]
+    UnknownTargetGroupLibc {
+        /// The os in question
+        os: String,
+        /// The libc in question
+        libc: String,
+        /// The libcs the os does support
+        known: String,
+    },
+
+    /// A target group asked for an arch that doesn't exist for the os/libc
+    #[error("Unrecognized arch in target group: there's no known {platform} target for {arch}")]
+    #[diagnostic(help("{platform} supports: {known}"))]
+    UnknownTargetGroupArch {
+        /// The os (and libc, if any) in question
+        platform: String,
+        /// The arch in question
+        arch: String,
+        /// The arches the platform does support
+        known: String,
+    },
+
     /// Installers requested despite having nothing to install
     #[error("Installers were requested, but app contains no installable binaries")]
     #[diagnostic(help(
@@ -584,6 +730,179 @@ pub enum DistError {
     #[diagnostic(help("Is the value of this environment variable valid base64?"))]
     CertificateDecodeError {},
 
+    /// minisign-public-key isn't a usable key
+    #[error("minisign-public-key isn't a valid minisign public key: {details}")]
+    #[diagnostic(help(
+        "this should be the second line of the minisign.pub made by `minisign -G` (it starts with RW)"
+    ))]
+    InvalidMinisignPublicKey {
+        /// The key as written in the config
+        key: String,
+        /// What's wrong with it
+        details: String,
+    },
+
+    /// MINISIGN_SECRET_KEY isn't a usable key
+    #[error("We failed to read the minisign secret key stored in the MINISIGN_SECRET_KEY environment variable: {details}")]
+    #[diagnostic(help("This should be the contents of the minisign.key made by `minisign -G`, or a base64-encoded Ed25519 secret key"))]
+    InvalidMinisignSecretKey {
+        /// What's wrong with it
+        details: String,
+    },
+
+    /// Signing was requested, but we have no key to do it with
+    #[error(
+        "Artifacts need to be signed with minisign key {key_id}, but MINISIGN_SECRET_KEY isn't set"
+    )]
+    #[diagnostic(help(
+        "In CI, make sure the MINISIGN_SECRET_KEY secret is set for your repository"
+    ))]
+    MissingMinisignSecretKey {
+        /// The id of the public key
+        key_id: String,
+    },
+
+    /// The secret key doesn't go with the public key
+    #[error("The minisign secret key in MINISIGN_SECRET_KEY doesn't match minisign-public-key (key id {key_id})")]
+    #[diagnostic(help("Did you rotate one key without the other?"))]
+    MinisignKeyMismatch {
+        /// The id of the public key
+        key_id: String,
+    },
+
+    /// SOURCE_DATE_EPOCH isn't a timestamp
+    #[error("SOURCE_DATE_EPOCH is set to {value:?}, which isn't a unix timestamp")]
+    #[diagnostic(help(
+        "SOURCE_DATE_EPOCH should be a number of seconds, like `git log -1 --format=%ct` prints"
+    ))]
+    InvalidSourceDateEpoch {
+        /// The value we found
+        value: String,
+    },
+
+    /// We couldn't figure out what time to stamp reproducible archives with
+    #[error(
+        "reproducible-archives is enabled, but we couldn't find the commit time of {tag} or HEAD"
+    )]
+    #[diagnostic(help(
+        "Either build from a git checkout, or set SOURCE_DATE_EPOCH to the time you want archive entries to have"
+    ))]
+    NoSourceDateEpoch {
+        /// The tag we were looking for
+        tag: String,
+    },
+
+    /// snapshot-version used a variable we don't know
+    #[error("snapshot-version template {template:?} uses unknown variable {{{var}}}")]
+    #[diagnostic(help(
+        "The available variables are {{version}}, {{date}}, {{short_sha}}, {{commit_count}}, and {{branch}}"
+    ))]
+    SnapshotVersionUnknownVar {
+        /// The template
+        template: String,
+        /// The variable
+        var: String,
+    },
+
+    /// snapshot-version didn't render to a valid version
+    #[error(
+        "snapshot-version template {template:?} produced {version:?}, which isn't a valid version"
+    )]
+    #[diagnostic(help(
+        "Snapshot versions need to be semver prereleases, like {{version}}-nightly.{{date}}.{{commit_count}}"
+    ))]
+    SnapshotVersionInvalid {
+        /// The template
+        template: String,
+        /// What it rendered to
+        version: String,
+        /// The error
+        #[source]
+        details: semver::Error,
+    },
+
+    /// We needed git to fill in a snapshot-version variable, but couldn't use it
+    #[error("snapshot-version uses {{{var}}}, but we couldn't get it from git")]
+    #[diagnostic(help(
+        "Build from a git checkout (with its full history, for {{commit_count}}), or remove {{{var}}} from snapshot-version"
+    ))]
+    SnapshotVersionNeedsGit {
+        /// The variable
+        var: String,
+    },
+
+    /// An error while writing a reproducible archive
+    #[error("failed to write archive: {dest_path}")]
+    ReproducibleArchive {
+        /// The archive we were writing
+        dest_path: Utf8PathBuf,
+        /// What went wrong
+        #[source]
+        details: std::io::Error,
+    },
+
+    /// A file we wanted to put in a universal binary isn't a Mach-O binary
+    #[error("couldn't make a universal binary: {path} isn't a Mach-O binary")]
+    NotMachO {
+        /// The file
+        path: Utf8PathBuf,
+    },
+
+    /// Two of the files we wanted to put in a universal binary are for the same architecture
+    #[error(
+        "couldn't make a universal binary: {path} is for the same architecture as another slice"
+    )]
+    #[diagnostic(help("a universal binary can only contain one slice per architecture"))]
+    DuplicateMachOArch {
+        /// The file
+        path: Utf8PathBuf,
+    },
+
+    /// A build step panicked while it had the manifest locked
+    #[error("a build step panicked while updating the dist manifest, so it can't be trusted")]
+    ManifestPoisoned,
+
+    /// The manifest passed to `dist verify` couldn't be parsed
+    #[error("couldn't parse {manifest} as a dist-manifest.json")]
+    VerifyManifestParse {
+        /// Where the manifest came from
+        manifest: String,
+        /// What went wrong
+        #[source]
+        details: serde_json::Error,
+    },
+
+    /// `dist verify` found problems
+    #[error("verification failed: {mismatched} mismatched, {missing} missing, {extra} extra")]
+    VerifyFailed {
+        /// How many checksums didn't match
+        mismatched: usize,
+        /// How many files were missing
+        missing: usize,
+        /// How many unexpected files there were
+        extra: usize,
+    },
+
+    /// The manifest passed to `dist publish` couldn't be parsed
+    #[error("couldn't parse {manifest} as a dist-manifest.json")]
+    PublishManifestParse {
+        /// Where the manifest came from
+        manifest: Utf8PathBuf,
+        /// What went wrong
+        #[source]
+        details: serde_json::Error,
+    },
+
+    /// `dist publish` was asked to publish something that wasn't built
+    #[error("{name} should be published, but it isn't in {dir}")]
+    #[diagnostic(help("Make sure all the artifacts of the release have been downloaded there"))]
+    PublishMissingArtifact {
+        /// The artifact
+        name: String,
+        /// The directory it should be in
+        dir: Utf8PathBuf,
+    },
+
     /// Missing configuration for a .pkg
     #[error("A Mac .pkg installer was requested, but the config is missing")]
     #[diagnostic(help("Please ensure a dist.mac-pkg-config section is present in your config. For more details see: https://example.com"))]
@@ -609,20 +928,52 @@ pub enum DistError {
         /// Version the project uses
         your_version: semver::Version,
     },
+
+    /// The configured desktop icon isn't in a format icon themes support
+    #[error("the desktop icon {path} isn't a .png or .svg")]
+    #[diagnostic(help("FreeDesktop icon themes only support PNG and SVG icons"))]
+    UnsupportedDesktopIcon {
+        /// path to the icon
+        path: Utf8PathBuf,
+    },
+
+    /// The configured desktop icon couldn't be installed into an icon theme
+    #[error("couldn't determine the size of the desktop icon {path}")]
+    #[diagnostic(help("{details}"))]
+    InvalidDesktopIcon {
+        /// path to the icon
+        path: Utf8PathBuf,
+        /// what went wrong
+        details: String,
+    },
+
+    /// The native msi builder couldn't understand a wxs file
+    #[error("dist's native msi builder couldn't use {wxs}: {details}")]
+    #[diagnostic(help(
+        "the native builder only supports what dist generates; set msi-builder = \"wix\" to use the WiX toolset instead"
+    ))]
+    NativeMsiWxs {
+        /// path to the wxs
+        wxs: Utf8PathBuf,
+        /// what went wrong
+        details: String,
+    },
+
+    /// The native msi builder failed to write an msi
+    #[error("failed to write {msi}")]
+    NativeMsi {
+        /// The msi we were trying to build
+        msi: String,
+        /// The underlying error
+        #[source]
+        details: std::io::Error,
+    },
 }
 
 impl From<minijinja::Error> for DistError {
     fn from(details: minijinja::Error) -> Self {
         let source: String = details.template_source().unwrap_or_default().to_owned();
-        let span = details.range().map(|r| r.into()).or_else(|| {
-            details.line().map(|line| {
-                // some minijinja errors only have a line, not a range, so let's just highlight the whole line
-                let start = SourceOffset::from_location(&source, line, 0);
-                let end = SourceOffset::from_location(&source, line + 1, 0);
-                let len = (end.offset() - start.offset()).wrapping_sub(1);
-                SourceSpan::from((start, len))
-            })
-        });
+        let span = jinja_error_span(&details, &source);
 
         DistError::Jinja {
             source,
@@ -631,6 +982,34 @@ impl From<minijinja::Error> for DistError {
         }
     }
 }
+impl DistError {
+    /// Wrap an error from rendering a user-provided template
+    pub fn user_jinja(path: &Utf8Path, details: minijinja::Error) -> Self {
+        let source: String = details.template_source().unwrap_or_default().to_owned();
+        let span = jinja_error_span(&details, &source);
+
+        DistError::UserJinja {
+            path: path.to_owned(),
+            source,
+            span,
+            details,
+        }
+    }
+}
+
+/// Find where in a template a minijinja error happened
+fn jinja_error_span(details: &minijinja::Error, source: &str) -> Option<SourceSpan> {
+    details.range().map(|r| r.into()).or_else(|| {
+        details.line().map(|line| {
+            // some minijinja errors only have a line, not a range, so let's just highlight the whole line
+            let start = SourceOffset::from_location(source, line, 0);
+            let end = SourceOffset::from_location(source, line + 1, 0);
+            let len = (end.offset() - start.offset()).wrapping_sub(1);
+            SourceSpan::from((start, len))
+        })
+    })
+}
+
 /// A struct that implements `std::error::Error` so it can be added as "related" to
 /// a miette diagnostic, and it'll show the backtrace.
 #[derive(Debug)]
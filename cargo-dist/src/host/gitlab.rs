@@ -0,0 +1,419 @@
+//! Hosting on GitLab
+//!
+//! Artifacts are uploaded to a generic package in the project's package registry
+//! (one package per app, versioned by the app's version), and then linked from a
+//! GitLab Release for the announcement tag.
+//!
+//! GitLab Releases can't be drafts, so the Release is only created when announcing.
+
+use axoasset::{reqwest, LocalAsset};
+use cargo_dist_schema::{ArtifactKind, DistManifest, GitlabHosting};
+use serde::{Deserialize, Serialize};
+use tracing::{info, warn};
+
+use crate::{
+    errors::{DistError, DistResult},
+    net::{create_reqwest_client, ClientSettings},
+    DistGraph,
+};
+
+/// Env var for a personal/project/group access token with the `api` scope
+const GITLAB_TOKEN_ENV_VAR: &str = "GITLAB_TOKEN";
+/// Env var GitLab CI sets to the job's own token
+const CI_JOB_TOKEN_ENV_VAR: &str = "CI_JOB_TOKEN";
+/// Env var GitLab CI sets to the commit the pipeline is running for
+const CI_COMMIT_SHA_ENV_VAR: &str = "CI_COMMIT_SHA";
+
+/// Credentials for the GitLab API
+#[derive(Debug, Clone)]
+pub enum GitlabToken {
+    /// A personal, project, or group access token (sent as `PRIVATE-TOKEN`)
+    Private(String),
+    /// A CI/CD job token (sent as `JOB-TOKEN`)
+    Job(String),
+}
+
+impl GitlabToken {
+    /// Find credentials in the environment, preferring an explicit access token
+    pub fn from_env() -> DistResult<Self> {
+        let read = |var: &str| std::env::var(var).ok().filter(|val| !val.is_empty());
+        if let Some(token) = read(GITLAB_TOKEN_ENV_VAR) {
+            Ok(Self::Private(token))
+        } else if let Some(token) = read(CI_JOB_TOKEN_ENV_VAR) {
+            Ok(Self::Job(token))
+        } else {
+            Err(DistError::GitlabNoToken {})
+        }
+    }
+
+    fn header(&self) -> (&'static str, &str) {
+        match self {
+            Self::Private(token) => ("PRIVATE-TOKEN", token),
+            Self::Job(token) => ("JOB-TOKEN", token),
+        }
+    }
+}
+
+/// A GitLab Release, as accepted by `POST /projects/:id/releases`
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitlabRelease {
+    /// The tag the release is for
+    pub tag_name: String,
+    /// The title of the release
+    pub name: String,
+    /// The body of the release (markdown)
+    pub description: String,
+    /// The commit to create the tag from, if it doesn't exist yet
+    #[serde(rename = "ref")]
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub git_ref: Option<String>,
+    /// Files attached to the release
+    pub assets: GitlabReleaseAssets,
+}
+
+/// The assets of a GitLab Release
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+pub struct GitlabReleaseAssets {
+    /// Links to files (in our case, in the package registry)
+    pub links: Vec<GitlabReleaseLink>,
+}
+
+/// A link attached to a GitLab Release
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct GitlabReleaseLink {
+    /// The name of the link (shown in the UI)
+    pub name: String,
+    /// The URL the link points to
+    pub url: String,
+    /// The kind of link ("package", "image", "runbook", or "other")
+    pub link_type: String,
+}
+
+/// A minimal client for the parts of the GitLab REST API we use
+#[derive(Debug, Clone)]
+pub struct GitlabClient {
+    client: reqwest::Client,
+    api_url: String,
+    project_id: String,
+    token: GitlabToken,
+}
+
+impl GitlabClient {
+    /// Create a client for the given project on the GitLab instance at `api_url`
+    /// (e.g. `"https://gitlab.com/api/v4"`)
+    pub fn new(api_url: &str, project_path: &str, token: GitlabToken) -> DistResult<Self> {
+        Ok(Self {
+            client: create_reqwest_client(&ClientSettings::new())?,
+            api_url: api_url.trim_end_matches('/').to_owned(),
+            project_id: project_path.replace('/', "%2F"),
+            token,
+        })
+    }
+
+    /// Create a client for the project a release is hosted in
+    pub fn for_hosting(hosting: &GitlabHosting) -> DistResult<Self> {
+        Self::new(
+            &hosting.api_url,
+            &hosting.project_path,
+            GitlabToken::from_env()?,
+        )
+    }
+
+    fn project_url(&self, path: &str) -> String {
+        format!("{}/projects/{}/{path}", self.api_url, self.project_id)
+    }
+
+    async fn send(
+        &self,
+        action: &str,
+        request: reqwest::RequestBuilder,
+    ) -> DistResult<reqwest::Response> {
+        let (header, token) = self.token.header();
+        let response = request
+            .header(header, token)
+            .send()
+            .await
+            .map_err(|details| DistError::GitlabRequest {
+                action: action.to_owned(),
+                details,
+            })?;
+        let status = response.status();
+        if status.is_success() {
+            Ok(response)
+        } else {
+            let body = response.text().await.unwrap_or_default();
+            Err(DistError::GitlabApi {
+                action: action.to_owned(),
+                status: status.as_u16(),
+                body,
+            })
+        }
+    }
+
+    /// Upload a file to a generic package in the project's package registry
+    pub async fn upload_package_file(
+        &self,
+        package_name: &str,
+        package_version: &str,
+        file_name: &str,
+        contents: Vec<u8>,
+    ) -> DistResult<()> {
+        let url = self.project_url(&format!(
+            "packages/generic/{package_name}/{package_version}/{file_name}"
+        ));
+        let action = format!("upload {file_name}");
+        self.send(&action, self.client.put(url).body(contents))
+            .await?;
+        Ok(())
+    }
+
+    /// Create a release, or update the title and body of the release if it already exists
+    pub async fn create_release(&self, release: &GitlabRelease) -> DistResult<()> {
+        let action = format!("create a release for {}", release.tag_name);
+        let result = self
+            .send(
+                &action,
+                self.client.post(self.project_url("releases")).json(release),
+            )
+            .await;
+        match result {
+            // A release for this tag already exists (e.g. this job was retried),
+            // so just bring its contents up to date.
+            Err(DistError::GitlabApi { status: 409, .. }) => {
+                #[derive(Serialize)]
+                struct ReleaseUpdate<'a> {
+                    name: &'a str,
+                    description: &'a str,
+                }
+                let action = format!("update the release for {}", release.tag_name);
+                let tag = release.tag_name.replace('/', "%2F");
+                let url = self.project_url(&format!("releases/{tag}"));
+                self.send(
+                    &action,
+                    self.client.put(url).json(&ReleaseUpdate {
+                        name: &release.name,
+                        description: &release.description,
+                    }),
+                )
+                .await?;
+                Ok(())
+            }
+            result => result.map(|_| ()),
+        }
+    }
+}
+
+/// Upload every artifact of every GitLab-hosted release to the package registry
+pub async fn upload(dist: &DistGraph, manifest: &DistManifest) -> DistResult<()> {
+    for release in &manifest.releases {
+        let Some(hosting) = &release.hosting.gitlab else {
+            continue;
+        };
+        let client = GitlabClient::for_hosting(hosting)?;
+        for (_id, artifact) in manifest.artifacts_for_release(release) {
+            // Artifacts with no name do not exist as files
+            let Some(name) = &artifact.name else {
+                continue;
+            };
+            let path = dist.dist_dir.join(name.as_str());
+            if !path.exists() {
+                warn!(
+                    "skipping upload of {name}, it wasn't found in {}",
+                    dist.dist_dir
+                );
+                continue;
+            }
+            info!("uploading {name} to GitLab");
+            let contents = LocalAsset::load_bytes(&path)?;
+            client
+                .upload_package_file(
+                    &hosting.package_name,
+                    &hosting.package_version,
+                    name.as_str(),
+                    contents,
+                )
+                .await?;
+        }
+    }
+    Ok(())
+}
+
+/// Create the GitLab Release, linking every uploaded artifact
+pub async fn announce(manifest: &DistManifest) -> DistResult<()> {
+    let Some(release) = announcement_release(manifest) else {
+        return Ok(());
+    };
+    let hosting = manifest
+        .releases
+        .iter()
+        .find_map(|release| release.hosting.gitlab.as_ref())
+        .expect("announcement_release requires GitLab hosting");
+    info!("creating GitLab release for {}", release.tag_name);
+    GitlabClient::for_hosting(hosting)?
+        .create_release(&release)
+        .await
+}
+
+/// Compute the GitLab Release for this announcement, if anything is hosted on GitLab
+pub fn announcement_release(manifest: &DistManifest) -> Option<GitlabRelease> {
+    let tag_name = manifest.announcement_tag.clone()?;
+    let mut links = vec![];
+    let mut any_hosted = false;
+    for release in &manifest.releases {
+        let Some(hosting) = &release.hosting.gitlab else {
+            continue;
+        };
+        any_hosted = true;
+        let download_url = hosting.artifact_download_url();
+        for (_id, artifact) in manifest.artifacts_for_release(release) {
+            let Some(name) = &artifact.name else {
+                continue;
+            };
+            let link_type = match artifact.kind {
+                ArtifactKind::Checksum
+                | ArtifactKind::Signature
+                | ArtifactKind::Provenance
+                | ArtifactKind::Unknown => "other",
+                _ => "package",
+            };
+            links.push(GitlabReleaseLink {
+                name: name.to_string(),
+                url: format!("{download_url}/{name}"),
+                link_type: link_type.to_owned(),
+            });
+        }
+    }
+    if !any_hosted {
+        return None;
+    }
+
+    let description = manifest
+        .announcement_github_body
+        .clone()
+        .or_else(|| manifest.announcement_changelog.clone())
+        .unwrap_or_default();
+    Some(GitlabRelease {
+        name: manifest
+            .announcement_title
+            .clone()
+            .unwrap_or_else(|| tag_name.clone()),
+        tag_name,
+        description,
+        git_ref: std::env::var(CI_COMMIT_SHA_ENV_VAR).ok(),
+        assets: GitlabReleaseAssets { links },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::tests::mock_server::{self, MockRequest};
+
+    /// Spawn a mock server with GitLab's API prefix on its URL
+    async fn mock_server(
+        responses: Vec<(u16, &'static str)>,
+    ) -> (String, tokio::task::JoinHandle<Vec<MockRequest>>) {
+        let (url, handle) = mock_server::mock_server(responses).await;
+        (format!("{url}/api/v4"), handle)
+    }
+
+    fn mock_release() -> GitlabRelease {
+        GitlabRelease {
+            tag_name: "v1.0.0".to_owned(),
+            name: "v1.0.0".to_owned(),
+            description: "## Release Notes".to_owned(),
+            git_ref: None,
+            assets: GitlabReleaseAssets {
+                links: vec![GitlabReleaseLink {
+                    name: "app-x86_64-unknown-linux-gnu.tar.xz".to_owned(),
+                    url: "https://gitlab.com/api/v4/projects/group%2Fapp/packages/generic/app/1.0.0/app-x86_64-unknown-linux-gnu.tar.xz".to_owned(),
+                    link_type: "package".to_owned(),
+                }],
+            },
+        }
+    }
+
+    #[tokio::test]
+    async fn uploads_package_file() {
+        let (url, server) = mock_server(vec![(201, r#"{"message":"201 Created"}"#)]).await;
+        let client = GitlabClient::new(
+            &url,
+            "group/subgroup/app",
+            GitlabToken::Private("secret".to_owned()),
+        )
+        .unwrap();
+        client
+            .upload_package_file("app", "1.0.0", "app.tar.xz", b"hello".to_vec())
+            .await
+            .unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "PUT");
+        assert_eq!(
+            request.path,
+            "/api/v4/projects/group%2Fsubgroup%2Fapp/packages/generic/app/1.0.0/app.tar.xz"
+        );
+        assert_eq!(request.header("PRIVATE-TOKEN"), Some("secret"));
+        assert_eq!(request.body, b"hello");
+    }
+
+    #[tokio::test]
+    async fn creates_release() {
+        let (url, server) = mock_server(vec![(201, r#"{"tag_name":"v1.0.0"}"#)]).await;
+        let client =
+            GitlabClient::new(&url, "group/app", GitlabToken::Job("job".to_owned())).unwrap();
+        client.create_release(&mock_release()).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 1);
+        let request = &requests[0];
+        assert_eq!(request.method, "POST");
+        assert_eq!(request.path, "/api/v4/projects/group%2Fapp/releases");
+        assert_eq!(request.header("JOB-TOKEN"), Some("job"));
+        let sent: serde_json::Value = serde_json::from_slice(&request.body).unwrap();
+        assert_eq!(sent["tag_name"], "v1.0.0");
+        assert_eq!(sent["assets"]["links"][0]["link_type"], "package");
+        assert!(sent.get("ref").is_none());
+    }
+
+    #[tokio::test]
+    async fn updates_existing_release() {
+        let (url, server) = mock_server(vec![
+            (409, r#"{"message":"Release already exists"}"#),
+            (200, r#"{"tag_name":"v1.0.0"}"#),
+        ])
+        .await;
+        let client =
+            GitlabClient::new(&url, "group/app", GitlabToken::Job("job".to_owned())).unwrap();
+        client.create_release(&mock_release()).await.unwrap();
+
+        let requests = server.await.unwrap();
+        assert_eq!(requests.len(), 2);
+        assert_eq!(requests[1].method, "PUT");
+        assert_eq!(
+            requests[1].path,
+            "/api/v4/projects/group%2Fapp/releases/v1.0.0"
+        );
+        let sent: serde_json::Value = serde_json::from_slice(&requests[1].body).unwrap();
+        assert_eq!(sent["description"], "## Release Notes");
+    }
+
+    #[tokio::test]
+    async fn reports_api_errors() {
+        let (url, server) = mock_server(vec![(403, r#"{"message":"403 Forbidden"}"#)]).await;
+        let client =
+            GitlabClient::new(&url, "group/app", GitlabToken::Job("job".to_owned())).unwrap();
+        let err = client
+            .upload_package_file("app", "1.0.0", "app.tar.xz", vec![])
+            .await
+            .unwrap_err();
+        server.await.unwrap();
+        let DistError::GitlabApi { status, body, .. } = err else {
+            panic!("unexpected error: {err:?}");
+        };
+        assert_eq!(status, 403);
+        assert!(body.contains("403 Forbidden"));
+    }
+}
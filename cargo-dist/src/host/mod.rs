@@ -1,5 +1,8 @@
 //! Details for hosting artifacts
 
+pub mod gitlab;
+pub mod s3;
+
 use crate::{
     announce::AnnouncementTag,
     check_integrity,
@@ -29,14 +32,56 @@ pub fn do_host(cfg: &Config, host_args: HostArgs) -> DistResult<DistManifest> {
 
     // The rest of the steps are more self-contained
 
+    let mock_networking = std::env::var("CARGO_DIST_MOCK_NETWORKING").is_ok();
     if let Some(hosting) = &dist.hosting {
         for host in &hosting.hosts {
             match host {
                 HostingStyle::Github => {
                     // implemented in CI backend
                 }
+                HostingStyle::Gitlab => {
+                    if mock_networking {
+                        continue;
+                    }
+                    // GitLab Releases can't be drafts, so there's nothing to create
+                    // ahead of time, but we can make sure we'll be able to upload later
+                    if host_args.steps.contains(&HostStyle::Create) {
+                        gitlab::GitlabToken::from_env()?;
+                    }
+                    let handle = tokio::runtime::Handle::current();
+                    if host_args.steps.contains(&HostStyle::Upload) {
+                        handle.block_on(gitlab::upload(&dist, &manifest))?;
+                    }
+                    // Creating the GitLab Release is what makes it public,
+                    // so the release step is folded into announce
+                    if host_args.steps.contains(&HostStyle::Announce) {
+                        handle.block_on(gitlab::announce(&manifest))?;
+                    }
+                }
                 HostingStyle::Simple => {
-                    // currently download-only
+                    // Without S3 configured this is download-only
+                    let Some(s3_config) = dist
+                        .config
+                        .hosts
+                        .simple
+                        .as_ref()
+                        .and_then(|simple| simple.s3.as_ref())
+                    else {
+                        continue;
+                    };
+                    if mock_networking {
+                        continue;
+                    }
+                    if host_args.steps.contains(&HostStyle::Create) {
+                        s3::S3Credentials::from_env()?;
+                    }
+                    let handle = tokio::runtime::Handle::current();
+                    if host_args.steps.contains(&HostStyle::Upload) {
+                        handle.block_on(s3::upload(s3_config, &dist, &manifest))?;
+                    }
+                    if host_args.steps.contains(&HostStyle::Announce) {
+                        handle.block_on(s3::announce(s3_config, &dist, &manifest))?;
+                    }
                 }
             }
         }
@@ -56,19 +101,27 @@ impl<'a> DistGraphBuilder<'a> {
     ) -> DistResult<()> {
         let mut ci = vec![];
         {
-            let CiConfig { github } = &self.inner.config.ci;
+            let CiConfig { github, gitlab } = &self.inner.config.ci;
             if github.is_some() {
                 ci.push(CiStyle::Github);
             }
+            if gitlab.is_some() {
+                ci.push(CiStyle::Gitlab);
+            }
         }
 
         let mut hosting = vec![];
         {
             let WorkspaceHostConfig {
                 github,
+                gitlab,
                 simple,
                 order,
                 force_latest: _,
+                changelog_from_commits: _,
+                snapshot_version: _,
+                snapshot_tag: _,
+                announcement_template: _,
             } = &self.inner.config.hosts;
             if simple.is_some() {
                 hosting.push(HostingStyle::Simple);
@@ -76,7 +129,10 @@ impl<'a> DistGraphBuilder<'a> {
             if github.is_some() {
                 hosting.push(HostingStyle::Github);
             }
-            // This is very silly but it's a list of like, 2 elements,
+            if gitlab.is_some() {
+                hosting.push(HostingStyle::Gitlab);
+            }
+            // This is very silly but it's a list of like, 3 elements,
             // and works fine with no defined order
             hosting.sort_by_key(|val| order.iter().position(|order_key| order_key == val))
         }
@@ -91,6 +147,23 @@ impl<'a> DistGraphBuilder<'a> {
             return Ok(());
         };
 
+        if let Some(s3) = self
+            .inner
+            .config
+            .hosts
+            .simple
+            .as_ref()
+            .and_then(|simple| simple.s3.as_ref())
+        {
+            for (field, val) in [("endpoint", &s3.endpoint), ("bucket", &s3.bucket)] {
+                if val.is_empty() {
+                    return Err(DistError::SimpleS3Incomplete {
+                        field: field.to_owned(),
+                    });
+                }
+            }
+        }
+
         let create_hosting =
             cfg.create_hosting && std::env::var("CARGO_DIST_MOCK_NETWORKING").is_err();
 
@@ -146,12 +219,34 @@ impl<'a> DistGraphBuilder<'a> {
                         })
                     }
                 }
+                HostingStyle::Gitlab => {
+                    // Each app gets its own generic package, versioned by the app's version
+                    let api_url = format!("{}/api/v4", hosting.domain);
+                    let project_path = hosting.repo_path.trim_start_matches('/').to_owned();
+                    for (name, version) in &releases_without_hosting {
+                        self.manifest
+                            .ensure_release(name.clone(), version.clone())
+                            .hosting
+                            .gitlab = Some(cargo_dist_schema::GitlabHosting {
+                            api_url: api_url.clone(),
+                            project_path: project_path.clone(),
+                            package_name: name.clone(),
+                            package_version: version.clone(),
+                        })
+                    }
+                }
                 HostingStyle::Simple => {
                     for (name, version) in &releases_without_hosting {
                         let tag = &announcing.tag;
                         let simple_config = self.inner.config.hosts.simple.as_ref().expect("should not be possible to select simple hosting without it defined in config!");
-                        // Apply templates to the simple URL
-                        let download_url = simple_config.download_url.replace("{tag}", tag);
+                        let download_url = match &simple_config.s3 {
+                            // If we're uploading to S3, default to downloading from there
+                            Some(s3) if simple_config.download_url.is_empty() => {
+                                s3.download_url(tag)
+                            }
+                            // Apply templates to the simple URL
+                            _ => simple_config.download_url.replace("{tag}", tag),
+                        };
                         self.manifest
                             .ensure_release(name.clone(), version.clone())
                             .hosting
@@ -173,6 +268,7 @@ impl<'a> DistGraphBuilder<'a> {
                         .iter()
                         .map(|style| match style {
                             HostingStyle::Github => cargo_dist_schema::HostingStyle::Github,
+                            HostingStyle::Gitlab => cargo_dist_schema::HostingStyle::Gitlab,
                             HostingStyle::Simple => cargo_dist_schema::HostingStyle::Simple,
                         })
                         .collect(),
@@ -225,7 +321,25 @@ pub(crate) fn select_hosting(
         }
     };
 
-    // Currently there's only one supported sourcehost provider
+    // GitLab can be self-managed on any domain, so only interpret the URL as a
+    // GitLab repo if that's what we're hosting on (and GitHub isn't)
+    if hosting_providers.contains(&HostingStyle::Gitlab)
+        && !hosting_providers.contains(&HostingStyle::Github)
+    {
+        let repo = raw_repository_url
+            .gitlab_repo()
+            .map_err(|e| DistError::CantEnableGitlabUrlNotGitlab { inner: e })?;
+        return Ok(Some(HostingInfo {
+            hosts: hosting_providers,
+            domain: repo.domain(),
+            repo_path: repo.web_path(),
+            source_host: "gitlab".to_owned(),
+            owner: repo.namespace,
+            project: repo.name,
+        }));
+    }
+
+    // Otherwise the repository must be on GitHub
     let repo = raw_repository_url
         .github_repo()
         .map_err(|e| DistError::CantEnableGithubUrlNotGithub { inner: e })?;
@@ -1,14 +1,13 @@
 use axoasset::{toml_edit, LocalAsset};
 use axoproject::{WorkspaceGraph, WorkspaceInfo, WorkspaceKind};
 use camino::Utf8PathBuf;
-use cargo_dist_schema::TripleNameRef;
 use semver::Version;
 use serde::Deserialize;
 
 use crate::{
     config::{
         self, CiStyle, Config, DistMetadata, HostingStyle, InstallPathStrategy, InstallerStyle,
-        MacPkgConfig, PublishStyle,
+        MacPkgConfig, PublishStyle, TargetSelector,
     },
     do_generate,
     errors::{DistError, DistResult},
@@ -488,16 +487,25 @@ fn get_new_dist_metadata(
             install_success_msg: None,
             tap: None,
             formula: None,
+            scoop_bucket: None,
+            winget_repo: None,
+            winget_publisher: None,
+            winget_package_identifier: None,
+            msi_builder: None,
             system_dependencies: None,
             targets: None,
             dist: None,
             include: None,
             auto_includes: None,
+            desktop_entry: None,
+            desktop_icon: None,
+            desktop_mime_types: None,
             windows_archive: None,
             unix_archive: None,
             npm_scope: None,
             npm_package: None,
             npm_shrinkwrap: None,
+            npm_registry: None,
             checksum: None,
             precise_builds: None,
             merge_tasks: None,
@@ -515,11 +523,17 @@ fn get_new_dist_metadata(
             global_artifacts_jobs: None,
             source_tarball: None,
             recursive_tarball: None,
+            slsa_provenance: None,
+            reproducible_archives: None,
             host_jobs: None,
             publish_jobs: None,
             post_announce_jobs: None,
             publish_prereleases: None,
             force_latest: None,
+            changelog_from_commits: None,
+            snapshot_version: None,
+            snapshot_tag: None,
+            announcement_template: None,
             create_release: None,
             github_releases_repo: None,
             github_releases_submodule_path: None,
@@ -529,6 +543,7 @@ fn get_new_dist_metadata(
             allow_dirty: None,
             ssldotcom_windows_sign: None,
             macos_sign: None,
+            minisign_public_key: None,
             github_attestations: None,
             github_attestations_filters: None,
             github_attestations_phase: None,
@@ -536,6 +551,7 @@ fn get_new_dist_metadata(
             hosting: None,
             extra_artifacts: None,
             github_custom_runners: None,
+            gitlab_custom_runners: None,
             github_custom_job_permissions: None,
             bin_aliases: None,
             tag_namespace: None,
@@ -548,11 +564,18 @@ fn get_new_dist_metadata(
             github_build_setup: None,
             mac_pkg_config: None,
             min_glibc_version: None,
+            enforce_min_glibc_version: None,
+            split_debuginfo: None,
+            symbol_store: None,
             binaries: None,
             cargo_auditable: None,
             cargo_cyclonedx: None,
             omnibor: None,
             simple_download_url: None,
+            simple_s3_endpoint: None,
+            simple_s3_bucket: None,
+            simple_s3_prefix: None,
+            simple_s3_region: None,
         }
     };
 
@@ -607,25 +630,63 @@ fn get_new_dist_metadata(
     }
 
     {
-        // Start with builtin targets
-        let default_platforms = crate::default_desktop_targets();
-        let mut known = crate::known_desktop_targets();
+        // Start with builtin targets (and some handy groups of them)
+        let default_platforms = crate::default_desktop_targets()
+            .into_iter()
+            .map(TargetSelector::Triple)
+            .collect::<Vec<_>>();
+        let mut known = crate::known_desktop_targets()
+            .into_iter()
+            .map(TargetSelector::Triple)
+            .chain(
+                crate::known_target_groups()
+                    .into_iter()
+                    .map(TargetSelector::Group),
+            )
+            .collect::<Vec<_>>();
         // If the config doesn't have targets at all, generate them
         let config_vals = meta.targets.as_deref().unwrap_or(&default_platforms);
-        let cli_vals = cfg.targets.as_slice();
+        let cli_vals = cfg
+            .targets
+            .iter()
+            .cloned()
+            .map(TargetSelector::Triple)
+            .collect::<Vec<_>>();
         // Add anything custom they did to the list (this will do some reordering if they hand-edited)
-        for val in config_vals.iter().chain(cli_vals) {
+        for val in config_vals.iter().chain(&cli_vals) {
             if !known.contains(val) {
                 known.push(val.clone());
             }
         }
 
-        // Prettify/sort things
-        let desc = move |triple: &TripleNameRef| -> String {
-            let pretty = triple_to_display_name(triple).unwrap_or("[unknown]");
-            format!("{pretty} ({triple})")
+        // Prettify/sort things (with the groups listed after all the single targets)
+        let desc = move |target: &TargetSelector| -> String {
+            match target {
+                TargetSelector::Triple(triple) => {
+                    let pretty = triple_to_display_name(triple).unwrap_or("[unknown]");
+                    format!("{pretty} ({triple})")
+                }
+                TargetSelector::Group(group) => {
+                    let triples = group
+                        .expand()
+                        .map(|triples| {
+                            triples
+                                .iter()
+                                .map(|triple| triple.as_str())
+                                .collect::<Vec<_>>()
+                                .join(", ")
+                        })
+                        .unwrap_or_else(|_| "[unknown]".to_owned());
+                    format!("target group {group} ({triples})")
+                }
+            }
         };
-        known.sort_by_cached_key(|k| desc(k).to_uppercase());
+        known.sort_by_cached_key(|k| {
+            (
+                matches!(k, TargetSelector::Group(_)),
+                desc(k).to_uppercase(),
+            )
+        });
 
         let mut defaults = vec![];
         let mut keys = vec![];
@@ -665,17 +726,12 @@ fn get_new_dist_metadata(
     }
 
     // Enable CI backends
-    // FIXME: when there is more than one option we maybe shouldn't hide this
-    // once the user has any one enabled, right now it's just annoying to always
-    // prompt for Github CI support.
+    // FIXME: we maybe shouldn't hide this once the user has any one enabled,
+    // right now it's just annoying to always prompt for CI support.
     if meta.ci.as_deref().unwrap_or_default().is_empty() {
-        // FIXME: when there is more than one option this should be a proper
-        // multiselect like the installer selector is! For now we do
-        // most of the multi-select logic and then just give a prompt.
-        let known = &[CiStyle::Github];
+        let known = &[CiStyle::Github, CiStyle::Gitlab];
         let mut defaults = vec![];
         let mut keys = vec![];
-        let mut github_key = 0;
         for item in known {
             // If this CI style is in their config, keep it
             // If they passed it on the CLI, flip it on
@@ -686,42 +742,39 @@ fn get_new_dist_metadata(
                 .unwrap_or(false)
                 || cfg.ci.contains(item);
 
-            // Currently default to enabling github CI because we don't
-            // support anything else and we can give a good error later
-            #[allow(irrefutable_let_patterns)]
+            // Default to enabling github CI if they didn't ask for anything else,
+            // since it's the most complete backend we have
             if let CiStyle::Github = item {
-                github_key = 0;
-                default = true;
+                default |= cfg.ci.is_empty();
             }
             defaults.push(default);
             // This match is here to remind you to add new CiStyles
             // to `known` above!
             keys.push(match item {
                 CiStyle::Github => "github",
+                CiStyle::Gitlab => "gitlab",
             });
         }
 
         // Prompt the user
-        let prompt = r#"enable Github CI and Releases?"#;
-        let default = defaults[github_key];
-
-        let github_selected = if args.yes {
-            default
+        let prompt = r#"enable CI backends?
+    (select with arrow keys and space, submit with enter)"#;
+        let selected = if args.yes {
+            defaults
+                .iter()
+                .enumerate()
+                .filter_map(|(idx, enabled)| enabled.then_some(idx))
+                .collect()
         } else {
-            let res = Confirm::with_theme(&theme)
+            let res = MultiSelect::with_theme(&theme)
+                .items(&keys)
+                .defaults(&defaults)
                 .with_prompt(prompt)
-                .default(default)
                 .interact()?;
             eprintln!();
             res
         };
 
-        let selected = if github_selected {
-            vec![github_key]
-        } else {
-            vec![]
-        };
-
         // Apply the results
         let ci: Vec<_> = selected.into_iter().map(|i| known[i]).collect();
         meta.ci = if ci.is_empty() { None } else { Some(ci) };
@@ -740,10 +793,18 @@ fn get_new_dist_metadata(
                 InstallerStyle::Npm,
                 InstallerStyle::Homebrew,
                 InstallerStyle::Msi,
+                InstallerStyle::Deb,
+                InstallerStyle::Rpm,
+                InstallerStyle::Scoop,
+                InstallerStyle::Winget,
             ]
         } else {
             eprintln!("{notice} no CI backends enabled, most installers have been hidden");
-            &[InstallerStyle::Msi]
+            &[
+                InstallerStyle::Msi,
+                InstallerStyle::Deb,
+                InstallerStyle::Rpm,
+            ]
         };
         let mut defaults = vec![];
         let mut keys = vec![];
@@ -769,6 +830,10 @@ fn get_new_dist_metadata(
                 InstallerStyle::Homebrew => "homebrew",
                 InstallerStyle::Msi => "msi",
                 InstallerStyle::Pkg => "pkg",
+                InstallerStyle::Deb => "deb",
+                InstallerStyle::Rpm => "rpm",
+                InstallerStyle::Scoop => "scoop",
+                InstallerStyle::Winget => "winget",
             });
         }
 
@@ -856,6 +921,125 @@ fn get_new_dist_metadata(
         }
     }
 
+    // Special handling of the Scoop installer
+    if meta
+        .installers
+        .as_deref()
+        .unwrap_or_default()
+        .contains(&InstallerStyle::Scoop)
+    {
+        let scoop_is_new = !orig_meta
+            .installers
+            .as_deref()
+            .unwrap_or_default()
+            .contains(&InstallerStyle::Scoop);
+
+        if scoop_is_new {
+            let prompt = r#"you've enabled Scoop support; if you want dist
+    to automatically push manifest updates to a bucket (repository) for you,
+    please enter the bucket name (in GitHub owner/name format)"#;
+            let default = "".to_string();
+
+            let bucket: String = if args.yes {
+                default
+            } else {
+                let res = Input::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .allow_empty(true)
+                    .interact_text()?;
+                eprintln!();
+                res
+            };
+            let bucket = bucket.trim();
+            if bucket.is_empty() {
+                eprintln!("Scoop manifests will not be automatically published");
+                meta.scoop_bucket = None;
+            } else {
+                meta.scoop_bucket = Some(bucket.to_owned());
+                publish_jobs.push(PublishStyle::Scoop);
+
+                eprintln!("{check} Scoop manifest will be published to {bucket}");
+
+                eprintln!(
+                    r#"{check} You must provision a GitHub token and expose it as a secret named
+    SCOOP_BUCKET_TOKEN in GitHub Actions. For more information,
+    see the documentation:
+    https://axodotdev.github.io/cargo-dist/book/installers/scoop.html"#
+                );
+            }
+        }
+    } else {
+        let scoop_toggled_off = orig_meta
+            .installers
+            .as_deref()
+            .unwrap_or_default()
+            .contains(&InstallerStyle::Scoop);
+        if scoop_toggled_off {
+            meta.scoop_bucket = None;
+            publish_jobs.retain(|job| job != &PublishStyle::Scoop);
+        }
+    }
+
+    // Special handling of the winget installer
+    if meta
+        .installers
+        .as_deref()
+        .unwrap_or_default()
+        .contains(&InstallerStyle::Winget)
+    {
+        let winget_is_new = !orig_meta
+            .installers
+            .as_deref()
+            .unwrap_or_default()
+            .contains(&InstallerStyle::Winget);
+
+        if winget_is_new {
+            let prompt = r#"you've enabled winget support; if you want dist
+    to automatically push manifest updates to a repository for you
+    (e.g. your fork of microsoft/winget-pkgs), please enter the
+    repository name (in GitHub owner/name format)"#;
+            let default = "".to_string();
+
+            let repo: String = if args.yes {
+                default
+            } else {
+                let res = Input::with_theme(&theme)
+                    .with_prompt(prompt)
+                    .allow_empty(true)
+                    .interact_text()?;
+                eprintln!();
+                res
+            };
+            let repo = repo.trim();
+            if repo.is_empty() {
+                eprintln!("winget manifests will not be automatically published");
+                meta.winget_repo = None;
+            } else {
+                meta.winget_repo = Some(repo.to_owned());
+                publish_jobs.push(PublishStyle::Winget);
+
+                eprintln!("{check} winget manifests will be published to {repo}");
+
+                eprintln!(
+                    r#"{check} You must provision a GitHub token and expose it as a secret named
+    WINGET_REPO_TOKEN in GitHub Actions. For more information,
+    see the documentation:
+    https://axodotdev.github.io/cargo-dist/book/installers/winget.html"#
+                );
+            }
+        }
+    } else {
+        let winget_toggled_off = orig_meta
+            .installers
+            .as_deref()
+            .unwrap_or_default()
+            .contains(&InstallerStyle::Winget);
+        if winget_toggled_off {
+            meta.winget_repo = None;
+            publish_jobs.retain(|job| job != &PublishStyle::Winget);
+        }
+    }
+
     // Special handling of the npm installer
     if meta
         .installers
@@ -998,14 +1182,23 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         install_success_msg,
         tap,
         formula,
+        scoop_bucket,
+        winget_repo,
+        winget_publisher,
+        winget_package_identifier,
+        msi_builder,
         targets,
         include,
         auto_includes,
+        desktop_entry,
+        desktop_icon,
+        desktop_mime_types,
         windows_archive,
         unix_archive,
         npm_scope,
         npm_package,
         npm_shrinkwrap,
+        npm_registry,
         checksum,
         precise_builds,
         merge_tasks,
@@ -1023,11 +1216,17 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         global_artifacts_jobs,
         source_tarball,
         recursive_tarball,
+        slsa_provenance,
+        reproducible_archives,
         host_jobs,
         publish_jobs,
         post_announce_jobs,
         publish_prereleases,
         force_latest,
+        changelog_from_commits,
+        snapshot_version,
+        snapshot_tag,
+        announcement_template,
         create_release,
         github_releases_repo,
         github_releases_submodule_path,
@@ -1035,6 +1234,7 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         allow_dirty,
         ssldotcom_windows_sign,
         macos_sign,
+        minisign_public_key,
         github_attestations,
         github_attestations_filters,
         github_attestations_phase,
@@ -1050,14 +1250,22 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         install_libraries,
         mac_pkg_config,
         min_glibc_version,
+        enforce_min_glibc_version,
+        split_debuginfo,
+        symbol_store,
         cargo_auditable,
         cargo_cyclonedx,
         simple_download_url,
+        simple_s3_endpoint,
+        simple_s3_bucket,
+        simple_s3_prefix,
+        simple_s3_region,
         omnibor,
         // These settings are complex enough that we don't support editing them in init
         github_action_commits: _,
         extra_artifacts: _,
         github_custom_runners: _,
+        gitlab_custom_runners: _,
         github_custom_job_permissions: _,
         bin_aliases: _,
         system_dependencies: _,
@@ -1132,7 +1340,42 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         formula.clone(),
     );
 
-    apply_string_list(
+    apply_optional_value(
+        table,
+        "scoop-bucket",
+        "# A GitHub repo (Scoop bucket) to push Scoop manifests to\n",
+        scoop_bucket.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "winget-repo",
+        "# A GitHub repo to push winget manifests to\n",
+        winget_repo.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "winget-publisher",
+        "# The publisher name to use in winget manifests\n",
+        winget_publisher.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "winget-package-identifier",
+        "# Customize the winget package identifier\n",
+        winget_package_identifier.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "msi-builder",
+        "# How to build msi installers (\"wix\" or \"native\")\n",
+        msi_builder.as_ref().map(|b| b.to_string()),
+    );
+
+    apply_target_list(
         table,
         "targets",
         "# Target platforms to build apps for (Rust target-triple syntax)\n",
@@ -1160,6 +1403,27 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         *auto_includes,
     );
 
+    apply_optional_value(
+        table,
+        "desktop-entry",
+        "# A desktop entry to bundle with linux builds (path relative to this Cargo.toml's dir)\n",
+        desktop_entry.as_ref().map(|p| p.to_string()),
+    );
+
+    apply_optional_value(
+        table,
+        "desktop-icon",
+        "# An icon to bundle with linux builds (path relative to this Cargo.toml's dir)\n",
+        desktop_icon.as_ref().map(|p| p.to_string()),
+    );
+
+    apply_string_list(
+        table,
+        "desktop-mime-types",
+        "# MIME types the app's desktop entry can open\n",
+        desktop_mime_types.as_ref(),
+    );
+
     apply_optional_value(
         table,
         "windows-archive",
@@ -1202,6 +1466,13 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         npm_scope.as_deref(),
     );
 
+    apply_optional_value(
+        table,
+        "npm-registry",
+        "# The npm registry to publish this package to\n",
+        npm_registry.as_deref(),
+    );
+
     apply_optional_value(
         table,
         "checksum",
@@ -1351,6 +1622,20 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         *recursive_tarball,
     );
 
+    apply_optional_value(
+        table,
+        "slsa-provenance",
+        "# Whether to generate an in-toto SLSA provenance statement\n",
+        *slsa_provenance,
+    );
+
+    apply_optional_value(
+        table,
+        "reproducible-archives",
+        "# Whether archives should be byte-for-byte reproducible\n",
+        *reproducible_archives,
+    );
+
     apply_string_list(
         table,
         "host-jobs",
@@ -1386,6 +1671,34 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         *force_latest,
     );
 
+    apply_optional_value(
+        table,
+        "changelog-from-commits",
+        "# Generate release notes from Conventional Commits instead of a CHANGELOG\n",
+        *changelog_from_commits,
+    );
+
+    apply_optional_value(
+        table,
+        "snapshot-version",
+        "# The version template for untagged releases\n",
+        snapshot_version.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "snapshot-tag",
+        "# A rolling tag to announce untagged releases under\n",
+        snapshot_tag.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "announcement-template",
+        "# A template for the body of GitHub/GitLab Releases (path relative to this Cargo.toml's dir)\n",
+        announcement_template.as_ref().map(|p| p.to_string()),
+    );
+
     apply_optional_value(
         table,
         "pr-run-mode",
@@ -1421,6 +1734,13 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         *macos_sign,
     );
 
+    apply_optional_value(
+        table,
+        "minisign-public-key",
+        "# A minisign public key to sign artifacts with (the secret key comes from MINISIGN_SECRET_KEY)\n",
+        minisign_public_key.as_deref(),
+    );
+
     apply_optional_value(
         table,
         "github-attestations",
@@ -1505,6 +1825,27 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         min_glibc_version.as_ref(),
     );
 
+    apply_optional_value(
+        table,
+        "enforce-min-glibc-version",
+        "# Whether to fail the build if a binary requires a newer glibc than min-glibc-version\n",
+        *enforce_min_glibc_version,
+    );
+
+    apply_optional_value(
+        table,
+        "split-debuginfo",
+        "# Whether to split the debuginfo of Linux binaries out into .debug files\n",
+        *split_debuginfo,
+    );
+
+    apply_optional_value(
+        table,
+        "symbol-store",
+        "# A directory to lay out split debuginfo in by build-id (debuginfod-style)\n",
+        symbol_store.as_ref().map(|p| p.to_string()),
+    );
+
     apply_optional_value(
         table,
         "cargo-auditable",
@@ -1533,6 +1874,34 @@ fn apply_dist_to_metadata(metadata: &mut toml_edit::Item, meta: &DistMetadata) {
         simple_download_url.clone(),
     );
 
+    apply_optional_value(
+        table,
+        "simple-s3-endpoint",
+        "# An S3-compatible endpoint to upload artifacts to\n",
+        simple_s3_endpoint.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "simple-s3-bucket",
+        "# The bucket to upload artifacts to\n",
+        simple_s3_bucket.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "simple-s3-prefix",
+        "# The prefix of the keys to upload artifacts to\n",
+        simple_s3_prefix.clone(),
+    );
+
+    apply_optional_value(
+        table,
+        "simple-s3-region",
+        "# The region to sign S3 requests for\n",
+        simple_s3_region.clone(),
+    );
+
     // Finalize the table
     table.decor_mut().set_prefix("\n# Config for 'dist'\n");
 }
@@ -1580,6 +1949,50 @@ where
     }
 }
 
+/// Same as [`apply_string_list`][] but for `targets`, which can contain target groups
+fn apply_target_list(
+    table: &mut toml_edit::Table,
+    key: &str,
+    desc: &str,
+    list: Option<&Vec<TargetSelector>>,
+) {
+    if let Some(list) = list {
+        let array: toml_edit::Array = list
+            .iter()
+            .map(|target| match target {
+                TargetSelector::Triple(triple) => toml_edit::Value::from(triple.as_str()),
+                TargetSelector::Group(group) => {
+                    let mut group_table = toml_edit::InlineTable::new();
+                    for (key, vals) in [
+                        ("os", &group.os),
+                        ("arch", &group.arch),
+                        ("libc", &group.libc),
+                    ] {
+                        match vals.as_deref() {
+                            None => {}
+                            Some([val]) => {
+                                group_table.insert(key, val.as_str().into());
+                            }
+                            Some(vals) => {
+                                let vals: toml_edit::Array =
+                                    vals.iter().map(String::as_str).collect();
+                                group_table.insert(key, vals.into());
+                            }
+                        }
+                    }
+                    toml_edit::Value::InlineTable(group_table)
+                }
+            })
+            .collect();
+        table.insert(key, toml_edit::Item::Value(toml_edit::Value::Array(array)));
+        if let Some(mut key) = table.key_mut(key) {
+            key.leaf_decor_mut().set_prefix(desc)
+        }
+    } else {
+        table.remove(key);
+    }
+}
+
 /// Same as [`apply_string_list`][] but when the list can be shorthanded as a string
 fn apply_string_or_list<I>(table: &mut toml_edit::Table, key: &str, desc: &str, list: Option<I>)
 where
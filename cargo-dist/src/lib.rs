@@ -20,21 +20,29 @@ use axoasset::LocalAsset;
 use axoprocess::Cmd;
 use backend::{
     ci::CiInfo,
+    desktop::write_desktop_entry,
     installer::{
         self, macpkg::PkgInstallerInfo, msi::MsiInstallerInfo, HomebrewImpl, InstallerImpl,
+        ScoopImpl, WingetImpl,
     },
 };
 use build::generic::{build_generic_target, run_extra_artifacts_build};
 use build::{
     cargo::{build_cargo_target, rustup_toolchain},
-    fake::{build_fake_cargo_target, build_fake_generic_target},
+    fake::{build_fake_cargo_target, build_fake_generic_target, build_fake_universal_binary},
+    graph::BuildGraph,
+    read_manifest,
+    universal::build_universal_binary,
+    write_manifest,
 };
 use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{ArtifactId, ChecksumValue, ChecksumValueRef, DistManifest, TripleName};
 use config::{
-    ArtifactMode, ChecksumStyle, CompressionImpl, Config, DirtyMode, GenerateMode, ZipStyle,
+    ArtifactMode, ChecksumStyle, CompressionImpl, Config, DirtyMode, GenerateMode, TargetGroup,
+    ZipStyle,
 };
 use semver::Version;
+use std::sync::RwLock;
 use temp_dir::TempDir;
 use tracing::info;
 
@@ -43,6 +51,7 @@ pub use init::{do_init, do_migrate, InitArgs};
 pub use tasks::*;
 
 pub mod announce;
+pub mod archive;
 pub mod backend;
 pub mod build;
 pub mod config;
@@ -54,10 +63,13 @@ pub mod linkage;
 pub mod manifest;
 pub mod net;
 pub mod platform;
+pub mod provenance;
+pub mod publish;
 pub mod sign;
 pub mod tasks;
 #[cfg(test)]
 mod tests;
+pub mod verify;
 
 /// dist env test -- make sure we have everything we need for a build.
 pub fn do_env_test(cfg: &Config) -> DistResult<()> {
@@ -74,6 +86,8 @@ pub fn do_env_test(cfg: &Config) -> DistResult<()> {
     let need_cargo_auditable = builds.cargo.cargo_auditable && local_builds;
     // omnibor is used in both local and global builds
     let need_omnibor = builds.omnibor;
+    // objcopy is used to split debuginfo out of linux binaries (of these targets)
+    let mut need_objcopy = tasks::SortedSet::new();
     let mut need_xwin = false;
     let mut need_zigbuild = false;
 
@@ -87,7 +101,15 @@ pub fn do_env_test(cfg: &Config) -> DistResult<()> {
         }
 
         match step {
+            BuildStep::Generic(step) => {
+                if builds.split_debuginfo && step.target_triple.is_linux() {
+                    need_objcopy.insert(&step.target_triple);
+                }
+            }
             BuildStep::Cargo(step) => {
+                if builds.split_debuginfo && step.target_triple.is_linux() {
+                    need_objcopy.insert(&step.target_triple);
+                }
                 let target = step.target_triple.parse()?;
                 let wrapper = tasks::build_wrapper_for_cross(&host, &target)?;
 
@@ -117,9 +139,18 @@ pub fn do_env_test(cfg: &Config) -> DistResult<()> {
     ];
 
     // Drop `None`s, then extract the values from the remaining `Option`s.
-    let needed_tools = all_tools.into_iter().flatten();
-
-    let missing: Vec<String> = needed_tools
+    // (objcopy is looked up per target, so it's checked for each of them)
+    let needed_tools = all_tools
+        .into_iter()
+        .flatten()
+        .map(|tool| tool.map(|_| ()))
+        .chain(
+            need_objcopy
+                .into_iter()
+                .map(|target| tools.objcopy(target).map(|_| ())),
+        );
+
+    let mut missing: Vec<String> = needed_tools
         .filter_map(|t| match t {
             // The tool was found.
             Ok(_) => None,
@@ -132,6 +163,7 @@ pub fn do_env_test(cfg: &Config) -> DistResult<()> {
             ),
         })
         .collect();
+    missing.dedup();
 
     missing
         .is_empty()
@@ -144,13 +176,19 @@ pub fn do_build(cfg: &Config) -> DistResult<DistManifest> {
     do_env_test(cfg)?;
     check_integrity(cfg)?;
 
-    let (dist, mut manifest) = tasks::gather_work(cfg)?;
+    let (dist, manifest) = tasks::gather_work(cfg)?;
 
-    // FIXME: parallelize this by working this like a dependency graph, so we can start
-    // bundling up an executable the moment it's built! Note however that you shouldn't
-    // parallelize Cargo invocations because it has global state that can get clobbered.
-    // Most problematically if you do two builds with different feature flags the final
-    // binaries will get copied to the same location and clobber each other :(
+    // Run the steps as a dependency graph, so we can start bundling up an executable
+    // the moment it's built. Note that Cargo invocations are never run in parallel,
+    // because it has global state that can get clobbered. Most problematically if you
+    // do two builds with different feature flags the final binaries will get copied to
+    // the same location and clobber each other :(
+    let steps = dist
+        .local_build_steps
+        .iter()
+        .chain(&dist.global_build_steps)
+        .collect::<Vec<_>>();
+    let graph = BuildGraph::new(&dist, &steps);
 
     // First set up our target dirs so things don't have to race to do it later
     if !dist.dist_dir.exists() {
@@ -163,26 +201,24 @@ pub fn do_build(cfg: &Config) -> DistResult<DistManifest> {
         init_artifact_dir(&dist, artifact)?;
     }
     eprintln!();
-
-    // Run all the local build steps first
-    for step in &dist.local_build_steps {
-        if dist.local_builds_are_lies {
-            build_fake(&dist, step, &mut manifest)?;
-        } else {
-            run_build_step(&dist, step, &mut manifest)?;
+    for step in &steps {
+        if let BuildStep::Lipo(step) = step {
+            init_lipo_dir(step)?;
         }
     }
 
-    // Next the global steps
-    for step in &dist.global_build_steps {
+    let manifest = RwLock::new(manifest);
+    graph.run(cfg.jobs, |idx| {
         if dist.local_builds_are_lies {
-            build_fake(&dist, step, &mut manifest)?;
+            build_fake(&dist, steps[idx], &manifest)
         } else {
-            run_build_step(&dist, step, &mut manifest)?;
+            run_build_step(&dist, steps[idx], &manifest)
         }
-    }
+    })?;
 
-    Ok(manifest)
+    manifest
+        .into_inner()
+        .map_err(|_| DistError::ManifestPoisoned)
 }
 
 /// Just generate the manifest produced by `dist build` without building
@@ -197,11 +233,12 @@ pub fn do_manifest(cfg: &Config) -> DistResult<DistManifest> {
 fn run_build_step(
     dist_graph: &DistGraph,
     target: &BuildStep,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
 ) -> DistResult<()> {
     match target {
         BuildStep::Generic(target) => build_generic_target(dist_graph, manifest, target)?,
         BuildStep::Cargo(target) => build_cargo_target(dist_graph, manifest, target)?,
+        BuildStep::Lipo(step) => build_universal_binary(dist_graph, manifest, step)?,
         BuildStep::Rustup(cmd) => rustup_toolchain(dist_graph, cmd)?,
         BuildStep::CopyFile(CopyStep {
             src_path,
@@ -215,14 +252,24 @@ fn run_build_step(
             src_path,
             dest_path,
         }) => copy_file_or_dir(src_path, dest_path)?,
+        BuildStep::DesktopEntry(DesktopEntryStep { entry, dest_dir }) => {
+            write_desktop_entry(entry, dest_dir)?
+        }
         BuildStep::Zip(ZipDirStep {
             src_path,
             dest_path,
             zip_style,
             with_root,
-        }) => zip_dir(src_path, dest_path, zip_style, with_root.as_deref())?,
+            source_date_epoch,
+        }) => zip_dir(
+            src_path,
+            dest_path,
+            zip_style,
+            with_root.as_deref(),
+            *source_date_epoch,
+        )?,
         BuildStep::GenerateInstaller(installer) => {
-            generate_installer(dist_graph, installer, manifest)?
+            generate_installer(dist_graph, installer, &*read_manifest(manifest)?)?
         }
         BuildStep::Checksum(ChecksumImpl {
             checksum,
@@ -239,7 +286,14 @@ fn run_build_step(
         BuildStep::UnifiedChecksum(UnifiedChecksumStep {
             checksum,
             dest_path,
-        }) => generate_unified_checksum(manifest, *checksum, dest_path)?,
+        }) => generate_unified_checksum(&*read_manifest(manifest)?, *checksum, dest_path)?,
+        BuildStep::Signature(SignatureImpl {
+            src_path,
+            dest_path,
+        }) => dist_graph.signer.write_signature(src_path, dest_path)?,
+        BuildStep::Provenance(provenance) => {
+            provenance::generate_provenance(dist_graph, &*read_manifest(manifest)?, provenance)?
+        }
         BuildStep::OmniborArtifactId(OmniborArtifactIdImpl {
             src_path,
             dest_path,
@@ -372,13 +426,14 @@ fn fetch_updater_from_binary(
 fn build_fake(
     dist_graph: &DistGraph,
     target: &BuildStep,
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
 ) -> DistResult<()> {
     match target {
         // These two are the meat: don't actually run these at all, just
         // fake them out
         BuildStep::Generic(target) => build_fake_generic_target(dist_graph, manifest, target)?,
         BuildStep::Cargo(target) => build_fake_cargo_target(dist_graph, manifest, target)?,
+        BuildStep::Lipo(step) => build_fake_universal_binary(dist_graph, manifest, step)?,
         // Never run rustup
         BuildStep::Rustup(_) => {}
         // Copying files is fairly safe
@@ -394,18 +449,28 @@ fn build_fake(
             src_path,
             dest_path,
         }) => copy_file_or_dir(src_path, dest_path)?,
+        BuildStep::DesktopEntry(DesktopEntryStep { entry, dest_dir }) => {
+            write_desktop_entry(entry, dest_dir)?
+        }
         // The remainder of these are mostly safe to run as fake steps
         BuildStep::Zip(ZipDirStep {
             src_path,
             dest_path,
             zip_style,
             with_root,
-        }) => zip_dir(src_path, dest_path, zip_style, with_root.as_deref())?,
+            source_date_epoch,
+        }) => zip_dir(
+            src_path,
+            dest_path,
+            zip_style,
+            with_root.as_deref(),
+            *source_date_epoch,
+        )?,
         BuildStep::GenerateInstaller(installer) => match installer {
             // MSI and pkg, unlike other installers, aren't safe to generate on any platform
-            InstallerImpl::Msi(msi) => generate_fake_msi(dist_graph, msi, manifest)?,
-            InstallerImpl::Pkg(pkg) => generate_fake_pkg(dist_graph, pkg, manifest)?,
-            _ => generate_installer(dist_graph, installer, manifest)?,
+            InstallerImpl::Msi(msi) => generate_fake_msi(dist_graph, msi)?,
+            InstallerImpl::Pkg(pkg) => generate_fake_pkg(dist_graph, pkg)?,
+            _ => generate_installer(dist_graph, installer, &*read_manifest(manifest)?)?,
         },
         BuildStep::Checksum(ChecksumImpl {
             checksum,
@@ -422,7 +487,22 @@ fn build_fake(
         BuildStep::UnifiedChecksum(UnifiedChecksumStep {
             checksum,
             dest_path,
-        }) => generate_unified_checksum(manifest, *checksum, dest_path)?,
+        }) => generate_unified_checksum(&*read_manifest(manifest)?, *checksum, dest_path)?,
+        // Signing is safe as long as we have a key, otherwise write a placeholder
+        BuildStep::Signature(SignatureImpl {
+            src_path,
+            dest_path,
+        }) => {
+            if dist_graph.signer.can_make_signatures() {
+                dist_graph.signer.write_signature(src_path, dest_path)?
+            } else {
+                LocalAsset::write_new_all("", dest_path)?;
+            }
+        }
+        // Provenance only describes what's already been made, so it's safe
+        BuildStep::Provenance(provenance) => {
+            provenance::generate_provenance(dist_graph, &*read_manifest(manifest)?, provenance)?
+        }
         BuildStep::OmniborArtifactId(OmniborArtifactIdImpl {
             src_path,
             dest_path,
@@ -457,21 +537,13 @@ fn run_fake_extra_artifacts_build(dist: &DistGraph, target: &ExtraBuildStep) ->
     Ok(())
 }
 
-fn generate_fake_msi(
-    _dist: &DistGraph,
-    msi: &MsiInstallerInfo,
-    _manifest: &DistManifest,
-) -> DistResult<()> {
+fn generate_fake_msi(_dist: &DistGraph, msi: &MsiInstallerInfo) -> DistResult<()> {
     LocalAsset::write_new_all("", &msi.file_path)?;
 
     Ok(())
 }
 
-fn generate_fake_pkg(
-    _dist: &DistGraph,
-    pkg: &PkgInstallerInfo,
-    _manifest: &DistManifest,
-) -> DistResult<()> {
+fn generate_fake_pkg(_dist: &DistGraph, pkg: &PkgInstallerInfo) -> DistResult<()> {
     LocalAsset::write_new_all("", &pkg.file_path)?;
 
     Ok(())
@@ -501,7 +573,7 @@ fn generate_omnibor_artifact_id(
 
 /// Generate a checksum for the src_path to dest_path
 fn generate_and_write_checksum(
-    manifest: &mut DistManifest,
+    manifest: &RwLock<DistManifest>,
     checksum: &ChecksumStyle,
     src_path: &Utf8Path,
     dest_path: Option<&Utf8Path>,
@@ -513,7 +585,7 @@ fn generate_and_write_checksum(
         write_checksum_file(&[(name, &output)], dest_path)?;
     }
     if let Some(artifact_id) = for_artifact {
-        if let Some(artifact) = manifest.artifacts.get_mut(artifact_id) {
+        if let Some(artifact) = write_manifest(manifest)?.artifacts.get_mut(artifact_id) {
             artifact.checksums.insert(checksum.ext().to_owned(), output);
         }
     }
@@ -738,6 +810,22 @@ fn write_checksum_file(
     Ok(())
 }
 
+/// Initialize the scratch dir that a universal binary's slices get staged in
+fn init_lipo_dir(step: &LipoStep) -> DistResult<()> {
+    if let Some(staging_dir) = step.dest_path.parent() {
+        if staging_dir.exists() {
+            LocalAsset::remove_dir_all(staging_dir)?;
+        }
+    }
+    for src_path in &step.src_paths {
+        if let Some(slice_dir) = src_path.parent() {
+            LocalAsset::create_dir_all(slice_dir)?;
+        }
+    }
+
+    Ok(())
+}
+
 /// Initialize the dir for an artifact (and delete the old artifact file).
 fn init_artifact_dir(_dist: &DistGraph, artifact: &Artifact) -> DistResult<()> {
     // Delete any existing bundle
@@ -783,7 +871,11 @@ fn zip_dir(
     dest_path: &Utf8Path,
     zip_style: &ZipStyle,
     with_root: Option<&Utf8Path>,
+    source_date_epoch: Option<u64>,
 ) -> DistResult<()> {
+    if let Some(mtime) = source_date_epoch {
+        return archive::reproducible_archive(src_path, dest_path, zip_style, with_root, mtime);
+    }
     match zip_style {
         ZipStyle::Zip => LocalAsset::zip_dir(src_path, dest_path, with_root)?,
         ZipStyle::Tar(CompressionImpl::Gzip) => {
@@ -877,7 +969,7 @@ pub fn run_generate(dist: &DistGraph, args: &GenerateArgs) -> DistResult<()> {
             match mode {
                 GenerateMode::Ci => {
                     // If you add a CI backend, call it here
-                    let CiInfo { github } = &dist.ci;
+                    let CiInfo { github, gitlab } = &dist.ci;
                     if let Some(github) = github {
                         if args.check {
                             github.check(dist)?;
@@ -885,6 +977,13 @@ pub fn run_generate(dist: &DistGraph, args: &GenerateArgs) -> DistResult<()> {
                             github.write_to_disk(dist)?;
                         }
                     }
+                    if let Some(gitlab) = gitlab {
+                        if args.check {
+                            gitlab.check(dist)?;
+                        } else {
+                            gitlab.write_to_disk(dist)?;
+                        }
+                    }
                 }
                 GenerateMode::Msi => {
                     for artifact in &dist.artifacts {
@@ -929,6 +1028,7 @@ pub fn check_integrity(cfg: &Config) -> DistResult<()> {
         ci: vec![],
         installers: vec![],
         root_cmd: "check".to_owned(),
+        jobs: 1,
     };
     let (dist, _manifest) = tasks::gather_work(&check_config)?;
 
@@ -960,6 +1060,22 @@ fn generate_installer(
         }
         InstallerImpl::Msi(info) => info.build(dist)?,
         InstallerImpl::Pkg(info) => info.build()?,
+        InstallerImpl::Deb(info) => info.build()?,
+        InstallerImpl::Rpm(info) => info.build()?,
+        InstallerImpl::Scoop(ScoopImpl { info, fragments }) => {
+            installer::scoop::write_scoop_manifest(dist, info, fragments, manifest)?
+        }
+        InstallerImpl::Winget(WingetImpl {
+            info,
+            manifest_type,
+            fragments,
+        }) => installer::winget::write_winget_manifest(
+            dist,
+            info,
+            *manifest_type,
+            fragments,
+            manifest,
+        )?,
     }
     Ok(())
 }
@@ -995,3 +1111,22 @@ pub fn known_desktop_targets() -> Vec<TripleName> {
         t::TARGET_ARM64_WINDOWS.to_owned(),
     ]
 }
+
+/// Get some handy target groups to offer alongside [`known_desktop_targets`][]
+pub fn known_target_groups() -> Vec<TargetGroup> {
+    let strings = |vals: &[&str]| Some(vals.iter().map(|val| val.to_string()).collect());
+    vec![
+        // All the unix-y desktops
+        TargetGroup {
+            os: strings(&["linux", "macos"]),
+            arch: strings(&["x86_64", "aarch64"]),
+            libc: None,
+        },
+        // Static linux builds
+        TargetGroup {
+            os: strings(&["linux"]),
+            arch: strings(&["x86_64", "aarch64"]),
+            libc: strings(&["musl"]),
+        },
+    ]
+}
@@ -1,23 +1,23 @@
 //! The Linkage Checker, which lets us detect what a binary dynamically links to (and why)
 
-use std::{
-    fs::{self, File},
-    io::{Cursor, Read},
-};
+use std::{collections::BTreeMap, fs};
 
 use axoasset::SourceFile;
 use axoprocess::Cmd;
 use camino::Utf8PathBuf;
 use cargo_dist_schema::{
     AssetInfo, BuildEnvironment, DistManifest, GlibcVersion, Library, Linkage, PackageManager,
-    TripleNameRef,
+    SortedSet, TripleNameRef,
 };
 use comfy_table::{presets::UTF8_FULL, Table};
-use goblin::Object;
-use mach_object::{LoadCommand, OFile};
+use goblin::{
+    elf::{header, section_header, Elf},
+    mach::{Mach, MachO},
+    Object,
+};
 use tracing::warn;
 
-use crate::{config::Config, errors::*, gather_work, Artifact, DistGraph};
+use crate::{config::Config, errors::*, gather_work, platform::LibcVersion, Artifact, DistGraph};
 
 /// Arguments for `dist linkage` ([`do_linkage][])
 #[derive(Debug)]
@@ -184,6 +184,16 @@ fn format_linkage_table(f: &mut std::fmt::Formatter<'_>, linkage: &Linkage) -> s
                 .collect::<Vec<String>>()
                 .join("\n")
                 .as_str(),
+        ])
+        .add_row(vec![
+            "glibc symbol versions",
+            linkage
+                .glibc_symbol_versions
+                .keys()
+                .cloned()
+                .collect::<Vec<String>>()
+                .join("\n")
+                .as_str(),
         ]);
     write!(f, "{table}")
 }
@@ -287,95 +297,176 @@ pub fn library_from_apt(library: String) -> DistResult<Library> {
     }
 }
 
-fn do_otool(path: &Utf8PathBuf) -> DistResult<Vec<String>> {
-    let mut libraries = vec![];
-
-    let mut f = File::open(path)?;
-    let mut buf = vec![];
-    let size = f.read_to_end(&mut buf).unwrap();
-    let mut cur = Cursor::new(&buf[..size]);
-    if let Ok(OFile::MachFile {
-        header: _,
-        commands,
-    }) = OFile::parse(&mut cur)
-    {
-        let commands = commands
-            .iter()
-            .map(|load| load.command())
-            .cloned()
-            .collect::<Vec<LoadCommand>>();
-
-        for command in commands {
-            match command {
-                LoadCommand::IdDyLib(ref dylib)
-                | LoadCommand::LoadDyLib(ref dylib)
-                | LoadCommand::LoadWeakDyLib(ref dylib)
-                | LoadCommand::ReexportDyLib(ref dylib)
-                | LoadCommand::LoadUpwardDylib(ref dylib)
-                | LoadCommand::LazyLoadDylib(ref dylib) => {
-                    libraries.push(dylib.name.to_string());
-                }
-                _ => {}
+/// Everything we can learn about a binary's dynamic linkage from its headers alone
+#[derive(Debug, Default)]
+struct ObjectLinkage {
+    /// The libraries the binary asks the loader for
+    libraries: Vec<String>,
+    /// glibc symbol versions the binary requires, and which symbols require them
+    glibc_symbol_versions: BTreeMap<String, SortedSet<String>>,
+}
+
+/// Parse the binary at the given path, regardless of what platform we're running on
+fn parse_object(path: &Utf8PathBuf) -> DistResult<ObjectLinkage> {
+    let buf = fs::read(path)?;
+    match Object::parse(&buf)? {
+        Object::Elf(elf) => Ok(do_elf(path, &elf)),
+        Object::Mach(mach) => Ok(ObjectLinkage {
+            libraries: do_macho(&buf, mach)?,
+            ..Default::default()
+        }),
+        Object::PE(pe) => Ok(ObjectLinkage {
+            libraries: pe.libraries.into_iter().map(|s| s.to_owned()).collect(),
+            ..Default::default()
+        }),
+        // Static libraries link against nothing
+        Object::Archive(_) => Ok(ObjectLinkage::default()),
+        _ => Err(DistError::LinkageCheckUnsupportedBinary),
+    }
+}
+
+/// Get the LC_LOAD_DYLIB (and friends) entries of a Mach-O binary;
+/// for universal binaries, this is the union of every architecture's libraries
+fn do_macho(buf: &[u8], mach: Mach) -> DistResult<Vec<String>> {
+    let mut libraries = SortedSet::new();
+    let mut collect = |macho: &MachO| {
+        // goblin puts a placeholder for the binary's own LC_ID_DYLIB first
+        libraries.extend(
+            macho
+                .libs
+                .iter()
+                .filter(|lib| **lib != "self")
+                .map(|lib| lib.to_string()),
+        );
+    };
+    match mach {
+        Mach::Binary(macho) => collect(&macho),
+        Mach::Fat(fat) => {
+            for arch in fat.iter_arches() {
+                let arch = arch?;
+                let start = arch.offset as usize;
+                let end = start + arch.size as usize;
+                let Some(slice) = buf.get(start..end) else {
+                    return Err(DistError::LinkageCheckUnsupportedBinary);
+                };
+                collect(&MachO::parse(slice, 0)?);
             }
         }
     }
 
-    Ok(libraries)
+    Ok(libraries.into_iter().collect())
 }
 
-fn do_ldd(path: &Utf8PathBuf) -> DistResult<Vec<String>> {
-    let mut libraries = vec![];
+/// Get the DT_NEEDED entries of an ELF binary, resolving them to paths on the
+/// current system where we can, and the glibc symbol versions it requires.
+fn do_elf(path: &Utf8PathBuf, elf: &Elf) -> ObjectLinkage {
+    let search_paths = elf_search_paths(path, elf);
+    let libraries = elf
+        .libraries
+        .iter()
+        .map(|soname| {
+            // Resolve any symlinks so that we return the real paths,
+            // making it easier to map them to their packages later.
+            search_paths
+                .iter()
+                .map(|dir| dir.join(soname))
+                .find(|candidate| candidate.is_file())
+                .and_then(|candidate| fs::canonicalize(candidate).ok())
+                .map(|realpath| realpath.to_string_lossy().to_string())
+                .unwrap_or_else(|| soname.to_string())
+        })
+        .collect();
+
+    ObjectLinkage {
+        libraries,
+        glibc_symbol_versions: elf_glibc_symbol_versions(elf),
+    }
+}
 
-    // We ignore the status here because for whatever reason arm64 glibc ldd can decide
-    // to return non-zero status on binaries with no dynamic linkage (e.g. musl-static).
-    // This was observed both in arm64 ubuntu and asahi (both glibc ldd).
-    // x64 glibc ldd is perfectly fine with this and returns 0, so... *shrug* compilers!
-    let output = Cmd::new("ldd", "get linkage info from ldd")
-        .arg(path)
-        .check(false)
-        .output()?;
+/// The directories the dynamic loader would search for this binary's libraries,
+/// to the extent that they exist on the current system.
+fn elf_search_paths(path: &Utf8PathBuf, elf: &Elf) -> Vec<Utf8PathBuf> {
+    let origin = path
+        .canonicalize_utf8()
+        .ok()
+        .and_then(|p| p.parent().map(|p| p.to_owned()))
+        .unwrap_or_default();
+    // DT_RPATH is ignored by the loader when DT_RUNPATH is present
+    let rpaths = if elf.runpaths.is_empty() {
+        &elf.rpaths
+    } else {
+        &elf.runpaths
+    };
+    let mut dirs = rpaths
+        .iter()
+        .flat_map(|rpath| rpath.split(':'))
+        .filter(|dir| !dir.is_empty())
+        .map(|dir| {
+            Utf8PathBuf::from(
+                dir.replace("${ORIGIN}", origin.as_str())
+                    .replace("$ORIGIN", origin.as_str()),
+            )
+        })
+        .collect::<Vec<_>>();
+
+    let (multiarch, host_arch) = match elf.header.e_machine {
+        header::EM_X86_64 => ("x86_64-linux-gnu", "x86_64"),
+        header::EM_AARCH64 => ("aarch64-linux-gnu", "aarch64"),
+        header::EM_386 => ("i386-linux-gnu", "x86"),
+        header::EM_ARM => ("arm-linux-gnueabihf", "arm"),
+        header::EM_RISCV => ("riscv64-linux-gnu", "riscv64"),
+        header::EM_PPC64 => ("powerpc64le-linux-gnu", "powerpc64"),
+        header::EM_S390 => ("s390x-linux-gnu", "s390x"),
+        _ => ("", ""),
+    };
+    if !multiarch.is_empty() {
+        dirs.push(Utf8PathBuf::from("/lib").join(multiarch));
+        dirs.push(Utf8PathBuf::from("/usr/lib").join(multiarch));
+    }
+    // The non-multiarch directories only hold libraries for the host's architecture,
+    // so don't go looking there for a cross-compiled binary's libraries
+    if std::env::consts::OS == "linux" && std::env::consts::ARCH == host_arch {
+        dirs.extend(["/lib64", "/usr/lib64", "/lib", "/usr/lib"].map(Utf8PathBuf::from));
+    }
 
-    let result = String::from_utf8_lossy(&output.stdout).to_string();
-    let lines = result.trim_end().split('\n');
+    dirs
+}
 
-    for line in lines {
-        let line = line.trim();
+/// Find the GLIBC_* versions attached to the binary's undefined dynamic symbols
+fn elf_glibc_symbol_versions(elf: &Elf) -> BTreeMap<String, SortedSet<String>> {
+    let mut output = BTreeMap::<String, SortedSet<String>>::new();
+    let (Some(versym), Some(verneed)) = (&elf.versym, &elf.verneed) else {
+        return output;
+    };
 
-        // There's no dynamic linkage at all; we can safely break,
-        // there will be nothing useful to us here.
-        if line.starts_with("not a dynamic executable") || line.starts_with("statically linked") {
-            break;
-        }
+    // Version indices are shared between every library the binary depends on
+    let version_names = verneed
+        .iter()
+        .flat_map(|need| need.iter().collect::<Vec<_>>())
+        .filter_map(|aux| Some((aux.vna_other, elf.dynstrtab.get_at(aux.vna_name)?)))
+        .filter(|(_, name)| name.starts_with("GLIBC_"))
+        .collect::<BTreeMap<_, _>>();
 
-        // Not a library that actually concerns us
-        if line.starts_with("linux-vdso") {
+    for (idx, sym) in elf.dynsyms.iter().enumerate() {
+        if sym.st_shndx != section_header::SHN_UNDEF as usize {
             continue;
         }
-
-        // Format: libname.so.1 => /path/to/libname.so.1 (address)
-        if let Some(path) = line.split(" => ").nth(1) {
-            // This may be a symlink rather than the actual underlying library;
-            // we resolve the symlink here so that we return the real paths,
-            // making it easier to map them to their packages later.
-            let lib = (path.split(' ').next().unwrap()).to_owned();
-            let realpath = fs::canonicalize(&lib)?;
-            libraries.push(realpath.to_string_lossy().to_string());
-        } else {
+        let Some(version) = versym.get_at(idx).map(|v| v.version()) else {
             continue;
-        }
+        };
+        let (Some(version_name), Some(sym_name)) = (
+            version_names.get(&version),
+            elf.dynstrtab.get_at(sym.st_name),
+        ) else {
+            continue;
+        };
+        output
+            .entry(version_name.to_string())
+            .or_default()
+            .insert(sym_name.to_owned());
     }
 
-    Ok(libraries)
-}
-
-fn do_pe(path: &Utf8PathBuf) -> DistResult<Vec<String>> {
-    let buf = std::fs::read(path)?;
-    match Object::parse(&buf)? {
-        Object::PE(pe) => Ok(pe.libraries.into_iter().map(|s| s.to_owned()).collect()),
-        // Static libraries link against nothing
-        Object::Archive(_) => Ok(vec![]),
-        _ => Err(DistError::LinkageCheckUnsupportedBinary),
-    }
+    output
 }
 
 /// Get the linkage for a single binary
@@ -392,30 +483,31 @@ pub fn determine_linkage(path: &Utf8PathBuf, target: &TripleNameRef) -> Linkage
 }
 
 /// Get the linkage for a single binary
-fn try_determine_linkage(path: &Utf8PathBuf, target: &TripleNameRef) -> DistResult<Linkage> {
-    let libraries = if target.is_darwin() {
-        do_otool(path)?
-    } else if target.is_linux() {
-        // Currently can only be run on Linux
-        if std::env::consts::OS != "linux" {
-            return Err(DistError::LinkageCheckInvalidOS {
-                host: std::env::consts::OS.to_owned(),
-                target: target.to_owned(),
-            });
-        }
-        do_ldd(path)?
-    } else if target.is_windows() {
-        do_pe(path)?
-    } else {
+///
+/// This works for any target on any host, since we read the binary's headers ourselves
+/// instead of asking the platform's tools. On Linux, libraries are resolved against the
+/// current system where possible; libraries which can't be found are reported by soname.
+pub fn try_determine_linkage(path: &Utf8PathBuf, target: &TripleNameRef) -> DistResult<Linkage> {
+    if !target.is_darwin() && !target.is_linux() && !target.is_windows() {
         return Err(DistError::LinkageCheckUnsupportedBinary);
-    };
-
+    }
+    let ObjectLinkage {
+        libraries,
+        glibc_symbol_versions,
+    } = parse_object(path)?;
+
+    let max_glibc_version = glibc_symbol_versions
+        .keys()
+        .filter_map(|version| glibc_from_symbol_version(version))
+        .max();
     let mut linkage = Linkage {
         system: Default::default(),
         homebrew: Default::default(),
         public_unmanaged: Default::default(),
         frameworks: Default::default(),
         other: Default::default(),
+        glibc_symbol_versions,
+        max_glibc_version,
     };
     for library in libraries {
         if library.starts_with("/opt/homebrew") {
@@ -423,13 +515,20 @@ fn try_determine_linkage(path: &Utf8PathBuf, target: &TripleNameRef) -> DistResu
                 .homebrew
                 .insert(library_from_homebrew(library.clone()));
         } else if library.starts_with("/usr/lib") || library.starts_with("/lib") {
-            linkage.system.insert(library_from_apt(library.clone())?);
+            if target.is_linux() {
+                linkage.system.insert(library_from_apt(library.clone())?);
+            } else {
+                linkage.system.insert(Library::new(library.clone()));
+            }
         } else if library.starts_with("/System/Library/Frameworks")
             || library.starts_with("/Library/Frameworks")
         {
             linkage.frameworks.insert(Library::new(library.clone()));
         } else if library.starts_with("/usr/local") {
-            if std::fs::canonicalize(&library)?.starts_with("/usr/local/Cellar") {
+            if fs::canonicalize(&library)
+                .map(|p| p.starts_with("/usr/local/Cellar"))
+                .unwrap_or(false)
+            {
                 linkage
                     .homebrew
                     .insert(library_from_homebrew(library.clone()));
@@ -438,14 +537,79 @@ fn try_determine_linkage(path: &Utf8PathBuf, target: &TripleNameRef) -> DistResu
                     .public_unmanaged
                     .insert(Library::new(library.clone()));
             }
-        } else {
+        } else if target.is_linux() && is_glibc_soname(&library) {
+            // We couldn't find it on this system (probably a cross-check),
+            // but these always come with the OS
+            linkage.system.insert(Library::new(library.clone()));
+        } else if target.is_linux() {
             linkage.other.insert(library_from_apt(library.clone())?);
+        } else {
+            linkage.other.insert(Library::new(library.clone()));
         }
     }
 
     Ok(linkage)
 }
 
+/// Whether this is the soname of a library shipped as part of glibc (or gcc's runtime)
+fn is_glibc_soname(soname: &str) -> bool {
+    const SONAMES: &[&str] = &[
+        "libc.so.",
+        "libm.so.",
+        "libpthread.so.",
+        "libdl.so.",
+        "librt.so.",
+        "libutil.so.",
+        "libgcc_s.so.",
+        "ld-linux",
+    ];
+    SONAMES.iter().any(|prefix| soname.starts_with(prefix))
+}
+
+/// Check that a binary doesn't require a newer glibc than the given ceiling
+/// (the configured min-glibc-version), listing every symbol which does.
+pub fn check_glibc_ceiling(
+    bin_name: &str,
+    target: &TripleNameRef,
+    linkage: &Linkage,
+    ceiling: LibcVersion,
+) -> DistResult<()> {
+    let offending = linkage
+        .glibc_symbol_versions
+        .iter()
+        .filter(|(version, _)| {
+            glibc_from_symbol_version(version)
+                .is_some_and(|v| LibcVersion::glibc_from_schema(&v) > ceiling)
+        })
+        .flat_map(|(version, symbols)| symbols.iter().map(move |sym| format!("{sym}@{version}")))
+        .collect::<Vec<_>>();
+    if offending.is_empty() {
+        return Ok(());
+    }
+
+    let required = linkage
+        .max_glibc_version
+        .as_ref()
+        .map(|v| format!("{}.{}", v.major, v.series))
+        .unwrap_or_default();
+    Err(DistError::GlibcCeilingExceeded {
+        bin_name: bin_name.to_owned(),
+        target: target.to_owned(),
+        ceiling: ceiling.to_string(),
+        required,
+        symbols: offending.join("\n"),
+    })
+}
+
+/// Parse a symbol version like "GLIBC_2.2.5" into a glibc version, ignoring
+/// the patch version (and non-numeric versions like "GLIBC_PRIVATE")
+fn glibc_from_symbol_version(version: &str) -> Option<GlibcVersion> {
+    let mut parts = version.strip_prefix("GLIBC_")?.split('.');
+    let major = parts.next()?.parse().ok()?;
+    let series = parts.next().unwrap_or("0").parse().ok()?;
+    Some(GlibcVersion { major, series })
+}
+
 /// Determine the build environment on the current host
 /// This should be done local to the builder!
 pub fn determine_build_environment(target: &TripleNameRef) -> BuildEnvironment {
@@ -511,3 +675,91 @@ fn determine_macos_build_environment() -> DistResult<BuildEnvironment> {
 
     Ok(BuildEnvironment::MacOS { os_version })
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    #[cfg(all(target_os = "linux", target_env = "gnu"))]
+    fn parses_own_elf_linkage() {
+        let exe = Utf8PathBuf::from_path_buf(std::env::current_exe().unwrap()).unwrap();
+        let parsed = parse_object(&exe).unwrap();
+        assert!(parsed.libraries.iter().any(|lib| lib.contains("libc.so.6")));
+        // Everything built against glibc needs at least one of these
+        assert!(!parsed.glibc_symbol_versions.is_empty());
+        assert!(parsed
+            .glibc_symbol_versions
+            .keys()
+            .all(|version| version.starts_with("GLIBC_")));
+    }
+
+    #[test]
+    fn categorizes_unresolved_sonames() {
+        assert!(is_glibc_soname("libc.so.6"));
+        assert!(is_glibc_soname("ld-linux-aarch64.so.1"));
+        assert!(is_glibc_soname("libgcc_s.so.1"));
+        assert!(!is_glibc_soname("libssl.so.3"));
+    }
+
+    #[test]
+    fn checks_glibc_ceiling() {
+        let target = TripleNameRef::from_str("x86_64-unknown-linux-gnu");
+        let mut linkage = Linkage::default();
+        for (version, sym) in [
+            ("GLIBC_2.2.5", "memcpy"),
+            ("GLIBC_2.17", "clock_gettime"),
+            ("GLIBC_2.34", "__libc_start_main"),
+            ("GLIBC_2.34", "pthread_create"),
+            ("GLIBC_PRIVATE", "_dl_find_object"),
+        ] {
+            linkage
+                .glibc_symbol_versions
+                .entry(version.to_owned())
+                .or_default()
+                .insert(sym.to_owned());
+        }
+        linkage.max_glibc_version = Some(GlibcVersion {
+            major: 2,
+            series: 34,
+        });
+
+        let ceiling = |major, series| LibcVersion { major, series };
+        check_glibc_ceiling("app", target, &linkage, ceiling(2, 34)).unwrap();
+        check_glibc_ceiling("app", target, &linkage, ceiling(3, 0)).unwrap();
+        let err = check_glibc_ceiling("app", target, &linkage, ceiling(2, 17)).unwrap_err();
+        let DistError::GlibcCeilingExceeded {
+            required, symbols, ..
+        } = err
+        else {
+            panic!("unexpected error {err:?}");
+        };
+        assert_eq!(required, "2.34");
+        assert_eq!(
+            symbols,
+            "__libc_start_main@GLIBC_2.34\npthread_create@GLIBC_2.34"
+        );
+    }
+
+    #[test]
+    fn unreadable_binary_is_an_error() {
+        // determine_linkage shrugs this off, but the glibc gate relies on the error
+        let dir = temp_dir::TempDir::new().unwrap();
+        let path = Utf8PathBuf::from_path_buf(dir.child("app")).unwrap();
+        std::fs::write(&path, "#!/bin/sh\n").unwrap();
+        let target = TripleNameRef::from_str("x86_64-unknown-linux-gnu");
+        assert!(try_determine_linkage(&path, target).is_err());
+        assert!(determine_linkage(&path, target)
+            .glibc_symbol_versions
+            .is_empty());
+    }
+
+    #[test]
+    fn parses_glibc_symbol_versions() {
+        let parse = |s| glibc_from_symbol_version(s).map(|v| (v.major, v.series));
+        assert_eq!(parse("GLIBC_2.2.5"), Some((2, 2)));
+        assert_eq!(parse("GLIBC_2.34"), Some((2, 34)));
+        assert_eq!(parse("GLIBC_PRIVATE"), None);
+        assert_eq!(parse("GCC_3.0"), None);
+    }
+}
@@ -14,7 +14,7 @@ use cargo_dist_schema::{AssetKind, DistManifest};
 use clap::Parser;
 use cli::{
     Cli, Commands, GenerateMode, HelpMarkdownArgs, HostArgs, ManifestArgs, OutputFormat, PlanArgs,
-    PrintUploadFilesFromManifestArgs,
+    PrintUploadFilesFromManifestArgs, PublishArgs, VerifyArgs,
 };
 use console::Term;
 use miette::{miette, IntoDiagnostic};
@@ -62,6 +62,8 @@ fn real_main(cli: &axocli::CliApp<Cli>) -> Result<(), miette::Report> {
             cmd_print_upload_files_from_manifest(config, args)
         }
         Commands::Host(args) => cmd_host(config, args),
+        Commands::Verify(args) => cmd_verify(config, args),
+        Commands::Publish(args) => cmd_publish(config, args),
         Commands::Selfupdate(args) => runtime.block_on(cmd_update(config, args)),
     }
 }
@@ -116,8 +118,10 @@ fn print_human(out: &mut Term, manifest: &DistManifest) -> Result<(), std::io::E
         )?;
         for artifact_id in &release.artifacts {
             let artifact = &manifest.artifacts[artifact_id];
-            if let cargo_dist_schema::ArtifactKind::Checksum = &artifact.kind {
-                // Don't print shasums at top-level
+            if let cargo_dist_schema::ArtifactKind::Checksum
+            | cargo_dist_schema::ArtifactKind::Signature = &artifact.kind
+            {
+                // Don't print shasums or signatures at top-level
                 continue;
             }
 
@@ -179,6 +183,13 @@ fn print_human(out: &mut Term, manifest: &DistManifest) -> Result<(), std::io::E
                 write!(out, "      [checksum] ")?;
                 print_human_artifact_path(out, checksum_artifact)?;
             }
+
+            // Same for the signature
+            if let Some(signature_id) = &artifact.signature {
+                let signature_artifact = &manifest.artifacts[signature_id];
+                write!(out, "      [signature] ")?;
+                print_human_artifact_path(out, signature_artifact)?;
+            }
         }
     }
     Ok(())
@@ -227,6 +238,7 @@ fn cmd_build(cli: &Cli, args: &BuildArgs) -> Result<(), miette::Report> {
         ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: "build".to_owned(),
+        jobs: args.jobs.get(),
     };
     let report = do_build(&config)?;
     print(
@@ -253,6 +265,56 @@ fn cmd_print_upload_files_from_manifest(
     Ok(())
 }
 
+fn cmd_verify(cli: &Cli, args: &VerifyArgs) -> Result<(), miette::Report> {
+    let args = cargo_dist::verify::VerifyArgs {
+        manifest: args.manifest.clone(),
+        dir: args.dir.clone(),
+    };
+    let report = cargo_dist::verify::do_verify(&args)?;
+
+    let mut out = Term::stdout();
+    match cli.output_format {
+        OutputFormat::Human => {
+            writeln!(out, "{}", cargo_dist::verify::VerifyDisplay(&report)).into_diagnostic()?
+        }
+        OutputFormat::Json => {
+            let string = serde_json::to_string_pretty(&report).unwrap();
+            writeln!(out, "{string}").into_diagnostic()?;
+        }
+    }
+
+    if !report.is_ok() {
+        Err(errors::DistError::VerifyFailed {
+            mismatched: report.mismatched.len(),
+            missing: report.missing.len(),
+            extra: report.extra.len(),
+        })?;
+    }
+    Ok(())
+}
+
+fn cmd_publish(cli: &Cli, args: &PublishArgs) -> Result<(), miette::Report> {
+    let args = cargo_dist::publish::PublishArgs {
+        dir: args.dir.clone(),
+        manifest: args.manifest.clone(),
+        publishers: args.publishers.iter().map(|p| p.to_lib()).collect(),
+        dry_run: args.dry_run,
+    };
+    let report = cargo_dist::publish::do_publish(&args)?;
+
+    let mut out = Term::stdout();
+    match cli.output_format {
+        OutputFormat::Human => {
+            writeln!(out, "{}", cargo_dist::publish::PublishDisplay(&report)).into_diagnostic()?
+        }
+        OutputFormat::Json => {
+            let string = serde_json::to_string_pretty(&report).unwrap();
+            writeln!(out, "{string}").into_diagnostic()?;
+        }
+    }
+    Ok(())
+}
+
 fn cmd_host(cli: &Cli, args: &HostArgs) -> Result<(), miette::Report> {
     let args = cargo_dist::config::HostArgs {
         steps: args.steps.iter().map(|m| m.to_lib()).collect(),
@@ -274,6 +336,7 @@ fn cmd_host(cli: &Cli, args: &HostArgs) -> Result<(), miette::Report> {
         ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: format!("host:{arg_key}"),
+        jobs: 1,
     };
 
     let report = cargo_dist::host::do_host(&config, args)?;
@@ -294,6 +357,7 @@ fn cmd_plan(cli: &Cli, _args: &PlanArgs) -> Result<(), miette::Report> {
         build_args: BuildArgs {
             artifacts: cli::ArtifactMode::All,
             print: vec![],
+            jobs: std::num::NonZeroUsize::MIN,
         },
     };
 
@@ -305,6 +369,8 @@ fn cmd_plan(cli: &Cli, _args: &PlanArgs) -> Result<(), miette::Report> {
         return print_manifest(&cli, args, needs_coherence);
     }
 
+    print_target_groups()?;
+
     let needs_coherence = false;
     let manifest = generate_manifest(&cli, args, needs_coherence)?;
     let version_map: SortedMap<String, Vec<String>> = manifest
@@ -368,6 +434,24 @@ fn cmd_plan(cli: &Cli, _args: &PlanArgs) -> Result<(), miette::Report> {
     Ok(())
 }
 
+/// Show what any target groups in the config expand to, since that's not obvious from the config
+fn print_target_groups() -> Result<(), miette::Report> {
+    let workspaces = cargo_dist::config::get_project()?;
+    let expansions = cargo_dist::config::target_group_expansions(&workspaces)?;
+    if expansions.is_empty() {
+        return Ok(());
+    }
+
+    let mut out = Term::stdout();
+    writeln!(out, "target groups:").into_diagnostic()?;
+    for (group, triples) in expansions {
+        writeln!(out, "  {}", out.style().blue().apply_to(group)).into_diagnostic()?;
+        writeln!(out, "    => {}", triples.join(", ")).into_diagnostic()?;
+    }
+    writeln!(out).into_diagnostic()?;
+    Ok(())
+}
+
 fn print_manifest(
     cli: &Cli,
     args: &ManifestArgs,
@@ -392,6 +476,7 @@ fn generate_manifest(
         ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: "plan".to_owned(),
+        jobs: 1,
     };
     let report = do_manifest(&config)?;
 
@@ -435,6 +520,7 @@ fn cmd_init(cli: &Cli, args: &InitArgs) -> Result<(), miette::Report> {
         ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: "init".to_owned(),
+        jobs: 1,
     };
     let args = cargo_dist::InitArgs {
         yes: args.yes,
@@ -457,6 +543,7 @@ fn cmd_generate(cli: &Cli, args: &GenerateArgs) -> Result<(), miette::Report> {
         ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: "generate".to_owned(),
+        jobs: 1,
     };
     let args = cargo_dist::GenerateArgs {
         check: args.check,
@@ -477,6 +564,7 @@ fn cmd_linkage(cli: &Cli, args: &LinkageArgs) -> Result<(), miette::Report> {
         ci: cli.ci.iter().map(|ci| ci.to_lib()).collect(),
         installers: cli.installer.iter().map(|ins| ins.to_lib()).collect(),
         root_cmd: "linkage".to_owned(),
+        jobs: 1,
     };
     let mut options = cargo_dist::linkage::LinkageArgs {
         print_output: args.print_output,
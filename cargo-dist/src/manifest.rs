@@ -39,7 +39,7 @@ use std::collections::btree_map::Entry;
 use camino::{Utf8Path, Utf8PathBuf};
 use cargo_dist_schema::{
     Artifact, ArtifactId, Asset, AssetKind, DistManifest, DynamicLibraryAsset, ExecutableAsset,
-    Hosting, StaticLibraryAsset,
+    HomebrewPublishing, Hosting, NpmPublishing, Publishing, StaticLibraryAsset,
 };
 use tracing::warn;
 
@@ -47,7 +47,11 @@ use crate::{
     announce::AnnouncementTag,
     backend::{
         installer::{
-            homebrew::HomebrewInstallerInfo, npm::NpmInstallerInfo, HomebrewImpl, InstallerImpl,
+            homebrew::HomebrewInstallerInfo,
+            npm::NpmInstallerInfo,
+            scoop::ScoopInstallerInfo,
+            winget::{WingetInstallerInfo, WingetManifestType},
+            HomebrewImpl, InstallerImpl, ScoopImpl, WingetImpl,
         },
         templates::{
             TemplateEntry, TEMPLATE_INSTALLER_NPM, TEMPLATE_INSTALLER_NPM_PACKAGE_JSON,
@@ -111,18 +115,25 @@ pub(crate) fn load_and_merge_manifests(
             // If the input has hosting info, apply it
             let Hosting {
                 github,
+                gitlab,
                 simple,
                 order,
             } = release.hosting;
             if let Some(hosting) = github {
                 out_release.hosting.github = Some(hosting);
             }
+            if let Some(hosting) = gitlab {
+                out_release.hosting.gitlab = Some(hosting);
+            }
             if let Some(hosting) = simple {
                 out_release.hosting.simple = Some(hosting);
             }
             if let Some(order) = order {
                 out_release.hosting.order = Some(order);
             }
+            if !release.publishing.is_empty() {
+                out_release.publishing = release.publishing;
+            }
             // If the input has a list of artifacts for this release, merge them
             for artifact in release.artifacts {
                 if !out_release.artifacts.contains(&artifact) {
@@ -240,11 +251,34 @@ pub(crate) fn add_releases_to_manifest(
 
         out_release.display = Some(release.config.hosts.display);
         out_release.display_name = Some(release.config.hosts.display_name.clone());
+        out_release.publishing = release_publishing(release);
     }
 
     Ok(())
 }
 
+/// Work out where `dist publish` should publish a release to
+fn release_publishing(release: &Release) -> Publishing {
+    let publishers = &release.config.publishers;
+    let installers = &release.config.installers;
+    // Formulae need a tap to go to (we warn about a missing one when making the installer)
+    let homebrew = publishers.homebrew.as_ref().and_then(|publisher| {
+        let tap = installers.homebrew.as_ref()?.tap.clone()?;
+        Some(HomebrewPublishing {
+            tap,
+            prereleases: publisher.prereleases,
+        })
+    });
+    let npm = publishers.npm.as_ref().and_then(|publisher| {
+        let installer = installers.npm.as_ref()?;
+        Some(NpmPublishing {
+            registry: installer.registry.clone(),
+            prereleases: publisher.prereleases,
+        })
+    });
+    Publishing { homebrew, npm }
+}
+
 fn add_manifest_artifact(
     cfg: &Config,
     dist: &DistGraph,
@@ -359,6 +393,21 @@ fn add_manifest_artifact(
         }
     }
 
+    // Desktop entries are generated too, so they also get recorded separately
+    if let Some(entry) = artifact
+        .archive
+        .as_ref()
+        .and_then(|archive| archive.desktop_entry.as_ref())
+    {
+        let entry_path = entry.entry_path();
+        static_assets.push(Asset {
+            id: None,
+            name: Some(entry_path.file_name().unwrap().to_owned()),
+            path: Some(entry_path.to_string()),
+            kind: AssetKind::Unknown,
+        });
+    }
+
     assets.extend(built_assets);
     assets.extend(static_assets);
     // Sort the assets by name to make things extra stable
@@ -386,12 +435,27 @@ fn add_manifest_artifact(
                 info: HomebrewInstallerInfo { inner: info, .. },
                 ..
             })
-            | InstallerImpl::Npm(NpmInstallerInfo { inner: info, .. }),
+            | InstallerImpl::Npm(NpmInstallerInfo { inner: info, .. })
+            | InstallerImpl::Scoop(ScoopImpl {
+                info: ScoopInstallerInfo { inner: info, .. },
+                ..
+            })
+            | InstallerImpl::Winget(WingetImpl {
+                info: WingetInstallerInfo { inner: info, .. },
+                manifest_type: WingetManifestType::Installer,
+                ..
+            }),
         ) => {
             install_hint = Some(info.hint.clone());
             description = Some(info.desc.clone());
             kind = cargo_dist_schema::ArtifactKind::Installer;
         }
+        ArtifactKind::Installer(InstallerImpl::Winget(..)) => {
+            // Only the installer manifest gets to advertise how to install the package
+            install_hint = None;
+            description = None;
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
         ArtifactKind::Installer(InstallerImpl::Msi(..)) => {
             install_hint = None;
             description = Some("install via msi".to_owned());
@@ -402,6 +466,16 @@ fn add_manifest_artifact(
             description = Some("install via pkg".to_owned());
             kind = cargo_dist_schema::ArtifactKind::Installer;
         }
+        ArtifactKind::Installer(InstallerImpl::Deb(..)) => {
+            install_hint = None;
+            description = Some("install via deb".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
+        ArtifactKind::Installer(InstallerImpl::Rpm(..)) => {
+            install_hint = None;
+            description = Some("install via rpm".to_owned());
+            kind = cargo_dist_schema::ArtifactKind::Installer;
+        }
         ArtifactKind::Checksum(_) => {
             install_hint = None;
             description = None;
@@ -412,6 +486,16 @@ fn add_manifest_artifact(
             description = None;
             kind = cargo_dist_schema::ArtifactKind::UnifiedChecksum;
         }
+        ArtifactKind::Signature(_) => {
+            install_hint = None;
+            description = None;
+            kind = cargo_dist_schema::ArtifactKind::Signature;
+        }
+        ArtifactKind::Provenance(_) => {
+            install_hint = None;
+            description = None;
+            kind = cargo_dist_schema::ArtifactKind::Provenance;
+        }
         ArtifactKind::SourceTarball(_) => {
             install_hint = None;
             description = None;
@@ -440,6 +524,7 @@ fn add_manifest_artifact(
     };
 
     let checksum = artifact.checksum.map(|idx| dist.artifact(idx).id.clone());
+    let signature = artifact.signature.map(|idx| dist.artifact(idx).id.clone());
 
     let out_artifact = cargo_dist_schema::Artifact {
         name: Some(artifact.id.clone()),
@@ -454,6 +539,7 @@ fn add_manifest_artifact(
         assets,
         kind,
         checksum,
+        signature,
         checksums: Default::default(),
     };
 
@@ -20,10 +20,10 @@ impl ClientSettings {
 
 /// Create a raw reqwest client
 ///
-/// As of this writing this shouldn't be used/exposed, as we'd prefer
-/// to avoid proliferating random http clients. For now AxoClient
-/// is sufficient.
-fn create_reqwest_client(ClientSettings {}: &ClientSettings) -> DistResult<reqwest::Client> {
+/// Prefer AxoClient where possible, as we'd prefer to avoid proliferating
+/// random http clients. This is only exposed for APIs that need more than
+/// simple downloads (like uploading to GitLab).
+pub fn create_reqwest_client(ClientSettings {}: &ClientSettings) -> DistResult<reqwest::Client> {
     let client = reqwest::Client::builder()
         .build()
         .expect("failed to initialize http client");
@@ -246,8 +246,8 @@ use crate::{
 };
 
 use targets::{
-    TARGET_ARM64_MAC, TARGET_ARM64_MINGW, TARGET_ARM64_WINDOWS, TARGET_X64_MAC, TARGET_X64_MINGW,
-    TARGET_X64_WINDOWS, TARGET_X86_MINGW, TARGET_X86_WINDOWS,
+    TARGET_ARM64_MAC, TARGET_ARM64_MINGW, TARGET_ARM64_WINDOWS, TARGET_UNIVERSAL2_MAC,
+    TARGET_X64_MAC, TARGET_X64_MINGW, TARGET_X64_WINDOWS, TARGET_X86_MINGW, TARGET_X86_WINDOWS,
 };
 
 /// values of the form `min-glibc-version = { some-target-triple = "2.8" }
@@ -276,8 +276,6 @@ pub type MinGlibcVersion = SortedMap<String, LibcVersion>;
 const LINUX_STATIC_LIBCS: &[&str] = &["linux-musl-static"];
 /// Dynamically linked linux libcs that static libcs can replace
 const LINUX_STATIC_REPLACEABLE_LIBCS: &[&str] = &["linux-gnu", "linux-musl-dynamic"];
-/// A fake TargetTriple for apple's universal2 format (staples x64 and arm64 together)
-const TARGET_MACOS_UNIVERSAL2: &str = "universal2-apple-darwin";
 
 /// The quality of support an archive provides for a given platform
 #[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord, Serialize)]
@@ -323,7 +321,7 @@ impl LibcVersion {
         }
     }
 
-    fn glibc_from_schema(schema: &GlibcVersion) -> Self {
+    pub(crate) fn glibc_from_schema(schema: &GlibcVersion) -> Self {
         Self {
             major: schema.major,
             series: schema.series,
@@ -421,6 +419,8 @@ pub struct FetchableArchive {
     pub zip_style: ZipStyle,
     /// The updater you should also fetch if you install this archive
     pub updater: Option<FetchableUpdaterIdx>,
+    /// The desktop entry in the archive, if any (relative to its root)
+    pub desktop_entry: Option<String>,
 }
 
 /// The checksum for a fetchable archive
@@ -519,6 +519,13 @@ impl PlatformSupport {
                     .map(|(_, dest_path)| dest_path.file_name().unwrap().to_owned())
                     .collect(),
                 zip_style: artifact.archive.as_ref().unwrap().zip_style,
+                desktop_entry: artifact
+                    .archive
+                    .as_ref()
+                    .unwrap()
+                    .desktop_entry
+                    .as_ref()
+                    .map(|entry| entry.entry_path().to_string()),
                 checksum: None,
                 native_runtime_conditions,
                 updater: updater_idx,
@@ -707,7 +714,7 @@ fn supports(
 
         // universal2 macos binaries are totally native for both arches, but bulkier than
         // necessary if we have builds for the individual platforms too.
-        if target == TARGET_MACOS_UNIVERSAL2 {
+        if target == TARGET_UNIVERSAL2_MAC.as_str() {
             res.push((
                 TARGET_X64_MAC.to_owned(),
                 PlatformEntry {
@@ -883,18 +890,22 @@ fn native_runtime_conditions_for_artifact(
 }
 
 fn get_glibc_override(dist: &DistGraphBuilder, artifact: &Artifact) -> Option<LibcVersion> {
-    let version_map = dist.inner.config.builds.min_glibc_version.clone();
-
-    version_map.and_then(|vmap| {
-        // if min-glibc-version config option is specified at all.
-        artifact
-            .target_triples
-            .first()
-            // if the target triple has a min-glibc-version specified, use it.
-            .and_then(|t: &TripleName| vmap.get(&t.to_string()).copied())
-            // or, try using the min-glibc-version for the "*" wildcard.
-            .or_else(|| vmap.get("*").copied())
-    })
+    let version_map = dist.inner.config.builds.min_glibc_version.as_ref()?;
+    let target = artifact.target_triples.first()?;
+    min_glibc_version_for_target(version_map, target)
+}
+
+/// Get the min-glibc-version configured for the given target, if any
+pub fn min_glibc_version_for_target(
+    version_map: &MinGlibcVersion,
+    target: &TripleNameRef,
+) -> Option<LibcVersion> {
+    // if the target triple has a min-glibc-version specified, use it.
+    version_map
+        .get(target.as_str())
+        .copied()
+        // or, try using the min-glibc-version for the "*" wildcard.
+        .or_else(|| version_map.get("*").copied())
 }
 
 fn native_runtime_conditions_for_asset(
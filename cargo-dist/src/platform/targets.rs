@@ -51,6 +51,28 @@ define_target_triples!(
 pub const KNOWN_MAC_TARGETS: &[&TripleNameRef] =
     &[TARGET_X86_MAC, TARGET_X64_MAC, TARGET_ARM64_MAC];
 
+define_target_triples!(
+    /// Intel and ARM64 macOS stapled together into one "universal" binary
+    ///
+    /// This isn't a real rustc target: we build each of [`UNIVERSAL2_MAC_SLICES`][]
+    /// and merge the results.
+    const TARGET_UNIVERSAL2_MAC = "universal2-apple-darwin";
+);
+
+/// The real targets that make up a universal2-apple-darwin binary
+pub const UNIVERSAL2_MAC_SLICES: &[&TripleNameRef] = &[TARGET_X64_MAC, TARGET_ARM64_MAC];
+
+/// The real rustc targets that need to be built to produce binaries for the given target
+///
+/// This is just the target itself, unless it's a magic target like [`TARGET_UNIVERSAL2_MAC`][].
+pub fn build_targets(target: &TripleNameRef) -> Vec<&TripleNameRef> {
+    if target == TARGET_UNIVERSAL2_MAC {
+        UNIVERSAL2_MAC_SLICES.to_vec()
+    } else {
+        vec![target]
+    }
+}
+
 define_target_triples!(
     /// 32-bit Linux (kernel 3.2+, glibc 2.17+)
     const TARGET_X86_LINUX_GNU = "i686-unknown-linux-gnu";
@@ -145,6 +167,34 @@ pub const KNOWN_LINUX_MUSL_TARGETS: &[&TripleNameRef] = &[
 pub const KNOWN_LINUX_TARGETS: &[&[&TripleNameRef]] =
     &[KNOWN_LINUX_GNU_TARGETS, KNOWN_LINUX_MUSL_TARGETS];
 
+/// Operating systems that can be used in target groups, with the libcs they support
+///
+/// The first libc is the default, and an empty list means the os only has the one.
+pub const TARGET_GROUP_OSES: &[(&str, &[&str])] = &[
+    ("linux", &["gnu", "musl"]),
+    ("macos", &[]),
+    ("windows", &["msvc", "gnu"]),
+];
+
+/// The known targets for one of the [`TARGET_GROUP_OSES`][] and its libc
+pub fn known_targets_for_os(os: &str, libc: &str) -> Vec<&'static TripleNameRef> {
+    match (os, libc) {
+        ("linux", "gnu") => KNOWN_LINUX_GNU_TARGETS.to_vec(),
+        ("linux", "musl") => KNOWN_LINUX_MUSL_TARGETS.to_vec(),
+        ("macos", _) => KNOWN_MAC_TARGETS
+            .iter()
+            .copied()
+            .chain([TARGET_UNIVERSAL2_MAC])
+            .collect(),
+        ("windows", "msvc" | "gnu") => KNOWN_WINDOWS_TARGETS
+            .iter()
+            .copied()
+            .filter(|target| target.as_str().ends_with(libc))
+            .collect(),
+        _ => vec![],
+    }
+}
+
 define_target_triples!(
     /// 64-bit FreeBSD
     const TARGET_X64_FREEBSD = "x86_64-unknown-freebsd";
@@ -0,0 +1,358 @@
+//! Portable SLSA provenance
+//!
+//! When `slsa-provenance = true` is set, the global build step writes an
+//! [in-toto Statement](https://github.com/in-toto/attestation/blob/main/spec/v1/statement.md)
+//! carrying a [SLSA v1 provenance](https://slsa.dev/spec/v1.0/provenance) predicate for
+//! each release, as `{release}.intoto.jsonl`.
+//!
+//! Unlike GitHub's attestations this doesn't depend on the CI provider at all: everything
+//! in the statement comes from the dist-manifest that the global step has already merged
+//! together (artifact checksums and the systems that built them), plus the git checkout
+//! and dist config it's running from.
+//!
+//! The file follows the in-toto bundle convention of one DSSE envelope per line. The
+//! envelope itself is unsigned; if minisign signing is enabled the whole file gets a
+//! detached signature like every other artifact.
+
+use std::collections::BTreeMap;
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use base64::Engine;
+use camino::Utf8Path;
+use cargo_dist_schema::{ArtifactKind, DistManifest};
+use serde_json::json;
+use tracing::warn;
+
+use crate::{config::ChecksumStyle, errors::DistResult, DistGraph, ProvenanceImpl};
+
+/// The in-toto Statement type we produce
+pub const STATEMENT_TYPE: &str = "https://in-toto.io/Statement/v1";
+/// The predicate type of the statement
+pub const PREDICATE_TYPE: &str = "https://slsa.dev/provenance/v1";
+/// The SLSA buildType describing how to interpret our buildDefinition
+pub const BUILD_TYPE: &str =
+    "https://opensource.axo.dev/cargo-dist/book/supplychain-security/provenance.html";
+/// The identity of the builder (dist itself, running on the systems listed alongside it)
+pub const BUILDER_ID: &str = "https://github.com/axodotdev/cargo-dist";
+/// The DSSE payloadType for in-toto statements
+pub const PAYLOAD_TYPE: &str = "application/vnd.in-toto+json";
+
+/// Information about the source a release was built from
+#[derive(Debug, Clone, Default)]
+pub struct SourceInfo {
+    /// The commit that was checked out, if we could find it
+    pub commit: Option<String>,
+    /// The sha256 of the dist config file, if we could read it
+    pub config_sha256: Option<String>,
+    /// A URI for this particular CI run, if we recognize the CI provider
+    pub invocation_id: Option<String>,
+}
+
+/// Generate the provenance file for a release
+pub fn generate_provenance(
+    dist: &DistGraph,
+    manifest: &DistManifest,
+    provenance: &ProvenanceImpl,
+) -> DistResult<()> {
+    let config_sha256 = if provenance.config_path.exists() {
+        Some(
+            crate::generate_checksum(&ChecksumStyle::Sha256, &provenance.config_path)?
+                .as_str()
+                .to_owned(),
+        )
+    } else {
+        None
+    };
+    let source = SourceInfo {
+        commit: source_commit(dist, &provenance.working_dir),
+        config_sha256,
+        invocation_id: invocation_id(),
+    };
+    let statement = provenance_statement(manifest, provenance, &dist.dist_dir, &source)?;
+    let envelope = dsse_envelope(&statement);
+    LocalAsset::write_new_all(&format!("{envelope}\n"), &provenance.dest_path)?;
+
+    Ok(())
+}
+
+/// Build the in-toto statement describing a release
+pub fn provenance_statement(
+    manifest: &DistManifest,
+    provenance: &ProvenanceImpl,
+    dist_dir: &Utf8Path,
+    source: &SourceInfo,
+) -> DistResult<serde_json::Value> {
+    let mut subjects = vec![];
+    if let Some(release) = manifest.release_by_name(&provenance.app_name) {
+        for (id, artifact) in manifest.artifacts_for_release(release) {
+            // These are all made after the statement, or describe it
+            if matches!(
+                artifact.kind,
+                ArtifactKind::UnifiedChecksum | ArtifactKind::Signature | ArtifactKind::Provenance
+            ) {
+                continue;
+            }
+
+            // in-toto digest names use underscores where our extensions use dashes
+            let mut digest = artifact
+                .checksums
+                .iter()
+                .map(|(ext, value)| (ext.as_str().replace('-', "_"), value.as_str().to_owned()))
+                .collect::<BTreeMap<_, _>>();
+            if digest.is_empty() {
+                let path = dist_dir.join(id.as_str());
+                if path.exists() {
+                    let value = crate::generate_checksum(&ChecksumStyle::Sha256, &path)?;
+                    digest.insert("sha256".to_owned(), value.as_str().to_owned());
+                }
+            }
+            if digest.is_empty() {
+                warn!("{id} has no checksum and wasn't found in {dist_dir}, leaving it out of the provenance");
+                continue;
+            }
+
+            subjects.push(json!({
+                "name": id.as_str(),
+                "digest": digest,
+            }));
+        }
+    }
+
+    let mut resolved_dependencies = vec![];
+    if let Some(commit) = &source.commit {
+        let uri = match &provenance.repository_url {
+            Some(url) => format!("git+{url}@refs/tags/{}", provenance.tag),
+            None => format!("git+refs/tags/{}", provenance.tag),
+        };
+        resolved_dependencies.push(json!({
+            "uri": uri,
+            "digest": { "gitCommit": commit },
+        }));
+    }
+    let config_name = provenance
+        .config_path
+        .strip_prefix(&provenance.working_dir)
+        .unwrap_or(&provenance.config_path);
+    if let Some(config_sha256) = &source.config_sha256 {
+        resolved_dependencies.push(json!({
+            "name": config_name,
+            "digest": { "sha256": config_sha256 },
+        }));
+    }
+
+    let builder_dependencies = manifest
+        .systems
+        .values()
+        .map(|system| {
+            json!({
+                "name": system.id,
+                "annotations": system,
+            })
+        })
+        .collect::<Vec<_>>();
+
+    let mut metadata = serde_json::Map::new();
+    if let Some(invocation_id) = &source.invocation_id {
+        metadata.insert("invocationId".to_owned(), json!(invocation_id));
+    }
+
+    Ok(json!({
+        "_type": STATEMENT_TYPE,
+        "subject": subjects,
+        "predicateType": PREDICATE_TYPE,
+        "predicate": {
+            "buildDefinition": {
+                "buildType": BUILD_TYPE,
+                "externalParameters": {
+                    "tag": provenance.tag,
+                    "app": {
+                        "name": provenance.app_name,
+                        "version": provenance.app_version,
+                    },
+                    "targets": provenance.targets,
+                    "config": config_name,
+                },
+                "resolvedDependencies": resolved_dependencies,
+            },
+            "runDetails": {
+                "builder": {
+                    "id": BUILDER_ID,
+                    "version": { "dist": env!("CARGO_PKG_VERSION") },
+                    "builderDependencies": builder_dependencies,
+                },
+                "metadata": metadata,
+            },
+        },
+    }))
+}
+
+/// Wrap a statement in an (unsigned) DSSE envelope
+fn dsse_envelope(statement: &serde_json::Value) -> serde_json::Value {
+    let payload = base64::prelude::BASE64_STANDARD.encode(statement.to_string());
+    json!({
+        "payloadType": PAYLOAD_TYPE,
+        "payload": payload,
+        "signatures": [],
+    })
+}
+
+/// Get the commit we're building from, if this is a git checkout
+fn source_commit(dist: &DistGraph, working_dir: &Utf8Path) -> Option<String> {
+    let git = dist.tools.git.as_ref()?;
+    let output = Cmd::new(&git.cmd, "get the commit being released")
+        .arg("rev-parse")
+        .arg("HEAD")
+        .current_dir(working_dir)
+        .log(None)
+        .output()
+        .ok()?;
+    let commit = String::from_utf8(output.stdout).ok()?.trim().to_owned();
+    (!commit.is_empty()).then_some(commit)
+}
+
+/// Get a URI for the current CI run, for the CI providers we know about
+fn invocation_id() -> Option<String> {
+    let github_run = (
+        std::env::var("GITHUB_SERVER_URL"),
+        std::env::var("GITHUB_REPOSITORY"),
+        std::env::var("GITHUB_RUN_ID"),
+    );
+    if let (Ok(server), Ok(repo), Ok(run_id)) = github_run {
+        let attempt = std::env::var("GITHUB_RUN_ATTEMPT").unwrap_or_else(|_| "1".to_owned());
+        return Some(format!(
+            "{server}/{repo}/actions/runs/{run_id}/attempts/{attempt}"
+        ));
+    }
+    std::env::var("CI_JOB_URL").ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use camino::Utf8PathBuf;
+    use cargo_dist_schema::{Artifact, ArtifactId, ChecksumExtension, ChecksumValue};
+
+    fn artifact(kind: ArtifactKind, checksums: &[(&str, &str)]) -> Artifact {
+        Artifact {
+            name: None,
+            path: None,
+            target_triples: vec![],
+            install_hint: None,
+            description: None,
+            assets: vec![],
+            kind,
+            checksum: None,
+            signature: None,
+            checksums: checksums
+                .iter()
+                .map(|(ext, value)| {
+                    (
+                        ChecksumExtension::new(ext.to_string()),
+                        ChecksumValue::new(value.to_string()),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    #[test]
+    fn statement_lists_release_artifacts() {
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let release = manifest.ensure_release("axolotlsay".to_owned(), "1.0.0".to_owned());
+        for name in [
+            "axolotlsay-x86_64-unknown-linux-gnu.tar.xz",
+            "axolotlsay-installer.sh",
+            "sha3-256.sum",
+            "axolotlsay-installer.sh.minisig",
+        ] {
+            release.artifacts.push(ArtifactId::new(name.to_owned()));
+        }
+        manifest.artifacts.insert(
+            ArtifactId::new("axolotlsay-x86_64-unknown-linux-gnu.tar.xz".to_owned()),
+            artifact(ArtifactKind::ExecutableZip, &[("sha3-256", "aaaa")]),
+        );
+        manifest.artifacts.insert(
+            ArtifactId::new("axolotlsay-installer.sh".to_owned()),
+            artifact(ArtifactKind::Installer, &[("sha3-256", "bbbb")]),
+        );
+        manifest.artifacts.insert(
+            ArtifactId::new("sha3-256.sum".to_owned()),
+            artifact(ArtifactKind::UnifiedChecksum, &[]),
+        );
+        manifest.artifacts.insert(
+            ArtifactId::new("axolotlsay-installer.sh.minisig".to_owned()),
+            artifact(ArtifactKind::Signature, &[]),
+        );
+
+        let provenance = ProvenanceImpl {
+            app_name: "axolotlsay".to_owned(),
+            app_version: "1.0.0".to_owned(),
+            tag: "v1.0.0".to_owned(),
+            repository_url: Some("https://github.com/axodotdev/axolotlsay".to_owned()),
+            targets: vec![],
+            config_path: Utf8PathBuf::from("/src/dist-workspace.toml"),
+            working_dir: Utf8PathBuf::from("/src"),
+            dest_path: Utf8PathBuf::from("/src/target/distrib/axolotlsay.intoto.jsonl"),
+        };
+        let source = SourceInfo {
+            commit: Some("0123456789abcdef".to_owned()),
+            config_sha256: Some("cccc".to_owned()),
+            invocation_id: None,
+        };
+        let statement = provenance_statement(
+            &manifest,
+            &provenance,
+            Utf8Path::new("/nonexistent"),
+            &source,
+        )
+        .unwrap();
+
+        assert_eq!(statement["_type"], STATEMENT_TYPE);
+        assert_eq!(statement["predicateType"], PREDICATE_TYPE);
+        assert_eq!(
+            statement["subject"],
+            json!([
+                {
+                    "name": "axolotlsay-x86_64-unknown-linux-gnu.tar.xz",
+                    "digest": { "sha3_256": "aaaa" },
+                },
+                {
+                    "name": "axolotlsay-installer.sh",
+                    "digest": { "sha3_256": "bbbb" },
+                },
+            ])
+        );
+        let definition = &statement["predicate"]["buildDefinition"];
+        assert_eq!(definition["externalParameters"]["tag"], "v1.0.0");
+        assert_eq!(
+            definition["externalParameters"]["config"],
+            "dist-workspace.toml"
+        );
+        assert_eq!(
+            definition["resolvedDependencies"],
+            json!([
+                {
+                    "uri": "git+https://github.com/axodotdev/axolotlsay@refs/tags/v1.0.0",
+                    "digest": { "gitCommit": "0123456789abcdef" },
+                },
+                {
+                    "name": "dist-workspace.toml",
+                    "digest": { "sha256": "cccc" },
+                },
+            ])
+        );
+    }
+
+    #[test]
+    fn envelope_wraps_statement() {
+        let statement = json!({ "_type": STATEMENT_TYPE });
+        let envelope = dsse_envelope(&statement);
+        assert_eq!(envelope["payloadType"], PAYLOAD_TYPE);
+        let payload = base64::prelude::BASE64_STANDARD
+            .decode(envelope["payload"].as_str().unwrap())
+            .unwrap();
+        let decoded: serde_json::Value = serde_json::from_slice(&payload).unwrap();
+        assert_eq!(decoded, statement);
+    }
+}
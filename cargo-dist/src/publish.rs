@@ -0,0 +1,473 @@
+//! dist publish -- publish built artifacts to package managers
+//!
+//! Every release in the dist-manifest says where its package wants to be published
+//! (see [`cargo_dist_schema::Publishing`][]), so this only needs the manifest and the
+//! artifacts next to it, not the project:
+//!
+//! * Homebrew formulae are committed to each package's tap (one commit per formula,
+//!   one push per tap)
+//! * npm packages are `npm publish`ed, to each package's registry if it has one
+//!
+//! With `--dry-run` nothing is touched, and the commands that would be run are reported.
+
+use std::collections::BTreeMap;
+
+use axoasset::LocalAsset;
+use axoprocess::Cmd;
+use base64::Engine;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{DistManifest, Publishing, Release};
+use serde::Serialize;
+
+use crate::{
+    config::PublishStyle,
+    errors::{DistError, DistResult},
+};
+
+/// Homebrew rules that would rewrite user-provided data like the description and homepage
+const BREW_STYLE_EXCEPT_COPS: &str = "FormulaAudit/Homepage,FormulaAudit/Desc,FormulaAuditStrict";
+
+/// Arguments for `dist publish` ([`do_publish`][])
+#[derive(Debug, Clone)]
+pub struct PublishArgs {
+    /// The directory containing the built artifacts
+    pub dir: Utf8PathBuf,
+    /// The dist-manifest.json to publish (defaults to the one in `dir`)
+    pub manifest: Option<Utf8PathBuf>,
+    /// Only run these publishers (all of them, if empty)
+    pub publishers: Vec<PublishStyle>,
+    /// Report what would be published instead of publishing it
+    pub dry_run: bool,
+}
+
+/// The result of `dist publish`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct PublishReport {
+    /// Whether the actions were only planned, and not run
+    pub dry_run: bool,
+    /// What was (or would be) published
+    pub actions: Vec<PublishAction>,
+    /// Things that were configured to be published, but weren't
+    pub skipped: Vec<SkippedPublish>,
+}
+
+/// Something `dist publish` does
+#[derive(Debug, Clone, Serialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum PublishAction {
+    /// Commit formulae to a Homebrew tap
+    Homebrew {
+        /// The GitHub repo of the tap
+        tap: String,
+        /// The formulae to commit
+        formulae: Vec<HomebrewFormula>,
+    },
+    /// Publish an npm package
+    Npm {
+        /// The app the package is for
+        app_name: String,
+        /// The package tarball
+        path: Utf8PathBuf,
+        /// The registry to publish to, if not the default
+        registry: Option<String>,
+    },
+}
+
+/// A Homebrew formula to commit to a tap
+#[derive(Debug, Clone, Serialize)]
+pub struct HomebrewFormula {
+    /// The formula's file name (e.g. `my-app.rb`)
+    pub file_name: String,
+    /// The built formula
+    pub path: Utf8PathBuf,
+    /// The message to commit it with
+    pub message: String,
+}
+
+/// A release that wasn't published somewhere it was configured to be
+#[derive(Debug, Clone, Serialize)]
+pub struct SkippedPublish {
+    /// The app
+    pub app_name: String,
+    /// Where it wasn't published to
+    pub publisher: String,
+    /// Why not
+    pub reason: String,
+}
+
+impl PublishAction {
+    /// The commands this action runs, for printing
+    pub fn commands(&self) -> Vec<String> {
+        match self {
+            PublishAction::Homebrew { tap, formulae } => {
+                let mut commands = vec![format!("git clone https://github.com/{tap}.git")];
+                for formula in formulae {
+                    commands.push(format!("cp {} Formula/{}", formula.path, formula.file_name));
+                    commands.push(format!("git add Formula/{}", formula.file_name));
+                    commands.push(format!("git commit -m {:?}", formula.message));
+                }
+                commands.push("git push".to_owned());
+                commands
+            }
+            PublishAction::Npm { path, registry, .. } => {
+                let mut command = format!("npm publish --access public {path}");
+                if let Some(registry) = registry {
+                    command.push_str(&format!(" --registry {registry}"));
+                }
+                vec![command]
+            }
+        }
+    }
+}
+
+/// dist publish -- publish the releases of a dist-manifest
+pub fn do_publish(args: &PublishArgs) -> DistResult<PublishReport> {
+    let manifest_path = args
+        .manifest
+        .clone()
+        .unwrap_or_else(|| args.dir.join("dist-manifest.json"));
+    let manifest: DistManifest = serde_json::from_str(&LocalAsset::load_string(&manifest_path)?)
+        .map_err(|details| DistError::PublishManifestParse {
+            manifest: manifest_path.clone(),
+            details,
+        })?;
+
+    let mut report = plan_publish(&manifest, &args.dir, &args.publishers)?;
+    report.dry_run = args.dry_run;
+    if !args.dry_run {
+        for action in &report.actions {
+            match action {
+                PublishAction::Homebrew { tap, formulae } => publish_homebrew(tap, formulae)?,
+                PublishAction::Npm { path, registry, .. } => {
+                    publish_npm(path, registry.as_deref())?
+                }
+            }
+        }
+    }
+
+    Ok(report)
+}
+
+/// Work out everything that needs publishing, without doing it
+pub fn plan_publish(
+    manifest: &DistManifest,
+    dir: &Utf8Path,
+    publishers: &[PublishStyle],
+) -> DistResult<PublishReport> {
+    let mut report = PublishReport::default();
+    let wants = |style: PublishStyle| publishers.is_empty() || publishers.contains(&style);
+    let prerelease = manifest.announcement_is_prerelease;
+
+    // Formulae going to the same tap get pushed together
+    let mut taps = BTreeMap::<String, Vec<HomebrewFormula>>::new();
+    for release in &manifest.releases {
+        let Publishing { homebrew, npm } = &release.publishing;
+        let mut skip = |publisher: &str, reason: &str| {
+            report.skipped.push(SkippedPublish {
+                app_name: release.app_name.clone(),
+                publisher: publisher.to_owned(),
+                reason: reason.to_owned(),
+            })
+        };
+
+        if let Some(homebrew) = homebrew.as_ref().filter(|_| wants(PublishStyle::Homebrew)) {
+            if prerelease && !homebrew.prereleases {
+                skip("homebrew", "it's a prerelease");
+            } else if let Some(name) = release_artifact(release, ".rb") {
+                let formula = name.trim_end_matches(".rb");
+                taps.entry(homebrew.tap.clone())
+                    .or_default()
+                    .push(HomebrewFormula {
+                        file_name: name.to_owned(),
+                        path: built_artifact(dir, name)?,
+                        message: format!("{formula} {}", release.app_version),
+                    });
+            } else {
+                skip("homebrew", "no Homebrew formula was built");
+            }
+        }
+
+        if let Some(npm) = npm.as_ref().filter(|_| wants(PublishStyle::Npm)) {
+            if prerelease && !npm.prereleases {
+                skip("npm", "it's a prerelease");
+            } else if let Some(name) = release_artifact(release, "-npm-package.tar.gz") {
+                report.actions.push(PublishAction::Npm {
+                    app_name: release.app_name.clone(),
+                    path: built_artifact(dir, name)?,
+                    registry: npm.registry.clone(),
+                });
+            } else {
+                skip("npm", "no npm package was built");
+            }
+        }
+    }
+    report.actions.extend(
+        taps.into_iter()
+            .map(|(tap, formulae)| PublishAction::Homebrew { tap, formulae }),
+    );
+
+    Ok(report)
+}
+
+/// Find the release's artifact with the given suffix
+fn release_artifact<'a>(release: &'a Release, suffix: &str) -> Option<&'a str> {
+    release
+        .artifacts
+        .iter()
+        .map(|id| id.as_str())
+        .find(|name| name.ends_with(suffix))
+}
+
+/// Get the path to a built artifact, making sure it's actually there
+fn built_artifact(dir: &Utf8Path, name: &str) -> DistResult<Utf8PathBuf> {
+    let path = dir.join(name);
+    if !path.exists() {
+        return Err(DistError::PublishMissingArtifact {
+            name: name.to_owned(),
+            dir: dir.to_owned(),
+        });
+    }
+    Ok(path)
+}
+
+/// Commit formulae to a tap and push them
+fn publish_homebrew(tap: &str, formulae: &[HomebrewFormula]) -> DistResult<()> {
+    let (_tmp, tmp_path) = crate::create_tmp()?;
+    let checkout = tmp_path.join("tap");
+    let git = |desc: &'static str| {
+        let mut cmd = Cmd::new("git", desc);
+        // Pass the token as a header (in the environment) so it never shows up in logs
+        if let Ok(token) = std::env::var("HOMEBREW_TAP_TOKEN") {
+            let auth =
+                base64::engine::general_purpose::STANDARD.encode(format!("x-access-token:{token}"));
+            cmd.env("GIT_CONFIG_COUNT", "1")
+                .env("GIT_CONFIG_KEY_0", "http.https://github.com/.extraheader")
+                .env("GIT_CONFIG_VALUE_0", format!("AUTHORIZATION: basic {auth}"));
+        }
+        cmd
+    };
+
+    git("clone the Homebrew tap")
+        .arg("clone")
+        .arg("--depth=1")
+        .arg(format!("https://github.com/{tap}.git"))
+        .arg(&checkout)
+        .run()?;
+    // CI doesn't have a git identity of its own
+    for (key, var) in [("user.name", "GITHUB_USER"), ("user.email", "GITHUB_EMAIL")] {
+        if let Ok(value) = std::env::var(var) {
+            git("set the commit author")
+                .arg("config")
+                .arg(key)
+                .arg(value)
+                .current_dir(&checkout)
+                .run()?;
+        }
+    }
+
+    let formula_dir = checkout.join("Formula");
+    LocalAsset::create_dir_all(&formula_dir)?;
+    for formula in formulae {
+        let dest = formula_dir.join(&formula.file_name);
+        LocalAsset::copy_file_to_file(&formula.path, &dest)?;
+        // Homebrew is picky about formatting, so let it tidy things up if it's around
+        let _ = Cmd::new("brew", "tidy up the formula")
+            .arg("style")
+            .arg("--except-cops")
+            .arg(BREW_STYLE_EXCEPT_COPS)
+            .arg("--fix")
+            .arg(&dest)
+            .check(false)
+            .log(None)
+            .output();
+        git("add the formula")
+            .arg("add")
+            .arg(format!("Formula/{}", formula.file_name))
+            .current_dir(&checkout)
+            .run()?;
+        git("commit the formula")
+            .arg("commit")
+            .arg("-m")
+            .arg(&formula.message)
+            .current_dir(&checkout)
+            .run()?;
+    }
+    git("push to the Homebrew tap")
+        .arg("push")
+        .current_dir(&checkout)
+        .run()?;
+
+    Ok(())
+}
+
+/// Publish an npm package
+fn publish_npm(path: &Utf8Path, registry: Option<&str>) -> DistResult<()> {
+    let mut cmd = Cmd::new("npm", "publish the npm package");
+    cmd.arg("publish").arg("--access").arg("public").arg(path);
+    if let Some(registry) = registry {
+        cmd.arg("--registry").arg(registry);
+    }
+    cmd.run()?;
+    Ok(())
+}
+
+/// Human-readable output for [`PublishReport`][]
+pub struct PublishDisplay<'a>(pub &'a PublishReport);
+
+impl std::fmt::Display for PublishDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let report = self.0;
+        for action in &report.actions {
+            match action {
+                PublishAction::Homebrew { tap, .. } => writeln!(f, "homebrew: {tap}")?,
+                PublishAction::Npm { app_name, .. } => writeln!(f, "npm: {app_name}")?,
+            }
+            if report.dry_run {
+                for command in action.commands() {
+                    writeln!(f, "  {command}")?;
+                }
+            }
+        }
+        for skipped in &report.skipped {
+            writeln!(
+                f,
+                "skipped {}: {} ({})",
+                skipped.publisher, skipped.app_name, skipped.reason
+            )?;
+        }
+        if report.actions.is_empty() {
+            writeln!(f, "nothing to publish")?;
+        } else if report.dry_run {
+            writeln!(f, "\n(dry run, nothing was published)")?;
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_dist_schema::{ArtifactId, HomebrewPublishing, NpmPublishing};
+
+    fn add_release(
+        manifest: &mut DistManifest,
+        name: &str,
+        artifacts: &[&str],
+        publishing: Publishing,
+    ) {
+        let release = manifest.ensure_release(name.to_owned(), "1.0.0".to_owned());
+        release.artifacts = artifacts
+            .iter()
+            .map(|a| ArtifactId::new((*a).to_owned()))
+            .collect();
+        release.publishing = publishing;
+    }
+
+    fn homebrew(tap: &str, prereleases: bool) -> Option<HomebrewPublishing> {
+        Some(HomebrewPublishing {
+            tap: tap.to_owned(),
+            prereleases,
+        })
+    }
+
+    #[test]
+    fn groups_formulae_by_tap() {
+        let (_tmp, dir) = crate::create_tmp().unwrap();
+        for file in ["one.rb", "two.rb", "three.rb", "two-npm-package.tar.gz"] {
+            std::fs::write(dir.join(file), "").unwrap();
+        }
+
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        for (name, tap) in [("one", "me/homebrew-a"), ("three", "me/homebrew-b")] {
+            let publishing = Publishing {
+                homebrew: homebrew(tap, false),
+                npm: None,
+            };
+            add_release(&mut manifest, name, &[&format!("{name}.rb")], publishing);
+        }
+        let publishing = Publishing {
+            homebrew: homebrew("me/homebrew-a", false),
+            npm: Some(NpmPublishing {
+                registry: Some("https://npm.example.com".to_owned()),
+                prereleases: false,
+            }),
+        };
+        add_release(
+            &mut manifest,
+            "two",
+            &["two.rb", "two-npm-package.tar.gz"],
+            publishing,
+        );
+
+        let report = plan_publish(&manifest, &dir, &[]).unwrap();
+        assert!(report.skipped.is_empty());
+        let [PublishAction::Npm {
+            app_name, registry, ..
+        }, PublishAction::Homebrew {
+            tap: tap_a,
+            formulae: formulae_a,
+        }, PublishAction::Homebrew {
+            tap: tap_b,
+            formulae: formulae_b,
+        }] = &report.actions[..]
+        else {
+            panic!("unexpected actions: {:?}", report.actions);
+        };
+        assert_eq!(app_name, "two");
+        assert_eq!(registry.as_deref(), Some("https://npm.example.com"));
+        assert_eq!(tap_a, "me/homebrew-a");
+        assert_eq!(
+            formulae_a
+                .iter()
+                .map(|f| f.message.as_str())
+                .collect::<Vec<_>>(),
+            ["one 1.0.0", "two 1.0.0"]
+        );
+        assert_eq!(tap_b, "me/homebrew-b");
+        assert_eq!(formulae_b[0].file_name, "three.rb");
+
+        // Only asking for npm leaves the taps alone
+        let report = plan_publish(&manifest, &dir, &[PublishStyle::Npm]).unwrap();
+        assert_eq!(report.actions.len(), 1);
+    }
+
+    #[test]
+    fn skips_prereleases_unless_opted_in() {
+        let (_tmp, dir) = crate::create_tmp().unwrap();
+        std::fs::write(dir.join("one.rb"), "").unwrap();
+        std::fs::write(dir.join("two.rb"), "").unwrap();
+
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        manifest.announcement_is_prerelease = true;
+        for (name, prereleases) in [("one", false), ("two", true)] {
+            let publishing = Publishing {
+                homebrew: homebrew("me/homebrew-tap", prereleases),
+                npm: None,
+            };
+            add_release(&mut manifest, name, &[&format!("{name}.rb")], publishing);
+        }
+
+        let report = plan_publish(&manifest, &dir, &[]).unwrap();
+        assert_eq!(report.skipped.len(), 1);
+        assert_eq!(report.skipped[0].app_name, "one");
+        let [PublishAction::Homebrew { formulae, .. }] = &report.actions[..] else {
+            panic!("unexpected actions: {:?}", report.actions);
+        };
+        assert_eq!(formulae.len(), 1);
+        assert_eq!(formulae[0].file_name, "two.rb");
+    }
+
+    #[test]
+    fn missing_artifacts_are_errors() {
+        let (_tmp, dir) = crate::create_tmp().unwrap();
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let publishing = Publishing {
+            homebrew: homebrew("me/homebrew-tap", false),
+            npm: None,
+        };
+        add_release(&mut manifest, "one", &["one.rb"], publishing);
+
+        let err = plan_publish(&manifest, &dir, &[]).unwrap_err();
+        assert!(matches!(err, DistError::PublishMissingArtifact { name, .. } if name == "one.rb"));
+    }
+}
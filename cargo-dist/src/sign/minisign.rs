@@ -0,0 +1,477 @@
+//! Detached artifact signatures in the [minisign] format.
+//!
+//! minisign signatures are tiny Ed25519 signatures that can be checked with
+//! the `minisign` CLI, [rsign2], or (with a bit of work) plain old openssl,
+//! which makes them a good fit for verifying downloads from anywhere.
+//!
+//! The public key is part of the config (so that installers can embed it),
+//! while the secret key is taken from the `MINISIGN_SECRET_KEY` environment
+//! variable. That can either be the contents of a secret key file made by
+//! `minisign -G` (decrypted with `MINISIGN_SECRET_KEY_PASSWORD` if it has one),
+//! or a base64-encoded raw Ed25519 key, in which case the key id is taken from
+//! the public key.
+//!
+//! Because decrypting a minisign secret key is deliberately expensive, the
+//! secret key is only loaded the first time we actually sign something.
+//!
+//! [minisign]: https://jedisct1.github.io/minisign/
+//! [rsign2]: https://github.com/jedisct1/rsign2
+
+use std::sync::OnceLock;
+
+use axoasset::LocalAsset;
+use base64::Engine;
+use blake2::{Blake2b512, Digest};
+use camino::Utf8Path;
+use ed25519_dalek::{Signer, SigningKey};
+use serde::Serialize;
+
+use crate::{DistError, DistResult};
+
+/// The environment variable the secret key is read from
+const SECRET_KEY_VAR: &str = "MINISIGN_SECRET_KEY";
+/// The environment variable the secret key's password is read from
+const PASSWORD_VAR: &str = "MINISIGN_SECRET_KEY_PASSWORD";
+
+/// Signature algorithm for Ed25519 over the BLAKE2b-512 hash of the file
+const SIG_ALG_PREHASHED: [u8; 2] = *b"ED";
+/// Key algorithm for Ed25519 keys
+const KEY_ALG: [u8; 2] = *b"Ed";
+/// Key derivation algorithm for encrypted secret keys (scrypt)
+const KDF_ALG_SCRYPT: [u8; 2] = *b"Sc";
+/// Key derivation algorithm for unencrypted secret keys
+const KDF_ALG_NONE: [u8; 2] = [0, 0];
+/// Checksum algorithm for secret keys (BLAKE2b-256)
+const CHK_ALG: [u8; 2] = *b"B2";
+
+/// DER prefix turning a raw Ed25519 public key into a SubjectPublicKeyInfo
+const ED25519_SPKI_PREFIX: [u8; 12] = [
+    0x30, 0x2a, 0x30, 0x05, 0x06, 0x03, 0x2b, 0x65, 0x70, 0x03, 0x21, 0x00,
+];
+
+/// A minisign public key
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MinisignPublicKey {
+    key_id: [u8; 8],
+    key: [u8; 32],
+}
+
+impl MinisignPublicKey {
+    /// Parse a public key, either as just the base64 line (`RW...`) or the
+    /// full contents of a `minisign.pub` file
+    pub fn parse(input: &str) -> DistResult<Self> {
+        let invalid = |details: &str| DistError::InvalidMinisignPublicKey {
+            key: input.to_owned(),
+            details: details.to_owned(),
+        };
+        let line = input
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .ok_or_else(|| invalid("the key is empty"))?;
+        let bytes = base64::prelude::BASE64_STANDARD
+            .decode(line)
+            .map_err(|_| invalid("the key isn't valid base64"))?;
+        if bytes.len() != 42 {
+            return Err(invalid("the key is the wrong length"));
+        }
+        if bytes[0..2] != KEY_ALG {
+            return Err(invalid("only Ed25519 keys are supported"));
+        }
+        Ok(Self {
+            key_id: bytes[2..10].try_into().unwrap(),
+            key: bytes[10..42].try_into().unwrap(),
+        })
+    }
+
+    /// The key id, formatted the way minisign displays it
+    pub fn key_id(&self) -> String {
+        format!("{:016X}", u64::from_le_bytes(self.key_id))
+    }
+
+    /// The key in minisign's base64 format (what `minisign -P` takes)
+    pub fn to_base64(&self) -> String {
+        let mut bytes = Vec::with_capacity(42);
+        bytes.extend_from_slice(&KEY_ALG);
+        bytes.extend_from_slice(&self.key_id);
+        bytes.extend_from_slice(&self.key);
+        base64::prelude::BASE64_STANDARD.encode(bytes)
+    }
+
+    /// The key as a PEM-encoded SubjectPublicKeyInfo (what openssl takes)
+    pub fn to_pem(&self) -> String {
+        let mut der = Vec::with_capacity(44);
+        der.extend_from_slice(&ED25519_SPKI_PREFIX);
+        der.extend_from_slice(&self.key);
+        format!(
+            "-----BEGIN PUBLIC KEY-----\n{}\n-----END PUBLIC KEY-----\n",
+            base64::prelude::BASE64_STANDARD.encode(der)
+        )
+    }
+}
+
+/// The public key as installers want to see it
+#[derive(Debug, Clone, Serialize)]
+pub struct InstallerSigningKey {
+    /// The key id, for display
+    pub key_id: String,
+    /// The key in minisign's format
+    pub minisign: String,
+    /// The key in PEM format, for openssl
+    pub pem: String,
+}
+
+impl From<&MinisignPublicKey> for InstallerSigningKey {
+    fn from(key: &MinisignPublicKey) -> Self {
+        Self {
+            key_id: key.key_id(),
+            minisign: key.to_base64(),
+            pem: key.to_pem(),
+        }
+    }
+}
+
+/// A minisign secret key
+struct MinisignSecretKey {
+    key_id: [u8; 8],
+    key: SigningKey,
+}
+
+impl MinisignSecretKey {
+    /// Parse a secret key from the environment, checking it against the public key
+    fn parse(input: &str, password: Option<&str>, public: &MinisignPublicKey) -> DistResult<Self> {
+        let line = input
+            .lines()
+            .map(str::trim)
+            .find(|line| !line.is_empty() && !line.starts_with("untrusted comment:"))
+            .ok_or_else(|| invalid_secret("the key is empty"))?;
+        let bytes = base64::prelude::BASE64_STANDARD
+            .decode(line)
+            .map_err(|_| invalid_secret("the key isn't valid base64"))?;
+
+        let secret = match bytes.len() {
+            // A raw Ed25519 key, which doesn't know its own key id
+            32 => Self {
+                key_id: public.key_id,
+                key: SigningKey::from_bytes(bytes[..].try_into().unwrap()),
+            },
+            // A raw Ed25519 key in libsodium's format (seed + public key)
+            64 => Self {
+                key_id: public.key_id,
+                key: SigningKey::from_bytes(bytes[..32].try_into().unwrap()),
+            },
+            158 => Self::parse_minisign(&bytes, password)?,
+            _ => return Err(invalid_secret("the key is the wrong length")),
+        };
+
+        if secret.key_id != public.key_id || secret.key.verifying_key().to_bytes() != public.key {
+            return Err(DistError::MinisignKeyMismatch {
+                key_id: public.key_id(),
+            });
+        }
+        Ok(secret)
+    }
+
+    /// Parse the contents of a secret key file made by `minisign -G`
+    fn parse_minisign(bytes: &[u8], password: Option<&str>) -> DistResult<Self> {
+        let (sig_alg, rest) = bytes.split_at(2);
+        let (kdf_alg, rest) = rest.split_at(2);
+        let (chk_alg, rest) = rest.split_at(2);
+        let (salt, rest) = rest.split_at(32);
+        let (opslimit, rest) = rest.split_at(8);
+        let (memlimit, keynum) = rest.split_at(8);
+        if sig_alg != KEY_ALG {
+            return Err(invalid_secret("only Ed25519 keys are supported"));
+        }
+        if chk_alg != CHK_ALG {
+            return Err(invalid_secret("unsupported checksum algorithm"));
+        }
+
+        let mut keynum = keynum.to_owned();
+        if kdf_alg == KDF_ALG_SCRYPT {
+            let Some(password) = password else {
+                return Err(invalid_secret(&format!(
+                    "the key is encrypted, but {PASSWORD_VAR} isn't set"
+                )));
+            };
+            let opslimit = u64::from_le_bytes(opslimit.try_into().unwrap());
+            let memlimit = u64::from_le_bytes(memlimit.try_into().unwrap());
+            let stream = derive_key_stream(password, salt, opslimit, memlimit, keynum.len())?;
+            for (byte, mask) in keynum.iter_mut().zip(stream) {
+                *byte ^= mask;
+            }
+        } else if kdf_alg != KDF_ALG_NONE {
+            return Err(invalid_secret("unsupported key derivation algorithm"));
+        }
+
+        let (key_id, rest) = keynum.split_at(8);
+        let (key, checksum) = rest.split_at(64);
+        let mut hasher = blake2::Blake2b::<blake2::digest::consts::U32>::new();
+        hasher.update(sig_alg);
+        hasher.update(key_id);
+        hasher.update(key);
+        if hasher.finalize().as_slice() != checksum {
+            return Err(invalid_secret(if kdf_alg == KDF_ALG_SCRYPT {
+                "the password is wrong"
+            } else {
+                "the key is corrupt"
+            }));
+        }
+
+        Ok(Self {
+            key_id: key_id.try_into().unwrap(),
+            key: SigningKey::from_bytes(key[..32].try_into().unwrap()),
+        })
+    }
+}
+
+fn invalid_secret(details: &str) -> DistError {
+    DistError::InvalidMinisignSecretKey {
+        details: details.to_owned(),
+    }
+}
+
+/// Derive the stream an encrypted secret key is xor'd with.
+///
+/// This is libsodium's `crypto_pwhash_scryptsalsa208sha256`, which picks
+/// the scrypt parameters from an "opslimit" and "memlimit".
+fn derive_key_stream(
+    password: &str,
+    salt: &[u8],
+    opslimit: u64,
+    memlimit: u64,
+    len: usize,
+) -> DistResult<Vec<u8>> {
+    let opslimit = opslimit.max(32768);
+    let r = 8u32;
+    let (log_n, p) = if opslimit < memlimit / 32 {
+        let max_n = opslimit / (u64::from(r) * 4);
+        (scrypt_log_n(max_n), 1)
+    } else {
+        let max_n = memlimit / (u64::from(r) * 128);
+        let log_n = scrypt_log_n(max_n);
+        let max_rp = ((opslimit / 4) >> log_n).min(0x3fff_ffff);
+        (log_n, (max_rp / u64::from(r)) as u32)
+    };
+    // (the length in the params only matters for password hashes, not raw output)
+    let params = scrypt::Params::new(log_n, r, p.max(1), scrypt::Params::RECOMMENDED_LEN)
+        .map_err(|_| invalid_secret("the key's scrypt parameters are invalid"))?;
+    let mut stream = vec![0; len];
+    scrypt::scrypt(password.as_bytes(), salt, &params, &mut stream)
+        .map_err(|_| invalid_secret("the key's scrypt parameters are invalid"))?;
+    Ok(stream)
+}
+
+fn scrypt_log_n(max_n: u64) -> u8 {
+    (1..63)
+        .find(|log_n| (1u64 << log_n) > max_n / 2)
+        .unwrap_or(63)
+}
+
+/// Signs files with a minisign key
+pub struct Minisign {
+    public_key: MinisignPublicKey,
+    secret_key: OnceLock<MinisignSecretKey>,
+}
+
+impl std::fmt::Debug for Minisign {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.debug_struct("Minisign")
+            .field("public_key", &self.public_key)
+            .field("secret_key", &"<hidden>")
+            .finish()
+    }
+}
+
+impl Minisign {
+    /// Setup signing with the given public key (from config)
+    pub fn new(public_key: &str) -> DistResult<Self> {
+        Ok(Self {
+            public_key: MinisignPublicKey::parse(public_key)?,
+            secret_key: OnceLock::new(),
+        })
+    }
+
+    /// The public key signatures can be checked with
+    pub fn public_key(&self) -> &MinisignPublicKey {
+        &self.public_key
+    }
+
+    /// Whether a secret key is available to sign with
+    pub fn has_secret_key(&self) -> bool {
+        self.secret_key.get().is_some() || std::env::var_os(SECRET_KEY_VAR).is_some()
+    }
+
+    fn secret_key(&self) -> DistResult<&MinisignSecretKey> {
+        if let Some(key) = self.secret_key.get() {
+            return Ok(key);
+        }
+        let Ok(secret) = std::env::var(SECRET_KEY_VAR) else {
+            return Err(DistError::MissingMinisignSecretKey {
+                key_id: self.public_key.key_id(),
+            });
+        };
+        let password = std::env::var(PASSWORD_VAR).ok();
+        let key = MinisignSecretKey::parse(&secret, password.as_deref(), &self.public_key)?;
+        Ok(self.secret_key.get_or_init(|| key))
+    }
+
+    /// Sign `src_path`, writing a detached signature to `dest_path`
+    pub fn sign(&self, src_path: &Utf8Path, dest_path: &Utf8Path) -> DistResult<()> {
+        let file_name = src_path.file_name().expect("signing file with no name!?");
+        let file_bytes = LocalAsset::load_bytes(src_path.as_str())?;
+        let timestamp = std::time::SystemTime::now()
+            .duration_since(std::time::UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        let trusted_comment = format!("timestamp:{timestamp}\tfile:{file_name}\thashed");
+
+        let signature = self.sign_bytes(&file_bytes, &trusted_comment)?;
+        LocalAsset::write_new_all(&signature, dest_path)?;
+        Ok(())
+    }
+
+    /// Produce the contents of a .minisig file for the given bytes
+    fn sign_bytes(&self, bytes: &[u8], trusted_comment: &str) -> DistResult<String> {
+        let secret = self.secret_key()?;
+        let hash = Blake2b512::digest(bytes);
+        let signature = secret.key.sign(&hash).to_bytes();
+
+        let mut sig_line = Vec::with_capacity(74);
+        sig_line.extend_from_slice(&SIG_ALG_PREHASHED);
+        sig_line.extend_from_slice(&secret.key_id);
+        sig_line.extend_from_slice(&signature);
+
+        // The "global" signature covers the trusted comment too
+        let mut global = signature.to_vec();
+        global.extend_from_slice(trusted_comment.as_bytes());
+        let global_signature = secret.key.sign(&global).to_bytes();
+
+        let b64 = base64::prelude::BASE64_STANDARD;
+        Ok(format!(
+            "untrusted comment: signature from dist secret key\n{}\ntrusted comment: {trusted_comment}\n{}\n",
+            b64.encode(sig_line),
+            b64.encode(global_signature),
+        ))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const SEED: [u8; 32] = [7; 32];
+    const KEY_ID: [u8; 8] = [1, 2, 3, 4, 5, 6, 7, 8];
+
+    fn public_key() -> MinisignPublicKey {
+        MinisignPublicKey {
+            key_id: KEY_ID,
+            key: SigningKey::from_bytes(&SEED).verifying_key().to_bytes(),
+        }
+    }
+
+    /// Make a secret key file the way `minisign -G` would (with cheap scrypt params)
+    fn minisign_secret_key(password: Option<&str>) -> String {
+        let key = SigningKey::from_bytes(&SEED);
+        let mut keynum = KEY_ID.to_vec();
+        keynum.extend_from_slice(&key.to_keypair_bytes());
+        let mut hasher = blake2::Blake2b::<blake2::digest::consts::U32>::new();
+        hasher.update(KEY_ALG);
+        hasher.update(KEY_ID);
+        hasher.update(key.to_keypair_bytes());
+        keynum.extend_from_slice(&hasher.finalize());
+
+        let salt = [9; 32];
+        let (opslimit, memlimit) = (32768u64, 1u64 << 20);
+        if let Some(password) = password {
+            let stream =
+                derive_key_stream(password, &salt, opslimit, memlimit, keynum.len()).unwrap();
+            for (byte, mask) in keynum.iter_mut().zip(stream) {
+                *byte ^= mask;
+            }
+        }
+
+        let mut bytes = KEY_ALG.to_vec();
+        bytes.extend_from_slice(if password.is_some() {
+            &KDF_ALG_SCRYPT
+        } else {
+            &KDF_ALG_NONE
+        });
+        bytes.extend_from_slice(&CHK_ALG);
+        bytes.extend_from_slice(&salt);
+        bytes.extend_from_slice(&opslimit.to_le_bytes());
+        bytes.extend_from_slice(&memlimit.to_le_bytes());
+        bytes.extend_from_slice(&keynum);
+        format!(
+            "untrusted comment: minisign encrypted secret key\n{}\n",
+            base64::prelude::BASE64_STANDARD.encode(bytes)
+        )
+    }
+
+    fn signer_with(secret: MinisignSecretKey) -> Minisign {
+        let signer = Minisign {
+            public_key: public_key(),
+            secret_key: OnceLock::new(),
+        };
+        signer.secret_key.set(secret).ok().unwrap();
+        signer
+    }
+
+    fn assert_verifies(signer: &Minisign) {
+        let public_key =
+            minisign_verify::PublicKey::from_base64(&signer.public_key().to_base64()).unwrap();
+        let contents = b"hello, world";
+        let signature = signer
+            .sign_bytes(contents, "timestamp:0\tfile:hello.txt\thashed")
+            .unwrap();
+        let signature = minisign_verify::Signature::decode(&signature).unwrap();
+        public_key.verify(contents, &signature, false).unwrap();
+        assert!(public_key.verify(b"goodbye", &signature, false).is_err());
+    }
+
+    #[test]
+    fn public_key_round_trips() {
+        let key = public_key();
+        let file = format!(
+            "untrusted comment: minisign public key {}\n{}\n",
+            key.key_id(),
+            key.to_base64()
+        );
+        assert_eq!(MinisignPublicKey::parse(&file).unwrap(), key);
+        assert_eq!(MinisignPublicKey::parse(&key.to_base64()).unwrap(), key);
+        assert_eq!(key.key_id(), "0807060504030201");
+        assert!(MinisignPublicKey::parse("not a key").is_err());
+    }
+
+    #[test]
+    fn signs_with_raw_key() {
+        let raw = base64::prelude::BASE64_STANDARD.encode(SEED);
+        let secret = MinisignSecretKey::parse(&raw, None, &public_key()).unwrap();
+        assert_verifies(&signer_with(secret));
+    }
+
+    #[test]
+    fn signs_with_minisign_key() {
+        let file = minisign_secret_key(None);
+        let secret = MinisignSecretKey::parse(&file, None, &public_key()).unwrap();
+        assert_verifies(&signer_with(secret));
+    }
+
+    #[test]
+    fn signs_with_encrypted_minisign_key() {
+        let file = minisign_secret_key(Some("hunter2"));
+        assert!(MinisignSecretKey::parse(&file, None, &public_key()).is_err());
+        assert!(MinisignSecretKey::parse(&file, Some("hunter3"), &public_key()).is_err());
+        let secret = MinisignSecretKey::parse(&file, Some("hunter2"), &public_key()).unwrap();
+        assert_verifies(&signer_with(secret));
+    }
+
+    #[test]
+    fn rejects_mismatched_key() {
+        let raw = base64::prelude::BASE64_STANDARD.encode([8; 32]);
+        assert!(matches!(
+            MinisignSecretKey::parse(&raw, None, &public_key()),
+            Err(DistError::MinisignKeyMismatch { .. })
+        ));
+    }
+}
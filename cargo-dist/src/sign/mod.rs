@@ -10,13 +10,17 @@ use cargo_dist_schema::TripleNameRef;
 use crate::{config::ProductionMode, DistResult};
 
 mod macos;
+mod minisign;
 mod ssldotcom;
 
+pub use minisign::{InstallerSigningKey, MinisignPublicKey};
+
 /// Code/artifact signing providers
 #[derive(Debug)]
 pub struct Signing {
     macos: Option<macos::Codesign>,
     ssldotcom: Option<ssldotcom::CodeSignTool>,
+    minisign: Option<minisign::Minisign>,
 }
 
 impl Signing {
@@ -27,6 +31,7 @@ impl Signing {
         dist_dir: &Utf8Path,
         ssldotcom_windows_sign: Option<ProductionMode>,
         macos_sign: bool,
+        minisign_public_key: Option<&str>,
     ) -> DistResult<Self> {
         let ssldotcom =
             ssldotcom::CodeSignTool::new(client, host_target, dist_dir, ssldotcom_windows_sign)?;
@@ -35,7 +40,33 @@ impl Signing {
         } else {
             None
         };
-        Ok(Self { macos, ssldotcom })
+        let minisign = minisign_public_key
+            .map(minisign::Minisign::new)
+            .transpose()?;
+        Ok(Self {
+            macos,
+            ssldotcom,
+            minisign,
+        })
+    }
+
+    /// The public key detached signatures are made with, if we're making them
+    pub fn minisign_public_key(&self) -> Option<&MinisignPublicKey> {
+        self.minisign.as_ref().map(|m| m.public_key())
+    }
+
+    /// Whether we're able to make detached signatures
+    pub fn can_make_signatures(&self) -> bool {
+        self.minisign.as_ref().is_some_and(|m| m.has_secret_key())
+    }
+
+    /// Write a detached signature for `file` to `dest_path`
+    pub fn write_signature(&self, file: &Utf8Path, dest_path: &Utf8Path) -> DistResult<()> {
+        let signer = self
+            .minisign
+            .as_ref()
+            .expect("tried to sign an artifact without minisign-public-key set!?");
+        signer.sign(file, dest_path)
     }
 
     /// Sign a file
@@ -50,9 +50,13 @@
 
 use std::collections::BTreeMap;
 
-use crate::backend::installer::{ExecutableZipFragment, HomebrewImpl};
+use crate::backend::installer::{
+    ExecutableZipFragment, HomebrewImpl, ScoopImpl, WindowsFragments, WingetImpl,
+};
 use crate::platform::targets::{
-    TARGET_ARM64_LINUX_GNU, TARGET_ARM64_MAC, TARGET_X64_LINUX_GNU, TARGET_X64_MAC,
+    TARGET_ARM64_LINUX_GNU, TARGET_ARM64_MAC, TARGET_ARM64_WINDOWS, TARGET_UNIVERSAL2_MAC,
+    TARGET_X64_LINUX_GNU, TARGET_X64_MAC, TARGET_X64_WINDOWS, TARGET_X86_WINDOWS,
+    UNIVERSAL2_MAC_SLICES,
 };
 use axoasset::AxoClient;
 use axoprocess::Cmd;
@@ -69,9 +73,15 @@ use tracing::{info, warn};
 
 use crate::announce::{self, AnnouncementTag, TagMode};
 use crate::backend::ci::github::GithubCiInfo;
+use crate::backend::ci::gitlab::GitlabCiInfo;
 use crate::backend::ci::CiInfo;
+use crate::backend::desktop::{DesktopEntry, IconFormat};
+use crate::backend::installer::deb::{self, DebInstallerInfo};
 use crate::backend::installer::homebrew::{to_homebrew_license_format, HomebrewFragments};
 use crate::backend::installer::macpkg::PkgInstallerInfo;
+use crate::backend::installer::rpm::{self, RpmInstallerInfo};
+use crate::backend::installer::scoop::ScoopInstallerInfo;
+use crate::backend::installer::winget::{self, WingetInstallerInfo};
 use crate::config::v1::builds::cargo::AppCargoBuildConfig;
 use crate::config::v1::ci::CiConfig;
 use crate::config::v1::installers::CommonInstallerConfig;
@@ -81,7 +91,7 @@ use crate::config::{DependencyKind, DirtyMode, LibraryStyle};
 use crate::linkage::determine_build_environment;
 use crate::net::ClientSettings;
 use crate::platform::{PlatformSupport, RuntimeConditions};
-use crate::sign::Signing;
+use crate::sign::{InstallerSigningKey, Signing};
 use crate::{
     backend::{
         installer::{
@@ -197,8 +207,12 @@ pub struct DistGraph {
     pub is_init: bool,
     /// What to allow to be dirty
     pub allow_dirty: DirtyMode,
-    /// Homebrew tap all packages agree on
-    pub global_homebrew_tap: Option<String>,
+    /// Every Homebrew tap some package publishes to
+    pub homebrew_taps: Vec<String>,
+    /// Scoop bucket all packages agree on
+    pub global_scoop_bucket: Option<String>,
+    /// winget manifest repo all packages agree on
+    pub global_winget_repo: Option<String>,
     /// builtin publish jobs all packages agree on
     pub global_publishers: Option<PublisherConfig>,
     /// Whether we can just build the workspace or need to build each package
@@ -239,6 +253,8 @@ pub struct DistGraph {
     pub hosting: Option<HostingInfo>,
     /// LIES ALL LIES
     pub local_builds_are_lies: bool,
+    /// The time to stamp archive entries with, if archives should be reproducible
+    pub source_date_epoch: Option<u64>,
     /// HTTP client settings
     pub client_settings: ClientSettings,
     /// A reusable client for basic http fetches
@@ -277,6 +293,10 @@ pub struct Tools {
     pub git: Option<Tool>,
     /// omnibor, used for generating OmniBOR Artifact IDs
     pub omnibor: Option<Tool>,
+    /// objcopy, used for splitting debuginfo out of ELF binaries
+    pub objcopy: Option<Tool>,
+    /// llvm-objcopy, used instead of objcopy when cross-compiling
+    pub llvm_objcopy: Option<Tool>,
     /// ssl.com's CodeSignTool, for Windows Code Signing
     ///
     /// <https://www.ssl.com/guide/esigner-codesigntool-command-guide/>
@@ -306,6 +326,27 @@ impl Tools {
         })
     }
 
+    /// Returns info on the objcopy to use on binaries for the given target, or an error
+    ///
+    /// The host's objcopy may not understand binaries for other architectures, so we
+    /// prefer the target's cross objcopy (like `aarch64-linux-gnu-objcopy`), and then
+    /// llvm-objcopy (which understands everything).
+    pub fn objcopy(&self, target: &TripleNameRef) -> DistResult<Tool> {
+        let mut parts = target.as_str().split('-');
+        let cross = match (parts.next(), parts.next(), parts.next(), parts.next()) {
+            (Some(arch), Some(_vendor), Some(os), Some(env)) => {
+                find_tool(&format!("{arch}-{os}-{env}-objcopy"), "--version")
+            }
+            _ => None,
+        };
+        cross
+            .or_else(|| self.llvm_objcopy.clone())
+            .or_else(|| self.objcopy.clone())
+            .ok_or(DistError::ToolMissing {
+                tool: "objcopy".to_owned(),
+            })
+    }
+
     /// Returns cargo-auditable info or an error
     pub fn cargo_auditable(&self) -> DistResult<&Tool> {
         self.cargo_auditable.as_ref().ok_or(DistError::ToolMissing {
@@ -390,6 +431,10 @@ pub struct Binary {
     pub features: CargoTargetFeatures,
     /// What kind of binary this is
     pub kind: BinaryKind,
+    /// For universal binaries, the single-architecture binaries that get merged into this one
+    ///
+    /// Universal binaries are never built directly, see [`LipoStep`][].
+    pub slices: Vec<BinaryIdx>,
 }
 
 /// Different kinds of binaries dist knows about
@@ -421,6 +466,8 @@ pub enum BuildStep {
     CopyDir(CopyStep),
     /// Copy a file or dir (unknown, don't check which until the last possible second)
     CopyFileOrDir(CopyStep),
+    /// Write a desktop entry (and its icon) into a dir
+    DesktopEntry(DesktopEntryStep),
     /// Zip up a directory
     Zip(ZipDirStep),
     /// Generate some kind of installer
@@ -431,12 +478,16 @@ pub enum BuildStep {
     Checksum(ChecksumImpl),
     /// Generate a unified checksum file, containing multiple entries
     UnifiedChecksum(UnifiedChecksumStep),
+    /// Make a detached signature of a file
+    Signature(SignatureImpl),
+    /// Generate an in-toto SLSA provenance statement for a release
+    Provenance(ProvenanceImpl),
     /// Generate an OmniBOR Artifact ID
     OmniborArtifactId(OmniborArtifactIdImpl),
     /// Fetch or build an updater binary
     Updater(UpdaterStep),
-    // FIXME: For macos universal builds we'll want
-    // Lipo(LipoStep)
+    /// Merge single-architecture binaries into a universal one
+    Lipo(LipoStep),
 }
 
 /// A cargo build (and copy the outputs to various locations)
@@ -583,6 +634,17 @@ pub struct RustupStep {
     pub target: TripleName,
 }
 
+/// Merge single-architecture macOS binaries into a universal (fat) one, like apple's `lipo`
+#[derive(Debug)]
+pub struct LipoStep {
+    /// The universal binary we're making
+    pub binary: BinaryIdx,
+    /// The single-architecture binaries to merge
+    pub src_paths: Vec<Utf8PathBuf>,
+    /// Where to write the universal binary (before it's copied to its final homes)
+    pub dest_path: Utf8PathBuf,
+}
+
 /// zip/tarball some directory
 #[derive(Debug)]
 pub struct ZipDirStep {
@@ -594,6 +656,8 @@ pub struct ZipDirStep {
     pub with_root: Option<Utf8PathBuf>,
     /// The kind of zip/tarball to make
     pub zip_style: ZipStyle,
+    /// If set, make a reproducible archive with every entry stamped with this time
+    pub source_date_epoch: Option<u64>,
 }
 
 /// Copy a file
@@ -605,6 +669,15 @@ pub struct CopyStep {
     pub dest_path: Utf8PathBuf,
 }
 
+/// Write a desktop entry (and its icon) into a dir
+#[derive(Debug)]
+pub struct DesktopEntryStep {
+    /// the entry to write
+    pub entry: DesktopEntry,
+    /// the dir to write it into (the root of an archive)
+    pub dest_dir: Utf8PathBuf,
+}
+
 /// Create a checksum
 #[derive(Debug, Clone)]
 pub struct ChecksumImpl {
@@ -636,6 +709,39 @@ pub struct UnifiedChecksumStep {
     pub dest_path: Utf8PathBuf,
 }
 
+/// Create a detached (minisign) signature of a file
+#[derive(Debug, Clone)]
+pub struct SignatureImpl {
+    /// of this file
+    pub src_path: Utf8PathBuf,
+    /// write it to here
+    pub dest_path: Utf8PathBuf,
+}
+
+/// Create an in-toto SLSA provenance statement describing every artifact in a Release
+///
+/// This is computed by the global build step, once every other artifact's checksum
+/// has been merged into the manifest.
+#[derive(Debug, Clone)]
+pub struct ProvenanceImpl {
+    /// The name of the app being released
+    pub app_name: String,
+    /// The version of the app being released
+    pub app_version: String,
+    /// The tag being announced
+    pub tag: String,
+    /// The repository the release is built from, if known
+    pub repository_url: Option<String>,
+    /// The targets the release was built for
+    pub targets: Vec<TripleName>,
+    /// The dist config file the build was driven by
+    pub config_path: Utf8PathBuf,
+    /// path to the git checkout
+    pub working_dir: Utf8PathBuf,
+    /// write it to here
+    pub dest_path: Utf8PathBuf,
+}
+
 /// Create a file containing the OmniBOR Artifact ID for a specific file.
 #[derive(Debug, Clone)]
 pub struct OmniborArtifactIdImpl {
@@ -681,6 +787,8 @@ pub enum SymbolKind {
     Dsym,
     /// DWARF DWPs
     Dwp,
+    /// ELF debuginfo split out of the binary (see [`crate::build::debuginfo`][])
+    Debug,
 }
 
 impl SymbolKind {
@@ -690,6 +798,7 @@ impl SymbolKind {
             SymbolKind::Pdb => "pdb",
             SymbolKind::Dsym => "dSYM",
             SymbolKind::Dwp => "dwp",
+            SymbolKind::Debug => "debug",
         }
     }
 }
@@ -721,10 +830,31 @@ pub struct Artifact {
     pub kind: ArtifactKind,
     /// A checksum for this artifact, if any
     pub checksum: Option<ArtifactIdx>,
+    /// A detached signature for this artifact, if any
+    pub signature: Option<ArtifactIdx>,
     /// Indicates whether the artifact is local or global
     pub is_global: bool,
 }
 
+impl Artifact {
+    /// Whether this artifact should get a detached signature (when signing is enabled)
+    ///
+    /// Checksums, OmniBOR ids, and signatures are all derived from something
+    /// that's already signed, and dSYMs are directories, so those are skipped.
+    /// The unified checksum and provenance are signed separately, once they exist.
+    pub fn wants_signature(&self) -> bool {
+        match &self.kind {
+            ArtifactKind::Checksum(_)
+            | ArtifactKind::UnifiedChecksum(_)
+            | ArtifactKind::Provenance(_)
+            | ArtifactKind::OmniborArtifactId(_)
+            | ArtifactKind::Signature(_) => false,
+            ArtifactKind::Symbols(symbols) => !matches!(symbols.kind, SymbolKind::Dsym),
+            _ => true,
+        }
+    }
+}
+
 /// Info about an archive (zip/tarball) that should be made. Currently this is always part
 /// of an Artifact, and the final output will be [`Artifact::file_path`][].
 #[derive(Clone, Debug)]
@@ -742,6 +872,8 @@ pub struct Archive {
     ///
     /// In the future this might add a custom relative dest path
     pub static_assets: Vec<(StaticAssetKind, Utf8PathBuf)>,
+    /// A desktop entry (and icon) to include under `share/`
+    pub desktop_entry: Option<DesktopEntry>,
 }
 
 /// A kind of artifact (more specific fields)
@@ -758,6 +890,10 @@ pub enum ArtifactKind {
     Checksum(ChecksumImpl),
     /// A unified checksum file, like `sha256.sum`
     UnifiedChecksum(UnifiedChecksumStep),
+    /// A detached signature, like `foo.tar.gz.minisig`
+    Signature(SignatureImpl),
+    /// An in-toto SLSA provenance statement, like `foo-v1.0.0.intoto.jsonl`
+    Provenance(ProvenanceImpl),
     /// A source tarball
     SourceTarball(SourceTarball),
     /// An extra artifact specified via config
@@ -780,7 +916,7 @@ pub struct ExecutableZip {
 #[derive(Clone, Debug)]
 pub struct Symbols {
     /// The kind of symbols this is
-    kind: SymbolKind,
+    pub kind: SymbolKind,
 }
 
 /// A source tarball artifact
@@ -995,8 +1131,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 root_workspace.dist_manifest_path.as_deref(),
                 root_workspace.cargo_metadata_table.as_ref(),
             )?;
-
-        let workspace_layer = workspace_metadata.to_toml_layer(true);
+        let workspace_layer = workspace_metadata.to_toml_layer(true)?;
         workspace_metadata.make_relative_to(&root_workspace.workspace_dir);
 
         let config = workspace_config(workspaces, workspace_layer.clone());
@@ -1021,7 +1156,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 workspaces,
                 pkg_idx,
                 workspace_layer.clone(),
-                package_metadata.to_toml_layer(false),
+                package_metadata.to_toml_layer(false)?,
             ));
 
             package_metadata.make_relative_to(&package.package_root);
@@ -1075,52 +1210,53 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             requires_precise
         };
 
-        // check homebrew taps for global publish jobs
-        // FIXME: when we add `dist publish` we can drop this,
+        // Homebrew formulae are published per-package by `dist publish`,
+        // so every package can have its own tap
+        let homebrew_taps = package_configs
+            .iter()
+            .filter_map(|config| config.installers.homebrew.as_ref()?.tap.clone())
+            .collect::<SortedSet<_>>()
+            .into_iter()
+            .collect::<Vec<_>>();
+        // check scoop buckets and winget repos for global publish jobs
+        // FIXME: when `dist publish` supports these we can drop this,
         // as we can support granular publish settings
-        let mut global_homebrew_tap = None;
-        let mut packages_with_mismatched_taps = vec![];
-        for ((_idx, package), package_config) in workspaces.all_packages().zip(&package_configs) {
-            if let Some(homebrew) = &package_config.installers.homebrew {
-                if let Some(new_tap) = &homebrew.tap {
-                    if let Some(current_tap) = &global_homebrew_tap {
-                        if current_tap != new_tap {
-                            packages_with_mismatched_taps.push(
-                                package
-                                    .dist_manifest_path
-                                    .clone()
-                                    .unwrap_or(package.manifest_path.clone()),
-                            );
-                        }
-                    } else {
-                        // This package gets to be the archetype, so if there's a mismatch it will
-                        // always be implicated. So push it to the error list, and only say there's an
-                        // error if there's two entries in this at the end.
-                        packages_with_mismatched_taps.push(
-                            package
-                                .dist_manifest_path
-                                .clone()
-                                .unwrap_or(package.manifest_path.clone()),
-                        );
-                        global_homebrew_tap = Some(new_tap.clone());
-                    }
-                }
-            }
-        }
-        if packages_with_mismatched_taps.len() > 1 {
-            return Err(DistError::MismatchedTaps {
-                packages: packages_with_mismatched_taps,
-            });
-        }
+        let global_scoop_bucket = global_publish_repo(workspaces, &package_configs, |config| {
+            config.installers.scoop.as_ref()?.bucket.as_ref()
+        })
+        .map_err(|packages| DistError::MismatchedScoopBuckets { packages })?;
+        let global_winget_repo = global_publish_repo(workspaces, &package_configs, |config| {
+            config.installers.winget.as_ref()?.repo.as_ref()
+        })
+        .map_err(|packages| DistError::MismatchedWingetRepos { packages })?;
 
         // check publish jobs for global publish jobs
-        // FIXME: when we add `dist publish` we can drop this,
+        //
+        // `dist publish` handles homebrew and npm for each package with its own settings,
+        // so CI just needs to run those if any package wants them.
+        // FIXME: as `dist publish` learns the other publishers we can drop this,
         // as we can support granular publish settings
-        let mut global_publishers = None;
+        let mut global_publishers: Option<PublisherConfig> = None;
         let mut packages_with_mismatched_publishers = vec![];
         for ((_idx, package), package_config) in workspaces.all_packages().zip(&package_configs) {
-            if let Some(cur_publishers) = &global_publishers {
-                if cur_publishers != &package_config.publishers {
+            let publishers = &package_config.publishers;
+            if let Some(cur_publishers) = &mut global_publishers {
+                let PublisherConfig {
+                    homebrew,
+                    npm,
+                    scoop,
+                    winget,
+                    user,
+                } = cur_publishers;
+                if homebrew.is_none() {
+                    homebrew.clone_from(&publishers.homebrew);
+                }
+                if npm.is_none() {
+                    npm.clone_from(&publishers.npm);
+                }
+                if (&*scoop, &*winget, &*user)
+                    != (&publishers.scoop, &publishers.winget, &publishers.user)
+                {
                     packages_with_mismatched_publishers.push(
                         package
                             .dist_manifest_path
@@ -1138,7 +1274,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                         .clone()
                         .unwrap_or(package.manifest_path.clone()),
                 );
-                global_publishers = Some(package_config.publishers.clone());
+                global_publishers = Some(publishers.clone());
             }
         }
         if packages_with_mismatched_publishers.len() > 1 {
@@ -1146,36 +1282,8 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 packages: packages_with_mismatched_publishers,
             });
         }
-        let global_publish_prereleases = global_publishers
-            .as_ref()
-            .map(|p| {
-                // until we have `dist publish` we need to enforce everyone agreeing on `prereleases`
-                let PublisherConfig {
-                    homebrew,
-                    npm,
-                    user,
-                } = p;
-                let h_pre = homebrew.as_ref().map(|p| p.prereleases);
-                let npm_pre = npm.as_ref().map(|p| p.prereleases);
-                let user_pre = user.as_ref().map(|p| p.prereleases);
-                let choices = [h_pre, npm_pre, user_pre];
-                let mut global_choice = None;
-                #[allow(clippy::manual_flatten)]
-                for choice in choices {
-                    if let Some(choice) = choice {
-                        if let Some(cur_choice) = global_choice {
-                            if cur_choice != choice {
-                                return Err(DistError::MismatchedPrereleases);
-                            }
-                        } else {
-                            global_choice = Some(choice);
-                        }
-                    }
-                }
-                Ok(global_choice.unwrap_or(false))
-            })
-            .transpose()?
-            .unwrap_or(false);
+        let global_publish_prereleases =
+            global_publish_prereleases(package_configs.iter().map(|config| &config.publishers))?;
 
         let templates = Templates::new()?;
         let allow_dirty = if allow_all_dirty {
@@ -1206,6 +1314,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             &dist_dir,
             config.builds.ssldotcom_windows_sign.clone(),
             config.builds.macos_sign,
+            config.builds.minisign_public_key.as_deref(),
         )?;
         let github_attestations = config
             .hosts
@@ -1231,7 +1340,9 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 system_id,
                 is_init: config.dist_version.is_some(),
                 allow_dirty,
-                global_homebrew_tap,
+                homebrew_taps,
+                global_scoop_bucket,
+                global_winget_repo,
                 global_publishers,
                 precise_cargo_builds,
                 target_dir,
@@ -1242,6 +1353,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 signer,
                 tools,
                 local_builds_are_lies,
+                source_date_epoch: None,
                 templates,
                 local_build_steps: vec![],
                 global_build_steps: vec![],
@@ -1283,7 +1395,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         })
     }
 
-    fn add_release(&mut self, pkg_idx: PackageIdx) -> ReleaseIdx {
+    pub(crate) fn add_release(&mut self, pkg_idx: PackageIdx) -> ReleaseIdx {
         let package_info = self.workspaces.package(pkg_idx);
         let config = self.package_config(pkg_idx).clone();
 
@@ -1362,6 +1474,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             ..
         } = self.release_mut(to_release);
         let static_assets = static_assets.clone();
+        let release_id = release_id.clone();
         let variant_id = format!("{release_id}-{target}");
         info!("added variant {variant_id}");
         let binaries_map = &config.artifacts.archives.binaries;
@@ -1416,92 +1529,23 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         // Add all the binaries of the release to this variant
         let mut binaries = vec![];
         for (pkg_idx, binary_name, kind) in packageables {
-            let package = self.workspaces.package(pkg_idx);
-            let package_config = self.package_config(pkg_idx);
-            let pkg_id = package.cargo_package_id.clone();
-            // For now we just use the name of the package as its package_spec.
-            // I'm not sure if there are situations where this is ambiguous when
-            // referring to a package in your workspace that you want to build an app for.
-            // If they do exist, that's deeply cursed and I want a user to tell me about it.
-            let pkg_spec = package.true_name.clone();
-            let kind_label = match kind {
-                BinaryKind::Executable => "exe",
-                BinaryKind::DynamicLibrary => "cdylib",
-                BinaryKind::StaticLibrary => "cstaticlib",
-            };
-            // FIXME: make this more of a GUID to allow variants to share binaries?
-            let bin_id = format!("{variant_id}-{kind_label}-{binary_name}");
-
-            let idx = if let Some(&idx) = self.binaries_by_id.get(&bin_id) {
-                // If we already are building this binary we don't need to do it again!
-                idx
-            } else {
-                // Compute the rest of the details and add the binary
-                let features = CargoTargetFeatures {
-                    default_features: package_config.builds.cargo.default_features,
-                    features: if package_config.builds.cargo.all_features {
-                        CargoTargetFeatureList::All
-                    } else {
-                        CargoTargetFeatureList::List(package_config.builds.cargo.features.clone())
-                    },
-                };
-
-                let target_is_windows = target.is_windows();
-                let platform_exe_ext;
-                let platform_lib_prefix;
-                if target_is_windows {
-                    platform_exe_ext = ".exe";
-                    platform_lib_prefix = "";
-                } else {
-                    platform_exe_ext = "";
-                    platform_lib_prefix = "lib";
-                };
-
-                let platform_lib_ext;
-                let platform_staticlib_ext;
-                if target_is_windows {
-                    platform_lib_ext = ".dll";
-                    platform_staticlib_ext = ".lib";
-                } else if target.is_linux() {
-                    platform_lib_ext = ".so";
-                    platform_staticlib_ext = ".a";
-                } else if target.is_darwin() {
-                    platform_lib_ext = ".dylib";
-                    platform_staticlib_ext = ".a";
-                } else {
-                    return Err(DistError::UnrecognizedTarget { target });
-                };
-
-                let file_name = match kind {
-                    BinaryKind::Executable => format!("{binary_name}{platform_exe_ext}"),
-                    BinaryKind::DynamicLibrary => {
-                        format!("{platform_lib_prefix}{binary_name}{platform_lib_ext}")
-                    }
-                    BinaryKind::StaticLibrary => {
-                        format!("{platform_lib_prefix}{binary_name}{platform_staticlib_ext}")
-                    }
-                };
-
-                info!("added binary {bin_id}");
-                let idx = BinaryIdx(self.inner.binaries.len());
-                let binary = Binary {
-                    id: bin_id.clone(),
-                    pkg_id,
-                    pkg_spec,
-                    pkg_idx,
-                    name: binary_name,
-                    file_name,
-                    target: target.clone(),
-                    copy_exe_to: vec![],
-                    copy_symbols_to: vec![],
-                    symbols_artifact: None,
-                    features,
-                    kind,
-                };
-                self.inner.binaries.push(binary);
-                self.binaries_by_id.insert(bin_id, idx);
-                idx
-            };
+            let idx =
+                self.add_binary_for_target(&release_id, &target, pkg_idx, &binary_name, kind)?;
+
+            // Universal binaries are made by merging one binary for each real target
+            if target == TARGET_UNIVERSAL2_MAC && self.binary(idx).slices.is_empty() {
+                let mut slices = vec![];
+                for &slice_target in UNIVERSAL2_MAC_SLICES {
+                    slices.push(self.add_binary_for_target(
+                        &release_id,
+                        slice_target,
+                        pkg_idx,
+                        &binary_name,
+                        kind,
+                    )?);
+                }
+                self.binary_mut(idx).slices = slices;
+            }
 
             binaries.push(idx);
         }
@@ -1516,6 +1560,108 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         Ok(idx)
     }
 
+    /// Get the binary with the given name for a target (adding it if it doesn't exist yet)
+    fn add_binary_for_target(
+        &mut self,
+        release_id: &str,
+        target: &TripleNameRef,
+        pkg_idx: PackageIdx,
+        binary_name: &str,
+        kind: BinaryKind,
+    ) -> DistResult<BinaryIdx> {
+        let variant_id = format!("{release_id}-{target}");
+        let package = self.workspaces.package(pkg_idx);
+        let package_config = self.package_config(pkg_idx);
+        let pkg_id = package.cargo_package_id.clone();
+        // For now we just use the name of the package as its package_spec.
+        // I'm not sure if there are situations where this is ambiguous when
+        // referring to a package in your workspace that you want to build an app for.
+        // If they do exist, that's deeply cursed and I want a user to tell me about it.
+        let pkg_spec = package.true_name.clone();
+        let kind_label = match kind {
+            BinaryKind::Executable => "exe",
+            BinaryKind::DynamicLibrary => "cdylib",
+            BinaryKind::StaticLibrary => "cstaticlib",
+        };
+        // FIXME: make this more of a GUID to allow variants to share binaries?
+        let bin_id = format!("{variant_id}-{kind_label}-{binary_name}");
+
+        let idx = if let Some(&idx) = self.binaries_by_id.get(&bin_id) {
+            // If we already are building this binary we don't need to do it again!
+            idx
+        } else {
+            // Compute the rest of the details and add the binary
+            let features = CargoTargetFeatures {
+                default_features: package_config.builds.cargo.default_features,
+                features: if package_config.builds.cargo.all_features {
+                    CargoTargetFeatureList::All
+                } else {
+                    CargoTargetFeatureList::List(package_config.builds.cargo.features.clone())
+                },
+            };
+
+            let target_is_windows = target.is_windows();
+            let platform_exe_ext;
+            let platform_lib_prefix;
+            if target_is_windows {
+                platform_exe_ext = ".exe";
+                platform_lib_prefix = "";
+            } else {
+                platform_exe_ext = "";
+                platform_lib_prefix = "lib";
+            };
+
+            let platform_lib_ext;
+            let platform_staticlib_ext;
+            if target_is_windows {
+                platform_lib_ext = ".dll";
+                platform_staticlib_ext = ".lib";
+            } else if target.is_linux() {
+                platform_lib_ext = ".so";
+                platform_staticlib_ext = ".a";
+            } else if target.is_darwin() {
+                platform_lib_ext = ".dylib";
+                platform_staticlib_ext = ".a";
+            } else {
+                return Err(DistError::UnrecognizedTarget {
+                    target: target.to_owned(),
+                });
+            };
+
+            let file_name = match kind {
+                BinaryKind::Executable => format!("{binary_name}{platform_exe_ext}"),
+                BinaryKind::DynamicLibrary => {
+                    format!("{platform_lib_prefix}{binary_name}{platform_lib_ext}")
+                }
+                BinaryKind::StaticLibrary => {
+                    format!("{platform_lib_prefix}{binary_name}{platform_staticlib_ext}")
+                }
+            };
+
+            info!("added binary {bin_id}");
+            let idx = BinaryIdx(self.inner.binaries.len());
+            let binary = Binary {
+                id: bin_id.clone(),
+                pkg_id,
+                pkg_spec,
+                pkg_idx,
+                name: binary_name.to_owned(),
+                file_name,
+                target: target.to_owned(),
+                copy_exe_to: vec![],
+                copy_symbols_to: vec![],
+                symbols_artifact: None,
+                features,
+                kind,
+                slices: vec![],
+            };
+            self.inner.binaries.push(binary);
+            self.binaries_by_id.insert(bin_id, idx);
+            idx
+        };
+        Ok(idx)
+    }
+
     fn add_binary(&mut self, to_release: ReleaseIdx, pkg_idx: PackageIdx, binary_name: String) {
         let release = self.release_mut(to_release);
         release.bins.push((pkg_idx, binary_name));
@@ -1597,6 +1743,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                         artifact_relpath,
                     }),
                     checksum: None,
+                    signature: None,
                     is_global: true,
                 };
 
@@ -1626,6 +1773,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 required_binaries: Default::default(),
                 kind: ArtifactKind::SBOM(SBOMImpl {}),
                 checksum: None,
+                signature: None,
                 is_global: true,
             },
         );
@@ -1654,6 +1802,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 dest_path,
             }),
             checksum: None,
+            signature: None,
             is_global,
         }
     }
@@ -1668,7 +1817,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         let file_name = ArtifactId::new(format!("{}.sum", checksum.ext()));
         let file_path = dist_dir.join(file_name.as_str());
 
-        self.add_global_artifact(
+        let artifact_idx = self.add_global_artifact(
             to_release,
             Artifact {
                 id: file_name,
@@ -1681,9 +1830,60 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     dest_path: file_path,
                 }),
                 checksum: None, // who checksums the checksummers...
+                signature: None,
+                is_global: true,
+            },
+        );
+        if self.inner.signer.minisign_public_key().is_some() {
+            self.add_artifact_signature(to_release, None, artifact_idx);
+        }
+    }
+
+    fn add_provenance(&mut self, tag: &str, to_release: ReleaseIdx) {
+        if !self.global_artifacts_enabled() {
+            return;
+        }
+
+        if !self.inner.config.artifacts.slsa_provenance {
+            return;
+        }
+
+        let release = self.release(to_release);
+        let file_name = ArtifactId::new(format!("{}.intoto.jsonl", release.id));
+        let file_path = self.inner.dist_dir.join(file_name.as_str());
+        let root_workspace = self.workspaces.root_workspace();
+        let config_path = root_workspace
+            .dist_manifest_path
+            .clone()
+            .unwrap_or_else(|| root_workspace.manifest_path.clone());
+        let provenance = ProvenanceImpl {
+            app_name: release.app_name.clone(),
+            app_version: release.version.to_string(),
+            tag: tag.to_owned(),
+            repository_url: release.app_repository_url.clone(),
+            targets: release.targets.clone(),
+            config_path,
+            working_dir: self.inner.repo_dir.clone(),
+            dest_path: file_path.clone(),
+        };
+
+        let artifact_idx = self.add_global_artifact(
+            to_release,
+            Artifact {
+                id: file_name,
+                target_triples: Default::default(),
+                archive: None,
+                file_path,
+                required_binaries: Default::default(),
+                kind: ArtifactKind::Provenance(provenance),
+                checksum: None,
+                signature: None,
                 is_global: true,
             },
         );
+        if self.inner.signer.minisign_public_key().is_some() {
+            self.add_artifact_signature(to_release, None, artifact_idx);
+        }
     }
 
     fn add_source_tarball(&mut self, _tag: &str, to_release: ReleaseIdx) {
@@ -1764,6 +1964,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 recursive,
             }),
             checksum: None,
+            signature: None,
             is_global: true,
         };
 
@@ -1786,6 +1987,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     for_artifact,
                 }),
                 checksum: None,
+                signature: None,
                 is_global: true,
             };
 
@@ -1829,6 +2031,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 required_binaries: Default::default(),
                 // Who checksums the checksummers...
                 checksum: None,
+                signature: None,
                 is_global: false,
             }
         };
@@ -1837,6 +2040,67 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         checksum_idx
     }
 
+    /// Sign every artifact in the Release that's worth signing
+    fn add_signatures(&mut self, to_release: ReleaseIdx) {
+        if self.inner.signer.minisign_public_key().is_none() {
+            return;
+        }
+
+        let release = self.release(to_release);
+        let global_artifacts = release.global_artifacts.clone();
+        let variants = release.variants.clone();
+        for artifact_idx in global_artifacts {
+            if self.artifact(artifact_idx).wants_signature() {
+                self.add_artifact_signature(to_release, None, artifact_idx);
+            }
+        }
+        for variant_idx in variants {
+            let local_artifacts = self.variant(variant_idx).local_artifacts.clone();
+            for artifact_idx in local_artifacts {
+                if self.artifact(artifact_idx).wants_signature() {
+                    self.add_artifact_signature(to_release, Some(variant_idx), artifact_idx);
+                }
+            }
+        }
+    }
+
+    /// Add a detached signature for an artifact, in the same scope as the artifact
+    fn add_artifact_signature(
+        &mut self,
+        to_release: ReleaseIdx,
+        to_variant: Option<ReleaseVariantIdx>,
+        artifact_idx: ArtifactIdx,
+    ) -> ArtifactIdx {
+        let artifact = self.artifact(artifact_idx);
+        let signature_id = ArtifactId::new(format!("{}.minisig", artifact.id));
+        let signature_path = artifact
+            .file_path
+            .parent()
+            .unwrap()
+            .join(signature_id.as_str());
+        let signature_artifact = Artifact {
+            id: signature_id,
+            kind: ArtifactKind::Signature(SignatureImpl {
+                src_path: artifact.file_path.clone(),
+                dest_path: signature_path.clone(),
+            }),
+            target_triples: artifact.target_triples.clone(),
+            archive: None,
+            file_path: signature_path,
+            required_binaries: Default::default(),
+            checksum: None,
+            signature: None,
+            is_global: artifact.is_global,
+        };
+        let signature_idx = if let Some(variant_idx) = to_variant {
+            self.add_local_artifact(variant_idx, signature_artifact)
+        } else {
+            self.add_global_artifact(to_release, signature_artifact)
+        };
+        self.artifact_mut(artifact_idx).signature = Some(signature_idx);
+        signature_idx
+    }
+
     fn add_updater(&mut self, variant_idx: ReleaseVariantIdx) {
         if !self.local_artifacts_enabled() {
             return;
@@ -1865,6 +2129,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 use_latest: self.inner.config.installers.always_use_latest_updater,
             }),
             checksum: None,
+            signature: None,
             is_global: false,
         }
     }
@@ -1896,6 +2161,11 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         let artifact_path = dist_dir.join(artifact_name.as_str());
 
         let static_assets = variant.static_assets.clone();
+        let desktop_entry = if variant.target.is_linux() {
+            self.desktop_entry_for_variant(release, variant)
+        } else {
+            None
+        };
         let mut built_assets = Vec::new();
         for &binary_idx in &variant.binaries {
             let binary = self.binary(binary_idx);
@@ -1922,16 +2192,54 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     dir_path: artifact_dir_path,
                     zip_style,
                     static_assets,
+                    desktop_entry,
                 }),
                 kind: ArtifactKind::ExecutableZip(ExecutableZip {}),
                 // May get filled in later
                 checksum: None,
+                signature: None,
                 is_global: false,
             },
             built_assets,
         )
     }
 
+    /// Get the desktop entry that should be bundled with a variant, if any
+    fn desktop_entry_for_variant(
+        &self,
+        release: &Release,
+        variant: &ReleaseVariant,
+    ) -> Option<DesktopEntry> {
+        let archives = &release.config.artifacts.archives;
+        if archives.desktop_entry.is_none()
+            && archives.desktop_icon.is_none()
+            && archives.desktop_mime_types.is_empty()
+        {
+            return None;
+        }
+        let executables = variant
+            .binaries
+            .iter()
+            .map(|&idx| self.binary(idx))
+            .filter(|binary| binary.kind == BinaryKind::Executable)
+            .collect::<Vec<_>>();
+        // Prefer the executable named after the app, since that's probably "the" app
+        let exec = executables
+            .iter()
+            .find(|binary| binary.name == release.app_name)
+            .or_else(|| executables.first())?;
+        Some(DesktopEntry {
+            id: release.app_name.clone(),
+            source: archives.desktop_entry.clone(),
+            name: release.config.hosts.display_name.clone(),
+            comment: release.app_desc.clone(),
+            exec: exec.file_name.clone(),
+            icon: archives.desktop_icon.clone(),
+            mime_types: archives.desktop_mime_types.clone(),
+            keywords: release.app_keywords.clone().unwrap_or_default(),
+        })
+    }
+
     /// Register that `for_artifact` requires `binary_idx` to actually be built for
     /// `for_variant`.
     ///
@@ -1951,6 +2259,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         dest_path: Utf8PathBuf,
     ) {
         let dist_dir = self.inner.dist_dir.clone();
+        let split_debuginfo = self.inner.config.builds.split_debuginfo;
         let binary = self.binary_mut(binary_idx);
 
         // Tell the binary that it should copy the exe to the given path
@@ -1958,7 +2267,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
 
         // Try to make a symbols artifact for this binary now that we're building it
         if binary.symbols_artifact.is_none() {
-            if let Some(symbol_kind) = target_symbol_kind(&binary.target) {
+            if let Some(symbol_kind) = target_symbol_kind(&binary.target, split_debuginfo) {
                 // FIXME: For some formats these won't be the same but for now stubbed out
 
                 // FIXME: rustc/cargo has so more complex logic to do platform-specific name remapping
@@ -1983,6 +2292,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     required_binaries: FastMap::new(),
                     kind: ArtifactKind::Symbols(Symbols { kind: symbol_kind }),
                     checksum: None,
+                    signature: None,
                     is_global: false,
                 };
 
@@ -2064,6 +2374,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             file_path: artifact_path.clone(),
             required_binaries: FastMap::new(),
             checksum: None,
+            signature: None,
             kind: ArtifactKind::Installer(InstallerImpl::Shell(InstallerInfo {
                 release: to_release,
                 dest_path: artifact_path,
@@ -2080,12 +2391,17 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 artifacts,
                 hint,
                 desc,
-                receipt: InstallReceipt::from_metadata(&self.inner, release)?,
+                receipt: Some(InstallReceipt::from_metadata(&self.inner, release)?),
                 bin_aliases,
                 install_libraries: config.install_libraries.clone(),
                 runtime_conditions,
                 platform_support: None,
                 env_vars,
+                signing_key: self
+                    .inner
+                    .signer
+                    .minisign_public_key()
+                    .map(InstallerSigningKey::from),
             })),
             is_global: true,
         };
@@ -2121,7 +2437,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         let artifact_path = self.inner.dist_dir.join(artifact_name.as_str());
 
         // If tap is specified, include that in the `brew install` message
-        let install_target = if let Some(tap) = &self.inner.global_homebrew_tap {
+        let install_target = if let Some(tap) = &config.tap {
             // So that, for example, axodotdev/homebrew-tap becomes axodotdev/tap
             let tap = tap.replace("/homebrew-", "/");
             format!("{tap}/{formula}")
@@ -2225,6 +2541,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             platform_support: None,
             // Not actually needed for this installer type
             env_vars: None,
+            signing_key: None,
         };
 
         let installer_artifact = Artifact {
@@ -2234,6 +2551,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             file_path: artifact_path,
             required_binaries: Default::default(),
             checksum: None,
+            signature: None,
             kind: ArtifactKind::Installer(InstallerImpl::Homebrew(HomebrewImpl {
                 info: HomebrewInstallerInfo {
                     name: app_name,
@@ -2255,89 +2573,356 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         Ok(())
     }
 
-    fn add_powershell_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+    fn add_scoop_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
         if !self.global_artifacts_enabled() {
             return Ok(());
         }
-
-        // Get the basic info about the installer
         let release = self.release(to_release);
-        let Some(config) = &release.config.installers.powershell else {
+        let Some(config) = &release.config.installers.scoop else {
             return Ok(());
         };
         require_nonempty_installer(release, config)?;
-        let release_id = &release.id;
         let schema_release = self
             .manifest
-            .release_by_name(&release.app_name)
+            .release_by_name(&release.id)
             .expect("couldn't find the release!?");
-
-        let env_vars = schema_release.env.clone();
-
         let download_urls = schema_release
             .artifact_download_urls()
             .expect("couldn't compute a URL to download artifacts from!?");
         let hosting = schema_release.hosting.clone();
-        let artifact_name = ArtifactId::new(format!("{release_id}-installer.ps1"));
+
+        let artifact_name = ArtifactId::new(format!("{}.json", release.id));
         let artifact_path = self.inner.dist_dir.join(artifact_name.as_str());
-        let best_download_url = download_urls
-            .first()
-            .expect("returned empty list of artifact URLs!?");
-        let installer_url = format!("{best_download_url}/{artifact_name}");
-        let hint = format!(r#"powershell -ExecutionPolicy Bypass -c "irm {installer_url} | iex""#);
-        let desc = "Install prebuilt binaries via powershell script".to_owned();
 
-        // Gather up the bundles the installer supports
-        let artifacts = release
-            .platform_support
-            .fragments()
-            .into_iter()
-            .filter(|a| a.target_triple.is_windows())
-            .collect::<Vec<_>>();
+        let bucket = config.bucket.clone();
+        let hint = if let Some(bucket) = &bucket {
+            // So that, for example, axodotdev/scoop-bucket gets added as scoop-bucket
+            let bucket_name = bucket.rsplit('/').next().unwrap_or(bucket);
+            format!(
+                "scoop bucket add {bucket_name} https://github.com/{bucket}; scoop install {bucket_name}/{}",
+                release.id
+            )
+        } else {
+            let best_download_url = download_urls
+                .first()
+                .expect("returned empty list of artifact URLs!?");
+            format!("scoop install {best_download_url}/{artifact_name}")
+        };
+        let desc = "Install prebuilt binaries via Scoop".to_owned();
+
+        let Some((artifacts, fragments)) = windows_fragments(&release.platform_support) else {
+            warn!("skipping Scoop installer: not building any supported platforms (use --artifacts=global)");
+            return Ok(());
+        };
         let target_triples = artifacts
             .iter()
             .map(|a| a.target_triple.clone())
             .collect::<Vec<_>>();
-        if artifacts.is_empty() {
-            warn!("skipping powershell installer: not building any supported platforms (use --artifacts=global)");
-            return Ok(());
-        };
-        let bin_aliases = BinaryAliases(config.bin_aliases.clone()).for_targets(&target_triples);
-        let installer_artifact = Artifact {
-            id: artifact_name,
-            target_triples,
-            file_path: artifact_path.clone(),
-            required_binaries: FastMap::new(),
-            archive: None,
-            checksum: None,
-            kind: ArtifactKind::Installer(InstallerImpl::Powershell(InstallerInfo {
-                release: to_release,
-                dest_path: artifact_path,
-                app_name: release.app_name.clone(),
-                app_version: release.version.to_string(),
-                install_paths: config
-                    .install_path
-                    .iter()
-                    .map(|p| p.clone().into_jinja())
-                    .collect(),
-                install_success_msg: config.install_success_msg.to_owned(),
-                base_urls: download_urls,
-                hosting,
-                artifacts,
-                hint,
-                desc,
-                receipt: InstallReceipt::from_metadata(&self.inner, release)?,
-                bin_aliases,
-                install_libraries: config.install_libraries.clone(),
-                runtime_conditions: RuntimeConditions::default(),
-                platform_support: None,
-                env_vars,
-            })),
-            is_global: true,
-        };
 
-        self.add_global_artifact(to_release, installer_artifact);
-        Ok(())
+        let app_desc = release.app_desc.clone().unwrap_or_else(|| {
+            warn!("The Scoop installer is enabled but no description was specified\n  consider adding `description = ` to package in Cargo.toml");
+            format!("The {} application", release.app_name)
+        });
+        let app_homepage_url = release
+            .app_homepage_url
+            .clone()
+            .or_else(|| release.app_repository_url.clone());
+
+        if bucket.is_some() && release.config.publishers.scoop.is_none() {
+            warn!("A Scoop bucket was specified but the Scoop publish job is disabled\n  consider adding \"scoop\" to publish-jobs in Cargo.toml");
+        }
+        if release.config.publishers.scoop.is_some() && bucket.is_none() {
+            warn!("The Scoop publish job is enabled but no bucket was specified\n  consider setting the scoop-bucket field in Cargo.toml");
+        }
+
+        let runtime_conditions = release.platform_support.safe_conflated_runtime_conditions();
+        let bin_aliases = BinaryAliases(config.bin_aliases.clone()).for_targets(&target_triples);
+
+        let inner = InstallerInfo {
+            release: to_release,
+            dest_path: artifact_path.clone(),
+            app_name: release.app_name.clone(),
+            app_version: release.version.to_string(),
+            install_paths: config
+                .install_path
+                .iter()
+                .map(|p| p.clone().into_jinja())
+                .collect(),
+            install_success_msg: config.install_success_msg.to_owned(),
+            base_urls: download_urls,
+            hosting,
+            artifacts,
+            hint,
+            desc,
+            receipt: None,
+            bin_aliases,
+            install_libraries: config.install_libraries.clone(),
+            runtime_conditions,
+            platform_support: None,
+            // Not actually needed for this installer type
+            env_vars: None,
+            signing_key: None,
+        };
+
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            archive: None,
+            file_path: artifact_path,
+            required_binaries: Default::default(),
+            checksum: None,
+            signature: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Scoop(ScoopImpl {
+                info: ScoopInstallerInfo {
+                    name: release.id.clone(),
+                    description: app_desc,
+                    homepage: app_homepage_url,
+                    license: release.app_license.clone(),
+                    bucket,
+                    inner,
+                },
+                fragments,
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+        Ok(())
+    }
+
+    fn add_winget_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.global_artifacts_enabled() {
+            return Ok(());
+        }
+        let release = self.release(to_release);
+        let Some(config) = &release.config.installers.winget else {
+            return Ok(());
+        };
+        require_nonempty_installer(release, config)?;
+        if self.inner.config.artifacts.checksum != ChecksumStyle::Sha256 {
+            warn!("skipping winget installer: winget manifests require checksum = \"sha256\"");
+            return Ok(());
+        }
+        let schema_release = self
+            .manifest
+            .release_by_name(&release.id)
+            .expect("couldn't find the release!?");
+        let download_urls = schema_release
+            .artifact_download_urls()
+            .expect("couldn't compute a URL to download artifacts from!?");
+        let hosting = schema_release.hosting.clone();
+
+        let Some((artifacts, fragments)) = windows_fragments(&release.platform_support) else {
+            warn!("skipping winget installer: not building any supported platforms (use --artifacts=global)");
+            return Ok(());
+        };
+        // winget can only unpack zips
+        let unzippable = artifacts
+            .iter()
+            .filter(|a| a.zip_style != ZipStyle::Zip)
+            .map(|a| a.id.to_string())
+            .collect::<Vec<_>>();
+        if !unzippable.is_empty() {
+            warn!("skipping winget installer: winget can only install .zip archives, but these aren't: {unzippable:?}\n  consider setting windows-archive = \".zip\" in Cargo.toml");
+            return Ok(());
+        }
+        let target_triples = artifacts
+            .iter()
+            .map(|a| a.target_triple.clone())
+            .collect::<Vec<_>>();
+
+        let publisher = config.publisher.clone().unwrap_or_else(|| {
+            // Use the name of the first author, without their email
+            release
+                .app_authors
+                .first()
+                .map(|author| author.split('<').next().unwrap_or(author).trim().to_owned())
+                .filter(|author| !author.is_empty())
+                .unwrap_or_else(|| format!("{} developers", release.app_name))
+        });
+        let package_identifier = config.package_identifier.clone().unwrap_or_else(|| {
+            format!(
+                "{}.{}",
+                winget::to_identifier_segment(&publisher),
+                winget::to_identifier_segment(&release.app_name)
+            )
+        });
+        let license = release.app_license.clone().unwrap_or_else(|| {
+            warn!("The winget installer is enabled but no license was specified\n  consider adding `license = ` to package in Cargo.toml");
+            "Proprietary".to_owned()
+        });
+        let short_description = release.app_desc.clone().unwrap_or_else(|| {
+            warn!("The winget installer is enabled but no description was specified\n  consider adding `description = ` to package in Cargo.toml");
+            format!("The {} application", release.app_name)
+        });
+        let homepage = release
+            .app_homepage_url
+            .clone()
+            .or_else(|| release.app_repository_url.clone());
+        let repo = config.repo.clone();
+
+        if repo.is_some() && release.config.publishers.winget.is_none() {
+            warn!("A winget repo was specified but the winget publish job is disabled\n  consider adding \"winget\" to publish-jobs in Cargo.toml");
+        }
+        if release.config.publishers.winget.is_some() && repo.is_none() {
+            warn!("The winget publish job is enabled but no repo was specified\n  consider setting the winget-repo field in Cargo.toml");
+        }
+
+        let hint = format!("winget install --exact --id {package_identifier}");
+        let desc = "Install prebuilt binaries via winget".to_owned();
+        let runtime_conditions = release.platform_support.safe_conflated_runtime_conditions();
+        let bin_aliases = BinaryAliases(config.bin_aliases.clone()).for_targets(&target_triples);
+
+        let mut installer_artifacts = vec![];
+        for manifest_type in winget::WingetManifestType::ALL {
+            let artifact_name = ArtifactId::new(manifest_type.file_name(&package_identifier));
+            let artifact_path = self.inner.dist_dir.join(artifact_name.as_str());
+            let inner = InstallerInfo {
+                release: to_release,
+                dest_path: artifact_path.clone(),
+                app_name: release.app_name.clone(),
+                app_version: release.version.to_string(),
+                install_paths: config
+                    .install_path
+                    .iter()
+                    .map(|p| p.clone().into_jinja())
+                    .collect(),
+                install_success_msg: config.install_success_msg.to_owned(),
+                base_urls: download_urls.clone(),
+                hosting: hosting.clone(),
+                artifacts: artifacts.clone(),
+                hint: hint.clone(),
+                desc: desc.clone(),
+                receipt: None,
+                bin_aliases: bin_aliases.clone(),
+                install_libraries: config.install_libraries.clone(),
+                runtime_conditions: runtime_conditions.clone(),
+                platform_support: None,
+                // Not actually needed for this installer type
+                env_vars: None,
+                signing_key: None,
+            };
+            installer_artifacts.push(Artifact {
+                id: artifact_name,
+                target_triples: target_triples.clone(),
+                archive: None,
+                file_path: artifact_path,
+                required_binaries: Default::default(),
+                checksum: None,
+                signature: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Winget(WingetImpl {
+                    info: WingetInstallerInfo {
+                        package_identifier: package_identifier.clone(),
+                        package_name: release.app_name.clone(),
+                        publisher: publisher.clone(),
+                        license: license.clone(),
+                        short_description: short_description.clone(),
+                        homepage: homepage.clone(),
+                        repo: repo.clone(),
+                        inner,
+                    },
+                    manifest_type,
+                    fragments: fragments.clone(),
+                })),
+                is_global: true,
+            });
+        }
+
+        for installer_artifact in installer_artifacts {
+            self.add_global_artifact(to_release, installer_artifact);
+        }
+        Ok(())
+    }
+
+    fn add_powershell_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.global_artifacts_enabled() {
+            return Ok(());
+        }
+
+        // Get the basic info about the installer
+        let release = self.release(to_release);
+        let Some(config) = &release.config.installers.powershell else {
+            return Ok(());
+        };
+        require_nonempty_installer(release, config)?;
+        let release_id = &release.id;
+        let schema_release = self
+            .manifest
+            .release_by_name(&release.app_name)
+            .expect("couldn't find the release!?");
+
+        let env_vars = schema_release.env.clone();
+
+        let download_urls = schema_release
+            .artifact_download_urls()
+            .expect("couldn't compute a URL to download artifacts from!?");
+        let hosting = schema_release.hosting.clone();
+        let artifact_name = ArtifactId::new(format!("{release_id}-installer.ps1"));
+        let artifact_path = self.inner.dist_dir.join(artifact_name.as_str());
+        let best_download_url = download_urls
+            .first()
+            .expect("returned empty list of artifact URLs!?");
+        let installer_url = format!("{best_download_url}/{artifact_name}");
+        let hint = format!(r#"powershell -ExecutionPolicy Bypass -c "irm {installer_url} | iex""#);
+        let desc = "Install prebuilt binaries via powershell script".to_owned();
+
+        // Gather up the bundles the installer supports
+        let artifacts = release
+            .platform_support
+            .fragments()
+            .into_iter()
+            .filter(|a| a.target_triple.is_windows())
+            .collect::<Vec<_>>();
+        let target_triples = artifacts
+            .iter()
+            .map(|a| a.target_triple.clone())
+            .collect::<Vec<_>>();
+        if artifacts.is_empty() {
+            warn!("skipping powershell installer: not building any supported platforms (use --artifacts=global)");
+            return Ok(());
+        };
+        let bin_aliases = BinaryAliases(config.bin_aliases.clone()).for_targets(&target_triples);
+        let installer_artifact = Artifact {
+            id: artifact_name,
+            target_triples,
+            file_path: artifact_path.clone(),
+            required_binaries: FastMap::new(),
+            archive: None,
+            checksum: None,
+            signature: None,
+            kind: ArtifactKind::Installer(InstallerImpl::Powershell(InstallerInfo {
+                release: to_release,
+                dest_path: artifact_path,
+                app_name: release.app_name.clone(),
+                app_version: release.version.to_string(),
+                install_paths: config
+                    .install_path
+                    .iter()
+                    .map(|p| p.clone().into_jinja())
+                    .collect(),
+                install_success_msg: config.install_success_msg.to_owned(),
+                base_urls: download_urls,
+                hosting,
+                artifacts,
+                hint,
+                desc,
+                receipt: Some(InstallReceipt::from_metadata(&self.inner, release)?),
+                bin_aliases,
+                install_libraries: config.install_libraries.clone(),
+                runtime_conditions: RuntimeConditions::default(),
+                platform_support: None,
+                env_vars,
+                signing_key: self
+                    .inner
+                    .signer
+                    .minisign_public_key()
+                    .map(InstallerSigningKey::from),
+            })),
+            is_global: true,
+        };
+
+        self.add_global_artifact(to_release, installer_artifact);
+        Ok(())
     }
 
     fn add_npm_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
@@ -2413,10 +2998,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 dir_path: dir_path.clone(),
                 zip_style,
                 static_assets,
+                desktop_entry: None,
             }),
             file_path: artifact_path.clone(),
             required_binaries: FastMap::new(),
             checksum: None,
+            signature: None,
             kind: ArtifactKind::Installer(InstallerImpl::Npm(NpmInstallerInfo {
                 npm_package_name,
                 npm_package_version,
@@ -2451,6 +3038,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     platform_support: None,
                     // Not actually needed for this installer type
                     env_vars: None,
+                    signing_key: None,
                 },
             })),
             is_global: true,
@@ -2468,11 +3056,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         // Clone info we need from the release to avoid borrowing across the loop
         let release = self.release(to_release);
         // FIXME: because we use cargo-wix and cargo-wix's config,
-        // msi installers really don't respect any of our own config!
+        // msi installers really don't respect most of our own config!
         // (We still look it up because it determines whether enabled or not.)
-        let Some(_config) = &release.config.installers.msi else {
+        let Some(config) = &release.config.installers.msi else {
             return Ok(());
         };
+        let builder = config.builder;
         // FIXME: MSI installer contents don't actually respect this
         // require_nonempty_installer(release, config)?;
         let variants = release.variants.clone();
@@ -2513,7 +3102,17 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             let Some((pkg_spec, pkg_idx)) = package_info else {
                 return Err(DistError::NoPackage { artifact_name })?;
             };
-            let manifest_path = self.workspaces.package(pkg_idx).manifest_path.clone();
+            let package = self.workspaces.package(pkg_idx);
+            let manifest_path = package.manifest_path.clone();
+            // msi versions are only Major.Minor.Patch (cargo-wix does the same)
+            let version = package
+                .version
+                .as_ref()
+                .map(|v| {
+                    let v = v.semver();
+                    format!("{}.{}.{}", v.major, v.minor, v.patch)
+                })
+                .unwrap_or_else(|| "0.0.0".to_owned());
             let wxs_path = manifest_path
                 .parent()
                 .expect("Cargo.toml had no parent dir!?")
@@ -2531,8 +3130,10 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     dir_path: dir_path.clone(),
                     zip_style: ZipStyle::TempDir,
                     static_assets: vec![],
+                    desktop_entry: None,
                 }),
                 checksum: None,
+                signature: None,
                 kind: ArtifactKind::Installer(InstallerImpl::Msi(MsiInstallerInfo {
                     package_dir: dir_path.clone(),
                     pkg_spec,
@@ -2540,6 +3141,8 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     file_path: artifact_path.clone(),
                     wxs_path,
                     manifest_path,
+                    version,
+                    builder,
                 })),
                 is_global: false,
             };
@@ -2645,8 +3248,10 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     dir_path: dir_path.clone(),
                     zip_style: ZipStyle::TempDir,
                     static_assets: vec![],
+                    desktop_entry: None,
                 }),
                 checksum: None,
+                signature: None,
                 kind: ArtifactKind::Installer(InstallerImpl::Pkg(PkgInstallerInfo {
                     file_path: artifact_path.clone(),
                     artifact,
@@ -2682,6 +3287,294 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         Ok(())
     }
 
+    fn add_deb_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.local_artifacts_enabled() {
+            return Ok(());
+        }
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let Some(config) = release.config.installers.deb.clone() else {
+            return Ok(());
+        };
+        require_nonempty_installer(release, &config)?;
+        let package_name = deb::debian_package_name(&release.app_name);
+        let version = deb::debian_version(&release.version.to_string());
+        let maintainer = release
+            .app_authors
+            .first()
+            .cloned()
+            .unwrap_or_else(|| format!("{} developers", release.app_name));
+        let description = release.app_desc.clone();
+        let authors = release.app_authors.clone();
+        let license = release.app_license.clone();
+        let homepage = release.app_homepage_url.clone();
+        let repository = release.app_repository_url.clone();
+        let apt_dependencies = release.config.builds.system_dependencies.apt.clone();
+        let static_assets = release.static_assets.clone();
+
+        let variants = release.variants.clone();
+        let checksum = self.inner.config.artifacts.checksum;
+
+        // Make a deb for every linux platform
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let bin_aliases = BinaryAliases(config.bin_aliases.clone());
+            let target = &variant.target;
+            if !target.is_linux() {
+                continue;
+            }
+            let Some(architecture) = deb::debian_arch(target) else {
+                warn!("skipping .deb for {target}, it has no known Debian architecture");
+                continue;
+            };
+
+            let variant_id = &variant.id;
+            let artifact_name = ArtifactId::new(format!("{variant_id}.deb"));
+            let artifact_path = self.inner.dist_dir.join(artifact_name.as_str());
+            let dir_name = format!("{variant_id}_deb");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+
+            // Sort out what goes where, skipping libraries the user didn't ask to install
+            let mut executables = vec![];
+            let mut libraries = vec![];
+            let mut packed_binaries = vec![];
+            for &binary_idx in &binaries {
+                let binary = self.binary(binary_idx);
+                match binary.kind {
+                    BinaryKind::Executable => executables.push(binary.file_name.clone()),
+                    BinaryKind::DynamicLibrary
+                        if config.install_libraries.contains(&LibraryStyle::CDynamic) =>
+                    {
+                        libraries.push(binary.file_name.clone())
+                    }
+                    BinaryKind::StaticLibrary
+                        if config.install_libraries.contains(&LibraryStyle::CStatic) =>
+                    {
+                        libraries.push(binary.file_name.clone())
+                    }
+                    _ => continue,
+                }
+                packed_binaries.push(binary_idx);
+            }
+            if packed_binaries.is_empty() {
+                return Err(DistError::NoPackage { artifact_name })?;
+            }
+
+            // Runtime apt dependencies become the package's dependencies
+            let depends = apt_dependencies
+                .iter()
+                .filter(|(_, dep)| {
+                    dep.0.stage_wanted(&DependencyKind::Run) && dep.0.wanted_for_target(target)
+                })
+                .map(|(name, dep)| match &dep.0.version {
+                    Some(version) => format!("{name} (>= {version})"),
+                    None => name.to_string(),
+                })
+                .collect();
+
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                    desktop_entry: None,
+                }),
+                checksum: None,
+                signature: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Deb(DebInstallerInfo {
+                    package_name: package_name.clone(),
+                    version: version.clone(),
+                    architecture: architecture.to_owned(),
+                    maintainer: maintainer.clone(),
+                    description: description.clone(),
+                    authors: authors.clone(),
+                    license: license.clone(),
+                    homepage: homepage.clone(),
+                    repository: repository.clone(),
+                    depends,
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    executables,
+                    libraries,
+                    bin_aliases: bin_aliases.for_target(target),
+                    static_assets: static_assets.clone(),
+                    source_date_epoch: self.inner.source_date_epoch,
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in packed_binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
+            }
+            if self.inner.config.builds.omnibor {
+                let omnibor = self.create_omnibor_artifact(installer_idx, false);
+                self.add_local_artifact(variant_idx, omnibor);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn add_rpm_installer(&mut self, to_release: ReleaseIdx) -> DistResult<()> {
+        if !self.local_artifacts_enabled() {
+            return Ok(());
+        }
+
+        // Clone info we need from the release to avoid borrowing across the loop
+        let release = self.release(to_release);
+        let Some(config) = release.config.installers.rpm.clone() else {
+            return Ok(());
+        };
+        require_nonempty_installer(release, &config)?;
+        let package_name = rpm::rpm_package_name(&release.app_name);
+        let version = rpm::rpm_version(&release.version.to_string());
+        let description = release.app_desc.clone();
+        let packager = release.app_authors.first().cloned();
+        let license = release.app_license.clone();
+        let homepage = release.app_homepage_url.clone();
+        let repository = release.app_repository_url.clone();
+        let dnf_dependencies = release.config.builds.system_dependencies.dnf.clone();
+        let static_assets = release.static_assets.clone();
+
+        let variants = release.variants.clone();
+        let checksum = self.inner.config.artifacts.checksum;
+
+        // Make an rpm for every x86_64/aarch64 linux platform
+        for variant_idx in variants {
+            let variant = self.variant(variant_idx);
+            let binaries = variant.binaries.clone();
+            let bin_aliases = BinaryAliases(config.bin_aliases.clone());
+            let target = &variant.target;
+            if !target.is_linux() {
+                continue;
+            }
+            let Some(arch) = rpm::rpm_arch(target) else {
+                warn!("skipping .rpm for {target}, only x86_64 and aarch64 are supported");
+                continue;
+            };
+
+            let variant_id = &variant.id;
+            let artifact_name = ArtifactId::new(format!("{variant_id}.rpm"));
+            let artifact_path = self.inner.dist_dir.join(artifact_name.as_str());
+            let dir_name = format!("{variant_id}_rpm");
+            let dir_path = self.inner.dist_dir.join(&dir_name);
+
+            // Sort out what goes where, skipping libraries the user didn't ask to install
+            let mut executables = vec![];
+            let mut libraries = vec![];
+            let mut packed_binaries = vec![];
+            for &binary_idx in &binaries {
+                let binary = self.binary(binary_idx);
+                match binary.kind {
+                    BinaryKind::Executable => executables.push(binary.file_name.clone()),
+                    BinaryKind::DynamicLibrary
+                        if config.install_libraries.contains(&LibraryStyle::CDynamic) =>
+                    {
+                        libraries.push(binary.file_name.clone())
+                    }
+                    BinaryKind::StaticLibrary
+                        if config.install_libraries.contains(&LibraryStyle::CStatic) =>
+                    {
+                        libraries.push(binary.file_name.clone())
+                    }
+                    _ => continue,
+                }
+                packed_binaries.push(binary_idx);
+            }
+            if packed_binaries.is_empty() {
+                return Err(DistError::NoPackage { artifact_name })?;
+            }
+
+            // Runtime dnf dependencies become the package's requirements
+            let requires = dnf_dependencies
+                .iter()
+                .filter(|(_, dep)| {
+                    dep.0.stage_wanted(&DependencyKind::Run) && dep.0.wanted_for_target(target)
+                })
+                .map(|(name, dep)| {
+                    (
+                        name.to_string(),
+                        dep.0.version.as_ref().map(|v| v.to_string()),
+                    )
+                })
+                .collect();
+
+            let installer_artifact = Artifact {
+                id: artifact_name,
+                target_triples: vec![target.clone()],
+                file_path: artifact_path.clone(),
+                required_binaries: FastMap::new(),
+                archive: Some(Archive {
+                    with_root: None,
+                    dir_path: dir_path.clone(),
+                    zip_style: ZipStyle::TempDir,
+                    static_assets: vec![],
+                    desktop_entry: None,
+                }),
+                checksum: None,
+                signature: None,
+                kind: ArtifactKind::Installer(InstallerImpl::Rpm(RpmInstallerInfo {
+                    package_name: package_name.clone(),
+                    version: version.clone(),
+                    arch: arch.to_owned(),
+                    description: description.clone(),
+                    packager: packager.clone(),
+                    license: license.clone(),
+                    homepage: homepage.clone(),
+                    repository: repository.clone(),
+                    requires,
+                    file_path: artifact_path.clone(),
+                    package_dir: dir_path.clone(),
+                    executables,
+                    libraries,
+                    bin_aliases: bin_aliases.for_target(target),
+                    static_assets: static_assets.clone(),
+                    source_date_epoch: self.inner.source_date_epoch,
+                })),
+                is_global: false,
+            };
+
+            // Register the artifact to various things
+            let installer_idx = self.add_local_artifact(variant_idx, installer_artifact);
+            for binary_idx in packed_binaries {
+                let binary = self.binary(binary_idx);
+                self.require_binary(
+                    installer_idx,
+                    variant_idx,
+                    binary_idx,
+                    dir_path.join(&binary.file_name),
+                );
+            }
+            if checksum != ChecksumStyle::False {
+                self.add_artifact_checksum(variant_idx, installer_idx, checksum);
+            }
+            if self.inner.config.builds.omnibor {
+                let omnibor = self.create_omnibor_artifact(installer_idx, false);
+                self.add_local_artifact(variant_idx, omnibor);
+            }
+        }
+
+        Ok(())
+    }
+
     fn add_local_artifact(
         &mut self,
         to_variant: ReleaseVariantIdx,
@@ -2714,12 +3607,14 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         idx
     }
 
-    fn compute_build_steps(&mut self) -> DistResult<()> {
+    pub(crate) fn compute_build_steps(&mut self) -> DistResult<()> {
         // FIXME: more intelligently schedule these in a proper graph?
 
         let mut local_build_steps = vec![];
         let mut global_build_steps = vec![];
 
+        // This needs to happen first, as it tells the slices where they need to be copied to
+        let universal_builds = self.compute_universal_builds();
         for workspace_idx in self.workspaces.all_workspace_indices() {
             let workspace_kind = self.workspaces.workspace(workspace_idx).kind;
             let builds = match workspace_kind {
@@ -2731,6 +3626,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             };
             local_build_steps.extend(builds);
         }
+        local_build_steps.extend(universal_builds);
         global_build_steps.extend(self.compute_extra_builds());
 
         Self::add_build_steps_for_artifacts(
@@ -2740,6 +3636,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 .iter()
                 .filter(|a| !a.is_global)
                 .collect(),
+            self.inner.source_date_epoch,
             &mut local_build_steps,
         );
         Self::add_build_steps_for_artifacts(
@@ -2749,6 +3646,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 .iter()
                 .filter(|a| a.is_global)
                 .collect(),
+            self.inner.source_date_epoch,
             &mut global_build_steps,
         );
 
@@ -2758,7 +3656,43 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         Ok(())
     }
 
-    fn add_build_steps_for_artifacts(artifacts: &Vec<&Artifact>, build_steps: &mut Vec<BuildStep>) {
+    /// Compute the steps that merge single-architecture binaries into universal ones
+    ///
+    /// Each slice gets staged in a scratch dir that the merge step then reads from.
+    fn compute_universal_builds(&mut self) -> Vec<BuildStep> {
+        let mut builds = vec![];
+        for binary_idx in 0..self.inner.binaries.len() {
+            let binary = &self.inner.binaries[binary_idx];
+            if binary.slices.is_empty() || binary.copy_exe_to.is_empty() {
+                continue;
+            }
+            let staging_dir = self.inner.dist_dir.join(&binary.id);
+            let dest_path = staging_dir.join(&binary.file_name);
+            let slices = binary.slices.clone();
+
+            let mut src_paths = vec![];
+            for slice_idx in slices {
+                let slice = self.binary_mut(slice_idx);
+                let src_path = staging_dir
+                    .join(slice.target.as_str())
+                    .join(&slice.file_name);
+                slice.copy_exe_to.push(src_path.clone());
+                src_paths.push(src_path);
+            }
+            builds.push(BuildStep::Lipo(LipoStep {
+                binary: BinaryIdx(binary_idx),
+                src_paths,
+                dest_path,
+            }));
+        }
+        builds
+    }
+
+    fn add_build_steps_for_artifacts(
+        artifacts: &Vec<&Artifact>,
+        source_date_epoch: Option<u64>,
+        build_steps: &mut Vec<BuildStep>,
+    ) {
         for artifact in artifacts {
             match &artifact.kind {
                 ArtifactKind::ExecutableZip(_zip) => {
@@ -2775,6 +3709,9 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                         SymbolKind::Dwp => {
                             // No additional steps needed?
                         }
+                        SymbolKind::Debug => {
+                            // Split out of the binary as soon as it's built
+                        }
                     }
                 }
                 ArtifactKind::Installer(installer) => {
@@ -2787,6 +3724,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                 ArtifactKind::UnifiedChecksum(unified_checksum) => {
                     build_steps.push(BuildStep::UnifiedChecksum(unified_checksum.clone()));
                 }
+                ArtifactKind::Signature(signature) => {
+                    build_steps.push(BuildStep::Signature(signature.clone()));
+                }
+                ArtifactKind::Provenance(provenance) => {
+                    build_steps.push(BuildStep::Provenance(provenance.clone()));
+                }
                 ArtifactKind::SourceTarball(tarball) => {
                     build_steps.push(BuildStep::GenerateSourceTarball(SourceTarballStep {
                         committish: tarball.committish.to_owned(),
@@ -2836,6 +3779,12 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                         dest_path,
                     }))
                 }
+                if let Some(entry) = &archive.desktop_entry {
+                    build_steps.push(BuildStep::DesktopEntry(DesktopEntryStep {
+                        entry: entry.clone(),
+                        dest_dir: artifact_dir.to_owned(),
+                    }));
+                }
 
                 // Zip up the artifact
                 build_steps.push(BuildStep::Zip(ZipDirStep {
@@ -2843,6 +3792,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     dest_path: artifact.file_path.clone(),
                     with_root: archive.with_root.clone(),
                     zip_style: archive.zip_style,
+                    source_date_epoch,
                 }));
                 // and get its sha256 checksum into the metadata
                 build_steps.push(BuildStep::Checksum(ChecksumImpl {
@@ -2912,7 +3862,7 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
         Ok(())
     }
 
-    fn compute_releases(
+    pub(crate) fn compute_releases(
         &mut self,
         cfg: &Config,
         announcing: &AnnouncementTag,
@@ -2924,6 +3874,11 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
             // FIXME: this clone is hacky but I'm in the middle of a nasty refactor
             let app_config = self.package_config(info.package_idx).clone();
 
+            // Catch icons we won't know how to install before we build anything
+            if let Some(icon) = &app_config.artifacts.archives.desktop_icon {
+                IconFormat::from_path(icon)?;
+            }
+
             // Create a Release for this binary
             let release = self.add_release(info.package_idx);
 
@@ -2987,6 +3942,10 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     InstallerStyle::Npm,
                     InstallerStyle::Msi,
                     InstallerStyle::Pkg,
+                    InstallerStyle::Deb,
+                    InstallerStyle::Rpm,
+                    InstallerStyle::Scoop,
+                    InstallerStyle::Winget,
                 ]
             } else {
                 &cfg.installers[..]
@@ -3000,12 +3959,22 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
                     InstallerStyle::Npm => self.add_npm_installer(release)?,
                     InstallerStyle::Msi => self.add_msi_installer(release)?,
                     InstallerStyle::Pkg => self.add_pkg_installer(release)?,
+                    InstallerStyle::Deb => self.add_deb_installer(release)?,
+                    InstallerStyle::Rpm => self.add_rpm_installer(release)?,
+                    InstallerStyle::Scoop => self.add_scoop_installer(release)?,
+                    InstallerStyle::Winget => self.add_winget_installer(release)?,
                 }
             }
 
             // Add SBOM file, if it exists.
             self.add_cyclonedx_sbom_file(info.package_idx, release);
 
+            // Sign everything we've got so far
+            self.add_signatures(release);
+
+            // Describe everything we've got so far
+            self.add_provenance(&announcing.tag, release);
+
             // Add the unified checksum file
             if self.inner.config.artifacts.checksum != ChecksumStyle::False {
                 self.add_unified_checksum_file(release);
@@ -3019,17 +3988,21 @@ impl<'pkg_graph> DistGraphBuilder<'pkg_graph> {
     }
 
     fn compute_ci(&mut self) -> DistResult<()> {
-        let CiConfig { github } = &self.inner.config.ci;
+        let CiConfig { github, gitlab } = &self.inner.config.ci;
 
         let mut has_ci = false;
         if let Some(github_config) = github {
             has_ci = true;
             self.inner.ci.github = Some(GithubCiInfo::new(&self.inner, github_config)?);
         }
+        if let Some(gitlab_config) = gitlab {
+            self.inner.ci.gitlab = Some(GitlabCiInfo::new(&self.inner, gitlab_config)?);
+        }
 
         // apply to manifest
         if has_ci {
-            let CiInfo { github } = &self.inner.ci;
+            // GitLab jobs are static, so there's nothing for the manifest to tell them
+            let CiInfo { github, gitlab: _ } = &self.inner.ci;
             let github = github.as_ref().map(|info| {
                 let external_repo_commit = info
                     .github_release
@@ -3198,11 +4171,20 @@ pub fn gather_work(cfg: &Config) -> DistResult<(DistGraph, DistManifest)> {
     // Figure out how artifacts should be hosted
     graph.compute_hosting(cfg, &announcing)?;
 
+    // Figure out what time reproducible archives should be stamped with
+    if graph.inner.config.artifacts.reproducible_archives {
+        graph.inner.source_date_epoch = Some(crate::archive::source_date_epoch(
+            graph.inner.tools.git.as_ref(),
+            &graph.inner.repo_dir,
+            &announcing.tag,
+        )?);
+    }
+
     // Figure out what we're releasing/building
     graph.compute_releases(cfg, &announcing, triples, bypass_package_target_prefs)?;
 
     // Prep the announcement's release notes and whatnot
-    graph.compute_announcement_info(&announcing);
+    graph.compute_announcement_info(&announcing)?;
 
     // Finally compute all the build steps!
     graph.compute_build_steps()?;
@@ -3240,7 +4222,7 @@ pub fn get_cargo_info(cargo: String) -> DistResult<CargoInfo> {
     Err(DistError::FailedCargoVersion)
 }
 
-fn target_symbol_kind(target: &TripleNameRef) -> Option<SymbolKind> {
+fn target_symbol_kind(target: &TripleNameRef, split_debuginfo: bool) -> Option<SymbolKind> {
     #[allow(clippy::if_same_then_else)]
     if target.is_windows_msvc() {
         // Temporary disabled pending redesign of symbol handling!
@@ -3258,9 +4240,9 @@ fn target_symbol_kind(target: &TripleNameRef) -> Option<SymbolKind> {
     } else {
         // Linux has DWPs but cargo doesn't properly uplift them
         // See: https://github.com/rust-lang/cargo/pull/11384
-
-        // Some(SymbolKind::Dwp)
-        None
+        //
+        // So instead, if asked, we split the debuginfo out of the binary ourselves
+        (split_debuginfo && target.is_linux()).then_some(SymbolKind::Debug)
     }
 }
 
@@ -3277,6 +4259,8 @@ fn tool_info() -> DistResult<Tools> {
         brew: find_tool("brew", "--version"),
         git: find_tool("git", "--version"),
         omnibor: find_tool("omnibor", "--version"),
+        objcopy: find_tool("objcopy", "--version"),
+        llvm_objcopy: find_tool("llvm-objcopy", "--version"),
         // Computed later if needed
         code_sign_tool: None,
 
@@ -3305,7 +4289,7 @@ fn find_cargo_subcommand(name: &str, arg: &str, test_flag: &str) -> Option<Tool>
     })
 }
 
-fn find_tool(name: &str, test_flag: &str) -> Option<Tool> {
+pub(crate) fn find_tool(name: &str, test_flag: &str) -> Option<Tool> {
     let output = Cmd::new(name, "detect tool")
         .arg(test_flag)
         .check(false)
@@ -3387,7 +4371,9 @@ pub struct InstallReceipt {
     /// A list of all C static libraries installed by this app
     pub cstaticlibs: Vec<String>,
     /// Information about where to request information on new releases
-    pub source: ReleaseSource,
+    /// (only if the updater can check there)
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub source: Option<ReleaseSource>,
     /// The version that was installed
     pub version: String,
     /// The software which installed this receipt
@@ -3400,22 +4386,25 @@ pub struct InstallReceipt {
 
 impl InstallReceipt {
     /// Produces an install receipt for the given DistGraph.
-    pub fn from_metadata(
-        manifest: &DistGraph,
-        release: &Release,
-    ) -> DistResult<Option<InstallReceipt>> {
-        let hosting = if let Some(hosting) = &manifest.hosting {
-            hosting
-        } else {
-            return Ok(None);
-        };
-        let source_type = if hosting.hosts.contains(&HostingStyle::Github) {
-            ReleaseSourceType::GitHub
-        } else {
-            return Err(DistError::NoGitHubHosting {});
+    ///
+    /// Every install gets a receipt (so it can be uninstalled), but it only names a
+    /// release source if that's somewhere the updater can check for new releases.
+    pub fn from_metadata(manifest: &DistGraph, release: &Release) -> DistResult<InstallReceipt> {
+        let source = match &manifest.hosting {
+            Some(hosting) if hosting.hosts.contains(&HostingStyle::Github) => Some(ReleaseSource {
+                release_type: ReleaseSourceType::GitHub,
+                owner: hosting.owner.to_owned(),
+                name: hosting.project.to_owned(),
+                app_name: release.app_name.to_owned(),
+            }),
+            // The updater can't check anywhere else for new releases
+            Some(_) if manifest.config.installers.updater => {
+                return Err(DistError::NoGitHubHosting {});
+            }
+            _ => None,
         };
 
-        Ok(Some(InstallReceipt {
+        Ok(InstallReceipt {
             // These first five are placeholder values which the installer will update
             install_prefix: "AXO_INSTALL_PREFIX".to_owned(),
             install_layout: InstallLayout::Unspecified,
@@ -3423,19 +4412,127 @@ impl InstallReceipt {
             cdylibs: vec!["CARGO_DIST_DYLIBS".to_owned()],
             cstaticlibs: vec!["CARGO_DIST_STATICLIBS".to_owned()],
             version: release.version.to_string(),
-            source: ReleaseSource {
-                release_type: source_type,
-                owner: hosting.owner.to_owned(),
-                name: hosting.project.to_owned(),
-                app_name: release.app_name.to_owned(),
-            },
+            source,
             provider: Provider {
                 source: ProviderSource::CargoDist,
                 version: env!("CARGO_PKG_VERSION").to_owned(),
             },
             binary_aliases: BTreeMap::default(),
             modify_path: true,
-        }))
+        })
+    }
+}
+
+/// Get the fragments for installers that only handle Windows (Scoop, winget)
+///
+/// Returns None if there aren't any Windows platforms being built.
+fn windows_fragments(
+    platform_support: &PlatformSupport,
+) -> Option<(
+    Vec<ExecutableZipFragment>,
+    WindowsFragments<ExecutableZipFragment>,
+)> {
+    let find_fragment = |triple: &TripleNameRef| -> Option<ExecutableZipFragment> {
+        platform_support
+            .fragments()
+            .into_iter()
+            .find(|a| a.target_triple == triple)
+    };
+    let fragments = WindowsFragments {
+        x86_64_windows: find_fragment(TARGET_X64_WINDOWS),
+        x86_windows: find_fragment(TARGET_X86_WINDOWS),
+        arm64_windows: find_fragment(TARGET_ARM64_WINDOWS),
+    };
+    let artifacts = [
+        &fragments.x86_64_windows,
+        &fragments.x86_windows,
+        &fragments.arm64_windows,
+    ]
+    .into_iter()
+    .flatten()
+    .cloned()
+    .collect::<Vec<_>>();
+    if artifacts.is_empty() {
+        None
+    } else {
+        Some((artifacts, fragments))
+    }
+}
+
+/// Work out whether CI should run the publish jobs for prereleases
+///
+/// `dist publish` skips prereleases according to each package's homebrew and npm
+/// settings, so CI just needs to run it if any package wants them. The other publish
+/// jobs run straight from CI, so their settings (which all packages share) have to
+/// agree, and can't turn prereleases off if `dist publish` needs them.
+pub(crate) fn global_publish_prereleases<'a>(
+    package_publishers: impl IntoIterator<Item = &'a PublisherConfig>,
+) -> DistResult<bool> {
+    let mut dist_publish_choice = false;
+    let mut ci_choice = None;
+    for publishers in package_publishers {
+        let PublisherConfig {
+            homebrew,
+            npm,
+            scoop,
+            winget,
+            user,
+        } = publishers;
+        dist_publish_choice |= homebrew.as_ref().is_some_and(|p| p.prereleases)
+            || npm.as_ref().is_some_and(|p| p.prereleases);
+        let choices = [
+            scoop.as_ref().map(|p| p.prereleases),
+            winget.as_ref().map(|p| p.prereleases),
+            user.as_ref().map(|p| p.prereleases),
+        ];
+        for choice in choices.into_iter().flatten() {
+            if ci_choice.is_some_and(|cur_choice| cur_choice != choice) {
+                return Err(DistError::MismatchedPrereleases);
+            }
+            ci_choice = Some(choice);
+        }
+    }
+    match ci_choice {
+        Some(false) if dist_publish_choice => Err(DistError::MismatchedPrereleases),
+        Some(choice) => Ok(choice),
+        None => Ok(dist_publish_choice),
+    }
+}
+
+/// Find the publish repo (like a Homebrew tap) that all packages agree on
+///
+/// If packages disagree, the manifests of the packages involved are returned.
+fn global_publish_repo<'a>(
+    workspaces: &WorkspaceGraph,
+    package_configs: &'a [AppConfig],
+    repo_for: impl Fn(&'a AppConfig) -> Option<&'a String>,
+) -> Result<Option<String>, Vec<Utf8PathBuf>> {
+    let mut global_repo = None;
+    let mut packages_with_mismatched_repos = vec![];
+    for ((_idx, package), package_config) in workspaces.all_packages().zip(package_configs) {
+        let Some(new_repo) = repo_for(package_config) else {
+            continue;
+        };
+        let manifest_path = package
+            .dist_manifest_path
+            .clone()
+            .unwrap_or(package.manifest_path.clone());
+        if let Some(current_repo) = &global_repo {
+            if current_repo != new_repo {
+                packages_with_mismatched_repos.push(manifest_path);
+            }
+        } else {
+            // This package gets to be the archetype, so if there's a mismatch it will
+            // always be implicated. So push it to the error list, and only say there's an
+            // error if there's two entries in this at the end.
+            packages_with_mismatched_repos.push(manifest_path);
+            global_repo = Some(new_repo.clone());
+        }
+    }
+    if packages_with_mismatched_repos.len() > 1 {
+        Err(packages_with_mismatched_repos)
+    } else {
+        Ok(global_repo)
     }
 }
 
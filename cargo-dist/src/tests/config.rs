@@ -1,7 +1,16 @@
 use crate::{
     backend::diff_source,
-    config::{parse_generic_config, parse_metadata_table, DistMetadata},
+    config::{
+        expand_targets, parse_generic_config, parse_metadata_table,
+        v1::publishers::{
+            homebrew::HomebrewPublisherConfig, scoop::ScoopPublisherConfig, CommonPublisherConfig,
+            PublisherConfig,
+        },
+        DistMetadata,
+    },
+    errors::DistError,
     init::apply_dist_to_workspace_toml,
+    tasks::global_publish_prereleases,
     DistResult,
 };
 use axoasset::SourceFile;
@@ -210,3 +219,124 @@ targets = ["aarch64-apple-darwin", "x86_64-apple-darwin"]
         .map_err(miette::Report::new)
         .unwrap();
 }
+
+#[test]
+fn target_groups_roundtrip() {
+    // Check that target groups survive being read and written back out
+    let input_kind = WorkspaceKind::Rust;
+    let input = r##"
+[package]
+name = "whatever"
+version = "1.0.0"
+
+# Config for 'dist'
+[workspace.metadata.dist]
+# The preferred dist version to use in CI (Cargo.toml SemVer syntax)
+cargo-dist-version = "0.13.1-prerelease.1"
+# Target platforms to build apps for (Rust target-triple syntax)
+targets = ["x86_64-pc-windows-msvc", { os = ["linux", "macos"], arch = "aarch64" }, { os = "linux", libc = "musl" }]
+"##;
+
+    let src = source(input, input_kind);
+    let config = parse_config(&src, input_kind).unwrap();
+    let result = format_config(&src, input_kind, &config).unwrap();
+    // Require no diff
+    diff_source(src, result.contents())
+        .map_err(miette::Report::new)
+        .unwrap();
+}
+
+#[test]
+fn target_groups_expand() {
+    let input_kind = WorkspaceKind::Generic;
+    let input = r##"
+[dist]
+targets = [
+    { os = ["linux", "macos"], arch = ["x86_64", "aarch64"], libc = "musl" },
+    "x86_64-apple-darwin",
+    { os = "windows", arch = "x86_64", libc = ["msvc", "gnu"] },
+]
+"##;
+
+    let src = source(input, input_kind);
+    let config = parse_config(&src, input_kind).unwrap();
+    let targets = expand_targets(config.targets.as_deref().unwrap()).unwrap();
+    assert_eq!(
+        targets.iter().map(|t| t.as_str()).collect::<Vec<_>>(),
+        vec![
+            "x86_64-unknown-linux-musl",
+            "aarch64-unknown-linux-musl",
+            "x86_64-apple-darwin",
+            "aarch64-apple-darwin",
+            "x86_64-pc-windows-msvc",
+            "x86_64-pc-windows-gnu",
+        ]
+    );
+}
+
+#[test]
+fn target_groups_reject_unknown() {
+    let input_kind = WorkspaceKind::Generic;
+    let check = |targets: &str| {
+        let src = source(&format!("[dist]\ntargets = [{targets}]\n"), input_kind);
+        parse_config(&src, input_kind)
+            .unwrap()
+            .to_toml_layer(true)
+            .map(|_| ())
+    };
+
+    assert!(check(r#"{ os = "linux", arch = "aarch64" }"#).is_ok());
+    assert!(matches!(
+        check(r#"{ os = "haiku" }"#),
+        Err(DistError::UnknownTargetGroupOs { os }) if os == "haiku"
+    ));
+    assert!(matches!(
+        check(r#"{ os = "windows", libc = "musl" }"#),
+        Err(DistError::UnknownTargetGroupLibc { libc, .. }) if libc == "musl"
+    ));
+    assert!(matches!(
+        check(r#"{ os = "macos", arch = "armv7" }"#),
+        Err(DistError::UnknownTargetGroupArch { arch, .. }) if arch == "armv7"
+    ));
+}
+
+#[test]
+fn publish_prereleases_any_package() {
+    let common = |prereleases| CommonPublisherConfig { prereleases };
+    let homebrew = |prereleases| PublisherConfig {
+        homebrew: Some(HomebrewPublisherConfig {
+            common: common(prereleases),
+        }),
+        ..Default::default()
+    };
+    let with_scoop = |mut publishers: PublisherConfig, prereleases| {
+        publishers.scoop = Some(ScoopPublisherConfig {
+            common: common(prereleases),
+        });
+        publishers
+    };
+
+    // `dist publish` handles each package's homebrew settings, so any of them can opt in
+    assert!(global_publish_prereleases(&[homebrew(false), homebrew(true)]).unwrap());
+    assert!(!global_publish_prereleases(&[homebrew(false), homebrew(false)]).unwrap());
+    assert!(global_publish_prereleases(&[
+        with_scoop(homebrew(false), true),
+        with_scoop(homebrew(false), true)
+    ])
+    .unwrap());
+    // ...but scoop runs straight from CI, so it can't opt out if homebrew opts in
+    assert!(matches!(
+        global_publish_prereleases(&[
+            with_scoop(homebrew(false), false),
+            with_scoop(homebrew(true), false)
+        ]),
+        Err(DistError::MismatchedPrereleases)
+    ));
+    assert!(matches!(
+        global_publish_prereleases(&[
+            with_scoop(PublisherConfig::default(), false),
+            with_scoop(PublisherConfig::default(), true)
+        ]),
+        Err(DistError::MismatchedPrereleases)
+    ));
+}
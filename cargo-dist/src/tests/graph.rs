@@ -0,0 +1,127 @@
+//! Tests for the build steps we plan for a release
+
+use super::mock::*;
+use crate::announce::{select_tag, TagMode, TagSettings};
+use crate::config::{ArtifactMode, Config};
+use crate::platform::targets::{
+    TARGET_ARM64_MAC, TARGET_UNIVERSAL2_MAC, TARGET_X64_LINUX_GNU, TARGET_X64_MAC,
+};
+use crate::tasks::{ArtifactKind, BuildStep, SymbolKind, Symbols};
+use crate::{DistGraph, DistGraphBuilder};
+use cargo_dist_schema::TripleName;
+
+/// Plan a release of axolotlsay for the given targets, tweaking the config first
+fn mock_plan(targets: &[TripleName], tweak: impl FnOnce(&mut DistGraph)) -> DistGraph {
+    let mut workspaces = workspace_just_axo();
+    let mut graph = DistGraphBuilder::new(
+        "plan:all:".to_owned(),
+        mock_tools(),
+        &mut workspaces,
+        ArtifactMode::All,
+        true,
+        false,
+    )
+    .unwrap();
+    tweak(&mut graph.inner);
+    let cfg = Config {
+        tag_settings: TagSettings {
+            needs_coherence: true,
+            tag: TagMode::Select(format!("v{BIN_AXO_VER}")),
+        },
+        create_hosting: false,
+        artifact_mode: ArtifactMode::All,
+        no_local_paths: false,
+        allow_all_dirty: true,
+        targets: targets.to_vec(),
+        ci: vec![],
+        installers: vec![],
+        root_cmd: "plan".to_owned(),
+        jobs: 1,
+    };
+    let announcing = select_tag(&mut graph, &cfg.tag_settings).unwrap();
+    graph
+        .compute_releases(&cfg, &announcing, targets, true)
+        .unwrap();
+    graph.compute_build_steps().unwrap();
+    graph.inner
+}
+
+#[test]
+fn universal2_builds_slices_then_merges() {
+    let dist = mock_plan(&[TARGET_UNIVERSAL2_MAC.to_owned()], |_| {});
+
+    let mut cargo_targets = dist
+        .local_build_steps
+        .iter()
+        .filter_map(|step| match step {
+            BuildStep::Cargo(step) => Some(step.target_triple.clone()),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    cargo_targets.sort();
+    assert_eq!(
+        cargo_targets,
+        vec![TARGET_ARM64_MAC.to_owned(), TARGET_X64_MAC.to_owned()]
+    );
+
+    let lipos = dist
+        .local_build_steps
+        .iter()
+        .filter_map(|step| match step {
+            BuildStep::Lipo(step) => Some(step),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    assert_eq!(lipos.len(), 1);
+    let lipo = lipos[0];
+    let universal = dist.binary(lipo.binary);
+    assert_eq!(universal.target, TARGET_UNIVERSAL2_MAC);
+    assert_eq!(universal.slices.len(), 2);
+    assert!(!universal.copy_exe_to.is_empty());
+
+    // Each slice gets built by its own cargo step, and copied to where the merge reads it
+    for (slice_idx, src_path) in universal.slices.iter().zip(&lipo.src_paths) {
+        let slice = dist.binary(*slice_idx);
+        assert!(slice.copy_exe_to.contains(src_path));
+        assert!(dist.local_build_steps.iter().any(|step| matches!(
+            step,
+            BuildStep::Cargo(step)
+                if step.target_triple == slice.target
+                    && step.expected_binaries.contains(slice_idx)
+        )));
+    }
+}
+
+#[test]
+fn split_debuginfo_makes_debug_symbols() {
+    let dist = mock_plan(&[TARGET_X64_LINUX_GNU.to_owned()], |dist| {
+        dist.config.builds.split_debuginfo = true;
+    });
+
+    let binary = dist
+        .binaries
+        .iter()
+        .find(|binary| binary.target == TARGET_X64_LINUX_GNU)
+        .unwrap();
+    let symbols = dist.artifact(binary.symbols_artifact.unwrap());
+    assert!(matches!(
+        symbols.kind,
+        ArtifactKind::Symbols(Symbols {
+            kind: SymbolKind::Debug
+        })
+    ));
+    assert_eq!(symbols.id.as_str(), format!("{}.debug", binary.id));
+    // The split happens on the copy we ship, and writes the .debug where the artifact wants it
+    assert_eq!(binary.copy_symbols_to, vec![symbols.file_path.clone()]);
+    assert!(!binary.copy_exe_to.is_empty());
+}
+
+#[test]
+fn no_split_debuginfo_no_symbols() {
+    let dist = mock_plan(&[TARGET_X64_LINUX_GNU.to_owned()], |_| {});
+
+    for binary in &dist.binaries {
+        assert!(binary.symbols_artifact.is_none());
+        assert!(binary.copy_symbols_to.is_empty());
+    }
+}
@@ -2,8 +2,9 @@ use super::mock::*;
 use crate::announce::{select_tag, AnnouncementTag, TagMode, TagSettings};
 use crate::config::{CiStyle, HostingStyle};
 use crate::host::select_hosting;
-use crate::DistError;
+use crate::tasks::{InstallReceipt, ReleaseSourceType};
 use crate::{config::ArtifactMode, DistGraphBuilder};
+use crate::{DistError, DistResult, HostingInfo};
 use axoproject::errors::AxoprojectError;
 use axoproject::{PackageIdx, WorkspaceGraph};
 use semver::Version;
@@ -242,3 +243,102 @@ fn github_trail_slash() {
     assert_eq!(hosting.project, REPO_PROJECT);
     assert_eq!(hosting.source_host, "github");
 }
+
+#[test]
+fn gitlab_implicit() {
+    // ci = "gitlab" and hosting = None, on a self-managed instance with subgroups
+    const GL_REPO_URL: &str = "https://git.mycoolorg.com/mycoolorg/tools/radproj.git";
+    let mut workspaces = workspace_unified();
+    let num_packages = workspaces.all_packages().count();
+    for pkg_idx in 0..num_packages {
+        let package = workspaces.package_mut(PackageIdx(pkg_idx));
+        package.repository_url = Some(GL_REPO_URL.to_owned());
+    }
+    let hosting = None;
+    let ci = Some(vec![CiStyle::Gitlab]);
+
+    let (_graph, announcing) = mock_announce(&mut workspaces);
+    let hosting = select_hosting(&workspaces, &announcing, hosting, ci.as_deref());
+
+    let hosting = hosting.unwrap().unwrap();
+    assert_eq!(hosting.hosts, &[HostingStyle::Gitlab]);
+    assert_eq!(hosting.domain, "https://git.mycoolorg.com");
+    assert_eq!(hosting.repo_path, "/mycoolorg/tools/radproj");
+    assert_eq!(hosting.owner, "mycoolorg/tools");
+    assert_eq!(hosting.project, "radproj");
+    assert_eq!(hosting.source_host, "gitlab");
+}
+
+#[test]
+fn gitlab_bad_repository() {
+    // repo url has no namespace, but gitlab hosting enabled
+    const BAD_REPO_URL: &str = "https://gitlab.com/radproj";
+    let mut workspaces = workspace_unified();
+    let num_packages = workspaces.all_packages().count();
+    for pkg_idx in 0..num_packages {
+        let package = workspaces.package_mut(PackageIdx(pkg_idx));
+        package.repository_url = Some(BAD_REPO_URL.to_owned());
+    }
+    let hosting = Some(vec![HostingStyle::Gitlab]);
+    let ci = Some(vec![CiStyle::Gitlab]);
+
+    let (_graph, announcing) = mock_announce(&mut workspaces);
+    let hosting = select_hosting(&workspaces, &announcing, hosting, ci.as_deref());
+
+    assert!(
+        matches!(
+            hosting,
+            Err(DistError::CantEnableGitlabUrlNotGitlab {
+                inner: AxoprojectError::RepoParseError { .. }
+            })
+        ),
+        "unexpected result: {hosting:?}"
+    );
+}
+
+fn mock_receipt(hosts: Vec<HostingStyle>, updater: bool) -> DistResult<InstallReceipt> {
+    let mut workspaces = workspace_just_axo();
+    let (mut graph, _announcing) = mock_announce(&mut workspaces);
+    graph.inner.hosting = Some(HostingInfo {
+        hosts,
+        domain: "https://github.com".to_owned(),
+        repo_path: format!("{REPO_OWNER}/{REPO_PROJECT}"),
+        source_host: "github".to_owned(),
+        owner: REPO_OWNER.to_owned(),
+        project: REPO_PROJECT.to_owned(),
+    });
+    graph.inner.config.installers.updater = updater;
+    let release = graph.add_release(BIN_AXO_IDX);
+    InstallReceipt::from_metadata(&graph.inner, graph.inner.release(release))
+}
+
+#[test]
+fn receipt_github() {
+    let receipt = mock_receipt(vec![HostingStyle::Github], true).unwrap();
+    let source = receipt.source.unwrap();
+    assert!(matches!(source.release_type, ReleaseSourceType::GitHub));
+    assert_eq!(source.owner, REPO_OWNER);
+    assert_eq!(source.name, REPO_PROJECT);
+}
+
+#[test]
+fn receipt_not_github() {
+    // Installs hosted elsewhere still get a receipt (so they can be uninstalled),
+    // it just doesn't say where to check for updates
+    for hosts in [vec![HostingStyle::Gitlab], vec![HostingStyle::Simple]] {
+        let receipt = mock_receipt(hosts, false).unwrap();
+        assert!(receipt.source.is_none());
+        let json = serde_json::to_value(&receipt).unwrap();
+        assert_eq!(json["install_prefix"], "AXO_INSTALL_PREFIX");
+        assert!(json.get("source").is_none());
+    }
+}
+
+#[test]
+fn receipt_not_github_updater() {
+    let err = mock_receipt(vec![HostingStyle::Simple], true).unwrap_err();
+    assert!(
+        matches!(err, DistError::NoGitHubHosting {}),
+        "unexpected error: {err:?}"
+    );
+}
@@ -83,6 +83,8 @@ pub fn mock_tools() -> Tools {
         brew: None,
         git: None,
         omnibor: None,
+        objcopy: None,
+        llvm_objcopy: None,
         code_sign_tool: None,
         cargo_auditable: None,
         cargo_cyclonedx: None,
@@ -1,4 +1,6 @@
 mod config;
+mod graph;
 mod host;
 mod mock;
+pub mod mock_server;
 mod tag;
@@ -660,3 +660,69 @@ fn parse_disjoint_lib() {
     assert_eq!(announcing.version, None);
     assert_eq!(announcing.rust_releases, vec![entry_other_lib()]);
 }
+
+#[test]
+fn force_rolling_snapshot_tag() {
+    // the plan picks the rolling tag, and later jobs are handed it back
+    let version: Version = format!("{BIN_AXO_VER}-nightly.1").parse().unwrap();
+
+    for tag in [
+        TagMode::ForceMaxAndTimestamp,
+        TagMode::Force("nightly".to_owned()),
+    ] {
+        let mut workspace = workspace_just_axo();
+        let tools = mock_tools();
+        let mut graph = DistGraphBuilder::new(
+            "a".to_owned(),
+            tools,
+            &mut workspace,
+            ArtifactMode::All,
+            true,
+            false,
+        )
+        .unwrap();
+        let hosts = &mut graph.inner.config.hosts;
+        hosts.snapshot_tag = Some("nightly".to_owned());
+        hosts.snapshot_version = Some("{version}-nightly.1".to_owned());
+        let settings = TagSettings {
+            needs_coherence: true,
+            tag,
+        };
+        let announcing = select_tag(&mut graph, &settings).unwrap();
+
+        assert!(announcing.prerelease);
+        assert_eq!(announcing.tag, "nightly");
+        assert_eq!(announcing.version, Some(version.clone()));
+        assert_eq!(announcing.rust_releases, vec![entry_axo_bin()]);
+    }
+}
+
+#[test]
+fn force_other_tag_with_snapshot_tag() {
+    // a normal tagged release is unaffected by snapshot-tag
+    let mut workspace = workspace_just_axo();
+    let version: Version = BIN_AXO_VER.parse().unwrap();
+    let tag = format!("v{version}");
+
+    let tools = mock_tools();
+    let mut graph = DistGraphBuilder::new(
+        "a".to_owned(),
+        tools,
+        &mut workspace,
+        ArtifactMode::All,
+        true,
+        false,
+    )
+    .unwrap();
+    graph.inner.config.hosts.snapshot_tag = Some("nightly".to_owned());
+    let settings = TagSettings {
+        needs_coherence: true,
+        tag: TagMode::Force(tag.clone()),
+    };
+    let announcing = select_tag(&mut graph, &settings).unwrap();
+
+    assert!(!announcing.prerelease);
+    assert_eq!(announcing.tag, tag);
+    assert_eq!(announcing.version, Some(version));
+    assert_eq!(announcing.rust_releases, vec![entry_axo_bin()]);
+}
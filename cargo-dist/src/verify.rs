@@ -0,0 +1,379 @@
+//! dist verify -- check downloaded release artifacts against a dist-manifest
+//!
+//! Every artifact with a checksum in the manifest is hashed again (with the same
+//! algorithms `dist build` used) and compared. If the release has a unified checksum
+//! file (like `sha256.sum`) in the directory, its entries are checked too.
+//!
+//! Anything the manifest expects that isn't there is reported as missing, and anything
+//! in the directory that the manifest doesn't know about is reported as extra.
+
+use std::collections::{BTreeMap, BTreeSet};
+
+use axoasset::LocalAsset;
+use camino::{Utf8Path, Utf8PathBuf};
+use cargo_dist_schema::{ArtifactKind, DistManifest};
+use serde::Serialize;
+use tracing::warn;
+
+use crate::{
+    config::ChecksumStyle,
+    errors::{DistError, DistResult},
+    net::{create_axoasset_client, ClientSettings},
+};
+
+/// Arguments for `dist verify` ([`do_verify`][])
+#[derive(Debug, Clone)]
+pub struct VerifyArgs {
+    /// A path or URL for the dist-manifest.json to check against
+    pub manifest: String,
+    /// The directory containing the downloaded files
+    pub dir: Utf8PathBuf,
+}
+
+/// The result of `dist verify`
+#[derive(Debug, Clone, Default, Serialize)]
+pub struct VerifyReport {
+    /// Files whose checksums all matched
+    pub verified: Vec<String>,
+    /// Checksums that didn't match
+    pub mismatched: Vec<ChecksumMismatch>,
+    /// Files that should have been in the directory, but weren't
+    pub missing: Vec<String>,
+    /// Files in the directory that the manifest doesn't know about
+    pub extra: Vec<String>,
+}
+
+/// A file whose checksum didn't match
+#[derive(Debug, Clone, Serialize)]
+pub struct ChecksumMismatch {
+    /// The name of the file
+    pub name: String,
+    /// Where the expected checksum came from (the manifest or a unified checksum file)
+    pub source: String,
+    /// The checksum algorithm (e.g. `sha256`)
+    pub algorithm: String,
+    /// The checksum we expected
+    pub expected: String,
+    /// The checksum the file actually has
+    pub actual: String,
+}
+
+impl VerifyReport {
+    /// Whether everything checked out
+    pub fn is_ok(&self) -> bool {
+        self.mismatched.is_empty() && self.missing.is_empty() && self.extra.is_empty()
+    }
+}
+
+/// dist verify -- check a directory of files against a dist-manifest
+pub fn do_verify(args: &VerifyArgs) -> DistResult<VerifyReport> {
+    let manifest_src = load_manifest(&args.manifest)?;
+    let manifest: DistManifest =
+        serde_json::from_str(&manifest_src).map_err(|details| DistError::VerifyManifestParse {
+            manifest: args.manifest.clone(),
+            details,
+        })?;
+
+    verify_dir(&manifest, &args.dir)
+}
+
+fn load_manifest(manifest: &str) -> DistResult<String> {
+    if manifest.starts_with("https://") || manifest.starts_with("http://") {
+        let client = create_axoasset_client(&ClientSettings::new())?;
+        let handle = tokio::runtime::Handle::current();
+        Ok(handle.block_on(client.load_string(manifest))?)
+    } else {
+        Ok(LocalAsset::load_string(manifest)?)
+    }
+}
+
+/// Check the files in `dir` against `manifest`
+pub fn verify_dir(manifest: &DistManifest, dir: &Utf8Path) -> DistResult<VerifyReport> {
+    let mut report = VerifyReport::default();
+
+    // name => (source, algorithm, expected checksum)
+    let mut expected = BTreeMap::<String, Vec<(String, ChecksumStyle, String)>>::new();
+    let mut known_files = BTreeSet::new();
+
+    for (id, artifact) in &manifest.artifacts {
+        let name = artifact.name.as_ref().unwrap_or(id).to_string();
+        known_files.insert(name.clone());
+        for (ext, checksum) in &artifact.checksums {
+            let Some(style) = ChecksumStyle::from_ext(ext.as_str()) else {
+                warn!("don't know how to check {ext} checksums, skipping it for {name}");
+                continue;
+            };
+            expected.entry(name.clone()).or_default().push((
+                "dist-manifest.json".to_owned(),
+                style,
+                checksum.to_string(),
+            ));
+        }
+
+        if matches!(artifact.kind, ArtifactKind::UnifiedChecksum) {
+            let path = dir.join(&name);
+            if !path.exists() {
+                // Reported as missing below
+                expected.entry(name.clone()).or_default();
+                continue;
+            }
+            let Some(style) = name.strip_suffix(".sum").and_then(ChecksumStyle::from_ext) else {
+                warn!("don't know what kind of checksums {name} contains, skipping it");
+                continue;
+            };
+            for (file, checksum) in parse_checksum_file(&LocalAsset::load_string(&path)?) {
+                expected
+                    .entry(file)
+                    .or_default()
+                    .push((name.clone(), style, checksum));
+            }
+        }
+    }
+
+    for (name, checks) in &expected {
+        let path = dir.join(name);
+        if !path.exists() {
+            report.missing.push(name.clone());
+            continue;
+        }
+
+        let mut ok = true;
+        let mut actuals = BTreeMap::new();
+        for (source, style, expected) in checks {
+            let actual = match actuals.get(style) {
+                Some(actual) => actual,
+                None => actuals
+                    .entry(*style)
+                    .or_insert(crate::generate_checksum(style, &path)?.to_string()),
+            };
+            if !actual.eq_ignore_ascii_case(expected) {
+                ok = false;
+                report.mismatched.push(ChecksumMismatch {
+                    name: name.clone(),
+                    source: source.clone(),
+                    algorithm: style.ext().to_string(),
+                    expected: expected.clone(),
+                    actual: actual.clone(),
+                });
+            }
+        }
+        if ok && !checks.is_empty() {
+            report.verified.push(name.clone());
+        }
+    }
+
+    for entry in dir.read_dir_utf8()? {
+        let entry = entry?;
+        if !entry.file_type()?.is_file() {
+            continue;
+        }
+        let name = entry.file_name();
+        // The manifest is usually uploaded alongside the files it describes
+        if name.ends_with("dist-manifest.json") {
+            continue;
+        }
+        if !known_files.contains(name) && !expected.contains_key(name) {
+            report.extra.push(name.to_owned());
+        }
+    }
+    report.extra.sort();
+
+    Ok(report)
+}
+
+/// Parse the `<checksum> *<name>` lines of a file like `sha256.sum`
+fn parse_checksum_file(contents: &str) -> Vec<(String, String)> {
+    contents
+        .lines()
+        .filter_map(|line| {
+            let (checksum, name) = line.trim().split_once(' ')?;
+            let name = name.trim_start();
+            let name = name.strip_prefix('*').unwrap_or(name);
+            Some((name.to_owned(), checksum.to_owned()))
+        })
+        .collect()
+}
+
+/// Human-readable output for [`VerifyReport`][]
+pub struct VerifyDisplay<'a>(pub &'a VerifyReport);
+
+impl std::fmt::Display for VerifyDisplay<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let report = self.0;
+        for name in &report.verified {
+            writeln!(f, "  ok        {name}")?;
+        }
+        for mismatch in &report.mismatched {
+            writeln!(
+                f,
+                "  MISMATCH  {} ({} from {})\n              expected {}\n              got      {}",
+                mismatch.name,
+                mismatch.algorithm,
+                mismatch.source,
+                mismatch.expected,
+                mismatch.actual
+            )?;
+        }
+        for name in &report.missing {
+            writeln!(f, "  MISSING   {name}")?;
+        }
+        for name in &report.extra {
+            writeln!(f, "  EXTRA     {name}")?;
+        }
+        writeln!(
+            f,
+            "\n{} verified, {} mismatched, {} missing, {} extra",
+            report.verified.len(),
+            report.mismatched.len(),
+            report.missing.len(),
+            report.extra.len()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use cargo_dist_schema::{Artifact, ArtifactId, ChecksumExtension, ChecksumValue};
+
+    fn artifact(kind: ArtifactKind, checksum: Option<(&str, String)>) -> Artifact {
+        Artifact {
+            name: None,
+            path: None,
+            target_triples: vec![],
+            install_hint: None,
+            description: None,
+            assets: vec![],
+            kind,
+            checksum: None,
+            signature: None,
+            checksums: checksum
+                .into_iter()
+                .map(|(ext, value)| {
+                    (
+                        ChecksumExtension::new(ext.to_owned()),
+                        ChecksumValue::new(value),
+                    )
+                })
+                .collect(),
+        }
+    }
+
+    fn checksum(style: ChecksumStyle, path: &Utf8Path) -> String {
+        crate::generate_checksum(&style, path).unwrap().to_string()
+    }
+
+    #[test]
+    fn reports_mismatched_missing_and_extra() {
+        let (_tmp, dir) = crate::create_tmp().unwrap();
+        let good = dir.join("app-x86_64-unknown-linux-gnu.tar.xz");
+        let bad = dir.join("app-aarch64-apple-darwin.tar.xz");
+        let installer = dir.join("app-installer.sh");
+        std::fs::write(&good, "good").unwrap();
+        std::fs::write(&bad, "bad").unwrap();
+        std::fs::write(&installer, "#!/bin/sh").unwrap();
+        std::fs::write(dir.join("dist-manifest.json"), "{}").unwrap();
+        std::fs::write(dir.join("surprise.txt"), "?").unwrap();
+
+        // The unified checksum file has a stale entry for the installer
+        std::fs::write(
+            dir.join("sha512.sum"),
+            format!(
+                "{} *app-x86_64-unknown-linux-gnu.tar.xz\n{} *app-installer.sh\n\n",
+                checksum(ChecksumStyle::Sha512, &good),
+                checksum(ChecksumStyle::Sha512, &bad),
+            ),
+        )
+        .unwrap();
+
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        let mut add = |name: &str, artifact: Artifact| {
+            manifest
+                .artifacts
+                .insert(ArtifactId::new(name.to_owned()), artifact);
+        };
+        add(
+            "app-x86_64-unknown-linux-gnu.tar.xz",
+            artifact(
+                ArtifactKind::ExecutableZip,
+                Some(("sha512", checksum(ChecksumStyle::Sha512, &good))),
+            ),
+        );
+        add(
+            "app-aarch64-apple-darwin.tar.xz",
+            artifact(
+                ArtifactKind::ExecutableZip,
+                Some(("sha512", checksum(ChecksumStyle::Sha512, &good))),
+            ),
+        );
+        add(
+            "app-x86_64-pc-windows-msvc.zip",
+            artifact(ArtifactKind::ExecutableZip, Some(("sha512", "00".into()))),
+        );
+        add("app-installer.sh", artifact(ArtifactKind::Installer, None));
+        add("sha512.sum", artifact(ArtifactKind::UnifiedChecksum, None));
+
+        let report = verify_dir(&manifest, &dir).unwrap();
+        assert!(!report.is_ok());
+        assert_eq!(report.verified, ["app-x86_64-unknown-linux-gnu.tar.xz"]);
+        assert_eq!(report.missing, ["app-x86_64-pc-windows-msvc.zip"]);
+        assert_eq!(report.extra, ["surprise.txt"]);
+
+        let mismatched = report
+            .mismatched
+            .iter()
+            .map(|m| (m.name.as_str(), m.source.as_str(), m.algorithm.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mismatched,
+            [
+                (
+                    "app-aarch64-apple-darwin.tar.xz",
+                    "dist-manifest.json",
+                    "sha512"
+                ),
+                ("app-installer.sh", "sha512.sum", "sha512"),
+            ]
+        );
+    }
+
+    #[test]
+    fn everything_checks_out() {
+        let (_tmp, dir) = crate::create_tmp().unwrap();
+        let file = dir.join("app.tar.gz");
+        std::fs::write(&file, "contents").unwrap();
+        std::fs::write(
+            dir.join("sha256.sum"),
+            format!("{} *app.tar.gz\n", checksum(ChecksumStyle::Sha256, &file)),
+        )
+        .unwrap();
+
+        let mut manifest = DistManifest::new(vec![], Default::default());
+        manifest.artifacts.insert(
+            ArtifactId::new("app.tar.gz".to_owned()),
+            artifact(
+                ArtifactKind::ExecutableZip,
+                Some(("sha256", checksum(ChecksumStyle::Sha256, &file))),
+            ),
+        );
+        manifest.artifacts.insert(
+            ArtifactId::new("sha256.sum".to_owned()),
+            artifact(ArtifactKind::UnifiedChecksum, None),
+        );
+
+        let report = verify_dir(&manifest, &dir).unwrap();
+        assert!(report.is_ok(), "{report:?}");
+        assert_eq!(report.verified, ["app.tar.gz"]);
+    }
+
+    #[test]
+    fn parses_checksum_files() {
+        assert_eq!(
+            parse_checksum_file("abc *foo.tar.gz\ndef  bar.zip\n\n"),
+            [
+                ("foo.tar.gz".to_owned(), "abc".to_owned()),
+                ("bar.zip".to_owned(), "def".to_owned()),
+            ]
+        );
+    }
+}
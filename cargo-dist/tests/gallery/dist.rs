@@ -116,6 +116,7 @@ pub struct PlanResult {
 pub struct GenerateResult {
     test_name: String,
     github_ci_path: Option<Utf8PathBuf>,
+    gitlab_ci_path: Option<Utf8PathBuf>,
     wxs_path: Option<Utf8PathBuf>,
 }
 
@@ -220,11 +221,15 @@ impl<'a> TestContext<'a, Tools> {
     ) -> Result<GenerateResult> {
         let ci_file_name = format!("{prefix}release.yml");
         let github_ci_path = Utf8Path::new(".github/workflows/").join(ci_file_name);
+        let gitlab_ci_path = Utf8Path::new(".gitlab-ci.yml").to_owned();
         let wxs_path = Utf8Path::new("wix/main.wxs").to_owned();
         // Delete files if they already exist
         if github_ci_path.exists() {
             LocalAsset::remove_file(&github_ci_path)?;
         }
+        if gitlab_ci_path.exists() {
+            LocalAsset::remove_file(&gitlab_ci_path)?;
+        }
         if wxs_path.exists() {
             LocalAsset::remove_file(&wxs_path)?;
         }
@@ -238,10 +243,19 @@ impl<'a> TestContext<'a, Tools> {
         Ok(GenerateResult {
             test_name: test_name.to_owned(),
             github_ci_path: github_ci_path.exists().then_some(github_ci_path),
+            gitlab_ci_path: gitlab_ci_path.exists().then_some(gitlab_ci_path),
             wxs_path: wxs_path.exists().then_some(wxs_path),
         })
     }
 
+    /// Run 'dist generate --check', without checking its status
+    pub fn cargo_dist_generate_check(&self) -> Result<std::process::Output> {
+        eprintln!("running dist generate --check...");
+        self.tools
+            .cargo_dist
+            .output(|cmd| cmd.arg("dist").arg("generate").arg("--check"))
+    }
+
     fn load_dist_results(&self, test_name: &str, trust_hashes: bool) -> Result<DistResult> {
         // read/analyze installers
         eprintln!("loading results...");
@@ -546,4 +560,21 @@ impl GenerateResult {
     pub fn check_all(&self) -> Result<Snapshots> {
         self.snapshot()
     }
+
+    /// Parse the generated .gitlab-ci.yml and get the names of its jobs (ignoring hidden `.templates`)
+    pub fn gitlab_ci_jobs(&self) -> Result<Vec<String>> {
+        let path = self
+            .gitlab_ci_path
+            .as_deref()
+            .ok_or_else(|| miette!("dist generate didn't write a .gitlab-ci.yml"))?;
+        let src = SourceFile::load_local(path)?;
+        let pipeline: BTreeMap<String, serde_json::Value> = src.deserialize_yaml()?;
+        Ok(pipeline
+            .into_keys()
+            .filter(|key| {
+                !key.starts_with('.')
+                    && !["stages", "include", "workflow", "variables"].contains(&key.as_str())
+            })
+            .collect())
+    }
 }
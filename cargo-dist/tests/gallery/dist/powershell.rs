@@ -179,6 +179,46 @@ impl AppResult {
             // check the install receipts
             self.check_install_receipt(ctx, &bin_dir, &receipt_file, ".exe");
             eprintln!("installer.ps1 worked!");
+
+            // Now undo it all with the same environment
+            eprintln!("running installer.ps1 -Uninstall...");
+            powershell.output_checked(|cmd| {
+                cmd.arg("-c")
+                    .arg(shell_path)
+                    .arg("-Uninstall")
+                    .arg("-Verbose")
+                    .env("UserProfile", &tempdir)
+                    .env("LOCALAPPDATA", &appdata)
+                    .env("MY_ENV_VAR", &app_home)
+                    .env_remove("CARGO_HOME")
+                    .env_remove("XDG_CONFIG_HOME")
+                    .env_remove("PSModulePath")
+            })?;
+            for bin_name in ctx.options.bins_with_aliases(app_name, &self.bins) {
+                let bin_path = bin_dir.join(format!("{bin_name}.exe"));
+                assert!(!bin_path.exists(), "{bin_name} wasn't uninstalled");
+            }
+            assert!(!receipt_file.exists(), "receipt wasn't removed");
+            // Cargo's bin dir stays on PATH, everything else should be taken back off
+            if !bin_dir.components().any(|d| d.as_str() == ".cargo") {
+                let uninstalled_path = run_ps1_script(
+                    &powershell,
+                    &tempdir,
+                    "savepath.ps1",
+                    r#"
+            $Item = Get-Item -Path "HKCU:\Environment"
+            $RegPath = $Item | Get-ItemPropertyValue -Name "Path"
+            return $RegPath
+            "#,
+                )?;
+                assert!(
+                    !uninstalled_path
+                        .split(';')
+                        .any(|dir| Utf8Path::new(dir) == bin_dir),
+                    "{bin_dir} is still on PATH: {uninstalled_path}"
+                );
+            }
+            eprintln!("installer.ps1 -Uninstall worked!");
         }
         Ok(())
     }
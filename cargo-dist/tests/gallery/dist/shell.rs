@@ -119,6 +119,37 @@ impl AppResult {
 
             // Check the install receipts
             self.check_install_receipt(ctx, &bin_dir, &receipt_file, "");
+
+            // Now undo it all with the same environment
+            eprintln!("running installer.sh --uninstall...");
+            let _output = script.output_checked(|cmd| {
+                cmd.arg("--uninstall")
+                    .env("HOME", &tempdir)
+                    .env("ZDOTDIR", &tempdir)
+                    .env("MY_ENV_VAR", &app_home)
+                    .env_remove("CARGO_HOME")
+                    .env_remove("XDG_CONFIG_HOME")
+            })?;
+            for bin_name in ctx.options.bins_with_aliases(&self.app_name, &self.bins) {
+                assert!(
+                    !bin_dir.join(&bin_name).exists(),
+                    "{bin_name} wasn't uninstalled"
+                );
+            }
+            assert!(!receipt_file.exists(), "receipt wasn't removed");
+            // Cargo's bin dir stays on PATH, everything else should be taken back off
+            if env_dir == bin_dir {
+                let env_script_rel = env_script.strip_prefix(&tempdir).unwrap_or(&env_script);
+                assert!(!env_script.exists(), "env script wasn't removed");
+                for rcfile in rcfiles {
+                    let contents = LocalAsset::load_string(rcfile)?;
+                    assert!(
+                        !contents.contains(env_script_rel.as_str()),
+                        "{rcfile} still sources the env script:\n{contents}"
+                    );
+                }
+            }
+            eprintln!("installer.sh --uninstall worked!");
         }
         Ok(())
     }
@@ -98,6 +98,12 @@ impl GenerateResult {
             self.github_ci_path.as_deref(),
         )?;
 
+        append_snapshot_file(
+            &mut snapshots,
+            ".gitlab-ci.yml",
+            self.gitlab_ci_path.as_deref(),
+        )?;
+
         append_snapshot_file(&mut snapshots, "main.wxs", self.wxs_path.as_deref())?;
 
         Ok(Snapshots {
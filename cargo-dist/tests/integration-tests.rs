@@ -707,6 +707,160 @@ install-location = "/opt/axolotlsay"
     })
 }
 
+#[test]
+fn axolotlsay_minisign() -> Result<(), miette::Report> {
+    let test_name = _function_name!();
+    AXOLOTLSAY.run_test(|ctx| {
+        let dist_version = ctx.tools.cargo_dist.version().unwrap();
+        ctx.patch_cargo_toml(format!(r#"
+[workspace.metadata.dist]
+cargo-dist-version = "{dist_version}"
+installers = ["shell", "powershell"]
+targets = ["x86_64-unknown-linux-gnu", "x86_64-apple-darwin", "x86_64-pc-windows-msvc", "aarch64-apple-darwin"]
+ci = ["github"]
+minisign-public-key = "RWQf6LRCGA9i53mlYecO4IzT51TGPpvWucNSCh1CBM0QTaLn73Y7GFO3"
+unix-archive = ".tar.gz"
+windows-archive = ".tar.gz"
+
+"#
+        ))?;
+
+        // Run generate to make sure stuff is up to date before running other commands
+        let ci_result = ctx.cargo_dist_generate(test_name)?;
+        let ci_snap = ci_result.check_all()?;
+        // There's no secret key here, so lie about the signatures
+        let main_result = ctx.cargo_dist_build_lies(test_name)?;
+        let main_snap = main_result.check_all(&ctx, ".cargo/bin/")?;
+        // snapshot all
+        main_snap.join(ci_snap).snap();
+        Ok(())
+    })
+}
+
+#[test]
+fn axolotlsay_changelog_from_commits() -> Result<(), miette::Report> {
+    let test_name = _function_name!();
+    AXOLOTLSAY.run_test(|ctx| {
+        let dist_version = ctx.tools.cargo_dist.version().unwrap();
+        ctx.patch_cargo_toml(format!(
+            r#"
+[workspace.metadata.dist]
+cargo-dist-version = "{dist_version}"
+installers = ["shell"]
+targets = ["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]
+ci = ["github"]
+changelog-from-commits = true
+
+"#
+        ))?;
+
+        // The notes depend on the repo's history, so just check the CI clones all of it
+        let ci_result = ctx.cargo_dist_generate(test_name)?;
+        let ci_snap = ci_result.check_all()?;
+        ci_snap.snap();
+        Ok(())
+    })
+}
+
+#[test]
+fn axolotlsay_snapshot_tag() -> Result<(), miette::Report> {
+    let test_name = _function_name!();
+    AXOLOTLSAY.run_test(|ctx| {
+        let dist_version = ctx.tools.cargo_dist.version().unwrap();
+        ctx.patch_cargo_toml(format!(
+            r#"
+[workspace.metadata.dist]
+cargo-dist-version = "{dist_version}"
+installers = ["shell"]
+targets = ["x86_64-unknown-linux-gnu", "aarch64-apple-darwin"]
+ci = ["github"]
+release-branch = "main"
+snapshot-tag = "nightly"
+
+"#
+        ))?;
+
+        // The version depends on the repo's history, so just check the CI clones all of it
+        let ci_result = ctx.cargo_dist_generate(test_name)?;
+        let ci_snap = ci_result.check_all()?;
+        ci_snap.snap();
+        Ok(())
+    })
+}
+
+#[test]
+fn axolotlsay_gitlab() -> Result<(), miette::Report> {
+    let test_name = _function_name!();
+    AXOLOTLSAY.run_test(|ctx| {
+        let dist_version = ctx.tools.cargo_dist.version().unwrap();
+        ctx.patch_cargo_toml(format!(
+            r#"
+[workspace.metadata.dist]
+cargo-dist-version = "{dist_version}"
+installers = ["shell", "homebrew"]
+tap = "axodotdev/homebrew-packages"
+publish-jobs = ["homebrew"]
+targets = ["x86_64-unknown-linux-gnu", "aarch64-unknown-linux-gnu", "aarch64-apple-darwin"]
+ci = ["gitlab"]
+hosting = ["gitlab"]
+
+"#
+        ))?;
+
+        let ci_result = ctx.cargo_dist_generate(test_name)?;
+        assert_eq!(
+            ci_result.gitlab_ci_jobs()?,
+            [
+                "announce",
+                "build-global-artifacts",
+                "build-local-artifacts (aarch64-apple-darwin)",
+                "build-local-artifacts (aarch64-unknown-linux-gnu)",
+                "build-local-artifacts (x86_64-unknown-linux-gnu)",
+                "host",
+                "plan",
+                "publish-homebrew-formula",
+            ]
+        );
+        let ci_snap = ci_result.check_all()?;
+        ci_snap.snap();
+        Ok(())
+    })
+}
+
+#[test]
+fn axolotlsay_gitlab_check() -> Result<(), miette::Report> {
+    AXOLOTLSAY.run_test(|ctx| {
+        let dist_version = ctx.tools.cargo_dist.version().unwrap();
+        ctx.patch_cargo_toml(format!(
+            r#"
+[workspace.metadata.dist]
+cargo-dist-version = "{dist_version}"
+installers = ["shell"]
+targets = ["x86_64-unknown-linux-gnu"]
+ci = ["gitlab"]
+
+"#
+        ))?;
+
+        ctx.cargo_dist_generate("axolotlsay_gitlab_check")?;
+        let output = ctx.cargo_dist_generate_check()?;
+        assert!(
+            output.status.success(),
+            "freshly generated CI was out of date"
+        );
+
+        // Hand-edit the pipeline, which --check should notice
+        let mut gitlab_ci = axoasset::LocalAsset::load_string(".gitlab-ci.yml")?;
+        gitlab_ci.push_str("\nmy-extra-job:\n  script: echo hi\n");
+        axoasset::LocalAsset::write_new(&gitlab_ci, ".gitlab-ci.yml")?;
+        let output = ctx.cargo_dist_generate_check()?;
+        assert!(!output.status.success(), "--check didn't notice the edit");
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        assert!(stderr.contains(".gitlab-ci.yml"), "{stderr}");
+        Ok(())
+    })
+}
+
 #[test]
 fn axolotlsay_user_plan_job() -> Result<(), miette::Report> {
     let test_name = _function_name!();